@@ -0,0 +1,124 @@
+//! `conditional-transfer`: inspect and drive a config from the shell.
+//!
+//! ```text
+//! conditional-transfer address <FROM> <TO>
+//! conditional-transfer show <CONFIG>
+//! conditional-transfer send <TO> <LAMPORTS> [--config <CONFIG>]
+//! conditional-transfer update-threshold <CONFIG> <LAMPORTS> [--update-id <ID>]
+//! conditional-transfer apply-rule <CONFIG> "amount >= 0.1 SOL AND time < 2025-06-30"
+//! ```
+//!
+//! `--signer` takes any source `conditional_transfer_client::load_signer`
+//! accepts (keypair file, `usb://ledger`, or an HTTP signer) and defaults to
+//! `~/.config/solana/id.json`; `--url` defaults to devnet.
+
+use std::error::Error;
+use std::str::FromStr;
+
+use clap::{Parser, Subcommand};
+use conditional_transfer_client::flows::{run_apply_rule, run_send, run_update_threshold};
+use conditional_transfer_client::{config_address, fetch_config, load_signer, Rule};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// RPC endpoint.
+    #[arg(long, env = "RPC_URL", default_value = "https://api.devnet.solana.com")]
+    url: String,
+    /// Signer uri; defaults to `~/.config/solana/id.json`.
+    #[arg(long, env = "SIGNER")]
+    signer: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the config PDA of `from`'s `route`.
+    Address { from: Pubkey, route: Pubkey },
+    /// Print a config's conditions, statistics, and vault balance.
+    Show { config: Pubkey },
+    /// Send `lamports` from the signer to `to` through the program.
+    Send {
+        to: Pubkey,
+        lamports: u64,
+        /// Config to send under; `--route` of the signer otherwise.
+        #[arg(long, required_unless_present = "route")]
+        config: Option<Pubkey>,
+        /// Route the signer initialized the config with.
+        #[arg(long)]
+        route: Option<Pubkey>,
+    },
+    /// Set a config's threshold, signed by its authority.
+    UpdateThreshold {
+        config: Pubkey,
+        lamports: u64,
+        /// Nonzero to make retries no-ops; see `update_threshold`.
+        #[arg(long, default_value_t = 0)]
+        update_id: u64,
+    },
+    /// Replace a config's amount bounds and time window with a text rule,
+    /// signed by its root authority.
+    ApplyRule { config: Pubkey, rule: String },
+}
+
+fn default_signer() -> String {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.config/solana/id.json")
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let rpc = RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed());
+    let signer = || load_signer(&cli.signer.clone().unwrap_or_else(default_signer));
+    match cli.command {
+        Command::Address { from, route } => println!("{}", config_address(&from, &route)),
+        Command::Show { config } => {
+            let (cfg, lamports) = fetch_config(&rpc, &config)?;
+            println!("authority:          {}", cfg.authority);
+            println!("from:               {}", cfg.from);
+            println!("to:                 {}", cfg.to);
+            println!("threshold_lamports: {}", cfg.threshold_lamports);
+            println!("max_lamports:       {}", cfg.max_lamports);
+            println!("valid_from_unix:    {:?}", cfg.valid_from_unix);
+            println!("valid_until_unix:   {:?}", cfg.valid_until_unix);
+            println!("paused:             {}", cfg.paused);
+            println!("event_seq:          {}", cfg.event_seq);
+            println!("total_transferred:  {}", cfg.stats.total_transferred);
+            println!("transfer_count:     {}", cfg.stats.transfer_count);
+            println!("lamports:           {lamports}");
+        }
+        Command::Send {
+            to,
+            lamports,
+            config,
+            route,
+        } => {
+            let from = signer()?;
+            let config = config.unwrap_or_else(|| config_address(&from.pubkey(), &route.unwrap()));
+            println!("{}", run_send(&rpc, from.as_ref(), config, to, lamports)?);
+        }
+        Command::UpdateThreshold {
+            config,
+            lamports,
+            update_id,
+        } => {
+            let authority = signer()?;
+            let sig = run_update_threshold(&rpc, authority.as_ref(), config, lamports, update_id)?;
+            println!("{sig}");
+        }
+        Command::ApplyRule { config, rule } => {
+            let rule = Rule::from_str(&rule)?;
+            let authority = signer()?;
+            println!(
+                "{}",
+                run_apply_rule(&rpc, authority.as_ref(), config, &rule)?
+            );
+        }
+    }
+    Ok(())
+}
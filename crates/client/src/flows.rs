@@ -0,0 +1,287 @@
+//! Instruction builders and submission for the common update and send flows.
+//! Every flow takes its signers as `&dyn Signer`, whatever backs them, and
+//! requests the program's recommended compute budget for its instruction.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use condition_core::Rule;
+use conditional_transfer::compute_budget::recommended_compute_budget;
+use conditional_transfer::{accounts, instruction, AmountUnit, Config, TypedAmount};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::Error as ClientError;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+/// The config PDA of `from`'s `route`, as passed to `initialize`.
+pub fn config_address(from: &Pubkey, route: &Pubkey) -> Pubkey {
+    pda(&[b"config", from.as_ref(), route.as_ref()])
+}
+
+/// Accounts of the config setters sharing the `Update` context.
+fn update_accounts(authority: &Pubkey, config: Pubkey) -> Vec<AccountMeta> {
+    accounts::Update {
+        authority: *authority,
+        config,
+        event_authority: pda(&[b"__event_authority"]),
+        program: conditional_transfer::ID,
+    }
+    .to_account_metas(None)
+}
+
+/// `update_threshold`, signed by `authority`. `recent_slot` must be within
+/// the program's tolerance of the current slot when it lands. A nonzero
+/// `update_id` above the config's `last_update_id` makes retries no-ops; `0`
+/// always applies.
+pub fn update_threshold(
+    authority: &Pubkey,
+    config: Pubkey,
+    new_threshold_lamports: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Instruction {
+    Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: update_accounts(authority, config),
+        data: instruction::UpdateThreshold {
+            new_threshold_lamports,
+            update_id,
+            recent_slot,
+        }
+        .data(),
+    }
+}
+
+/// `update_limits`, setting the threshold and maximum (`0` = none) together.
+pub fn update_limits(
+    authority: &Pubkey,
+    config: Pubkey,
+    threshold_lamports: u64,
+    max_lamports: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Instruction {
+    Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: update_accounts(authority, config),
+        data: instruction::UpdateLimits {
+            threshold_lamports,
+            max_lamports,
+            update_id,
+            recent_slot,
+        }
+        .data(),
+    }
+}
+
+/// `update_time_window`, signed by the root `authority`.
+pub fn update_time_window(
+    authority: &Pubkey,
+    config: Pubkey,
+    valid_from_unix: Option<i64>,
+    valid_until_unix: Option<i64>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Instruction {
+    Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: update_accounts(authority, config),
+        data: instruction::UpdateTimeWindow {
+            valid_from_unix,
+            valid_until_unix,
+            update_id,
+            recent_slot,
+        }
+        .data(),
+    }
+}
+
+/// `update_limits` and `update_time_window` replacing `config`'s conditions
+/// with `rule`. The time window needs the root authority. Both are untracked
+/// (`update_id = 0`): they set absolute values, so resubmitting them is
+/// harmless.
+pub fn apply_rule(
+    authority: &Pubkey,
+    config: Pubkey,
+    rule: &Rule,
+    recent_slot: u64,
+) -> Vec<Instruction> {
+    vec![
+        update_limits(
+            authority,
+            config,
+            rule.threshold_lamports,
+            rule.max_lamports,
+            0,
+            recent_slot,
+        ),
+        update_time_window(
+            authority,
+            config,
+            rule.valid_from_unix,
+            rule.valid_until_unix,
+            0,
+            recent_slot,
+        ),
+    ]
+}
+
+/// `send_if_over_threshold_v2` of `lamports` from `from` to `to` for a plain
+/// SOL config (no match pool, policy, or epoch summary).
+pub fn send(from: &Pubkey, to: Pubkey, config: Pubkey, lamports: u64) -> Instruction {
+    Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::SendIfOverThreshold {
+            config,
+            from: *from,
+            to,
+            instructions: None,
+            match_pool: None,
+            epoch_summary: None,
+            system_program: system_program::ID,
+            token_program: None,
+            recipient_token_account: None,
+            global: pda(&[b"global"]),
+            sweep_destination: None,
+            policy: None,
+            approver: None,
+            recipient_list: None,
+            price_feed: None,
+            range_proof: None,
+            fee_collector: None,
+        }
+        .to_account_metas(None),
+        data: instruction::SendIfOverThresholdV2 {
+            amount: TypedAmount {
+                unit: AmountUnit::Lamports,
+                value: lamports,
+            },
+            memo: None,
+        }
+        .data(),
+    }
+}
+
+/// Compute-budget instructions for `instruction` (IDL name) with `items`
+/// remaining-account items: the program's unit hint, priced at its hinted
+/// percentile of recent prioritization fees on `writable`.
+pub fn compute_budget(
+    rpc: &RpcClient,
+    instruction: &str,
+    items: u32,
+    writable: &[Pubkey],
+) -> Result<Vec<Instruction>, Box<ClientError>> {
+    let hint = recommended_compute_budget(instruction);
+    let mut fees: Vec<u64> = rpc
+        .get_recent_prioritization_fees(writable)?
+        .into_iter()
+        .map(|sample| sample.prioritization_fee)
+        .collect();
+    fees.sort_unstable();
+    let index = (fees.len() * hint.priority_fee_percentile as usize / 100)
+        .min(fees.len().saturating_sub(1));
+    let price = fees.get(index).copied().unwrap_or(0);
+    Ok(vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(hint.compute_unit_limit(items)),
+        ComputeBudgetInstruction::set_compute_unit_price(price),
+    ])
+}
+
+/// Fetch and decode the config account at `config`, with the lamports it
+/// holds. Configs not yet migrated decode in their stored version's layout.
+/// Fails if the account is missing or is not a `Config`.
+pub fn fetch_config(rpc: &RpcClient, config: &Pubkey) -> Result<(Config, u64), Box<ClientError>> {
+    let account = rpc.get_account(config)?;
+    let decoded = Config::try_deserialize_versioned(&account.data)
+        .map_err(|err| ClientError::from(std::io::Error::other(err.to_string())))?;
+    Ok((decoded, account.lamports))
+}
+
+/// Sign `instructions` with `payer` plus `signers` and send them, waiting for
+/// confirmation. A Ledger prompts once per signer here.
+pub fn submit(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &dyn Signer,
+    signers: &[&dyn Signer],
+) -> Result<Signature, Box<ClientError>> {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let blockhash = rpc.get_latest_blockhash()?;
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    Ok(rpc.send_and_confirm_transaction(&tx)?)
+}
+
+/// Fetch the current slot and run `update_threshold` with `authority` paying.
+pub fn run_update_threshold(
+    rpc: &RpcClient,
+    authority: &dyn Signer,
+    config: Pubkey,
+    new_threshold_lamports: u64,
+    update_id: u64,
+) -> Result<Signature, Box<ClientError>> {
+    let ix = update_threshold(
+        &authority.pubkey(),
+        config,
+        new_threshold_lamports,
+        update_id,
+        rpc.get_slot()?,
+    );
+    let mut ixs = compute_budget(rpc, "update_threshold", 0, &[config])?;
+    ixs.push(ix);
+    submit(rpc, &ixs, authority, &[])
+}
+
+/// Fetch the current slot and replace `config`'s conditions with `rule`,
+/// with `authority` paying. The unit limit covers both setters.
+pub fn run_apply_rule(
+    rpc: &RpcClient,
+    authority: &dyn Signer,
+    config: Pubkey,
+    rule: &Rule,
+) -> Result<Signature, Box<ClientError>> {
+    let mut ixs = compute_budget(rpc, "update_limits", 0, &[config])?;
+    ixs[0] = ComputeBudgetInstruction::set_compute_unit_limit(
+        ["update_limits", "update_time_window"]
+            .iter()
+            .map(|name| recommended_compute_budget(name).compute_unit_limit(0))
+            .sum(),
+    );
+    ixs.extend(apply_rule(
+        &authority.pubkey(),
+        config,
+        rule,
+        rpc.get_slot()?,
+    ));
+    submit(rpc, &ixs, authority, &[])
+}
+
+/// Send `lamports` on the `from` → `to` route with `from` paying.
+pub fn run_send(
+    rpc: &RpcClient,
+    from: &dyn Signer,
+    config: Pubkey,
+    to: Pubkey,
+    lamports: u64,
+) -> Result<Signature, Box<ClientError>> {
+    let mut ixs = compute_budget(
+        rpc,
+        "send_if_over_threshold_v2",
+        0,
+        &[config, from.pubkey(), to],
+    )?;
+    ixs.push(send(&from.pubkey(), to, config, lamports));
+    submit(rpc, &ixs, from, &[])
+}
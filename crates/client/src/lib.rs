@@ -0,0 +1,29 @@
+//! Rust client for `conditional_transfer`
+//! --------------------------------------
+//! Builds and submits the update and send flows for CLIs and bots, with the
+//! signing key behind [`solana_sdk::signer::Signer`] so the same flow runs
+//! against a local keypair file, a Ledger, or a remote HTTP signer. Pick one
+//! at runtime with [`wallet::load_signer`]:
+//!
+//! - `~/.config/solana/id.json`: local keypair file
+//! - `usb://ledger?key=0`: Ledger via `solana-remote-wallet`
+//! - `https://signer.example/sign?pubkey=<base58>`: [`wallet::HttpSigner`]
+//!
+//! Ledger access needs `solana-remote-wallet`'s `hidapi` feature in the final
+//! binary (e.g. `solana-remote-wallet = { version = "1.18", features = ["hidapi"] }`);
+//! without it, `usb://` sources fail with `NoLedger`.
+//!
+//! [`Rule`] (from `condition_core`) parses text conditions such as
+//! `amount >= 0.1 SOL AND time in [2025-01-01..2025-06-30]`;
+//! [`flows::apply_rule`] turns one into the `update_limits` /
+//! `update_time_window` calls that enforce it.
+
+pub mod flows;
+pub mod wallet;
+
+pub use condition_core::{rules, Condition, Rule, RuleError};
+pub use flows::{
+    apply_rule, compute_budget, config_address, fetch_config, send, submit, update_limits,
+    update_threshold, update_time_window,
+};
+pub use wallet::{load_signer, HttpSigner, SignerSource, WalletError};
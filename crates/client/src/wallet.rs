@@ -0,0 +1,212 @@
+//! Signer sources: local keypair files, Ledgers, and remote HTTP signers.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use base64::Engine;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use solana_remote_wallet::locator::Locator;
+use solana_remote_wallet::remote_keypair::generate_remote_keypair;
+use solana_remote_wallet::remote_wallet::{maybe_wallet_manager, RemoteWalletError};
+use solana_sdk::derivation_path::DerivationPath;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Signature};
+use solana_sdk::signer::{Signer, SignerError};
+use uriparse::URIReference;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WalletError {
+    #[error("invalid signer uri `{0}`: {1}")]
+    InvalidUri(String, String),
+    #[error("cannot read keypair {0}: {1}")]
+    Keypair(PathBuf, String),
+    #[error("no Ledger connected")]
+    NoLedger,
+    #[error(transparent)]
+    RemoteWallet(#[from] RemoteWalletError),
+}
+
+/// Where a signer comes from, parsed from a CLI-style uri.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerSource {
+    /// Path to a JSON keypair file.
+    Keypair(PathBuf),
+    /// `usb://ledger[/<wallet>][?key=<account>[/<change>]]`.
+    Ledger(String),
+    /// `http(s)://...?pubkey=<base58>`; the `pubkey` parameter names the key the
+    /// service signs with and is stripped from the request url.
+    Remote { url: Url, pubkey: Pubkey },
+}
+
+impl SignerSource {
+    pub fn parse(uri: &str) -> Result<Self, WalletError> {
+        let invalid = |reason: &str| WalletError::InvalidUri(uri.to_string(), reason.to_string());
+        if uri.starts_with("usb://") {
+            return Ok(SignerSource::Ledger(uri.to_string()));
+        }
+        if uri.starts_with("http://") || uri.starts_with("https://") {
+            let mut url = Url::parse(uri).map_err(|err| invalid(&err.to_string()))?;
+            let pubkey = url
+                .query_pairs()
+                .find(|(name, _)| name == "pubkey")
+                .map(|(_, value)| value.into_owned())
+                .ok_or_else(|| invalid("missing `pubkey` query parameter"))?;
+            let pubkey = Pubkey::from_str(&pubkey).map_err(|err| invalid(&err.to_string()))?;
+            let rest: Vec<(String, String)> = url
+                .query_pairs()
+                .filter(|(name, _)| name != "pubkey")
+                .map(|(name, value)| (name.into_owned(), value.into_owned()))
+                .collect();
+            url.set_query(None);
+            if !rest.is_empty() {
+                url.query_pairs_mut().extend_pairs(rest);
+            }
+            return Ok(SignerSource::Remote { url, pubkey });
+        }
+        Ok(SignerSource::Keypair(PathBuf::from(uri)))
+    }
+}
+
+/// Load the signer `uri` names (see [`SignerSource`]). Ledger keys are not
+/// confirmed on the device here; each signature is.
+pub fn load_signer(uri: &str) -> Result<Box<dyn Signer>, WalletError> {
+    match SignerSource::parse(uri)? {
+        SignerSource::Keypair(path) => {
+            let keypair = read_keypair_file(&path)
+                .map_err(|err| WalletError::Keypair(path, err.to_string()))?;
+            Ok(Box::new(keypair))
+        }
+        SignerSource::Ledger(uri) => {
+            let invalid = |err: String| WalletError::InvalidUri(uri.clone(), err);
+            let reference =
+                URIReference::try_from(uri.as_str()).map_err(|err| invalid(err.to_string()))?;
+            let locator =
+                Locator::new_from_uri(&reference).map_err(|err| invalid(err.to_string()))?;
+            let derivation_path = DerivationPath::from_uri_key_query(&reference)
+                .map_err(|err| invalid(err.to_string()))?
+                .unwrap_or_default();
+            let wallet_manager = maybe_wallet_manager()?.ok_or(WalletError::NoLedger)?;
+            let keypair = generate_remote_keypair(
+                locator,
+                derivation_path,
+                &wallet_manager,
+                false,
+                "authority",
+            )?;
+            Ok(Box::new(keypair))
+        }
+        SignerSource::Remote { url, pubkey } => Ok(Box::new(HttpSigner::new(url, pubkey))),
+    }
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    pubkey: String,
+    /// Base64 of the serialized transaction message.
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    /// Base58 signature.
+    signature: String,
+}
+
+/// Signer backed by an HTTP service holding the key (an HSM front end, a
+/// custody API, ...). Each signature is a `POST` of
+/// `{"pubkey": <base58>, "message": <base64>}` answered with
+/// `{"signature": <base58>}`; signatures that do not verify against `pubkey`
+/// are rejected.
+pub struct HttpSigner {
+    url: Url,
+    pubkey: Pubkey,
+    client: reqwest::blocking::Client,
+}
+
+impl HttpSigner {
+    pub fn new(url: Url, pubkey: Pubkey) -> Self {
+        Self {
+            url,
+            pubkey,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+}
+
+impl Signer for HttpSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let request = SignRequest {
+            pubkey: self.pubkey.to_string(),
+            message: base64::engine::general_purpose::STANDARD.encode(message),
+        };
+        let response: SignResponse = self
+            .client
+            .post(self.url.clone())
+            .json(&request)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(|err| SignerError::Connection(err.to_string()))?;
+        let signature = Signature::from_str(&response.signature)
+            .map_err(|err| SignerError::Protocol(err.to_string()))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(SignerError::Protocol(format!(
+                "remote signer returned a signature that does not verify for {}",
+                self.pubkey
+            )));
+        }
+        Ok(signature)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{write_keypair_file, Keypair};
+
+    #[test]
+    fn parse_recognizes_each_source() {
+        let pubkey = Pubkey::new_unique();
+        assert_eq!(
+            SignerSource::parse("usb://ledger?key=1").unwrap(),
+            SignerSource::Ledger("usb://ledger?key=1".to_string())
+        );
+        assert_eq!(
+            SignerSource::parse(&format!(
+                "https://signer.example/sign?pubkey={pubkey}&tenant=a"
+            ))
+            .unwrap(),
+            SignerSource::Remote {
+                url: Url::parse("https://signer.example/sign?tenant=a").unwrap(),
+                pubkey,
+            }
+        );
+        assert_eq!(
+            SignerSource::parse("/tmp/id.json").unwrap(),
+            SignerSource::Keypair(PathBuf::from("/tmp/id.json"))
+        );
+        assert!(matches!(
+            SignerSource::parse("https://signer.example/sign"),
+            Err(WalletError::InvalidUri(..))
+        ));
+    }
+
+    #[test]
+    fn load_signer_reads_local_keypair() {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("{}.json", keypair.pubkey()));
+        write_keypair_file(&keypair, &path).unwrap();
+        let signer = load_signer(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(signer.pubkey(), keypair.pubkey());
+    }
+}
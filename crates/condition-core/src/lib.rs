@@ -0,0 +1,14 @@
+//! Send-condition types shared by `conditional_transfer` and its off-chain
+//! crates. Nothing here depends on Anchor or an RPC client, so the program
+//! links it on-chain and the client, CLI, and exporter agree with it on how a
+//! condition is written and priced.
+//!
+//! - [`price`]: SOL/USD prices and the lamport amounts they imply.
+//! - [`rules`]: the text format for a config's send conditions, e.g.
+//!   `amount >= 0.1 SOL AND time in [2025-01-01..2025-06-30]`.
+
+pub mod price;
+pub mod rules;
+
+pub use price::SolUsdPrice;
+pub use rules::{Condition, Rule, RuleError};
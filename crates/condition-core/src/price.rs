@@ -0,0 +1,26 @@
+//! Pricing SOL amounts in US dollars.
+
+/// A SOL/USD price: one SOL is worth `price × 10^exponent` US dollars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SolUsdPrice {
+    pub price: i64,
+    pub exponent: i32,
+}
+
+impl SolUsdPrice {
+    /// Lamports worth at least `cents` US cents at this price, rounded up.
+    /// `None` for a non-positive price or an out-of-range result.
+    pub fn lamports_for_usd_cents(&self, cents: u64) -> Option<u64> {
+        let price = u128::try_from(self.price).ok().filter(|&price| price > 0)?;
+        // cents / 100 / (price × 10^exponent) SOL, times 10^9 lamports per SOL.
+        let scale = 7 - self.exponent as i64;
+        let pow = |exp: i64| 10u128.checked_pow(u32::try_from(exp).ok()?);
+        let (numerator, denominator) = if scale >= 0 {
+            ((cents as u128).checked_mul(pow(scale)?)?, price)
+        } else {
+            (cents as u128, price.checked_mul(pow(-scale)?)?)
+        };
+        let lamports = numerator.checked_add(denominator - 1)? / denominator;
+        u64::try_from(lamports).ok()
+    }
+}
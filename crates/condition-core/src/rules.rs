@@ -0,0 +1,295 @@
+//! A small text format for a config's send conditions, compiled to the
+//! config fields that enforce them:
+//!
+//! ```text
+//! amount >= 0.1 SOL AND amount <= 50 SOL AND time in [2025-01-01..2025-06-30]
+//! ```
+//!
+//! Clauses are joined with `AND` (case-insensitive) and their tokens are
+//! separated by spaces. There is no `OR`: the program checks every condition
+//! on every send.
+//!
+//! - `amount <op> <number> SOL|lamports`, `<op>` one of `>=`, `>`, `<=`, `<`:
+//!   `threshold_lamports` (lower bound) and `max_lamports` (upper bound).
+//! - `time <op> <date>` and `time in [<date>..<date>]`: `valid_from_unix` and
+//!   `valid_until_unix`. Dates are `YYYY-MM-DD` in UTC or unix seconds; a day
+//!   used as an upper bound includes the whole day.
+//!
+//! A [`Rule`] describes all of these fields, so applying it clears any bound
+//! the text leaves out. Building the instructions that apply it is left to
+//! `conditional_transfer_client`.
+
+use std::str::FromStr;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const SOL_DECIMALS: usize = 9;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// One parsed clause, named after the bound it sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition {
+    /// `threshold_lamports`: sends below fail.
+    MinLamports(u64),
+    /// `max_lamports`: sends above fail.
+    MaxLamports(u64),
+    /// `valid_from_unix`: sends before fail.
+    ValidFrom(i64),
+    /// `valid_until_unix`: sends after fail.
+    ValidUntil(i64),
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RuleError {
+    #[error("invalid clause `{0}`: {1}")]
+    Syntax(String, String),
+    #[error("{0} is bounded more than once")]
+    Duplicate(&'static str),
+    #[error("amount lower bound is above its upper bound")]
+    EmptyAmountRange,
+    #[error("time window ends before it starts")]
+    EmptyTimeWindow,
+}
+
+/// A config's complete set of send conditions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rule {
+    pub threshold_lamports: u64,
+    /// `0` means no maximum, as on-chain.
+    pub max_lamports: u64,
+    pub valid_from_unix: Option<i64>,
+    pub valid_until_unix: Option<i64>,
+}
+
+impl Rule {
+    /// Fold `conditions` into a rule, rejecting repeated or contradictory bounds.
+    pub fn from_conditions(conditions: &[Condition]) -> Result<Self, RuleError> {
+        let (mut min, mut max, mut from, mut until) = (None, None, None, None);
+        for condition in conditions {
+            let (fresh, name) = match *condition {
+                Condition::MinLamports(v) => (set(&mut min, v), "amount lower bound"),
+                Condition::MaxLamports(v) => (set(&mut max, v), "amount upper bound"),
+                Condition::ValidFrom(v) => (set(&mut from, v), "time lower bound"),
+                Condition::ValidUntil(v) => (set(&mut until, v), "time upper bound"),
+            };
+            if !fresh {
+                return Err(RuleError::Duplicate(name));
+            }
+        }
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return Err(RuleError::EmptyAmountRange);
+            }
+        }
+        if let (Some(from), Some(until)) = (from, until) {
+            if from > until {
+                return Err(RuleError::EmptyTimeWindow);
+            }
+        }
+        Ok(Rule {
+            threshold_lamports: min.unwrap_or(0),
+            max_lamports: max.unwrap_or(0),
+            valid_from_unix: from,
+            valid_until_unix: until,
+        })
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(src: &str) -> Result<Self, RuleError> {
+        Rule::from_conditions(&parse_conditions(src)?)
+    }
+}
+
+fn set<T>(slot: &mut Option<T>, value: T) -> bool {
+    slot.replace(value).is_none()
+}
+
+/// Parse `src` into its clauses, in order.
+pub fn parse_conditions(src: &str) -> Result<Vec<Condition>, RuleError> {
+    let spaced = src
+        .replace('[', " [ ")
+        .replace(']', " ] ")
+        .replace("..", " .. ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut conditions = Vec::new();
+    for clause in tokens.split(|token| token.eq_ignore_ascii_case("and")) {
+        let invalid = |reason: &str| RuleError::Syntax(clause.join(" "), reason.to_string());
+        if clause.iter().any(|token| token.eq_ignore_ascii_case("or")) {
+            return Err(invalid("OR is not supported; every condition applies"));
+        }
+        match clause {
+            [subject, op, value, unit] if subject.eq_ignore_ascii_case("amount") => {
+                let amount = parse_lamports(value, unit).map_err(|reason| invalid(&reason))?;
+                conditions.push(match *op {
+                    ">=" => Condition::MinLamports(amount),
+                    ">" => Condition::MinLamports(
+                        amount
+                            .checked_add(1)
+                            .ok_or_else(|| invalid("amount overflows"))?,
+                    ),
+                    "<=" => Condition::MaxLamports(amount),
+                    "<" => Condition::MaxLamports(amount.saturating_sub(1)),
+                    _ => return Err(invalid("expected >=, >, <=, or <")),
+                });
+                if conditions.last() == Some(&Condition::MaxLamports(0)) {
+                    return Err(invalid("upper bound must allow a non-zero amount"));
+                }
+            }
+            [subject, op, date] if subject.eq_ignore_ascii_case("time") => {
+                let (start, end) = parse_day(date).map_err(|reason| invalid(&reason))?;
+                conditions.push(match *op {
+                    ">=" => Condition::ValidFrom(start),
+                    ">" => Condition::ValidFrom(end.saturating_add(1)),
+                    "<=" => Condition::ValidUntil(end),
+                    "<" => Condition::ValidUntil(start.saturating_sub(1)),
+                    _ => return Err(invalid("expected >=, >, <=, <, or in")),
+                });
+            }
+            [subject, keyword, "[", from, "..", until, "]"]
+                if subject.eq_ignore_ascii_case("time") && keyword.eq_ignore_ascii_case("in") =>
+            {
+                let (start, _) = parse_day(from).map_err(|reason| invalid(&reason))?;
+                let (_, end) = parse_day(until).map_err(|reason| invalid(&reason))?;
+                conditions.push(Condition::ValidFrom(start));
+                conditions.push(Condition::ValidUntil(end));
+            }
+            [] => return Err(invalid("empty clause")),
+            _ => return Err(invalid("expected `amount <op> <n> <unit>` or `time ...`")),
+        }
+    }
+    Ok(conditions)
+}
+
+/// `value` in `unit` (`SOL` or `lamports`), exactly; SOL takes up to 9 decimals.
+fn parse_lamports(value: &str, unit: &str) -> Result<u64, String> {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let overflow = || "amount overflows".to_string();
+    if unit.eq_ignore_ascii_case("lamports") {
+        if !digits(value) {
+            return Err("lamports must be a whole number".into());
+        }
+        return value.parse().map_err(|_| overflow());
+    }
+    if !unit.eq_ignore_ascii_case("sol") {
+        return Err(format!("unknown unit `{unit}`; expected SOL or lamports"));
+    }
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if !digits(whole) || !(fraction.is_empty() || digits(fraction)) {
+        return Err(format!("`{value}` is not a number"));
+    }
+    if fraction.len() > SOL_DECIMALS {
+        return Err("SOL has at most 9 decimals".into());
+    }
+    let whole: u64 = whole.parse().map_err(|_| overflow())?;
+    let fraction: u64 = format!("{fraction:0<SOL_DECIMALS$}").parse().unwrap();
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+        .ok_or_else(overflow)
+}
+
+/// First and last unix second of `date`: a UTC `YYYY-MM-DD` day, or unix seconds.
+fn parse_day(date: &str) -> Result<(i64, i64), String> {
+    if let Ok(seconds) = date.parse::<i64>() {
+        return Ok((seconds, seconds));
+    }
+    let invalid = || format!("`{date}` is not YYYY-MM-DD or unix seconds");
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    if !(0..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+    {
+        return Err(invalid());
+    }
+    let start = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+    Ok((start, start + SECONDS_PER_DAY - 1))
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_amount_and_date_window() {
+        let rule: Rule = "amount >= 0.1 SOL AND time in [2025-01-01..2025-06-30]"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            rule,
+            Rule {
+                threshold_lamports: 100_000_000,
+                max_lamports: 0,
+                valid_from_unix: Some(1_735_689_600),
+                valid_until_unix: Some(1_751_327_999),
+            }
+        );
+    }
+
+    #[test]
+    fn strict_bounds_step_past_the_value() {
+        let rule: Rule = "amount > 5 lamports and amount < 1 SOL and time < 1700000000"
+            .parse()
+            .unwrap();
+        assert_eq!(rule.threshold_lamports, 6);
+        assert_eq!(rule.max_lamports, LAMPORTS_PER_SOL - 1);
+        assert_eq!(rule.valid_from_unix, None);
+        assert_eq!(rule.valid_until_unix, Some(1_699_999_999));
+    }
+
+    #[test]
+    fn rejects_unsafe_rules() {
+        let parse = |src: &str| src.parse::<Rule>().unwrap_err();
+        assert!(matches!(
+            parse("amount >= 1 SOL OR amount <= 2 SOL"),
+            RuleError::Syntax(..)
+        ));
+        assert!(matches!(
+            parse("amount >= 1.5 lamports"),
+            RuleError::Syntax(..)
+        ));
+        assert!(matches!(parse("time >= 2025-02-29"), RuleError::Syntax(..)));
+        assert!(matches!(
+            parse("amount < 1 lamports"),
+            RuleError::Syntax(..)
+        ));
+        assert_eq!(
+            parse("amount >= 1 SOL AND amount > 2 SOL"),
+            RuleError::Duplicate("amount lower bound")
+        );
+        assert_eq!(
+            parse("amount >= 2 SOL AND amount <= 1 SOL"),
+            RuleError::EmptyAmountRange
+        );
+        assert_eq!(
+            parse("time in [2025-06-30..2025-01-01]"),
+            RuleError::EmptyTimeWindow
+        );
+    }
+}
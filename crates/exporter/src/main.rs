@@ -0,0 +1,192 @@
+//! `conditional-transfer-exporter`: Prometheus metrics for a set of configs,
+//! in the text exposition format.
+//!
+//! Without `--output` it prints one scrape to stdout. With `--output` it
+//! rewrites that file every `--interval` seconds, for node_exporter's
+//! textfile collector; the file is replaced atomically, so a scrape never
+//! reads a partial write. A config that fails to load is reported through
+//! `conditional_transfer_up 0` rather than stopping the others.
+
+use std::error::Error;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use conditional_transfer::Config;
+use conditional_transfer_client::fetch_config;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// RPC endpoint.
+    #[arg(long, env = "RPC_URL", default_value = "https://api.devnet.solana.com")]
+    url: String,
+    /// File to keep rewriting; prints once to stdout when omitted.
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Seconds between rewrites of `--output`.
+    #[arg(long, default_value_t = 30)]
+    interval: u64,
+    /// Config accounts to report on.
+    #[arg(required = true)]
+    configs: Vec<Pubkey>,
+}
+
+/// The figures exported for one config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Snapshot {
+    threshold_lamports: u64,
+    max_lamports: u64,
+    paused: bool,
+    event_seq: u64,
+    total_transferred: u64,
+    transfer_count: u64,
+    last_transfer_slot: u64,
+    lamports: u64,
+}
+
+impl Snapshot {
+    fn new(cfg: &Config, lamports: u64) -> Self {
+        Snapshot {
+            threshold_lamports: cfg.threshold_lamports,
+            max_lamports: cfg.max_lamports,
+            paused: cfg.paused,
+            event_seq: cfg.event_seq,
+            total_transferred: cfg.stats.total_transferred,
+            transfer_count: cfg.stats.transfer_count,
+            last_transfer_slot: cfg.stats.last_transfer_slot,
+            lamports,
+        }
+    }
+}
+
+/// Metric name, type, help, and value for each exported figure.
+const METRICS: &[(&str, &str, &str, fn(&Snapshot) -> u64)] = &[
+    (
+        "conditional_transfer_threshold_lamports",
+        "gauge",
+        "Minimum send in lamports.",
+        |s| s.threshold_lamports,
+    ),
+    (
+        "conditional_transfer_max_lamports",
+        "gauge",
+        "Maximum send in lamports; 0 when unbounded.",
+        |s| s.max_lamports,
+    ),
+    (
+        "conditional_transfer_paused",
+        "gauge",
+        "1 while sends are paused.",
+        |s| s.paused as u64,
+    ),
+    (
+        "conditional_transfer_event_seq",
+        "counter",
+        "Sequence number of the last event emitted.",
+        |s| s.event_seq,
+    ),
+    (
+        "conditional_transfer_transferred_lamports",
+        "counter",
+        "Lamports sent since creation or the last reset_stats.",
+        |s| s.total_transferred,
+    ),
+    (
+        "conditional_transfer_transfers",
+        "counter",
+        "Transfers since creation or the last reset_stats.",
+        |s| s.transfer_count,
+    ),
+    (
+        "conditional_transfer_last_transfer_slot",
+        "gauge",
+        "Slot of the latest transfer; 0 before the first.",
+        |s| s.last_transfer_slot,
+    ),
+    (
+        "conditional_transfer_account_lamports",
+        "gauge",
+        "Lamports held by the config account.",
+        |s| s.lamports,
+    ),
+];
+
+/// Render one scrape. `None` marks a config that could not be loaded.
+fn render(configs: &[(Pubkey, Option<Snapshot>)]) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP conditional_transfer_up 1 if the config was fetched and decoded.\n");
+    out.push_str("# TYPE conditional_transfer_up gauge\n");
+    for (config, snapshot) in configs {
+        let up = snapshot.is_some() as u8;
+        let _ = writeln!(out, "conditional_transfer_up{{config=\"{config}\"}} {up}");
+    }
+    for (name, kind, help, value) in METRICS {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for (config, snapshot) in configs {
+            if let Some(snapshot) = snapshot {
+                let _ = writeln!(out, "{name}{{config=\"{config}\"}} {}", value(snapshot));
+            }
+        }
+    }
+    out
+}
+
+fn scrape(rpc: &RpcClient, configs: &[Pubkey]) -> String {
+    let snapshots: Vec<_> = configs
+        .iter()
+        .map(|config| match fetch_config(rpc, config) {
+            Ok((cfg, lamports)) => (*config, Some(Snapshot::new(&cfg, lamports))),
+            Err(err) => {
+                eprintln!("{config}: {err}");
+                (*config, None)
+            }
+        })
+        .collect();
+    render(&snapshots)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let rpc = RpcClient::new_with_commitment(args.url, CommitmentConfig::confirmed());
+    let Some(output) = args.output else {
+        print!("{}", scrape(&rpc, &args.configs));
+        return Ok(());
+    };
+    let staging = output.with_extension("prom.tmp");
+    loop {
+        std::fs::write(&staging, scrape(&rpc, &args.configs))?;
+        std::fs::rename(&staging, &output)?;
+        std::thread::sleep(Duration::from_secs(args.interval));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_reports_loaded_and_missing_configs() {
+        let (loaded, missing) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let snapshot = Snapshot {
+            threshold_lamports: 100_000_000,
+            paused: true,
+            transfer_count: 3,
+            ..Snapshot::default()
+        };
+        let text = render(&[(loaded, Some(snapshot)), (missing, None)]);
+        let line = |name: &str, config: Pubkey| format!("{name}{{config=\"{config}\"}} ");
+        assert!(text.contains(&(line("conditional_transfer_up", loaded) + "1\n")));
+        assert!(text.contains(&(line("conditional_transfer_up", missing) + "0\n")));
+        let threshold = line("conditional_transfer_threshold_lamports", loaded);
+        assert!(text.contains(&(threshold + "100000000\n")));
+        assert!(text.contains(&(line("conditional_transfer_paused", loaded) + "1\n")));
+        assert!(text.contains(&(line("conditional_transfer_transfers", loaded) + "3\n")));
+        assert!(!text.contains(&line("conditional_transfer_paused", missing)));
+    }
+}
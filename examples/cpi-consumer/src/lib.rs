@@ -0,0 +1,85 @@
+//! CPI consumer example
+//! -------------------
+//! A minimal invoicing program that pays invoices through `conditional_transfer`
+//! by CPI instead of moving lamports itself, so the threshold, recipient, and
+//! every other config condition are enforced by the callee. If a condition
+//! fails, the callee's error (e.g. `BelowThreshold`) aborts the whole
+//! transaction.
+//!
+//! Depends on `conditional_transfer` with the `cpi` feature, which brings in the
+//! generated `cpi` module and drops its entrypoint. `tests/cpi.rs` runs both
+//! programs under `solana-program-test`.
+
+use anchor_lang::prelude::*;
+use conditional_transfer::cpi::accounts::SendIfOverThreshold;
+use conditional_transfer::program::ConditionalTransfer;
+use conditional_transfer::{AmountUnit, TypedAmount};
+
+declare_id!("GiJpWbUXENSxRDsPEUiB5m8ceU9Li7rXG1XKHtPFDmhC");
+
+#[program]
+pub mod cpi_consumer {
+    use super::*;
+
+    /// Pay `lamports` toward invoice `invoice_id` via
+    /// `send_if_over_threshold_v2`, with the invoice id as the memo. `payer`
+    /// must be the config's `from`; its signature carries through the CPI.
+    pub fn pay_invoice(ctx: Context<PayInvoice>, invoice_id: u64, lamports: u64) -> Result<()> {
+        let accounts = SendIfOverThreshold {
+            config: ctx.accounts.config.to_account_info(),
+            from: ctx.accounts.payer.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            instructions: None,
+            match_pool: None,
+            epoch_summary: None,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: None,
+            recipient_token_account: None,
+            global: ctx.accounts.global.to_account_info(),
+            sweep_destination: None,
+            policy: None,
+            approver: None,
+            recipient_list: None,
+            price_feed: None,
+            range_proof: None,
+            fee_collector: None,
+        };
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.conditional_transfer.to_account_info(),
+            accounts,
+        );
+        conditional_transfer::cpi::send_if_over_threshold_v2(
+            cpi_ctx,
+            TypedAmount {
+                unit: AmountUnit::Lamports,
+                value: lamports,
+            },
+            Some(invoice_id.to_string()),
+        )?;
+        msg!("Paid invoice {}: {} lamports", invoice_id, lamports);
+        Ok(())
+    }
+}
+
+/// Accounts context for paying an invoice through `conditional_transfer`.
+#[derive(Accounts)]
+pub struct PayInvoice<'info> {
+    /// CHECK: The conditional_transfer config PDA; validated by the callee.
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+
+    /// The config's `from`; signs and funds the payment.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: The config's recipient; validated by the callee.
+    #[account(mut)]
+    pub to: UncheckedAccount<'info>,
+
+    /// CHECK: conditional_transfer's global state PDA; validated by the callee.
+    pub global: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub conditional_transfer: Program<'info, ConditionalTransfer>,
+}
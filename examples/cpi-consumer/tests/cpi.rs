@@ -0,0 +1,152 @@
+//! Runs `cpi_consumer` and `conditional_transfer` natively under
+//! `solana-program-test`: one invoice paid at the threshold, one rejected
+//! below it with the callee's error.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use conditional_transfer::ConditionalError;
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+const THRESHOLD: u64 = 100_000_000;
+const ROUTE: Pubkey = Pubkey::new_from_array([1; 32]);
+
+// Anchor's `entry` ties the account slice to the accounts' own lifetime, which
+// `processor!` cannot express; program-test keeps both alive for the whole call.
+fn conditional_transfer_entry<'a, 'b, 'c>(
+    program_id: &'a Pubkey,
+    accounts: &'b [AccountInfo<'c>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'c [AccountInfo<'c>] = unsafe { std::mem::transmute(accounts) };
+    conditional_transfer::entry(program_id, accounts, data)
+}
+
+fn cpi_consumer_entry<'a, 'b, 'c>(
+    program_id: &'a Pubkey,
+    accounts: &'b [AccountInfo<'c>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'c [AccountInfo<'c>] = unsafe { std::mem::transmute(accounts) };
+    cpi_consumer::entry(program_id, accounts, data)
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+async fn process(
+    banks: &mut BanksClient,
+    payer: &Keypair,
+    ix: Instruction,
+) -> Result<(), TransactionError> {
+    let blockhash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    banks
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+/// Start both programs and initialize a config paying `to` from the payer.
+async fn setup(to: Pubkey) -> (BanksClient, Keypair) {
+    let mut program_test = ProgramTest::new(
+        "cpi_consumer",
+        cpi_consumer::ID,
+        processor!(cpi_consumer_entry),
+    );
+    program_test.add_program(
+        "conditional_transfer",
+        conditional_transfer::ID,
+        processor!(conditional_transfer_entry),
+    );
+    let (mut banks, payer, _) = program_test.start().await;
+
+    let initialize = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: conditional_transfer::accounts::Initialize {
+            authority: payer.pubkey(),
+            from: payer.pubkey(),
+            config: pda(&[b"config", payer.pubkey().as_ref(), ROUTE.as_ref()]),
+            recipient_index: pda(&[b"recipient_index", to.as_ref()]),
+            system_program: system_program::ID,
+            event_authority: pda(&[b"__event_authority"]),
+            program: conditional_transfer::ID,
+        }
+        .to_account_metas(None),
+        data: conditional_transfer::instruction::Initialize {
+            route: ROUTE,
+            to,
+            threshold_lamports: THRESHOLD,
+        }
+        .data(),
+    };
+    process(&mut banks, &payer, initialize).await.unwrap();
+    (banks, payer)
+}
+
+fn pay_invoice(payer: Pubkey, to: Pubkey, invoice_id: u64, lamports: u64) -> Instruction {
+    Instruction {
+        program_id: cpi_consumer::ID,
+        accounts: cpi_consumer::accounts::PayInvoice {
+            config: pda(&[b"config", payer.as_ref(), ROUTE.as_ref()]),
+            payer,
+            to,
+            global: pda(&[b"global"]),
+            system_program: system_program::ID,
+            conditional_transfer: conditional_transfer::ID,
+        }
+        .to_account_metas(None),
+        data: cpi_consumer::instruction::PayInvoice {
+            invoice_id,
+            lamports,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+async fn pays_invoice_at_threshold() {
+    let to = Pubkey::new_unique();
+    let (mut banks, payer) = setup(to).await;
+
+    process(
+        &mut banks,
+        &payer,
+        pay_invoice(payer.pubkey(), to, 1, THRESHOLD),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(banks.get_balance(to).await.unwrap(), THRESHOLD);
+}
+
+#[tokio::test]
+async fn rejects_invoice_below_threshold() {
+    let to = Pubkey::new_unique();
+    let (mut banks, payer) = setup(to).await;
+
+    let err = process(
+        &mut banks,
+        &payer,
+        pay_invoice(payer.pubkey(), to, 2, THRESHOLD - 1),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ConditionalError::BelowThreshold.into())
+        )
+    );
+    assert_eq!(banks.get_balance(to).await.unwrap(), 0);
+}
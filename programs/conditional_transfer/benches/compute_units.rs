@@ -0,0 +1,2677 @@
+//! Compute-unit regression bench
+//! -----------------------------
+//! Runs every instruction against the SBF build of the program inside
+//! `solana-program-test` and records the compute units each scenario consumed.
+//! Results are compared against `benches/compute_units.json`; the bench exits
+//! non-zero if any scenario uses more than the baseline plus a tolerance, or
+//! more than its instruction's `compute_budget` hint. Scenarios are named
+//! `<instruction>[/<variant>]`; an `x<n>` variant has `n` remaining-account items.
+//!
+//! Usage:
+//! 1) `cargo build-sbf` (or `anchor build`) so `target/deploy/conditional_transfer.so` exists.
+//! 2) `cargo bench --bench compute_units`
+//!
+//! Environment:
+//! - `CU_TOLERANCE_PCT`: allowed regression in percent (default 5).
+//! - `CU_UPDATE_BASELINE=1`: rewrite the baseline with the measured values.
+//!
+//! The bench also fails if the baseline file is missing or unreadable, or
+//! lacks a measured scenario; `CU_UPDATE_BASELINE=1` records them instead.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use conditional_transfer::compute_budget::recommended_compute_budget;
+use conditional_transfer::{accounts, instruction};
+use solana_program_test::{BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use solana_zk_token_sdk::encryption::pedersen::PedersenOpening;
+use solana_zk_token_sdk::instruction::{ProofType, ZkProofData};
+use solana_zk_token_sdk::zk_token_proof_state::ProofContextState;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const THRESHOLD: u64 = LAMPORTS_PER_SOL / 10;
+
+struct Bench {
+    banks: BanksClient,
+    payer: Keypair,
+    /// Kept only to warp past slot-gated scenarios.
+    context: ProgramTestContext,
+    results: BTreeMap<String, u64>,
+}
+
+impl Bench {
+    /// Execute `ix` signed by the payer (plus `signers`) and record its compute units.
+    async fn measure(&mut self, scenario: &str, ix: Instruction, signers: &[&Keypair]) {
+        self.measure_tx(scenario, &[ix], signers).await;
+    }
+
+    /// Like `measure`, for scenarios that need several instructions in one transaction.
+    async fn measure_tx(&mut self, scenario: &str, ixs: &[Instruction], signers: &[&Keypair]) {
+        let units = self.execute(scenario, ixs, signers).await;
+        self.results.insert(scenario.to_string(), units);
+    }
+
+    /// Execute `ixs` signed by the payer (plus `signers`), returning compute units consumed.
+    async fn execute(&mut self, scenario: &str, ixs: &[Instruction], signers: &[&Keypair]) -> u64 {
+        let blockhash = self.banks.get_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let outcome = self
+            .banks
+            .process_transaction_with_metadata(tx)
+            .await
+            .unwrap();
+        if let Err(err) = outcome.result {
+            panic!("scenario `{scenario}` failed: {err:?}");
+        }
+        outcome
+            .metadata
+            .map(|m| m.compute_units_consumed)
+            .unwrap_or_default()
+    }
+}
+
+fn manifest_path(rel: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(rel)
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+fn recipient_index(recipient: Pubkey) -> Pubkey {
+    pda(&[b"recipient_index", recipient.as_ref()])
+}
+
+fn ix(data: Vec<u8>, metas: Vec<AccountMeta>) -> Instruction {
+    Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: metas,
+        data,
+    }
+}
+
+/// Keys and addresses the scenario groups share. Every group runs against
+/// the one config, in `GROUPS` order.
+struct Fixture {
+    authority: Pubkey,
+    sponsor: Keypair,
+    approver: Keypair,
+    delegate: Keypair,
+    to: Pubkey,
+    route: Pubkey,
+    config: Pubkey,
+    global: Pubkey,
+    event_authority: Pubkey,
+    program: Pubkey,
+    escrow: Pubkey,
+    vault: Pubkey,
+    match_pool: Pubkey,
+    milestone: Pubkey,
+    epoch_summary: Pubkey,
+    price_feed: Pubkey,
+    range_proof: Pubkey,
+    threshold_opening: PedersenOpening,
+    fee_collector: Pubkey,
+    recent_slot: u64,
+}
+
+impl Fixture {
+    fn send_accounts_with(
+        &self,
+        match_pool: Option<Pubkey>,
+        epoch_summary: Option<Pubkey>,
+    ) -> accounts::SendIfOverThreshold {
+        accounts::SendIfOverThreshold {
+            config: self.config,
+            from: self.authority,
+            to: self.to,
+            instructions: None,
+            match_pool,
+            epoch_summary,
+            system_program: system_program::ID,
+            token_program: None,
+            recipient_token_account: None,
+            global: self.global,
+            sweep_destination: None,
+            policy: None,
+            approver: None,
+            recipient_list: None,
+            price_feed: None,
+            range_proof: None,
+            fee_collector: None,
+        }
+    }
+
+    fn send_accounts(&self, match_pool: Option<Pubkey>) -> accounts::SendIfOverThreshold {
+        self.send_accounts_with(match_pool, None)
+    }
+}
+
+/// A group of scenarios, measured in order. Each leaves the config in the
+/// state the next group expects.
+type Group = for<'a> fn(&'a mut Bench, &'a Fixture) -> Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// A fully verified Pyth `PriceUpdateV2` for SOL/USD at `price × 10^exponent`.
+fn sol_usd_price_update(price: i64, exponent: i32, publish_time: i64) -> Vec<u8> {
+    let mut data = solana_sdk::hash::hashv(&[b"account:PriceUpdateV2"]).to_bytes()[..8].to_vec();
+    data.extend_from_slice(&[0; 32]); // write_authority
+    data.push(1); // VerificationLevel::Full
+    data.extend_from_slice(&conditional_transfer::pyth::SOL_USD_FEED_ID);
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // conf
+    data.extend_from_slice(&exponent.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes()); // prev_publish_time
+    data.extend_from_slice(&price.to_le_bytes()); // ema_price
+    data.extend_from_slice(&0u64.to_le_bytes()); // ema_conf
+    data.extend_from_slice(&0u64.to_le_bytes()); // posted_slot
+    data
+}
+
+/// A ZK Token proof `RangeProofU64` context account showing `amount` meets
+/// `threshold` committed under `opening`.
+fn threshold_range_proof_context(
+    amount: u64,
+    threshold: u64,
+    opening: &PedersenOpening,
+) -> Vec<u8> {
+    let proof =
+        conditional_transfer::private_threshold::threshold_range_proof(amount, threshold, opening)
+            .unwrap();
+    ProofContextState::encode(
+        &Pubkey::new_unique(),
+        ProofType::RangeProofU64,
+        proof.context_data(),
+    )
+}
+
+/// Every scenario group, in the order they run.
+const GROUPS: &[Group] = &[
+    |bench, fx| Box::pin(initialize_and_configure(bench, fx)),
+    |bench, fx| Box::pin(authority_handoff(bench, fx)),
+    |bench, fx| Box::pin(escrow_and_refund(bench, fx)),
+    |bench, fx| Box::pin(sends(bench, fx)),
+    |bench, fx| Box::pin(approvals(bench, fx)),
+    |bench, fx| Box::pin(references_and_receipts(bench, fx)),
+    |bench, fx| Box::pin(send_variants(bench, fx)),
+    |bench, fx| Box::pin(epoch_summary_and_matching(bench, fx)),
+    |bench, fx| Box::pin(milestones(bench, fx)),
+    |bench, fx| Box::pin(scheduled_transfers(bench, fx)),
+    |bench, fx| Box::pin(streams(bench, fx)),
+    |bench, fx| Box::pin(bounties(bench, fx)),
+    |bench, fx| Box::pin(vault_allowances(bench, fx)),
+    |bench, fx| Box::pin(admin_and_views(bench, fx)),
+    |bench, fx| Box::pin(recovery_committee(bench, fx)),
+    |bench, fx| Box::pin(closing(bench, fx)),
+];
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    // The native (processor!) path is not metered, so insist on the SBF artifact.
+    let deploy_dir = std::env::var("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| manifest_path("../../target/deploy"));
+    if !deploy_dir.join("conditional_transfer.so").exists() {
+        eprintln!(
+            "conditional_transfer.so not found in {}; run `cargo build-sbf` first",
+            deploy_dir.display()
+        );
+        std::process::exit(1);
+    }
+    std::env::set_var("SBF_OUT_DIR", &deploy_dir);
+
+    let mut program_test = ProgramTest::new("conditional_transfer", conditional_transfer::ID, None);
+    program_test.prefer_bpf(true);
+    let price_feed = Pubkey::new_unique();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    program_test.add_account(
+        price_feed,
+        solana_sdk::account::Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: sol_usd_price_update(15_000_000_000, -8, now),
+            owner: conditional_transfer::pyth::PYTH_RECEIVER_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let range_proof = Pubkey::new_unique();
+    let threshold_opening = PedersenOpening::new_rand();
+    program_test.add_account(
+        range_proof,
+        solana_sdk::account::Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: threshold_range_proof_context(THRESHOLD, THRESHOLD / 2, &threshold_opening),
+            owner: conditional_transfer::private_threshold::ZK_PROOF_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let fee_collector = Pubkey::new_unique();
+    program_test.add_account(
+        fee_collector,
+        solana_sdk::account::Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: Vec::new(),
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let delegate = Keypair::new();
+    program_test.add_account(
+        delegate.pubkey(),
+        solana_sdk::account::Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: Vec::new(),
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let context = program_test.start_with_context().await;
+    let mut bench = Bench {
+        banks: context.banks_client.clone(),
+        payer: context.payer.insecure_clone(),
+        context,
+        results: BTreeMap::new(),
+    };
+
+    let authority = bench.payer.pubkey();
+    let route = Pubkey::new_unique();
+    let config = pda(&[b"config", authority.as_ref(), route.as_ref()]);
+    let clock = bench
+        .banks
+        .get_sysvar::<solana_sdk::clock::Clock>()
+        .await
+        .unwrap();
+    let fx = Fixture {
+        authority,
+        sponsor: Keypair::new(),
+        approver: Keypair::new(),
+        delegate,
+        to: Pubkey::new_unique(),
+        route,
+        config,
+        global: pda(&[b"global"]),
+        event_authority: pda(&[b"__event_authority"]),
+        program: conditional_transfer::ID,
+        escrow: pda(&[b"escrow", config.as_ref()]),
+        vault: pda(&[b"vault", config.as_ref()]),
+        match_pool: pda(&[b"match_pool", config.as_ref(), authority.as_ref()]),
+        milestone: pda(&[b"milestone", config.as_ref(), &0u32.to_le_bytes()]),
+        epoch_summary: pda(&[
+            b"epoch_summary",
+            config.as_ref(),
+            &clock.epoch.to_le_bytes(),
+        ]),
+        price_feed,
+        range_proof,
+        threshold_opening,
+        fee_collector,
+        recent_slot: clock.slot,
+    };
+    for group in GROUPS {
+        group(&mut bench, &fx).await;
+    }
+
+    std::process::exit(report(&bench.results));
+}
+
+/// Create the config and apply the settings later groups rely on.
+async fn initialize_and_configure(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        route,
+        config,
+        event_authority,
+        program,
+        recent_slot,
+        ..
+    } = fx;
+    bench
+        .measure(
+            "initialize",
+            ix(
+                instruction::Initialize {
+                    route,
+                    to,
+                    threshold_lamports: THRESHOLD,
+                }
+                .data(),
+                accounts::Initialize {
+                    authority,
+                    from: authority,
+                    config,
+                    recipient_index: recipient_index(to),
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "set_large_transfer_lamports",
+            ix(
+                instruction::SetLargeTransferLamports {
+                    lamports: THRESHOLD,
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "set_bridge_route",
+            ix(
+                instruction::SetBridgeRoute {
+                    route: Some(conditional_transfer::BridgeRoute {
+                        wormhole_program: conditional_transfer::WORMHOLE_LOCALNET_PROGRAM_ID,
+                        chain: 2,
+                        recipient: [7; 32],
+                    }),
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "set_token_threshold",
+            ix(
+                instruction::SetTokenThreshold {
+                    token_threshold: Some(conditional_transfer::TokenThreshold {
+                        mint: Pubkey::new_unique(),
+                        threshold_amount: 1_000_000,
+                    }),
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "register_viewer_key",
+            ix(
+                instruction::RegisterViewerKey {
+                    key_hash: [3; 32],
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::ManageViewerKeys {
+                    authority,
+                    config,
+                    viewer_keys: pda(&[b"viewer_keys", config.as_ref()]),
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "update_time_window",
+            ix(
+                instruction::UpdateTimeWindow {
+                    valid_from_unix: Some(0),
+                    valid_until_unix: None,
+                    update_id: 1,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "set_recipient_owners",
+            ix(
+                instruction::SetRecipientOwners {
+                    owners: vec![system_program::ID],
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "update_limits",
+            ix(
+                instruction::UpdateLimits {
+                    threshold_lamports: THRESHOLD,
+                    max_lamports: 100 * LAMPORTS_PER_SOL,
+                    update_id: 2,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "update_rate_limit",
+            ix(
+                instruction::UpdateRateLimit {
+                    capacity: 1_000 * LAMPORTS_PER_SOL,
+                    refill_per_second: 11_574_074,
+                    update_id: 3,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Clone the config, reset its stats, and hand its authority over and back.
+async fn authority_handoff(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        config,
+        event_authority,
+        program,
+        recent_slot,
+        ref sponsor,
+        ..
+    } = fx;
+    let clone_to = Pubkey::new_unique();
+    let clone_route = Pubkey::new_unique();
+    let clone_config = pda(&[b"config", authority.as_ref(), clone_route.as_ref()]);
+    bench
+        .measure(
+            "clone_config",
+            ix(
+                instruction::CloneConfig {
+                    route: clone_route,
+                    to: clone_to,
+                    recent_slot,
+                }
+                .data(),
+                accounts::CloneConfig {
+                    authority,
+                    from: authority,
+                    source: config,
+                    config: clone_config,
+                    recipient_index: pda(&[b"recipient_index", clone_to.as_ref()]),
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "reset_stats",
+            ix(
+                instruction::ResetStats {
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "propose_authority",
+            ix(
+                instruction::ProposeAuthority {
+                    new_authority: Some(sponsor.pubkey()),
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config: clone_config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "accept_authority",
+            ix(
+                instruction::AcceptAuthority {
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::AcceptAuthority {
+                    new_authority: sponsor.pubkey(),
+                    config: clone_config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[sponsor],
+        )
+        .await;
+}
+
+/// Deposit into the escrow, release part of it, then expire and refund the rest.
+async fn escrow_and_refund(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        global,
+        event_authority,
+        program,
+        recent_slot,
+        escrow,
+        ..
+    } = fx;
+    bench
+        .measure(
+            "deposit",
+            ix(
+                instruction::Deposit {
+                    lamports: 2 * THRESHOLD,
+                }
+                .data(),
+                accounts::Deposit {
+                    config,
+                    from: authority,
+                    escrow,
+                    system_program: system_program::ID,
+                    global,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "release",
+            ix(
+                instruction::Release {
+                    lamports: THRESHOLD,
+                }
+                .data(),
+                accounts::Release {
+                    config,
+                    escrow,
+                    to,
+                    system_program: system_program::ID,
+                    instructions: None,
+                    global,
+                    policy: None,
+                    approver: None,
+                    token_program: None,
+                    recipient_token_account: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    let set_escrow_expiry = |expiry_slot| {
+        ix(
+            instruction::SetEscrowExpiry {
+                expiry_slot,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    // Expired from the current slot, so the rest of the deposit is refundable.
+    bench
+        .measure(
+            "set_escrow_expiry",
+            set_escrow_expiry(Some(recent_slot)),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "refund",
+            ix(
+                instruction::Refund {}.data(),
+                accounts::Refund {
+                    config,
+                    from: authority,
+                    escrow,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .execute("set_escrow_expiry/clear", &[set_escrow_expiry(None)], &[])
+        .await;
+}
+
+/// `send_if_over_threshold` under each kind of threshold, fee, and signer, plus
+/// rejection logging and pausing.
+async fn sends(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        config,
+        global,
+        event_authority,
+        program,
+        price_feed,
+        range_proof,
+        fee_collector,
+        recent_slot,
+        ref delegate,
+        ref threshold_opening,
+        ..
+    } = fx;
+    bench
+        .measure(
+            "send_if_over_threshold",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                fx.send_accounts(None).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/memo",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: Some("m".repeat(conditional_transfer::MAX_MEMO_LEN)),
+                }
+                .data(),
+                fx.send_accounts(None).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    let set_usd_threshold = |threshold_usd_cents| {
+        ix(
+            instruction::SetUsdThreshold {
+                threshold_usd_cents,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    // $10 against 0.1 SOL at $150.
+    bench
+        .measure("set_usd_threshold", set_usd_threshold(Some(1_000)), &[])
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/usd_threshold",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                accounts::SendIfOverThreshold {
+                    price_feed: Some(price_feed),
+                    ..send_accounts(None)
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .execute("set_usd_threshold/clear", &[set_usd_threshold(None)], &[])
+        .await;
+    let set_auto_adjust = |auto_adjust| {
+        ix(
+            instruction::SetAutoAdjust {
+                auto_adjust,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    // $15 at $150 re-pegs the threshold at 0.1 SOL, where it already is.
+    bench
+        .measure(
+            "set_auto_adjust",
+            set_auto_adjust(Some(conditional_transfer::AutoAdjust {
+                target_usd_cents: 1_500,
+                min_lamports: THRESHOLD / 2,
+                max_lamports: THRESHOLD * 2,
+                interval_seconds: 3_600,
+            })),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "adjust_threshold",
+            ix(
+                instruction::AdjustThreshold {}.data(),
+                accounts::AdjustThreshold {
+                    cranker: authority,
+                    config,
+                    price_feed,
+                    global,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .execute("set_auto_adjust/clear", &[set_auto_adjust(None)], &[])
+        .await;
+    let set_private_threshold = |commitment| {
+        ix(
+            instruction::SetPrivateThreshold {
+                commitment,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure(
+            "set_private_threshold",
+            set_private_threshold(Some(
+                conditional_transfer::private_threshold::commit_threshold(
+                    THRESHOLD / 2,
+                    threshold_opening,
+                ),
+            )),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/private_threshold",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                accounts::SendIfOverThreshold {
+                    range_proof: Some(range_proof),
+                    ..send_accounts(None)
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .execute(
+            "set_private_threshold/clear",
+            &[set_private_threshold(None)],
+            &[],
+        )
+        .await;
+    let set_fee = |fee_bps| {
+        ix(
+            instruction::SetFee {
+                fee_bps,
+                fee_collector,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench.measure("set_fee", set_fee(25), &[]).await;
+    bench
+        .measure(
+            "send_if_over_threshold/fee",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                accounts::SendIfOverThreshold {
+                    fee_collector: Some(fee_collector),
+                    ..send_accounts(None)
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench.execute("set_fee/clear", &[set_fee(0)], &[]).await;
+    let manage_delegate = |data: Vec<u8>| {
+        ix(
+            data,
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure(
+            "add_delegate",
+            manage_delegate(
+                instruction::AddDelegate {
+                    delegate: delegate.pubkey(),
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/delegate",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                accounts::SendIfOverThreshold {
+                    from: delegate.pubkey(),
+                    ..send_accounts(None)
+                }
+                .to_account_metas(None),
+            ),
+            &[delegate],
+        )
+        .await;
+    bench
+        .measure(
+            "remove_delegate",
+            manage_delegate(
+                instruction::RemoveDelegate {
+                    delegate: delegate.pubkey(),
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "update_comparison_mode",
+            ix(
+                instruction::UpdateComparisonMode {
+                    comparison_mode: conditional_transfer::ComparisonMode::Gte,
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    let set_log_rejections = |enabled| {
+        ix(
+            instruction::SetLogRejections {
+                enabled,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure("set_log_rejections", set_log_rejections(true), &[])
+        .await;
+    bench
+        .measure(
+            "try_send/rejected",
+            ix(
+                instruction::TrySend { lamports: 2 }.data(),
+                fx.send_accounts(None).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .execute("set_log_rejections/off", &[set_log_rejections(false)], &[])
+        .await;
+    let set_paused = |paused| {
+        ix(
+            instruction::SetPaused {
+                paused,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench.measure("set_paused", set_paused(true), &[]).await;
+    bench
+        .execute("set_paused/resume", &[set_paused(false)], &[])
+        .await;
+}
+
+/// Quorum approval: propose, approve, execute, and cancel pending transfers.
+async fn approvals(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        event_authority,
+        program,
+        recent_slot,
+        ref sponsor,
+        ..
+    } = fx;
+    let set_approvers = |members: Vec<Pubkey>, quorum| {
+        ix(
+            instruction::SetApprovers {
+                members,
+                quorum,
+                update_id: 0,
+                recent_slot,
+            }
+            .data(),
+            accounts::Update {
+                authority,
+                config,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure(
+            "set_approvers",
+            set_approvers(vec![authority, sponsor.pubkey()], 2),
+            &[],
+        )
+        .await;
+    let propose_transfer = |id: u64| {
+        ix(
+            instruction::ProposeTransfer {
+                id,
+                to,
+                lamports: THRESHOLD,
+            }
+            .data(),
+            accounts::ProposeTransfer {
+                proposer: authority,
+                config,
+                pending_transfer: pda(&[b"pending_transfer", config.as_ref(), &id.to_le_bytes()]),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure("propose_transfer", propose_transfer(1), &[])
+        .await;
+    let pending_transfer = pda(&[b"pending_transfer", config.as_ref(), &1u64.to_le_bytes()]);
+    bench
+        .measure(
+            "approve_transfer",
+            ix(
+                instruction::ApproveTransfer {}.data(),
+                accounts::ApproveTransfer {
+                    approver: sponsor.pubkey(),
+                    config,
+                    pending_transfer,
+                }
+                .to_account_metas(None),
+            ),
+            &[sponsor],
+        )
+        .await;
+    bench
+        .measure(
+            "execute_transfer",
+            ix(
+                instruction::ExecuteTransfer {}.data(),
+                accounts::ExecuteTransfer {
+                    send: fx.send_accounts(None),
+                    pending_transfer,
+                    proposer: authority,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .execute("propose_transfer/2", &[propose_transfer(2)], &[])
+        .await;
+    bench
+        .measure(
+            "cancel_transfer",
+            ix(
+                instruction::CancelTransfer {}.data(),
+                accounts::CancelTransfer {
+                    signer: authority,
+                    config,
+                    pending_transfer: pda(&[
+                        b"pending_transfer",
+                        config.as_ref(),
+                        &2u64.to_le_bytes(),
+                    ]),
+                    proposer: authority,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .execute("set_approvers/clear", &[set_approvers(Vec::new(), 0)], &[])
+        .await;
+}
+
+/// Payment references, recipient lists, receipts, and nonces.
+async fn references_and_receipts(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        config,
+        event_authority,
+        program,
+        recent_slot,
+        ..
+    } = fx;
+    let mut metas = fx.send_accounts(None).to_account_metas(None);
+    metas.extend(
+        (0..conditional_transfer::MAX_PAYMENT_REFERENCES)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false)),
+    );
+    bench
+        .measure(
+            "send_if_over_threshold/payment_references",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                metas,
+            ),
+            &[],
+        )
+        .await;
+
+    let listed = Pubkey::new_unique();
+    let recipient_list = pda(&[b"recipient_list", config.as_ref()]);
+    bench
+        .measure(
+            "add_recipient",
+            ix(
+                instruction::AddRecipient {
+                    recipient: listed,
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::ManageRecipientList {
+                    authority,
+                    config,
+                    recipient_list,
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/listed_recipient",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                accounts::SendIfOverThreshold {
+                    to: listed,
+                    recipient_list: Some(recipient_list),
+                    ..send_accounts(None)
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    let reference = [7u8; 32];
+    let receipt = pda(&[b"receipt", config.as_ref(), &reference]);
+    bench
+        .measure(
+            "send_with_receipt",
+            ix(
+                instruction::SendWithReceipt {
+                    lamports: THRESHOLD,
+                    reference,
+                }
+                .data(),
+                accounts::SendWithReceipt {
+                    send: fx.send_accounts(None),
+                    payer: authority,
+                    receipt,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    let send_with_nonce = |nonce| {
+        ix(
+            instruction::SendWithNonce {
+                lamports: THRESHOLD,
+                nonce,
+            }
+            .data(),
+            accounts::SendWithNonce {
+                send: fx.send_accounts(None),
+                payer: authority,
+                nonces: pda(&[b"send_nonces", config.as_ref(), authority.as_ref()]),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure("send_with_nonce", send_with_nonce(1), &[])
+        .await;
+    // A nonce far past the window slides it, the costliest claim.
+    bench
+        .measure("send_with_nonce/slide", send_with_nonce(1_000), &[])
+        .await;
+    bench
+        .measure(
+            "verify_payment",
+            ix(
+                instruction::VerifyPayment {
+                    reference,
+                    min_lamports: THRESHOLD,
+                }
+                .data(),
+                accounts::VerifyPayment { config, receipt }.to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "migrate_receipt",
+            ix(
+                instruction::MigrateReceipt {}.data(),
+                accounts::MigrateReceipt {
+                    payer: authority,
+                    receipt,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "close_receipt",
+            ix(
+                instruction::CloseReceipt {}.data(),
+                accounts::CloseReceipt {
+                    payer: authority,
+                    receipt,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// The other send instructions: v2, `try_send`, joint, split, and seeded sends.
+async fn send_variants(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        global,
+        event_authority,
+        program,
+        recent_slot,
+        ..
+    } = fx;
+    bench
+        .measure(
+            "send_if_over_threshold_v2",
+            ix(
+                instruction::SendIfOverThresholdV2 {
+                    amount: conditional_transfer::TypedAmount {
+                        unit: conditional_transfer::AmountUnit::MilliSol,
+                        value: 100,
+                    },
+                    memo: None,
+                }
+                .data(),
+                fx.send_accounts(None).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    bench
+        .measure(
+            "try_send/below_threshold",
+            ix(
+                instruction::TrySend { lamports: 1 }.data(),
+                fx.send_accounts(None).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    bench
+        .measure(
+            "set_below_threshold_behavior",
+            ix(
+                instruction::SetBelowThresholdBehavior {
+                    behavior: conditional_transfer::BelowThresholdBehavior::Noop,
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/skipped",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: 1,
+                    memo: None,
+                }
+                .data(),
+                fx.send_accounts(None).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    bench
+        .measure(
+            "set_cpi_policy",
+            ix(
+                instruction::SetCpiPolicy {
+                    restrict: true,
+                    allowed_programs: vec![],
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    let joint_send = |lamports| {
+        ix(
+            instruction::SendJointIfOverThreshold { lamports }.data(),
+            accounts::SendJoint {
+                config,
+                from: authority,
+                to,
+                instructions: solana_sdk::sysvar::instructions::ID,
+                system_program: system_program::ID,
+                token_program: None,
+                recipient_token_account: None,
+                global,
+                policy: None,
+                approver: None,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure_tx(
+            "send_joint_if_over_threshold/x2",
+            &[joint_send(THRESHOLD * 6 / 10), joint_send(THRESHOLD / 2)],
+            &[],
+        )
+        .await;
+
+    let split_table = pda(&[b"split_table", config.as_ref()]);
+    let mut split_recipients = [to, Pubkey::new_unique(), Pubkey::new_unique()];
+    split_recipients.sort();
+    bench
+        .measure(
+            "set_split_recipients",
+            ix(
+                instruction::SetSplitRecipients {
+                    entries: split_recipients
+                        .iter()
+                        .zip([5_000, 3_000, 2_000])
+                        .map(|(&recipient, share_bps)| conditional_transfer::SplitEntry {
+                            recipient,
+                            share_bps,
+                        })
+                        .collect(),
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::ManageSplitTable {
+                    authority,
+                    config,
+                    split_table,
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    let mut metas = accounts::SendSplit {
+        config,
+        from: authority,
+        split_table,
+        system_program: system_program::ID,
+        instructions: None,
+        global,
+        policy: None,
+        approver: None,
+    }
+    .to_account_metas(None);
+    metas.extend(
+        split_recipients
+            .iter()
+            .map(|&recipient| AccountMeta::new(recipient, false)),
+    );
+    bench
+        .measure(
+            "send_split_if_over_threshold/x3",
+            ix(
+                instruction::SendSplitIfOverThreshold {
+                    lamports: THRESHOLD,
+                }
+                .data(),
+                metas,
+            ),
+            &[],
+        )
+        .await;
+
+    let seeded_recipient = conditional_transfer::SeededRecipient {
+        base: authority,
+        owner: system_program::ID,
+    };
+    bench
+        .measure(
+            "set_seeded_recipient",
+            ix(
+                instruction::SetSeededRecipient {
+                    seeded_recipient: Some(seeded_recipient),
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    let seed = "deposit-0001";
+    bench
+        .measure(
+            "send_to_seeded_address",
+            ix(
+                instruction::SendToSeededAddress {
+                    lamports: THRESHOLD,
+                    seed: seed.to_string(),
+                }
+                .data(),
+                accounts::SendSeeded {
+                    config,
+                    from: authority,
+                    to: seeded_recipient.address(seed).unwrap(),
+                    system_program: system_program::ID,
+                    instructions: None,
+                    global,
+                    policy: None,
+                    approver: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Sends recorded in an epoch summary and topped up from a match pool.
+async fn epoch_summary_and_matching(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        config,
+        match_pool,
+        epoch_summary,
+        ..
+    } = fx;
+    bench
+        .measure(
+            "open_epoch_summary",
+            ix(
+                instruction::OpenEpochSummary {}.data(),
+                accounts::OpenEpochSummary {
+                    payer: authority,
+                    config,
+                    epoch_summary,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/epoch_summary",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                fx.send_accounts_with(None, Some(epoch_summary))
+                    .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    bench
+        .measure(
+            "create_match_pool",
+            ix(
+                instruction::CreateMatchPool {
+                    ratio_bps: 10_000,
+                    cap_lamports: LAMPORTS_PER_SOL,
+                }
+                .data(),
+                accounts::CreateMatchPool {
+                    sponsor: authority,
+                    config,
+                    match_pool,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "fund_match_pool",
+            ix(
+                instruction::FundMatchPool {
+                    lamports: LAMPORTS_PER_SOL,
+                }
+                .data(),
+                accounts::FundMatchPool {
+                    funder: authority,
+                    match_pool,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "send_if_over_threshold/matched",
+            ix(
+                instruction::SendIfOverThreshold {
+                    lamports: THRESHOLD,
+                    memo: None,
+                }
+                .data(),
+                fx.send_accounts(Some(match_pool)).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Create, approve, release, and close a milestone.
+async fn milestones(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        milestone,
+        ref approver,
+        ..
+    } = fx;
+    bench
+        .measure(
+            "create_milestone",
+            ix(
+                instruction::CreateMilestone {
+                    index: 0,
+                    amount: THRESHOLD,
+                    approver: approver.pubkey(),
+                }
+                .data(),
+                accounts::CreateMilestone {
+                    config,
+                    from: authority,
+                    milestone,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "approve_milestone",
+            ix(
+                instruction::ApproveMilestone {}.data(),
+                accounts::ApproveMilestone {
+                    approver: approver.pubkey(),
+                    milestone,
+                }
+                .to_account_metas(None),
+            ),
+            &[approver],
+        )
+        .await;
+    bench
+        .measure(
+            "release_milestone",
+            ix(
+                instruction::ReleaseMilestone {}.data(),
+                accounts::ReleaseMilestone {
+                    config,
+                    milestone,
+                    to,
+                    policy: None,
+                    instructions: None,
+                    approver: None,
+                    token_program: None,
+                    recipient_token_account: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    bench
+        .measure(
+            "close_milestone",
+            ix(
+                instruction::CloseMilestone {}.data(),
+                accounts::CloseMilestone {
+                    payer: authority,
+                    milestone,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Schedule, cancel, execute, and refund scheduled transfers, warping past
+/// their unlock and expiry slots.
+async fn scheduled_transfers(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        global,
+        ..
+    } = fx;
+    let scheduled = |id: u64| pda(&[b"scheduled_transfer", config.as_ref(), &id.to_le_bytes()]);
+    let schedule_transfer = |id: u64, unlock_slot, expiry_slot| {
+        ix(
+            instruction::ScheduleTransfer {
+                id,
+                lamports: THRESHOLD,
+                unlock_slot,
+                expiry_slot,
+            }
+            .data(),
+            accounts::ScheduleTransfer {
+                config,
+                from: authority,
+                scheduled: scheduled(id),
+                policy: None,
+                system_program: system_program::ID,
+                instructions: None,
+                global,
+            }
+            .to_account_metas(None),
+        )
+    };
+    let unlock_slot = bench
+        .banks
+        .get_sysvar::<solana_sdk::clock::Clock>()
+        .await
+        .unwrap()
+        .slot
+        + 2;
+    bench
+        .measure(
+            "schedule_transfer",
+            schedule_transfer(0, unlock_slot, None),
+            &[],
+        )
+        .await;
+    bench
+        .execute(
+            "schedule_transfer/to_cancel",
+            &[schedule_transfer(1, u64::MAX, None)],
+            &[],
+        )
+        .await;
+    bench
+        .execute(
+            "schedule_transfer/to_expire",
+            &[schedule_transfer(2, unlock_slot, Some(unlock_slot + 1))],
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "cancel_scheduled",
+            ix(
+                instruction::CancelScheduled {}.data(),
+                accounts::CancelScheduled {
+                    payer: authority,
+                    scheduled: scheduled(1),
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench.context.warp_to_slot(unlock_slot).unwrap();
+    bench
+        .measure(
+            "execute_scheduled",
+            ix(
+                instruction::ExecuteScheduled {}.data(),
+                accounts::ExecuteScheduled {
+                    config,
+                    scheduled: scheduled(0),
+                    payer: authority,
+                    to,
+                    policy: None,
+                    instructions: None,
+                    global,
+                    approver: None,
+                    token_program: None,
+                    recipient_token_account: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench.context.warp_to_slot(unlock_slot + 1).unwrap();
+    bench
+        .measure(
+            "refund_scheduled",
+            ix(
+                instruction::RefundScheduled {}.data(),
+                accounts::CancelScheduled {
+                    payer: authority,
+                    scheduled: scheduled(2),
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Create, withdraw from, and cancel a stream, then close the match pool.
+async fn streams(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        match_pool,
+        ..
+    } = fx;
+    let stream = pda(&[b"stream", config.as_ref(), &0u32.to_le_bytes()]);
+    bench
+        .measure(
+            "create_stream",
+            ix(
+                instruction::CreateStream {
+                    index: 0,
+                    total: 3 * THRESHOLD,
+                    start_ts: 0,
+                    end_ts: 1,
+                }
+                .data(),
+                accounts::CreateStream {
+                    config,
+                    from: authority,
+                    stream,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "withdraw_stream",
+            ix(
+                instruction::WithdrawStream {
+                    lamports: THRESHOLD,
+                }
+                .data(),
+                accounts::WithdrawStream {
+                    config,
+                    stream,
+                    to,
+                    policy: None,
+                    instructions: None,
+                    approver: None,
+                    token_program: None,
+                    recipient_token_account: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "cancel_stream",
+            ix(
+                instruction::CancelStream {}.data(),
+                accounts::CancelStream {
+                    payer: authority,
+                    config,
+                    stream,
+                    to,
+                    token_program: None,
+                    recipient_token_account: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "close_match_pool",
+            ix(
+                instruction::CloseMatchPool {}.data(),
+                accounts::CloseMatchPool {
+                    sponsor: authority,
+                    match_pool,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Create, win, and close a bounty.
+async fn bounties(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture { authority, to, .. } = fx;
+    let bounty = pda(&[b"bounty", authority.as_ref(), to.as_ref()]);
+    bench
+        .measure(
+            "create_bounty",
+            ix(
+                instruction::CreateBounty {
+                    target: to,
+                    min_lamports: THRESHOLD,
+                    reward: THRESHOLD,
+                    expires_at: i64::MAX,
+                }
+                .data(),
+                accounts::CreateBounty {
+                    sponsor: authority,
+                    bounty,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "fund_bounty_target/won",
+            ix(
+                instruction::FundBountyTarget {
+                    lamports: THRESHOLD,
+                }
+                .data(),
+                accounts::FundBountyTarget {
+                    sender: authority,
+                    bounty,
+                    target: to,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "close_bounty",
+            ix(
+                instruction::CloseBounty {}.data(),
+                accounts::CloseBounty {
+                    sponsor: authority,
+                    bounty,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Fund the vault and mint, redeem, and revoke an allowance against it.
+async fn vault_allowances(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        global,
+        vault,
+        ref sponsor,
+        ..
+    } = fx;
+    let holder = sponsor;
+    let allowance = pda(&[b"allowance", config.as_ref(), holder.pubkey().as_ref()]);
+    bench
+        .measure(
+            "fund_vault",
+            ix(
+                instruction::FundVault {
+                    lamports: LAMPORTS_PER_SOL,
+                }
+                .data(),
+                accounts::FundVault {
+                    funder: authority,
+                    config,
+                    vault,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "mint_allowance",
+            ix(
+                instruction::MintAllowance {
+                    holder: holder.pubkey(),
+                    amount: THRESHOLD * 2,
+                    expires_at: i64::MAX,
+                    recipient: None,
+                }
+                .data(),
+                accounts::MintAllowance {
+                    authority,
+                    config,
+                    allowance,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "redeem_allowance",
+            ix(
+                instruction::RedeemAllowance {
+                    lamports: THRESHOLD,
+                }
+                .data(),
+                accounts::RedeemAllowance {
+                    holder: holder.pubkey(),
+                    config,
+                    allowance,
+                    vault,
+                    recipient: to,
+                    system_program: system_program::ID,
+                    global,
+                    policy: None,
+                    recipient_list: None,
+                    approver: None,
+                    instructions: None,
+                    token_program: None,
+                    recipient_token_account: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[holder],
+        )
+        .await;
+    bench
+        .measure(
+            "revoke_allowance",
+            ix(
+                instruction::RevokeAllowance {}.data(),
+                accounts::RevokeAllowance {
+                    authority,
+                    config,
+                    allowance,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Lamport recovery, the event schema, view instructions, and remaining
+/// config updates.
+async fn admin_and_views(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        to,
+        config,
+        event_authority,
+        program,
+        recent_slot,
+        ref sponsor,
+        ..
+    } = fx;
+    // Stray lamports sent straight to the config PDA, then swept back out.
+    bench
+        .execute(
+            "setup/stray_lamports",
+            &[solana_sdk::system_instruction::transfer(
+                &authority, &config, THRESHOLD,
+            )],
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "recover_lamports",
+            ix(
+                instruction::RecoverLamports {}.data(),
+                accounts::RecoverLamports {
+                    authority,
+                    config,
+                    destination: authority,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    let event_schema = pda(&[b"event_schema"]);
+    bench
+        .measure(
+            "initialize_event_schema",
+            ix(
+                instruction::InitializeEventSchema {}.data(),
+                accounts::InitializeEventSchema {
+                    payer: authority,
+                    event_schema,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "sync_event_schema",
+            ix(
+                instruction::SyncEventSchema {}.data(),
+                accounts::SyncEventSchema { event_schema }.to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+
+    bench
+        .measure(
+            "get_event_seq",
+            ix(
+                instruction::GetEventSeq {}.data(),
+                accounts::ReadConfig { config }.to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "get_config",
+            ix(
+                instruction::GetConfig {}.data(),
+                accounts::ReadEffectivePolicy {
+                    config,
+                    policy: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "resolve_effective_policy",
+            ix(
+                instruction::ResolveEffectivePolicy {}.data(),
+                accounts::ReadEffectivePolicy {
+                    config,
+                    policy: None,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "update_threshold",
+            ix(
+                instruction::UpdateThreshold {
+                    new_threshold_lamports: THRESHOLD,
+                    update_id: 4,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "update_default_memo",
+            ix(
+                instruction::UpdateDefaultMemo {
+                    memo: "invoice".to_string(),
+                    update_id: 5,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "attest",
+            ix(
+                instruction::Attest {
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "migrate_config",
+            ix(
+                instruction::MigrateConfig {}.data(),
+                accounts::MigrateConfig {
+                    authority,
+                    config,
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "set_authority_permissions",
+            ix(
+                instruction::SetAuthorityPermissions {
+                    key: sponsor.pubkey(),
+                    permissions: conditional_transfer::PERM_UPDATE_THRESHOLD,
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "update_addresses",
+            ix(
+                instruction::UpdateAddresses {
+                    new_from: authority,
+                    new_to: sponsor.pubkey(),
+                    update_id: 6,
+                    recent_slot,
+                }
+                .data(),
+                accounts::UpdateAddresses {
+                    authority,
+                    config,
+                    old_recipient_index: Some(recipient_index(to)),
+                    new_recipient_index: recipient_index(sponsor.pubkey()),
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Configure the recovery committee and propose, approve, and veto a recovery.
+async fn recovery_committee(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        config,
+        event_authority,
+        program,
+        recent_slot,
+        ref approver,
+        ..
+    } = fx;
+    let recovery = pda(&[b"recovery", config.as_ref()]);
+    let recovery_action = |member| accounts::RecoveryAction {
+        member,
+        config,
+        recovery,
+        event_authority,
+        program,
+    };
+    bench
+        .measure(
+            "configure_recovery",
+            ix(
+                instruction::ConfigureRecovery {
+                    committee: vec![authority, approver.pubkey()],
+                    threshold: 2,
+                    delay_seconds: 7 * 24 * 60 * 60,
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::ConfigureRecovery {
+                    authority,
+                    config,
+                    recovery,
+                    system_program: system_program::ID,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "propose_recovery",
+            ix(
+                instruction::ProposeRecovery {
+                    new_authority: Pubkey::new_unique(),
+                }
+                .data(),
+                recovery_action(authority).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "approve_recovery",
+            ix(
+                instruction::ApproveRecovery {}.data(),
+                recovery_action(approver.pubkey()).to_account_metas(None),
+            ),
+            &[approver],
+        )
+        .await;
+    bench
+        .measure(
+            "veto_recovery",
+            ix(
+                instruction::VetoRecovery {}.data(),
+                recovery_action(authority).to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+}
+
+/// Request, cancel, and finally perform the config's close; runs last.
+async fn closing(bench: &mut Bench, fx: &Fixture) {
+    let &Fixture {
+        authority,
+        config,
+        event_authority,
+        program,
+        recent_slot,
+        escrow,
+        vault,
+        ref sponsor,
+        ..
+    } = fx;
+    let close_config = || {
+        ix(
+            instruction::CloseConfig {}.data(),
+            accounts::CloseConfig {
+                authority,
+                config,
+                recipient_index: Some(recipient_index(sponsor.pubkey())),
+                escrow,
+                vault,
+                event_authority,
+                program,
+            }
+            .to_account_metas(None),
+        )
+    };
+    bench
+        .measure("close_config/request", close_config(), &[])
+        .await;
+    bench
+        .measure(
+            "withdraw_vault",
+            ix(
+                instruction::WithdrawVault {}.data(),
+                accounts::WithdrawVault {
+                    from: authority,
+                    config,
+                    vault,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    bench
+        .measure(
+            "cancel_close_config",
+            ix(
+                instruction::CancelCloseConfig {
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    // One-way, so it runs after every scenario that lowers the threshold.
+    bench
+        .measure(
+            "enable_monotonic_threshold",
+            ix(
+                instruction::EnableMonotonicThreshold {
+                    update_id: 0,
+                    recent_slot,
+                }
+                .data(),
+                accounts::Update {
+                    authority,
+                    config,
+                    event_authority,
+                    program,
+                }
+                .to_account_metas(None),
+            ),
+            &[],
+        )
+        .await;
+    // The close repeats the request's instruction, so it needs a new blockhash.
+    let stale = bench.banks.get_latest_blockhash().await.unwrap();
+    while bench.banks.get_latest_blockhash().await.unwrap() == stale {}
+    bench.measure("close_config", close_config(), &[]).await;
+}
+
+/// The `compute_budget` limit for `scenario`'s instruction and item count.
+fn hinted_units(scenario: &str) -> u64 {
+    let (instruction, variant) = scenario.split_once('/').unwrap_or((scenario, ""));
+    let items = variant
+        .strip_prefix('x')
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0);
+    recommended_compute_budget(instruction).compute_unit_limit(items) as u64
+}
+
+/// Compare `measured` with the stored baseline and the budget hints, print a
+/// table, and return the exit code.
+fn report(measured: &BTreeMap<String, u64>) -> i32 {
+    let baseline_path = manifest_path("benches/compute_units.json");
+    let update = std::env::var("CU_UPDATE_BASELINE").is_ok_and(|v| v == "1");
+    let stored: Option<BTreeMap<String, u64>> = std::fs::read_to_string(&baseline_path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok());
+    if stored.is_none() && !update {
+        eprintln!(
+            "no readable baseline at {}; run with CU_UPDATE_BASELINE=1 to record one",
+            baseline_path.display()
+        );
+        return 1;
+    }
+    let mut baseline = stored.unwrap_or_default();
+    let tolerance_pct: u64 = std::env::var("CU_TOLERANCE_PCT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+
+    let mut regressions = 0;
+    let mut missing = 0;
+    let mut over_hint = 0;
+    println!(
+        "{:<36} {:>10} {:>10} {:>10}",
+        "scenario", "baseline", "measured", "hint"
+    );
+    for (scenario, &units) in measured {
+        let hint = hinted_units(scenario);
+        let status = match baseline.get(scenario) {
+            _ if units > hint => {
+                over_hint += 1;
+                "OVER HINT"
+            }
+            Some(&base) if units * 100 > base * (100 + tolerance_pct) => {
+                regressions += 1;
+                "REGRESSED"
+            }
+            Some(_) => "ok",
+            None => {
+                missing += 1;
+                "MISSING"
+            }
+        };
+        let base = baseline
+            .get(scenario)
+            .map(|b| b.to_string())
+            .unwrap_or_else(|| "-".into());
+        println!("{scenario:<36} {base:>10} {units:>10} {hint:>10}  {status}");
+    }
+
+    if update {
+        baseline.extend(measured.clone());
+        let json = serde_json::to_string_pretty(&baseline).unwrap();
+        std::fs::write(&baseline_path, json + "\n").unwrap();
+        println!("baseline written to {}", baseline_path.display());
+    }
+
+    if over_hint > 0 {
+        eprintln!("{over_hint} scenario(s) exceeded their compute_budget hint");
+        return 1;
+    }
+    if regressions > 0 && !update {
+        eprintln!("{regressions} scenario(s) exceeded baseline by more than {tolerance_pct}%");
+        return 1;
+    }
+    if missing > 0 && !update {
+        eprintln!("{missing} scenario(s) missing from the baseline; run with CU_UPDATE_BASELINE=1");
+        return 1;
+    }
+    0
+}
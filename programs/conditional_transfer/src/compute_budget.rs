@@ -0,0 +1,309 @@
+//! Compute-budget guidance for each instruction, for wallets and SDKs.
+//!
+//! Every figure is an upper bound on the compute units the instruction
+//! consumes on the worst path through it (policy, match pool, epoch summary,
+//! event CPI), with headroom for account-size variance. The SBF bench
+//! (`benches/compute_units.rs`) fails if any scenario exceeds its hint. The
+//! numbers are `#[constant]`s so they land in the IDL's `constants`, named
+//! `<INSTRUCTION>_COMPUTE_UNITS`; instructions whose cost grows with the
+//! remaining accounts add `<INSTRUCTION>_COMPUTE_UNITS_PER_ITEM`, and ones that
+//! should outbid the default priority fee add
+//! `<INSTRUCTION>_PRIORITY_FEE_PERCENTILE`. Instructions without their own
+//! constants use the `DEFAULT_*` values.
+
+use anchor_lang::prelude::*;
+
+/// Largest compute-unit limit a transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Budget for instructions without their own hint (setters, closes, views).
+#[constant]
+pub const DEFAULT_COMPUTE_UNITS: u32 = 40_000;
+
+/// Percentile of recent prioritization fees to bid by default.
+#[constant]
+pub const DEFAULT_PRIORITY_FEE_PERCENTILE: u8 = 50;
+
+/// Percentile for sends, which race the balance and config they were
+/// simulated against.
+#[constant]
+pub const SEND_PRIORITY_FEE_PERCENTILE: u8 = 75;
+
+#[constant]
+pub const INITIALIZE_COMPUTE_UNITS: u32 = 60_000;
+
+#[constant]
+pub const CLONE_CONFIG_COMPUTE_UNITS: u32 = 60_000;
+
+#[constant]
+pub const SEND_IF_OVER_THRESHOLD_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const SEND_IF_OVER_THRESHOLD_V2_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const TRY_SEND_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const SEND_WITH_RECEIPT_COMPUTE_UNITS: u32 = 70_000;
+
+/// Includes creating the sender's nonce account on first use.
+#[constant]
+pub const SEND_WITH_NONCE_COMPUTE_UNITS: u32 = 70_000;
+
+#[constant]
+pub const SEND_TOKENS_IF_OVER_THRESHOLD_COMPUTE_UNITS: u32 = 60_000;
+
+/// Includes the Wormhole `post_message` CPI and its fee transfer.
+#[constant]
+pub const SEND_BRIDGED_COMPUTE_UNITS: u32 = 100_000;
+
+#[constant]
+pub const SEND_JOINT_IF_OVER_THRESHOLD_COMPUTE_UNITS: u32 = 30_000;
+
+/// Per joint sender: its config checks and transfer.
+#[constant]
+pub const SEND_JOINT_IF_OVER_THRESHOLD_COMPUTE_UNITS_PER_ITEM: u32 = 30_000;
+
+#[constant]
+pub const SEND_VALIDATOR_PAYOUT_COMPUTE_UNITS: u32 = 30_000;
+
+/// Per payout-table entry: the vote-account read and the share transfer.
+#[constant]
+pub const SEND_VALIDATOR_PAYOUT_COMPUTE_UNITS_PER_ITEM: u32 = 15_000;
+
+#[constant]
+pub const SEND_SPLIT_IF_OVER_THRESHOLD_COMPUTE_UNITS: u32 = 30_000;
+
+/// Per split-table recipient: the recipient checks and the share transfer.
+#[constant]
+pub const SEND_SPLIT_IF_OVER_THRESHOLD_COMPUTE_UNITS_PER_ITEM: u32 = 10_000;
+
+#[constant]
+pub const SEND_TO_SEEDED_ADDRESS_COMPUTE_UNITS: u32 = 40_000;
+
+/// The `send_if_over_threshold` path plus the pending-transfer close.
+#[constant]
+pub const EXECUTE_TRANSFER_COMPUTE_UNITS: u32 = 60_000;
+
+#[constant]
+pub const RELEASE_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const FUND_BOUNTY_TARGET_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const RELEASE_MILESTONE_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const EXECUTE_SCHEDULED_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const REDEEM_ALLOWANCE_COMPUTE_UNITS: u32 = 50_000;
+
+#[constant]
+pub const OPEN_EPOCH_SUMMARY_COMPUTE_UNITS: u32 = 50_000;
+
+/// Includes the ed25519 instruction introspection.
+#[constant]
+pub const APPLY_SIGNED_PROPOSAL_COMPUTE_UNITS: u32 = 60_000;
+
+#[constant]
+pub const UPDATE_ADDRESSES_COMPUTE_UNITS: u32 = 60_000;
+
+#[constant]
+pub const EXECUTE_RECOVERY_COMPUTE_UNITS: u32 = 60_000;
+
+/// Budget guidance for one instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputeBudgetHint {
+    /// Instruction name, as in the IDL (snake case).
+    pub instruction: &'static str,
+    /// Units for the instruction with no remaining-account items.
+    pub compute_units: u32,
+    /// Extra units per item: joint sender, payout-table entry, or split recipient.
+    pub compute_units_per_item: u32,
+    /// Percentile of recent prioritization fees to bid.
+    pub priority_fee_percentile: u8,
+}
+
+impl ComputeBudgetHint {
+    /// Compute-unit limit to request for a call with `items` remaining-account
+    /// items, capped at the transaction maximum.
+    pub fn compute_unit_limit(&self, items: u32) -> u32 {
+        self.compute_units
+            .saturating_add(self.compute_units_per_item.saturating_mul(items))
+            .min(MAX_COMPUTE_UNIT_LIMIT)
+    }
+}
+
+const fn hint(
+    instruction: &'static str,
+    compute_units: u32,
+    compute_units_per_item: u32,
+    priority_fee_percentile: u8,
+) -> ComputeBudgetHint {
+    ComputeBudgetHint {
+        instruction,
+        compute_units,
+        compute_units_per_item,
+        priority_fee_percentile,
+    }
+}
+
+/// Every instruction with its own hint.
+pub const COMPUTE_BUDGET_HINTS: &[ComputeBudgetHint] = &[
+    hint(
+        "initialize",
+        INITIALIZE_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "clone_config",
+        CLONE_CONFIG_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_if_over_threshold",
+        SEND_IF_OVER_THRESHOLD_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_if_over_threshold_v2",
+        SEND_IF_OVER_THRESHOLD_V2_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "try_send",
+        TRY_SEND_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_with_receipt",
+        SEND_WITH_RECEIPT_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_with_nonce",
+        SEND_WITH_NONCE_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_tokens_if_over_threshold",
+        SEND_TOKENS_IF_OVER_THRESHOLD_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_bridged",
+        SEND_BRIDGED_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_joint_if_over_threshold",
+        SEND_JOINT_IF_OVER_THRESHOLD_COMPUTE_UNITS,
+        SEND_JOINT_IF_OVER_THRESHOLD_COMPUTE_UNITS_PER_ITEM,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_validator_payout",
+        SEND_VALIDATOR_PAYOUT_COMPUTE_UNITS,
+        SEND_VALIDATOR_PAYOUT_COMPUTE_UNITS_PER_ITEM,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_split_if_over_threshold",
+        SEND_SPLIT_IF_OVER_THRESHOLD_COMPUTE_UNITS,
+        SEND_SPLIT_IF_OVER_THRESHOLD_COMPUTE_UNITS_PER_ITEM,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "send_to_seeded_address",
+        SEND_TO_SEEDED_ADDRESS_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "execute_transfer",
+        EXECUTE_TRANSFER_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "release",
+        RELEASE_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "fund_bounty_target",
+        FUND_BOUNTY_TARGET_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "release_milestone",
+        RELEASE_MILESTONE_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "execute_scheduled",
+        EXECUTE_SCHEDULED_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "redeem_allowance",
+        REDEEM_ALLOWANCE_COMPUTE_UNITS,
+        0,
+        SEND_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "open_epoch_summary",
+        OPEN_EPOCH_SUMMARY_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "apply_signed_proposal",
+        APPLY_SIGNED_PROPOSAL_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "update_addresses",
+        UPDATE_ADDRESSES_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+    hint(
+        "execute_recovery",
+        EXECUTE_RECOVERY_COMPUTE_UNITS,
+        0,
+        DEFAULT_PRIORITY_FEE_PERCENTILE,
+    ),
+];
+
+/// The budget to request for `instruction` (IDL name). Instructions without
+/// their own hint get the defaults, under an empty `instruction`.
+pub fn recommended_compute_budget(instruction: &str) -> ComputeBudgetHint {
+    COMPUTE_BUDGET_HINTS
+        .iter()
+        .find(|hint| hint.instruction == instruction)
+        .copied()
+        .unwrap_or(ComputeBudgetHint {
+            instruction: "",
+            compute_units: DEFAULT_COMPUTE_UNITS,
+            compute_units_per_item: 0,
+            priority_fee_percentile: DEFAULT_PRIORITY_FEE_PERCENTILE,
+        })
+}
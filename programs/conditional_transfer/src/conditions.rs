@@ -0,0 +1,294 @@
+//! Pure evaluation of the send conditions, shared by the program and clients.
+//!
+//! `send_if_over_threshold` and its variants run [`evaluate`] on-chain before
+//! any account-level checks, so an off-chain caller that feeds it the fetched
+//! config, policy, clock, and balances gets the same verdict the chain will.
+//! Checks that need the live transaction (CPI caller, beneficiary account
+//! layout, epoch summary, feature flags) are not covered here.
+
+use anchor_lang::prelude::*;
+
+use crate::{private_threshold, ComparisonMode, ConditionalError, Config, Policy, PolicyTerms};
+
+pub use condition_core::SolUsdPrice;
+
+/// Account state the conditions depend on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Balances {
+    /// Lamports held by `from` at execution time.
+    pub from_lamports: u64,
+    /// Whether `to` is an executable (program) account.
+    pub recipient_executable: bool,
+    /// Program owning `to`; `None` when the send has no local recipient.
+    pub recipient_owner: Option<Pubkey>,
+    /// Fresh SOL/USD price, when the send was given a price feed.
+    pub sol_usd_price: Option<SolUsdPrice>,
+    /// Whether the send executes a quorum-approved `PendingTransfer`.
+    pub quorum_approved: bool,
+    /// Commitment the send's `RangeProofU64` proof context shows is in range.
+    pub range_proof_commitment: Option<[u8; 32]>,
+}
+
+/// One reason a send would be rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// Config is paused with `set_paused`.
+    Paused,
+    /// Config has a pending `close_config`.
+    ClosePending,
+    /// Config has approvers and the send is not an approved pending transfer.
+    ApprovalRequired,
+    /// Config is linked to a policy that was not supplied (or the wrong one was).
+    PolicyRequired,
+    /// Amount is under the effective threshold, or equal to it in `Gt` mode.
+    /// Under `Noop` below-threshold behavior the send is skipped rather than
+    /// failed, as for `AboveThreshold`.
+    BelowThreshold {
+        amount: u64,
+        threshold_lamports: u64,
+    },
+    /// Amount is over the effective threshold in `Lte` or `Eq` mode, or
+    /// equal to it in `Lt` mode.
+    AboveThreshold {
+        amount: u64,
+        threshold_lamports: u64,
+    },
+    /// Config has a USD threshold but the send carried no usable price.
+    PriceFeedRequired,
+    /// Config has a private threshold but the send carried no range proof
+    /// that the amount meets it.
+    RangeProofRequired,
+    /// Amount is over the resolved non-zero `max_lamports`.
+    AboveMaximum { amount: u64, max_lamports: u64 },
+    /// Amount is over what the rate limit's current window still allows.
+    RateLimitExceeded { amount: u64, remaining: u64 },
+    /// `to` is executable and the config does not allow that.
+    ExecutableRecipient,
+    /// `to` is owned by a program missing from `recipient_owners`.
+    RecipientOwnerNotAllowed { owner: Pubkey },
+    /// Token-mode delivery uses a mint missing from the config's allowlist.
+    MintNotAllowed,
+    /// Token send on a config without a token threshold.
+    TokenThresholdNotSet,
+    /// Token amount is under the config's token threshold.
+    TokenBelowThreshold { amount: u64, threshold_amount: u64 },
+    /// Config has not been attested within `max_config_age_seconds`.
+    ConfigStale,
+    /// `now` is before `valid_from_unix` or after `valid_until_unix`.
+    OutsideTimeWindow { now: i64 },
+    /// `from` cannot cover the amount.
+    InsufficientFunds { available: u64, required: u64 },
+}
+
+impl Violation {
+    /// The program error the chain reports for this violation.
+    pub fn error(&self) -> ConditionalError {
+        match self {
+            Violation::Paused => ConditionalError::ProgramPaused,
+            Violation::ClosePending => ConditionalError::ConfigClosing,
+            Violation::ApprovalRequired => ConditionalError::ApprovalRequired,
+            Violation::PolicyRequired => ConditionalError::PolicyRequired,
+            Violation::BelowThreshold { .. } => ConditionalError::BelowThreshold,
+            Violation::AboveThreshold { .. } => ConditionalError::AboveThreshold,
+            Violation::PriceFeedRequired => ConditionalError::PriceFeedRequired,
+            Violation::RangeProofRequired => ConditionalError::RangeProofRequired,
+            Violation::AboveMaximum { .. } => ConditionalError::AboveMaximum,
+            Violation::RateLimitExceeded { .. } => ConditionalError::RateLimitExceeded,
+            Violation::ExecutableRecipient => ConditionalError::ExecutableRecipient,
+            Violation::RecipientOwnerNotAllowed { .. } => {
+                ConditionalError::RecipientOwnerNotAllowed
+            }
+            Violation::MintNotAllowed => ConditionalError::MintNotAllowed,
+            Violation::TokenThresholdNotSet => ConditionalError::TokenThresholdNotSet,
+            Violation::TokenBelowThreshold { .. } => ConditionalError::BelowThreshold,
+            Violation::ConfigStale => ConditionalError::ConfigStale,
+            Violation::OutsideTimeWindow { .. } => ConditionalError::OutsideTimeWindow,
+            Violation::InsufficientFunds { .. } => ConditionalError::InsufficientFunds,
+        }
+    }
+}
+
+impl From<Violation> for Error {
+    fn from(violation: Violation) -> Self {
+        violation.error().into()
+    }
+}
+
+/// Every violation a send of `amount` would hit, in the order the program
+/// checks them; empty means the pure conditions pass. `policy` is the linked
+/// policy's address and contents, if the config has one.
+pub fn evaluate(
+    config: &Config,
+    policy: Option<(Pubkey, &Policy)>,
+    amount: u64,
+    clock: &Clock,
+    balances: &Balances,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    if config.paused {
+        violations.push(Violation::Paused);
+    }
+    if config.close_requested_at.is_some() {
+        violations.push(Violation::ClosePending);
+    }
+    if config.approvers.quorum > 0 && !balances.quorum_approved {
+        violations.push(Violation::ApprovalRequired);
+    }
+    let terms = match config.resolve_terms(policy) {
+        Some(terms) => {
+            violations.extend(threshold_violation(&terms, amount));
+            terms
+        }
+        None => {
+            violations.push(Violation::PolicyRequired);
+            config.own_terms()
+        }
+    };
+    if let Some(cents) = config.threshold_usd_cents {
+        let threshold = balances
+            .sol_usd_price
+            .and_then(|price| price.lamports_for_usd_cents(cents));
+        match threshold {
+            Some(threshold_lamports) if amount < threshold_lamports => {
+                violations.push(Violation::BelowThreshold {
+                    amount,
+                    threshold_lamports,
+                })
+            }
+            Some(_) => {}
+            None => violations.push(Violation::PriceFeedRequired),
+        }
+    }
+    if let Some(commitment) = config.private_threshold {
+        let proven = balances.range_proof_commitment.is_some_and(|proven| {
+            private_threshold::proves_threshold(&commitment, amount, &proven)
+        });
+        if !proven {
+            violations.push(Violation::RangeProofRequired);
+        }
+    }
+    if terms.max_lamports != 0 && amount > terms.max_lamports {
+        violations.push(Violation::AboveMaximum {
+            amount,
+            max_lamports: terms.max_lamports,
+        });
+    }
+    if !config.rate_limit.allows(amount, clock.unix_timestamp) {
+        violations.push(Violation::RateLimitExceeded {
+            amount,
+            remaining: config.rate_limit.remaining(clock.unix_timestamp),
+        });
+    }
+    if balances.recipient_executable && !config.allow_executable_recipient {
+        violations.push(Violation::ExecutableRecipient);
+    }
+    if let Some(owner) = balances.recipient_owner {
+        if !config.recipient_owner_allowed(&owner) {
+            violations.push(Violation::RecipientOwnerNotAllowed { owner });
+        }
+    }
+    if let Some(mint) = config.beneficiary.mint() {
+        if !config.mint_allowed(&mint) {
+            violations.push(Violation::MintNotAllowed);
+        }
+    }
+    if !config_is_fresh(config, clock.unix_timestamp) {
+        violations.push(Violation::ConfigStale);
+    }
+    if !terms.within_time_window(clock.unix_timestamp) {
+        violations.push(Violation::OutsideTimeWindow {
+            now: clock.unix_timestamp,
+        });
+    }
+    if balances.from_lamports < amount {
+        violations.push(Violation::InsufficientFunds {
+            available: balances.from_lamports,
+            required: amount,
+        });
+    }
+    violations
+}
+
+/// How `amount` misses the lamport threshold of `terms` under their
+/// comparison mode, if it does.
+pub fn threshold_violation(terms: &PolicyTerms, amount: u64) -> Option<Violation> {
+    if terms.admits(amount) {
+        return None;
+    }
+    let threshold_lamports = terms.threshold_lamports;
+    let below = amount < threshold_lamports
+        || (amount == threshold_lamports && terms.comparison_mode == ComparisonMode::Gt);
+    Some(if below {
+        Violation::BelowThreshold {
+            amount,
+            threshold_lamports,
+        }
+    } else {
+        Violation::AboveThreshold {
+            amount,
+            threshold_lamports,
+        }
+    })
+}
+
+/// Every violation a `send_tokens_if_over_threshold` of `amount` base units
+/// would hit, given the balance of `from`'s token account. `InsufficientFunds`
+/// is in token units here.
+pub fn evaluate_tokens(
+    config: &Config,
+    amount: u64,
+    clock: &Clock,
+    from_token_amount: u64,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    if config.paused {
+        violations.push(Violation::Paused);
+    }
+    if config.close_requested_at.is_some() {
+        violations.push(Violation::ClosePending);
+    }
+    if config.approvers.quorum > 0 {
+        violations.push(Violation::ApprovalRequired);
+    }
+    match config.token_threshold {
+        Some(terms) => {
+            if amount < terms.threshold_amount {
+                violations.push(Violation::TokenBelowThreshold {
+                    amount,
+                    threshold_amount: terms.threshold_amount,
+                });
+            }
+            if !config.mint_allowed(&terms.mint) {
+                violations.push(Violation::MintNotAllowed);
+            }
+        }
+        None => violations.push(Violation::TokenThresholdNotSet),
+    }
+    if !config_is_fresh(config, clock.unix_timestamp) {
+        violations.push(Violation::ConfigStale);
+    }
+    if !within_time_window(config, clock.unix_timestamp) {
+        violations.push(Violation::OutsideTimeWindow {
+            now: clock.unix_timestamp,
+        });
+    }
+    if from_token_amount < amount {
+        violations.push(Violation::InsufficientFunds {
+            available: from_token_amount,
+            required: amount,
+        });
+    }
+    violations
+}
+
+/// Whether the config was attested recently enough at unix time `now`.
+pub fn config_is_fresh(config: &Config, now: i64) -> bool {
+    config.max_config_age_seconds == 0
+        || now.saturating_sub(config.last_attested_ts) <= config.max_config_age_seconds as i64
+}
+
+/// Whether unix time `now` is inside the config's own send window (bounds
+/// inclusive); a linked policy's is in `PolicyTerms::within_time_window`.
+pub fn within_time_window(config: &Config, now: i64) -> bool {
+    config.own_terms().within_time_window(now)
+}
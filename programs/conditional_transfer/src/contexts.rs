@@ -0,0 +1,2627 @@
+//! Account contexts of the instructions, and the send path shared by the
+//! `SendIfOverThreshold` instructions.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::*;
+
+/// Accounts context for initialization. Creates the config PDA at
+/// `[CONFIG_SEED, from, route]`, so each route gets its own independent config.
+/// `from` signs, so nobody else can take a route under its key, and the seeds
+/// never contain the recipient, which hashed-recipient mode keeps private.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(route: Pubkey, to: Pubkey)]
+pub struct Initialize<'info> {
+    /// Authority who can update the config parameters.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The config's `from`; may be the same key as `authority`.
+    pub from: Signer<'info>,
+
+    /// Program Derived Address storing config (created & paid by authority).
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::INIT_SPACE, // discriminator + fields
+        seeds = [CONFIG_SEED, from.key().as_ref(), route.as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Reverse index of configs paying `to`, at
+    /// `[RECIPIENT_INDEX_SEED, to]`; created on first use by `create_program_account`.
+    #[account(mut)]
+    pub recipient_index: UncheckedAccount<'info>,
+
+    /// Built-in System Program is needed for CPI transfer & allocation.
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for cloning a config onto a new route.
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(route: Pubkey, to: Pubkey)]
+pub struct CloneConfig<'info> {
+    /// Root authority of `source`; becomes the new config's authority.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The new config's `from`; may be the same key as `authority`.
+    pub from: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, source.seed_from.as_ref(), source.seed_route.as_ref()],
+        bump = source.bump,
+        has_one = authority @ ConditionalError::Unauthorized,
+        constraint = source.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub source: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED, from.key().as_ref(), route.as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Reverse index of configs paying `to`, at
+    /// `[RECIPIENT_INDEX_SEED, to]`; created on first use by `create_program_account`.
+    #[account(mut)]
+    pub recipient_index: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for transfer call.
+#[derive(Accounts)]
+pub struct SendIfOverThreshold<'info> {
+    /// The config PDA (mutable so events can advance `event_seq`).
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// `config.from` or one of its `delegates`; signs and funds the send.
+    #[account(
+        mut,
+        constraint = config.is_sender(&from.key()) @ ConditionalError::SenderNotAllowed
+    )]
+    pub from: Signer<'info>,
+
+    /// CHECK: Must be the config's recipient, or listed in `recipient_list`,
+    /// and will receive lamports. Executable accounts are rejected in the
+    /// handler unless the config opts in.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key())
+            || matches!(&recipient_list, Some(list) if list.contains(&to.key()))
+            @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// Optional matching pool; when present, `to` is topped up from it.
+    #[account(
+        mut,
+        seeds = [MATCH_POOL_SEED, config.key().as_ref(), match_pool.sponsor.as_ref()],
+        bump = match_pool.bump
+    )]
+    pub match_pool: Option<Account<'info, MatchPool>>,
+
+    /// Summary for the current epoch; required when `config.epoch_reporting` is on.
+    #[account(
+        mut,
+        seeds = [EPOCH_SUMMARY_SEED, config.key().as_ref(), &epoch_summary.epoch.to_le_bytes()],
+        bump = epoch_summary.bump
+    )]
+    pub epoch_summary: Option<Account<'info, EpochSummary>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// CHECK: Required when `to` is a `Sweep` PDA whose balance reaches its
+    /// threshold; must match the sweep's cold wallet.
+    #[account(mut)]
+    pub sweep_destination: Option<UncheckedAccount<'info>>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the send and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+
+    /// The config's recipient list; required when `to` is a listed address
+    /// rather than `config.to`.
+    #[account(
+        seeds = [RECIPIENT_LIST_SEED, config.key().as_ref()],
+        bump = recipient_list.bump
+    )]
+    pub recipient_list: Option<Account<'info, RecipientList>>,
+
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`; required when the config has a
+    /// `threshold_usd_cents`. Owner, feed, verification, and age are checked in
+    /// `pyth::sol_usd_price`; under `test-mode` the `test_mocks` PDA may stand in.
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: ZK Token proof `RangeProofU64` context account; required when the
+    /// config has a `private_threshold`. Owner and proof type are checked in
+    /// `private_threshold::range_proof_commitment`.
+    pub range_proof: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Must be the resolved fee collector (the linked policy's when it
+    /// sets a fee, else `config.fee_collector`); required when the send owes a fee.
+    #[account(mut)]
+    pub fee_collector: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for read-only views of the config.
+#[derive(Accounts)]
+pub struct ReadConfig<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Accounts context for the `resolve_effective_policy` and `get_config` views.
+#[derive(Accounts)]
+pub struct ReadEffectivePolicy<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+}
+
+impl<'info> SendIfOverThreshold<'info> {
+    /// Shared send path: condition checks, then the transfer. Amounts that miss
+    /// the threshold are skipped instead of failing when the config says `Noop`.
+    pub(crate) fn process(
+        &mut self,
+        lamports: u64,
+        clock: &Clock,
+        references: Vec<Pubkey>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let terms = self.config.resolve_policy(self.policy.as_ref())?;
+        if !terms.admits(lamports) && terms.below_threshold_behavior == BelowThresholdBehavior::Noop
+        {
+            let cfg = &mut self.config;
+            emit!(TransferSkipped {
+                seq: cfg.next_event_seq()?,
+                config: cfg.key(),
+                from: self.from.key(),
+                to: self.to.key(),
+                lamports,
+                threshold_lamports: terms.threshold_lamports,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+            return Ok(());
+        }
+        self.check_send(lamports, clock, false)?;
+        self.execute(lamports, references, memo)
+    }
+
+    /// Anomaly guard for this send; records it in the rolling average.
+    fn check_velocity(&mut self, lamports: u64) -> Result<()> {
+        check_velocity(
+            &mut self.config,
+            self.from.key(),
+            self.to.key(),
+            lamports,
+            self.approver.as_ref(),
+        )
+    }
+
+    /// `check_conditions`, then `check_velocity`. With `log_rejections` on, a
+    /// failure is first logged as `TransferRejected`.
+    pub(crate) fn check_send(
+        &mut self,
+        lamports: u64,
+        clock: &Clock,
+        approved: bool,
+    ) -> Result<()> {
+        let checked = self
+            .check_conditions(lamports, clock, approved)
+            .and_then(|()| self.check_velocity(lamports));
+        if let Err(error) = &checked {
+            if self.config.log_rejections {
+                emit!(TransferRejected {
+                    seq: self.config.next_event_seq()?,
+                    config: self.config.key(),
+                    from: self.from.key(),
+                    to: self.to.key(),
+                    lamports,
+                    error_code: error_code(error),
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+        }
+        checked
+    }
+
+    /// Every condition a send must satisfy; fails without side effects.
+    /// `approved` is set only by `execute_transfer`, for a quorum-approved
+    /// `PendingTransfer`.
+    fn check_conditions(&self, lamports: u64, clock: &Clock, approved: bool) -> Result<()> {
+        require_feature_enabled(&self.global, FEATURE_SEND)?;
+        let cfg = &self.config;
+        if cfg.threshold_usd_cents.is_some() {
+            require_feature_enabled(&self.global, FEATURE_ORACLE)?;
+        }
+        // NOTE: Behavior is "≥ threshold" (at least) unless `comparison_mode` says
+        // otherwise. Adjust in `conditions::evaluate` if you want different
+        // rules; clients run the same function.
+        let balances = conditions::Balances {
+            from_lamports: self.from.lamports(),
+            recipient_executable: self.to.executable,
+            recipient_owner: Some(*self.to.owner),
+            sol_usd_price: match (cfg.threshold_usd_cents, &self.price_feed) {
+                (Some(_), Some(feed)) => {
+                    Some(test_mode::sol_usd_price(feed, clock.unix_timestamp)?)
+                }
+                _ => None,
+            },
+            quorum_approved: approved,
+            range_proof_commitment: match (cfg.private_threshold, &self.range_proof) {
+                (Some(_), Some(proof)) => Some(private_threshold::range_proof_commitment(proof)?),
+                _ => None,
+            },
+        };
+        let policy = self.policy.as_ref().map(|policy| (policy.key(), &**policy));
+        let violations = conditions::evaluate(cfg, policy, lamports, clock, &balances);
+        if let Some(violation) = violations.first() {
+            return Err((*violation).into());
+        }
+        cfg.beneficiary
+            .validate(&self.to, self.recipient_token_account.as_deref())?;
+        require_cpi_caller_allowed(cfg, self.instructions.as_ref())?;
+        if cfg.epoch_reporting {
+            let summary = self
+                .epoch_summary
+                .as_ref()
+                .ok_or(ConditionalError::EpochSummaryRequired)?;
+            require!(
+                summary.epoch == Clock::get()?.epoch && !summary.finalized,
+                ConditionalError::EpochSummaryRequired
+            );
+        }
+        Ok(())
+    }
+
+    /// CPI transfer, then optional matching. Assumes conditions were checked.
+    /// `memo` overrides the config's default memo.
+    pub(crate) fn execute(
+        &mut self,
+        lamports: u64,
+        references: Vec<Pubkey>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        let cfg = &self.config;
+        let destination = cfg
+            .beneficiary
+            .delivery_account(&self.to, self.recipient_token_account.as_deref())?;
+
+        // The fee leg goes first, so the balance check below covers only the
+        // recipient's leg even when the collector is `to`.
+        let terms = cfg.resolve_policy(self.policy.as_ref())?;
+        let fee = terms.fee_for(lamports);
+        if fee > 0 {
+            let collector = self
+                .fee_collector
+                .as_ref()
+                .filter(|collector| collector.key() == terms.fee_collector)
+                .ok_or(ConditionalError::FeeCollectorRequired)?;
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.from.to_account_info(),
+                        to: collector.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+        let lamports = lamports - fee;
+
+        let same_account = self.from.key() == destination.key();
+        let before = BalanceSnapshot::take(&self.from, destination);
+
+        // CPI to transfer SOL from `from` -> `to` (or its wSOL ATA)
+        let cpi_ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.from.to_account_info(),
+                to: destination.clone(),
+            },
+        );
+        system_program::transfer(cpi_ctx, lamports)?;
+        match &memo {
+            Some(memo) => msg!("memo: {}", memo),
+            None => log_default_memo(cfg),
+        }
+
+        // Optional matching: top up `to` from the sponsor's pool, bounded by the
+        // remaining match budget and the pool's rent-exempt balance.
+        let mut matched = 0;
+        if let Some(pool) = self.match_pool.as_mut() {
+            let info = pool.to_account_info();
+            let rent_floor = Rent::get()?.minimum_balance(info.data_len());
+            let available = info.lamports().saturating_sub(rent_floor);
+            matched = pool.apply_match(lamports, available)?;
+            pool.last_activity_ts = Clock::get()?.unix_timestamp;
+            if matched > 0 {
+                pool.sub_lamports(matched)?;
+                destination.add_lamports(matched)?;
+                msg!("Matched {} lamports from pool {}", matched, pool.key());
+            }
+        }
+        before.require_moved(
+            &BalanceSnapshot::take(&self.from, destination),
+            lamports,
+            lamports
+                .checked_add(matched)
+                .ok_or(ConditionalError::MathOverflow)?,
+            same_account,
+        )?;
+        cfg.beneficiary
+            .settle(destination, self.token_program.as_ref())?;
+        forward_sweep(&self.to, self.sweep_destination.as_ref())?;
+
+        if let Some(summary) = self.epoch_summary.as_mut() {
+            require!(
+                summary.epoch == Clock::get()?.epoch && !summary.finalized,
+                ConditionalError::EpochSummaryRequired
+            );
+            summary.record(lamports, fee)?;
+        }
+        // The fee leg is reported but not counted: `stats` and the large-transfer
+        // check see the send once, as its recipient leg.
+        if let Some(collector) = self.fee_collector.as_ref().filter(|_| fee > 0) {
+            emit_transfer_executed(
+                &mut self.config,
+                &self.from,
+                collector,
+                fee,
+                Vec::new(),
+                memo.clone(),
+            )?;
+        }
+        record_transfer(
+            &mut self.config,
+            &self.from,
+            &self.to,
+            lamports,
+            references,
+            memo,
+        )
+    }
+}
+
+/// Accounts context for a send that records a receipt under an external reference.
+#[derive(Accounts)]
+#[instruction(lamports: u64, reference: [u8; 32])]
+pub struct SendWithReceipt<'info> {
+    pub send: SendIfOverThreshold<'info>,
+
+    /// Pays the receipt's rent and may later close it; usually `from`.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Receipt::INIT_SPACE,
+        seeds = [RECEIPT_SEED, send.config.key().as_ref(), reference.as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, Receipt>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for a send deduplicated by a sender-chosen nonce.
+#[derive(Accounts)]
+pub struct SendWithNonce<'info> {
+    pub send: SendIfOverThreshold<'info>,
+
+    /// Pays for the nonce account on first use; usually `from`.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SendNonces::INIT_SPACE,
+        seeds = [SEND_NONCES_SEED, send.config.key().as_ref(), send.from.key().as_ref()],
+        bump
+    )]
+    pub nonces: Account<'info, SendNonces>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for closing a receipt.
+#[derive(Accounts)]
+pub struct CloseReceipt<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [RECEIPT_SEED, receipt.config.as_ref(), receipt.reference.as_ref()],
+        bump = receipt.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub receipt: Account<'info, Receipt>,
+}
+
+/// Accounts context for resizing a receipt to the current layout.
+#[derive(Accounts)]
+pub struct MigrateReceipt<'info> {
+    /// The receipt's payer; pays for growth and receives shrink refunds.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: May still hold an older `Receipt` layout, so it is not
+    /// deserialized. Owner, discriminator, and payer are checked in the handler.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for `verify_payment`.
+#[derive(Accounts)]
+#[instruction(reference: [u8; 32])]
+pub struct VerifyPayment<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: the receipt PDA for `reference`; empty until the reference is paid.
+    #[account(seeds = [RECEIPT_SEED, config.key().as_ref(), reference.as_ref()], bump)]
+    pub receipt: UncheckedAccount<'info>,
+}
+
+/// Accounts context for a token transfer between the route's associated token accounts.
+#[derive(Accounts)]
+pub struct SendTokens<'info> {
+    /// The config PDA (mutable so events can advance `event_seq`).
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The `from` account must match config.from AND must sign the transaction.
+    #[account(address = config.from)]
+    pub from: Signer<'info>,
+
+    /// CHECK: Must be the config's recipient; only its token account is written.
+    #[account(
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    /// The mint in `config.token_threshold`.
+    #[account(
+        constraint = config.token_threshold.map(|terms| terms.mint) == Some(mint.key())
+            @ ConditionalError::InvalidTokenAccount
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// `from`'s associated token account for `mint`.
+    #[account(
+        mut,
+        address = associated_token_address(&from.key(), &mint.key()) @ ConditionalError::InvalidTokenAccount,
+        token::mint = mint,
+        token::authority = from
+    )]
+    pub from_token_account: Account<'info, TokenAccount>,
+
+    /// `to`'s associated token account for `mint`.
+    #[account(
+        mut,
+        address = associated_token_address(&to.key(), &mint.key()) @ ConditionalError::InvalidTokenAccount,
+        token::mint = mint,
+        token::authority = to
+    )]
+    pub to_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+}
+
+/// Accounts context for a bridged payout through Wormhole.
+#[derive(Accounts)]
+pub struct SendBridged<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The `from` account must match config.from AND must sign the transaction.
+    /// Also pays the Wormhole message fee and the message account's rent.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// Holds the lamports locked behind bridged payouts.
+    #[account(mut, seeds = [BRIDGE_CUSTODY_SEED, config.key().as_ref()], bump)]
+    pub custody: SystemAccount<'info>,
+
+    /// CHECK: This program's Wormhole emitter PDA; signs the posted message.
+    #[account(seeds = [BRIDGE_EMITTER_SEED], bump)]
+    pub emitter: UncheckedAccount<'info>,
+
+    /// CHECK: Core bridge config; validated by the bridge, read here for its fee.
+    #[account(mut, owner = wormhole_program.key())]
+    pub wormhole_bridge: UncheckedAccount<'info>,
+    /// CHECK: Validated by the bridge.
+    #[account(mut)]
+    pub wormhole_fee_collector: UncheckedAccount<'info>,
+    /// CHECK: The emitter's sequence account; validated by the bridge.
+    #[account(mut)]
+    pub wormhole_sequence: UncheckedAccount<'info>,
+    /// Fresh account the bridge writes the message into.
+    #[account(mut)]
+    pub wormhole_message: Signer<'info>,
+
+    /// CHECK: Must match `config.bridge_route`'s core bridge.
+    #[account(executable)]
+    pub wormhole_program: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the send and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+}
+
+/// Accounts context for writing the test-mode mocks.
+#[cfg(feature = "test-mode")]
+#[derive(Accounts)]
+pub struct SetTestMocks<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + test_mode::TestMocks::INIT_SPACE,
+        seeds = [test_mode::TEST_MOCKS_SEED],
+        bump
+    )]
+    pub mocks: Account<'info, test_mode::TestMocks>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for depositing into the config's escrow.
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The `from` account must match config.from AND must sign the transaction.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// Holds deposited lamports until `release`.
+    #[account(mut, seeds = [ESCROW_SEED, config.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+}
+
+/// Accounts context for releasing escrowed lamports to the recipient.
+#[derive(Accounts)]
+pub struct Release<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [ESCROW_SEED, config.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+
+    /// CHECK: Must be the config's recipient (plaintext or matching the stored hash).
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the release and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for refunding an expired escrow to `from`.
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    #[account(mut, seeds = [ESCROW_SEED, config.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for a validator payout distribution.
+#[derive(Accounts)]
+pub struct SendValidatorPayout<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The `from` account must match config.from AND must sign the transaction.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    #[account(
+        seeds = [PAYOUT_TABLE_SEED, config.key().as_ref()],
+        bump = payout_table.bump,
+        has_one = config
+    )]
+    pub payout_table: Account<'info, PayoutTable>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the total and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+}
+
+/// Accounts context for a split send across the split table.
+#[derive(Accounts)]
+pub struct SendSplit<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The `from` account must match config.from AND must sign the transaction.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    #[account(
+        seeds = [SPLIT_TABLE_SEED, config.key().as_ref()],
+        bump = split_table.bump,
+        has_one = config
+    )]
+    pub split_table: Account<'info, SplitTable>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the total and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+}
+
+/// Accounts context for a send to a seed-derived address.
+#[derive(Accounts)]
+pub struct SendSeeded<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The `from` account must match config.from AND must sign the transaction.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// CHECK: Must be derived from `config.seeded_recipient` and the seed, and
+    /// uninitialized; both are checked in the handler.
+    #[account(mut)]
+    pub to: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the send and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+}
+
+/// Accounts context for the joint (multi-instruction) send.
+#[derive(Accounts)]
+pub struct SendJoint<'info> {
+    /// The config PDA. Must be the first account so sibling instructions can be matched.
+    /// Mutable so events can advance `event_seq`.
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The `from` account must match config.from AND must sign the transaction.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// CHECK: Must be the config's recipient; executable recipients are rejected
+    /// in the handler.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, used to find sibling joint sends.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags this leg and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+}
+
+/// Accounts context for creating a matching pool under a config.
+#[derive(Accounts)]
+pub struct CreateMatchPool<'info> {
+    /// Sponsor who owns the pool and pays for its creation.
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Pool PDA, one per (config, sponsor).
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + MatchPool::INIT_SPACE,
+        seeds = [MATCH_POOL_SEED, config.key().as_ref(), sponsor.key().as_ref()],
+        bump
+    )]
+    pub match_pool: Account<'info, MatchPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for funding a matching pool.
+#[derive(Accounts)]
+pub struct FundMatchPool<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MATCH_POOL_SEED, match_pool.config.as_ref(), match_pool.sponsor.as_ref()],
+        bump = match_pool.bump
+    )]
+    pub match_pool: Account<'info, MatchPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for a sponsor closing their matching pool.
+#[derive(Accounts)]
+pub struct CloseMatchPool<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MATCH_POOL_SEED, match_pool.config.as_ref(), sponsor.key().as_ref()],
+        bump = match_pool.bump,
+        has_one = sponsor,
+        close = sponsor
+    )]
+    pub match_pool: Account<'info, MatchPool>,
+}
+
+/// Accounts context for a config authority closing an abandoned matching pool.
+#[derive(Accounts)]
+pub struct ForceCloseMatchPool<'info> {
+    /// Root authority or a holder of `PERM_CLOSE`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Receives the pool's lamports; must match match_pool.sponsor.
+    #[account(mut)]
+    pub sponsor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [MATCH_POOL_SEED, config.key().as_ref(), sponsor.key().as_ref()],
+        bump = match_pool.bump,
+        has_one = config,
+        has_one = sponsor,
+        close = sponsor
+    )]
+    pub match_pool: Account<'info, MatchPool>,
+}
+
+/// Accounts context for escrowing a milestone tranche.
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct CreateMilestone<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Funds the tranche; must match config.from.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// Milestone PDA, one per (config, index).
+    #[account(
+        init,
+        payer = from,
+        space = 8 + Milestone::INIT_SPACE,
+        seeds = [MILESTONE_SEED, config.key().as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub milestone: Account<'info, Milestone>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for approving a milestone.
+#[derive(Accounts)]
+pub struct ApproveMilestone<'info> {
+    pub approver: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_SEED, milestone.config.as_ref(), &milestone.index.to_le_bytes()],
+        bump = milestone.bump,
+        has_one = approver
+    )]
+    pub milestone: Account<'info, Milestone>,
+}
+
+/// Accounts context for releasing a milestone tranche.
+#[derive(Accounts)]
+pub struct ReleaseMilestone<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_SEED, config.key().as_ref(), &milestone.index.to_le_bytes()],
+        bump = milestone.bump,
+        has_one = config
+    )]
+    pub milestone: Account<'info, Milestone>,
+
+    /// CHECK: Must be the config's recipient. Executable accounts are rejected
+    /// in the handler unless the config opts in.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the payout and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for a payer closing a released milestone.
+#[derive(Accounts)]
+pub struct CloseMilestone<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_SEED, milestone.config.as_ref(), &milestone.index.to_le_bytes()],
+        bump = milestone.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub milestone: Account<'info, Milestone>,
+}
+
+/// Accounts context for creating a payment stream.
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct CreateStream<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Funds the stream; must match config.from.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// Stream PDA, one per (config, index).
+    #[account(
+        init,
+        payer = from,
+        space = 8 + Stream::INIT_SPACE,
+        seeds = [STREAM_SEED, config.key().as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub stream: Account<'info, Stream>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for withdrawing from a payment stream.
+#[derive(Accounts)]
+pub struct WithdrawStream<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [STREAM_SEED, config.key().as_ref(), &stream.index.to_le_bytes()],
+        bump = stream.bump,
+        has_one = config
+    )]
+    pub stream: Account<'info, Stream>,
+
+    /// CHECK: Must be the config's recipient. Executable accounts are rejected
+    /// in the handler unless the config opts in.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the payout and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for a payer cancelling a payment stream.
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [STREAM_SEED, config.key().as_ref(), &stream.index.to_le_bytes()],
+        bump = stream.bump,
+        has_one = config,
+        has_one = payer,
+        close = payer
+    )]
+    pub stream: Account<'info, Stream>,
+
+    /// CHECK: Must be the config's recipient. Executable accounts are rejected
+    /// in the handler unless the config opts in.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for creating a proof-of-delivery escrow.
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct CreateDelivery<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Funds the escrow; must match config.from.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// Delivery PDA, one per (config, index).
+    #[account(
+        init,
+        payer = from,
+        space = 8 + Delivery::INIT_SPACE,
+        seeds = [DELIVERY_SEED, config.key().as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub delivery: Account<'info, Delivery>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+}
+
+/// Accounts context for the recipient acknowledging a delivery.
+#[derive(Accounts)]
+pub struct AcknowledgeDelivery<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [DELIVERY_SEED, config.key().as_ref(), &delivery.index.to_le_bytes()],
+        bump = delivery.bump,
+        has_one = config
+    )]
+    pub delivery: Account<'info, Delivery>,
+
+    /// The config's recipient; must sign the acknowledgment.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: Signer<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the payout and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for a payer closing a delivery.
+#[derive(Accounts)]
+pub struct CloseDelivery<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [DELIVERY_SEED, delivery.config.as_ref(), &delivery.index.to_le_bytes()],
+        bump = delivery.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub delivery: Account<'info, Delivery>,
+}
+
+/// Accounts context for scheduling a transfer.
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct ScheduleTransfer<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Funds the transfer and the rent; must match config.from.
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    /// Scheduled transfer PDA, one per (config, id).
+    #[account(
+        init,
+        payer = from,
+        space = 8 + ScheduledTransfer::INIT_SPACE,
+        seeds = [SCHEDULED_TRANSFER_SEED, config.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub scheduled: Account<'info, ScheduledTransfer>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+}
+
+/// Accounts context for executing an unlocked scheduled transfer.
+#[derive(Accounts)]
+pub struct ExecuteScheduled<'info> {
+    /// The config PDA (mutable so the transfer counts in the rate limit,
+    /// statistics, and `event_seq`).
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [SCHEDULED_TRANSFER_SEED, config.key().as_ref(), &scheduled.id.to_le_bytes()],
+        bump = scheduled.bump,
+        has_one = config,
+        has_one = payer,
+        close = payer
+    )]
+    pub scheduled: Account<'info, ScheduledTransfer>,
+
+    /// CHECK: Receives the scheduled transfer's rent; must be its payer.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// CHECK: Must be the config's recipient. Executable accounts are rejected
+    /// in the handler unless the config opts in.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &to.key()) @ ConditionalError::RecipientMismatch
+    )]
+    pub to: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the transfer and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `to`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for a payer cancelling or refunding a scheduled transfer.
+#[derive(Accounts)]
+pub struct CancelScheduled<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SCHEDULED_TRANSFER_SEED, scheduled.config.as_ref(), &scheduled.id.to_le_bytes()],
+        bump = scheduled.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub scheduled: Account<'info, ScheduledTransfer>,
+}
+
+/// Accounts context for opening a bounty.
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct CreateBounty<'info> {
+    /// Funds the reward and pays for the account.
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    /// Bounty PDA, one per (sponsor, target).
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + Bounty::INIT_SPACE,
+        seeds = [BOUNTY_SEED, sponsor.key().as_ref(), target.as_ref()],
+        bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for a send that may win a bounty.
+#[derive(Accounts)]
+pub struct FundBountyTarget<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BOUNTY_SEED, bounty.sponsor.as_ref(), bounty.target.as_ref()],
+        bump = bounty.bump,
+        has_one = target
+    )]
+    pub bounty: Account<'info, Bounty>,
+
+    /// The bounty's target recipient.
+    #[account(mut)]
+    pub target: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for a sponsor closing a bounty.
+#[derive(Accounts)]
+pub struct CloseBounty<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BOUNTY_SEED, sponsor.key().as_ref(), bounty.target.as_ref()],
+        bump = bounty.bump,
+        has_one = sponsor,
+        close = sponsor
+    )]
+    pub bounty: Account<'info, Bounty>,
+}
+
+/// Accounts context for registering a recipient-side sweep.
+#[derive(Accounts)]
+pub struct CreateSweep<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Sweep PDA, one per owner.
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Sweep::INIT_SPACE,
+        seeds = [SWEEP_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub sweep: Account<'info, Sweep>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for updating a sweep.
+#[derive(Accounts)]
+pub struct UpdateSweep<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SWEEP_SEED, owner.key().as_ref()],
+        bump = sweep.bump,
+        has_one = owner
+    )]
+    pub sweep: Account<'info, Sweep>,
+}
+
+/// Accounts context for resizing a sweep to the current layout.
+#[derive(Accounts)]
+pub struct MigrateSweep<'info> {
+    /// Sweep owner; pays for growth and receives shrink refunds.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: May still hold an older `Sweep` layout, so it is not
+    /// deserialized. Owner, discriminator, and sweep owner are checked in the handler.
+    #[account(mut, seeds = [SWEEP_SEED, owner.key().as_ref()], bump)]
+    pub sweep: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for closing a sweep.
+#[derive(Accounts)]
+pub struct CloseSweep<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SWEEP_SEED, owner.key().as_ref()],
+        bump = sweep.bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub sweep: Account<'info, Sweep>,
+}
+
+/// Accounts context for a config authority closing an abandoned milestone.
+#[derive(Accounts)]
+pub struct ForceCloseMilestone<'info> {
+    /// Root authority or a holder of `PERM_CLOSE`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Receives the milestone's lamports; must match milestone.payer.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [MILESTONE_SEED, config.key().as_ref(), &milestone.index.to_le_bytes()],
+        bump = milestone.bump,
+        has_one = config,
+        has_one = payer,
+        close = payer
+    )]
+    pub milestone: Account<'info, Milestone>,
+}
+
+/// Accounts context for updates. Permissions are checked per instruction.
+/// Every config update takes a trailing `recent_slot` argument; see
+/// `require_recent_slot`.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// Root authority or a granted authority; must sign.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Config PDA being updated.
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Accounts context for `adjust_threshold`.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AdjustThreshold<'info> {
+    /// Whoever runs the crank; recorded as `changed_by`.
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Pyth SOL/USD `PriceUpdateV2`. Owner, feed, verification, and age
+    /// are checked in `pyth::sol_usd_price`.
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+}
+
+/// Accounts context for `accept_authority`.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    /// The config's `pending_authority`; must sign.
+    pub new_authority: Signer<'info>,
+
+    /// Config PDA changing hands.
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Accounts context for applying an offline-signed config proposal.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApplySignedProposal<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Instructions sysvar, used to find the ed25519 verification.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+}
+
+/// Accounts context for creating the validator payout table.
+#[derive(Accounts)]
+pub struct CreatePayoutTable<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_ADDRESSES`; pays for the account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PayoutTable::INIT_SPACE,
+        seeds = [PAYOUT_TABLE_SEED, config.key().as_ref()],
+        bump
+    )]
+    pub payout_table: Account<'info, PayoutTable>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for updating the validator payout table.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdatePayoutTable<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_ADDRESSES`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [PAYOUT_TABLE_SEED, config.key().as_ref()],
+        bump = payout_table.bump,
+        has_one = config
+    )]
+    pub payout_table: Account<'info, PayoutTable>,
+}
+
+/// Accounts context for setting the split-send recipients.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageSplitTable<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_ADDRESSES`; pays for the
+    /// table on first use.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + SplitTable::INIT_SPACE,
+        seeds = [SPLIT_TABLE_SEED, config.key().as_ref()],
+        bump
+    )]
+    pub split_table: Account<'info, SplitTable>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for creating a shared policy.
+#[derive(Accounts)]
+#[instruction(id: u32)]
+pub struct CreatePolicy<'info> {
+    /// Owner of the policy; pays for the account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Policy PDA, one per (authority, id).
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Policy::INIT_SPACE,
+        seeds = [POLICY_SEED, authority.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub policy: Account<'info, Policy>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for updating a shared policy.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdatePolicy<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POLICY_SEED, authority.key().as_ref(), &policy.id.to_le_bytes()],
+        bump = policy.bump,
+        has_one = authority
+    )]
+    pub policy: Account<'info, Policy>,
+}
+
+/// Accounts context for resizing a shared policy to the current layout.
+#[derive(Accounts)]
+pub struct MigratePolicy<'info> {
+    /// Policy authority; pays for growth and receives shrink refunds.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: May still hold an older `Policy` layout, so it is not
+    /// deserialized. Owner, discriminator, and authority are checked in the handler.
+    #[account(mut)]
+    pub policy: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for changing from/to, keeping recipient indexes in sync.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateAddresses<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_ADDRESSES`; pays for a new index.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Omitted when the recipient is hashed, as it is then in no index.
+    #[account(
+        mut,
+        seeds = [RECIPIENT_INDEX_SEED, config.to.as_ref()],
+        bump = old_recipient_index.bump
+    )]
+    pub old_recipient_index: Option<Account<'info, RecipientIndex>>,
+
+    /// CHECK: Index at `[RECIPIENT_INDEX_SEED, new_to]`; created on first use by
+    /// `create_program_account`.
+    #[account(mut)]
+    pub new_recipient_index: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for closing the config.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseConfig<'info> {
+    /// Root authority or a holder of `PERM_CLOSE`; receives the rent.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Closed by the handler, which may only record the request instead.
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Omitted when the recipient is hashed, as it is then in no index.
+    #[account(
+        mut,
+        seeds = [RECIPIENT_INDEX_SEED, config.to.as_ref()],
+        bump = recipient_index.bump
+    )]
+    pub recipient_index: Option<Account<'info, RecipientIndex>>,
+
+    /// The close fails while it holds escrowed lamports.
+    #[account(seeds = [ESCROW_SEED, config.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+
+    /// While it holds pooled lamports, the close only records a request and
+    /// then fails until `withdraw_vault` empties it.
+    #[account(seeds = [VAULT_SEED, config.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+}
+
+/// Accounts context for switching to a hashed recipient.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetHashedRecipient<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_ADDRESSES`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// The current recipient's index; omitted if the recipient is already hashed.
+    #[account(
+        mut,
+        seeds = [RECIPIENT_INDEX_SEED, config.to.as_ref()],
+        bump = recipient_index.bump
+    )]
+    pub recipient_index: Option<Account<'info, RecipientIndex>>,
+}
+
+/// Accounts context for setting the recovery committee.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureRecovery<'info> {
+    /// Root authority; pays for the recovery account on first use.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        has_one = authority @ ConditionalError::Unauthorized,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Recovery::INIT_SPACE,
+        seeds = [RECOVERY_SEED, config.key().as_ref()],
+        bump
+    )]
+    pub recovery: Account<'info, Recovery>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for proposing, approving, vetoing, or executing a recovery.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecoveryAction<'info> {
+    /// A committee member, or the root authority when vetoing.
+    pub member: Signer<'info>,
+
+    /// Mutable so events can advance `event_seq` and recovery can set `authority`.
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [RECOVERY_SEED, config.key().as_ref()], bump = recovery.bump)]
+    pub recovery: Account<'info, Recovery>,
+}
+
+/// Accounts context for proposing a transfer to the config's approvers.
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct ProposeTransfer<'info> {
+    /// `from` or an approver; pays for the pending transfer.
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + PendingTransfer::INIT_SPACE,
+        seeds = [PENDING_TRANSFER_SEED, config.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub pending_transfer: Account<'info, PendingTransfer>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for approving a pending transfer.
+#[derive(Accounts)]
+pub struct ApproveTransfer<'info> {
+    pub approver: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_TRANSFER_SEED, config.key().as_ref(), &pending_transfer.id.to_le_bytes()],
+        bump = pending_transfer.bump,
+        has_one = config
+    )]
+    pub pending_transfer: Account<'info, PendingTransfer>,
+}
+
+/// Accounts context for executing an approved pending transfer.
+#[derive(Accounts)]
+pub struct ExecuteTransfer<'info> {
+    pub send: SendIfOverThreshold<'info>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_TRANSFER_SEED, send.config.key().as_ref(), &pending_transfer.id.to_le_bytes()],
+        bump = pending_transfer.bump,
+        constraint = pending_transfer.to == send.to.key() @ ConditionalError::RecipientMismatch,
+        close = proposer
+    )]
+    pub pending_transfer: Account<'info, PendingTransfer>,
+
+    /// CHECK: Receives the pending transfer's rent; must be its proposer.
+    #[account(mut, address = pending_transfer.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+/// Accounts context for cancelling a pending transfer.
+#[derive(Accounts)]
+pub struct CancelTransfer<'info> {
+    /// The proposer or the root authority.
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_TRANSFER_SEED, config.key().as_ref(), &pending_transfer.id.to_le_bytes()],
+        bump = pending_transfer.bump,
+        has_one = config,
+        close = proposer
+    )]
+    pub pending_transfer: Account<'info, PendingTransfer>,
+
+    /// CHECK: Receives the pending transfer's rent; must be its proposer.
+    #[account(mut, address = pending_transfer.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+/// Accounts context for registering or revoking exporter API keys.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageViewerKeys<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_SETTINGS`; pays for the
+    /// key list on first use.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ViewerKeys::INIT_SPACE,
+        seeds = [VIEWER_KEYS_SEED, config.key().as_ref()],
+        bump
+    )]
+    pub viewer_keys: Account<'info, ViewerKeys>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for adding or removing listed recipients.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageRecipientList<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_ADDRESSES`; pays for the
+    /// list on first use.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + RecipientList::INIT_SPACE,
+        seeds = [RECIPIENT_LIST_SEED, config.key().as_ref()],
+        bump
+    )]
+    pub recipient_list: Account<'info, RecipientList>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for resizing the config PDA to the current layout.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// Root authority; pays for growth and receives shrink refunds.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: May still hold an older `Config` layout, so it is not deserialized
+    /// and its seeds cannot be read. Owner, discriminator, and authority are
+    /// checked in the handler.
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for sweeping stray lamports out of the config PDA.
+#[derive(Accounts)]
+pub struct RecoverLamports<'info> {
+    /// Root authority or a holder of `PERM_RECOVER`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Any account chosen by the authority to receive the excess lamports.
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+/// Accounts context for sweeping stray tokens held by the config PDA.
+#[derive(Accounts)]
+pub struct RecoverTokens<'info> {
+    /// Root authority or a holder of `PERM_RECOVER`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Token account whose owner is the config PDA.
+    #[account(mut, token::authority = config)]
+    pub stray: Account<'info, TokenAccount>,
+
+    /// Receives the tokens; must be for the same mint.
+    #[account(mut, token::mint = stray.mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Accounts context for funding the config vault.
+#[derive(Accounts)]
+pub struct FundVault<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// System-owned PDA holding the config's pooled lamports.
+    #[account(mut, seeds = [VAULT_SEED, config.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for withdrawing the vault during a pending close.
+#[derive(Accounts)]
+pub struct WithdrawVault<'info> {
+    #[account(mut, address = config.from)]
+    pub from: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [VAULT_SEED, config.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for enabling vault staking.
+#[derive(Accounts)]
+pub struct ConfigureVaultStaking<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_SETTINGS`; pays for the account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VaultStake::INIT_SPACE,
+        seeds = [VAULT_STAKE_SEED, config.key().as_ref()],
+        bump
+    )]
+    pub vault_stake: Account<'info, VaultStake>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for depositing vault lamports into the stake pool.
+#[derive(Accounts)]
+pub struct StakeVault<'info> {
+    /// Root authority or a holder of `PERM_UPDATE_SETTINGS`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_STAKE_SEED, config.key().as_ref()],
+        bump = vault_stake.bump,
+        has_one = config,
+        has_one = stake_pool,
+        has_one = pool_mint
+    )]
+    pub vault_stake: Account<'info, VaultStake>,
+
+    #[account(mut, seeds = [VAULT_SEED, config.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    /// Vault-owned pool token account receiving the minted pool tokens.
+    #[account(mut, token::mint = pool_mint, token::authority = vault)]
+    pub pool_tokens: Account<'info, TokenAccount>,
+
+    /// CHECK: Matches `vault_stake.stake_pool`; validated by the stake pool program.
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: Validated by the stake pool program.
+    pub withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: Validated by the stake pool program.
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: Validated by the stake pool program.
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    /// CHECK: Matches `vault_stake.pool_mint`; validated by the stake pool program.
+    #[account(mut)]
+    pub pool_mint: UncheckedAccount<'info>,
+
+    /// CHECK: The SPL stake pool program.
+    #[account(address = SPL_STAKE_POOL_PROGRAM_ID)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+}
+
+/// Accounts context for withdrawing the vault's stake pool position.
+#[derive(Accounts)]
+pub struct UnstakeVault<'info> {
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_STAKE_SEED, config.key().as_ref()],
+        bump = vault_stake.bump,
+        has_one = config,
+        has_one = stake_pool,
+        has_one = pool_mint,
+        has_one = beneficiary
+    )]
+    pub vault_stake: Account<'info, VaultStake>,
+
+    #[account(mut, seeds = [VAULT_SEED, config.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut, token::mint = pool_mint, token::authority = vault)]
+    pub pool_tokens: Account<'info, TokenAccount>,
+
+    /// Receives yield above the staked principal.
+    #[account(mut)]
+    pub beneficiary: SystemAccount<'info>,
+
+    /// CHECK: Matches `vault_stake.stake_pool`; validated by the stake pool program.
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: Validated by the stake pool program.
+    pub withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: Validated by the stake pool program.
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: Validated by the stake pool program.
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    /// CHECK: Matches `vault_stake.pool_mint`; validated by the stake pool program.
+    #[account(mut)]
+    pub pool_mint: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub stake_history: Sysvar<'info, StakeHistory>,
+    /// CHECK: The native stake program.
+    #[account(address = anchor_lang::solana_program::stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    /// CHECK: The SPL stake pool program.
+    #[account(address = SPL_STAKE_POOL_PROGRAM_ID)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for minting an allowance.
+#[derive(Accounts)]
+#[instruction(holder: Pubkey)]
+pub struct MintAllowance<'info> {
+    /// Root authority or a holder of `PERM_ALLOWANCES`; pays for the account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Allowance PDA, one per (config, holder).
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Allowance::INIT_SPACE,
+        seeds = [ALLOWANCE_SEED, config.key().as_ref(), holder.as_ref()],
+        bump
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for redeeming an allowance against the vault.
+#[derive(Accounts)]
+pub struct RedeemAllowance<'info> {
+    pub holder: Signer<'info>,
+
+    /// Mutable so the redemption counts in the rate limit and statistics.
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [ALLOWANCE_SEED, config.key().as_ref(), holder.key().as_ref()],
+        bump = allowance.bump,
+        has_one = holder
+    )]
+    pub allowance: Account<'info, Allowance>,
+
+    #[account(mut, seeds = [VAULT_SEED, config.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Must be the config's recipient, or listed in `recipient_list`;
+    /// checked against the allowance's own restriction in the handler.
+    #[account(
+        mut,
+        constraint = config.is_recipient(&config.key(), &recipient.key())
+            || matches!(&recipient_list, Some(list) if list.contains(&recipient.key()))
+            @ ConditionalError::RecipientMismatch
+    )]
+    pub recipient: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Global state PDA, checked for disabled features. May be
+    /// uninitialized, in which case every feature is enabled.
+    #[account(seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    /// Shared policy; required when `config.policy` is set.
+    pub policy: Option<Account<'info, Policy>>,
+
+    /// The config's recipient list; required when `recipient` is a listed
+    /// address rather than `config.to`.
+    #[account(
+        seeds = [RECIPIENT_LIST_SEED, config.key().as_ref()],
+        bump = recipient_list.bump
+    )]
+    pub recipient_list: Option<Account<'info, RecipientList>>,
+
+    /// Co-signer holding `PERM_APPROVE_ANOMALY`; required when the anomaly guard
+    /// flags the redemption and `require_approval` is on.
+    pub approver: Option<Signer<'info>>,
+
+    /// CHECK: Instructions sysvar; required when invoked via CPI under a restricted CPI policy.
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    /// Required when `config.beneficiary` is `TokenAccount` or
+    /// `AssociatedTokenAccount`, to sync the wSOL balance.
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: `recipient`'s wSOL associated token account, required (and derived
+    /// on-chain) when `config.beneficiary` is `AssociatedTokenAccount`.
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts context for revoking an allowance.
+#[derive(Accounts)]
+pub struct RevokeAllowance<'info> {
+    /// Root authority or a holder of `PERM_ALLOWANCES`; receives the rent.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [ALLOWANCE_SEED, config.key().as_ref(), allowance.holder.as_ref()],
+        bump = allowance.bump,
+        close = authority
+    )]
+    pub allowance: Account<'info, Allowance>,
+}
+
+/// Accounts context for opening the current epoch's summary.
+#[derive(Accounts)]
+pub struct OpenEpochSummary<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + EpochSummary::INIT_SPACE,
+        seeds = [
+            EPOCH_SUMMARY_SEED,
+            config.key().as_ref(),
+            &Clock::get()?.epoch.to_le_bytes()
+        ],
+        bump
+    )]
+    pub epoch_summary: Account<'info, EpochSummary>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for finalizing a past epoch's summary.
+#[derive(Accounts)]
+pub struct FinalizeEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [EPOCH_SUMMARY_SEED, config.key().as_ref(), &epoch_summary.epoch.to_le_bytes()],
+        bump = epoch_summary.bump,
+        has_one = config
+    )]
+    pub epoch_summary: Account<'info, EpochSummary>,
+}
+
+/// Accounts context for resizing the global state PDA to the current layout.
+#[derive(Accounts)]
+pub struct MigrateGlobal<'info> {
+    /// Program admin; pays for growth and receives shrink refunds.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: May still hold an older `GlobalState` layout, so it is not
+    /// deserialized. Owner, discriminator, and admin are checked in the handler.
+    #[account(mut, seeds = [GLOBAL_SEED], bump)]
+    pub global: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for creating the program-wide state.
+#[derive(Accounts)]
+pub struct InitializeGlobal<'info> {
+    /// Must be the program's upgrade authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + GlobalState::INIT_SPACE,
+        seeds = [GLOBAL_SEED],
+        bump
+    )]
+    pub global: Account<'info, GlobalState>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::ConditionalTransfer>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ ConditionalError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for rotating the program admin.
+#[derive(Accounts)]
+pub struct UpdateAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [GLOBAL_SEED], bump = global.bump, has_one = admin)]
+    pub global: Account<'info, GlobalState>,
+}
+
+/// Accounts context for the dual-signed emergency transfer.
+#[derive(Accounts)]
+pub struct AdminForceTransfer<'info> {
+    /// Program admin from global state.
+    pub admin: Signer<'info>,
+
+    /// Config authority.
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [GLOBAL_SEED], bump = global.bump, has_one = admin)]
+    pub global: Account<'info, GlobalState>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED, config.seed_from.as_ref(), config.seed_route.as_ref()],
+        bump = config.bump,
+        has_one = authority,
+        constraint = config.version == CONFIG_VERSION @ ConditionalError::ConfigMigrationRequired
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Program-owned escrow account of this config; validated in the handler.
+    #[account(mut)]
+    pub source: UncheckedAccount<'info>,
+
+    /// CHECK: Any account chosen by the two signers to receive the funds.
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+/// Accounts context for creating the event schema registry.
+#[derive(Accounts)]
+pub struct InitializeEventSchema<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + EventSchema::INIT_SPACE,
+        seeds = [EVENT_SCHEMA_SEED],
+        bump
+    )]
+    pub event_schema: Account<'info, EventSchema>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts context for syncing the event schema registry.
+#[derive(Accounts)]
+pub struct SyncEventSchema<'info> {
+    #[account(mut, seeds = [EVENT_SCHEMA_SEED], bump = event_schema.bump)]
+    pub event_schema: Account<'info, EventSchema>,
+}
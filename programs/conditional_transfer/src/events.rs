@@ -0,0 +1,304 @@
+//! Events emitted by the instructions. Changing one means bumping
+//! `EVENT_SCHEMA_VERSION`.
+
+use anchor_lang::prelude::*;
+
+/// Emitted on every use of the emergency `admin_force_transfer`.
+#[event]
+pub struct AdminForceTransferExecuted {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    pub admin: Pubkey,
+    pub authority: Pubkey,
+    /// Lifetime use count including this one.
+    pub use_count: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted after every lamport send from a config's funds: each
+/// `send_*` transfer (per payout share for `send_validator_payout`) and each
+/// escrow `release`. `to` is the recipient, not its wSOL account. A send
+/// that owes a fee emits one event for the fee, to `fee_collector`, before
+/// the one for the remainder.
+#[event]
+pub struct TransferExecuted {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    /// Solana Pay reference keys passed with the send; empty for other paths.
+    pub references: Vec<Pubkey>,
+    /// Balances of `from` and `to` after the transfer (and any match or
+    /// sweep), so consecutive events can be reconciled without historical RPC.
+    pub from_balance: u64,
+    pub to_balance: u64,
+    /// The send's memo, else the config's default memo; empty when neither
+    /// is set.
+    pub memo: String,
+    pub timestamp: i64,
+}
+
+/// Emitted when a below-threshold send is skipped under `BelowThresholdBehavior::Noop`.
+#[event]
+pub struct TransferSkipped {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    pub threshold_lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Logged when a `SendIfOverThreshold` send is rejected on a config with
+/// `log_rejections` on. The transaction usually fails (`try_send` returns
+/// `false` instead), so look for it in failed transactions' logs.
+#[event]
+pub struct TransferRejected {
+    /// Advances `event_seq` like any other event; the advance only sticks
+    /// when the transaction succeeds, as under `try_send`.
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    /// The amount attempted.
+    pub lamports: u64,
+    /// The rejection as the runtime reports it: `6000 + n` for
+    /// `ConditionalError` code `n`, or a builtin program error.
+    pub error_code: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `execute_scheduled` pays a scheduled transfer.
+#[event]
+pub struct ScheduledTransferExecuted {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub id: u64,
+    pub to: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted alongside a send of at least the config's `large_transfer_lamports`,
+/// so monitors can subscribe to high-value movements alone.
+#[event]
+pub struct LargeTransferExecuted {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    pub large_transfer_lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted after each `send_tokens_if_over_threshold` transfer.
+#[event]
+pub struct TokenTransferExecuted {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    /// In the mint's base units.
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `deposit` moves lamports into the escrow; `escrowed` is the
+/// escrow balance afterwards.
+#[event]
+pub struct EscrowDeposited {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub lamports: u64,
+    pub escrowed: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `release` pays escrowed lamports to the recipient.
+#[event]
+pub struct EscrowReleased {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    pub escrowed: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `refund` returns an expired escrow to `from`.
+#[event]
+pub struct EscrowRefunded {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `send_bridged` locks lamports and posts its Wormhole message.
+#[event]
+pub struct BridgedTransferInitiated {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub lamports: u64,
+    pub target_chain: u16,
+    pub recipient: [u8; 32],
+    pub nonce: u32,
+    /// Account the bridge posted the message to.
+    pub message: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the anomaly guard flags a send; `approver` is set when one co-signed.
+#[event]
+pub struct AnomalousTransfer {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    pub average_lamports: u64,
+    pub multiplier: u16,
+    pub approver: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when a committee member announces a recovery proposal.
+#[event]
+pub struct RecoveryProposed {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub proposer: Pubkey,
+    pub new_authority: Pubkey,
+    pub unlocks_at: i64,
+}
+
+/// Emitted when the root authority vetoes a pending recovery.
+#[event]
+pub struct RecoveryVetoed {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Emitted when a recovery replaces the root authority.
+#[event]
+pub struct AuthorityRecovered {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Emitted when `initialize` or `clone_config` creates a config.
+#[event]
+pub struct ConfigInitialized {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub authority: Pubkey,
+    pub from: Pubkey,
+    /// Zeroed while the recipient is hashed.
+    pub to: Pubkey,
+    pub threshold_lamports: u64,
+    /// Source config for `clone_config`.
+    pub cloned_from: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+/// Emitted when the threshold changes through `update_threshold`,
+/// `update_limits`, `adjust_threshold`, or a signed proposal.
+#[event]
+pub struct ThresholdUpdated {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub changed_by: Pubkey,
+    pub old_threshold_lamports: u64,
+    pub threshold_lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Logged when a monotonic config refuses to lower its threshold. The
+/// transaction fails, so it appears only in failed transactions' logs.
+#[event]
+pub struct ThresholdDecreaseRejected {
+    /// The config's current `event_seq`, not advanced: the transaction fails,
+    /// so the next committed event would reuse the number.
+    pub seq: u64,
+    pub config: Pubkey,
+    pub attempted_by: Pubkey,
+    /// The threshold in force, which stays.
+    pub threshold_lamports: u64,
+    pub rejected_threshold_lamports: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `update_addresses` or a signed proposal changes the route.
+#[event]
+pub struct AddressesUpdated {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub changed_by: Pubkey,
+    pub old_from: Pubkey,
+    /// Zeroed if the recipient was hashed.
+    pub old_to: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Which mutation produced a `ConfigChanged`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigChange {
+    Created,
+    Updated,
+    Closed,
+    /// `close_config` started the cooling period of a funded vault.
+    CloseRequested,
+    CloseCancelled,
+}
+
+/// Emitted through a self-CPI (`emit_cpi!`) after every config mutation, with
+/// the terms in force afterwards. It lands in the transaction's inner
+/// instructions rather than its logs, so wallets tracking standing payment
+/// authorizations stay in sync even when logs are truncated.
+#[event]
+pub struct ConfigChanged {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub change: ConfigChange,
+    /// Signer of the mutation; the root authority for signed proposals.
+    pub changed_by: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub from: Pubkey,
+    /// Zeroed while the recipient is hashed.
+    pub to: Pubkey,
+    pub hashed_recipient: bool,
+    pub threshold_lamports: u64,
+    pub max_lamports: u64,
+    pub valid_from_unix: Option<i64>,
+    pub valid_until_unix: Option<i64>,
+    pub policy: Option<Pubkey>,
+    pub threshold_override: Option<u64>,
+    pub timestamp: i64,
+}
+
+/// Emitted when an epoch summary is finalized.
+#[event]
+pub struct EpochFinalized {
+    pub seq: u64,
+    pub config: Pubkey,
+    pub epoch: u64,
+    pub volume: u64,
+    pub count: u64,
+    pub fees: u64,
+}
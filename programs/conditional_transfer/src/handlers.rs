@@ -0,0 +1,4602 @@
+//! Instruction handlers behind the `#[program]` entry points in `lib.rs`, and
+//! the checks, events, and account plumbing they share.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::{
+    get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT,
+};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar::instructions::{
+    get_instruction_relative, load_instruction_at_checked,
+};
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Token};
+
+use crate::*;
+
+pub(crate) fn initialize(
+    ctx: Context<Initialize>,
+    route: Pubkey,
+    to: Pubkey,
+    threshold_lamports: u64,
+) -> Result<()> {
+    let from = ctx.accounts.from.key();
+    let cfg = &mut ctx.accounts.config;
+    cfg.authority = ctx.accounts.authority.key();
+    cfg.from = from;
+    cfg.to = to;
+    cfg.threshold_lamports = threshold_lamports;
+    cfg.bump = ctx.bumps.config;
+    cfg.event_seq = 0;
+    cfg.default_memo = String::new();
+    cfg.allow_executable_recipient = false;
+    cfg.authorities = Vec::new();
+    cfg.below_threshold_behavior = BelowThresholdBehavior::Fail;
+    cfg.restrict_cpi = false;
+    cfg.cpi_allowlist = Vec::new();
+    cfg.epoch_reporting = false;
+    cfg.proposal_nonce = 0;
+    cfg.max_config_age_seconds = 0;
+    cfg.last_attested_ts = Clock::get()?.unix_timestamp;
+    cfg.beneficiary = Beneficiary::Sol;
+    cfg.policy = None;
+    cfg.policy_overrides = PolicyOverrides::default();
+    cfg.recipient_hash = None;
+    cfg.large_transfer_lamports = 0;
+    cfg.anomaly_guard = AnomalyGuard::default();
+    cfg.mint_allowlist = Vec::new();
+    cfg.bridge_route = None;
+    cfg.seed_from = from;
+    cfg.seed_route = route;
+    cfg.token_threshold = None;
+    cfg.max_lamports = 0;
+    cfg.valid_from_unix = None;
+    cfg.valid_until_unix = None;
+    cfg.pending_authority = None;
+    cfg.recipient_owners = Vec::new();
+    cfg.stats = TransferStats::default();
+    cfg.rate_limit = RateLimit::default();
+    cfg.last_update_id = 0;
+    cfg.threshold_usd_cents = None;
+    cfg.seeded_recipient = None;
+    cfg.paused = false;
+    cfg.approvers = Approvers::default();
+    cfg.private_threshold = None;
+    cfg.fee_bps = 0;
+    cfg.fee_collector = Pubkey::default();
+    cfg.close_requested_at = None;
+    cfg.monotonic_threshold = false;
+    cfg.comparison_mode = ComparisonMode::Gte;
+    cfg.log_rejections = false;
+    cfg.last_auto_adjust_unix = 0;
+    cfg.escrow_expiry_slot = None;
+    cfg.version = CONFIG_VERSION;
+    cfg.delegates = Vec::new();
+    cfg.auto_adjust = None;
+    add_to_recipient_index(
+        &ctx.accounts.recipient_index,
+        to,
+        cfg.key(),
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+    )?;
+    emit_config_initialized(&mut ctx.accounts.config, None)?;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Created,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn clone_config(
+    ctx: Context<CloneConfig>,
+    route: Pubkey,
+    to: Pubkey,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let now = Clock::get()?.unix_timestamp;
+    let from = ctx.accounts.from.key();
+    let cloned = ctx
+        .accounts
+        .source
+        .cloned_for(from, route, to, ctx.bumps.config, now);
+    ctx.accounts.config.set_inner(cloned);
+    add_to_recipient_index(
+        &ctx.accounts.recipient_index,
+        to,
+        ctx.accounts.config.key(),
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+    )?;
+    let source = ctx.accounts.source.key();
+    emit_config_initialized(&mut ctx.accounts.config, Some(source))?;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Created,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn send_if_over_threshold(
+    ctx: Context<SendIfOverThreshold>,
+    lamports: u64,
+    memo: Option<String>,
+) -> Result<()> {
+    require_not_sunset(&ctx.accounts.global)?;
+    require!(
+        !matches!(&memo, Some(memo) if memo.len() > MAX_MEMO_LEN),
+        ConditionalError::MemoTooLong
+    );
+    let clock = test_mode::clock(ctx.remaining_accounts)?;
+    let references = payment_references(ctx.remaining_accounts)?;
+    ctx.accounts.process(lamports, &clock, references, memo)
+}
+
+pub(crate) fn send_if_over_threshold_v2(
+    ctx: Context<SendIfOverThreshold>,
+    amount: TypedAmount,
+    memo: Option<String>,
+) -> Result<()> {
+    require!(
+        !matches!(&memo, Some(memo) if memo.len() > MAX_MEMO_LEN),
+        ConditionalError::MemoTooLong
+    );
+    let lamports = test_mode::to_lamports(&amount, ctx.remaining_accounts)?;
+    let clock = test_mode::clock(ctx.remaining_accounts)?;
+    let references = payment_references(ctx.remaining_accounts)?;
+    ctx.accounts.process(lamports, &clock, references, memo)
+}
+
+pub(crate) fn try_send(ctx: Context<SendIfOverThreshold>, lamports: u64) -> Result<bool> {
+    let clock = test_mode::clock(ctx.remaining_accounts)?;
+    if let Err(err) = ctx.accounts.check_send(lamports, &clock, false) {
+        msg!("try_send skipped: {}", err);
+        return Ok(false);
+    }
+    let references = payment_references(ctx.remaining_accounts)?;
+    ctx.accounts.execute(lamports, references, None)?;
+    Ok(true)
+}
+
+pub(crate) fn send_with_receipt(
+    ctx: Context<SendWithReceipt>,
+    lamports: u64,
+    reference: [u8; 32],
+) -> Result<()> {
+    let clock = test_mode::clock(ctx.remaining_accounts)?;
+    let references = payment_references(ctx.remaining_accounts)?;
+    let send = &mut ctx.accounts.send;
+    send.check_send(lamports, &clock, false)?;
+    send.execute(lamports, references, None)?;
+
+    let clock = Clock::get()?;
+    ctx.accounts.receipt.set_inner(Receipt {
+        config: send.config.key(),
+        reference,
+        payer: ctx.accounts.payer.key(),
+        from: send.from.key(),
+        to: send.to.key(),
+        lamports,
+        slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+        bump: ctx.bumps.receipt,
+        from_balance: send.from.lamports(),
+        to_balance: send.to.lamports(),
+    });
+    Ok(())
+}
+
+pub(crate) fn send_with_nonce(
+    ctx: Context<SendWithNonce>,
+    lamports: u64,
+    nonce: u64,
+) -> Result<()> {
+    let nonces = &mut ctx.accounts.nonces;
+    nonces.config = ctx.accounts.send.config.key();
+    nonces.sender = ctx.accounts.send.from.key();
+    nonces.bump = ctx.bumps.nonces;
+    require!(nonces.claim(nonce), ConditionalError::NonceAlreadyUsed);
+    msg!("Nonce {}", nonce);
+
+    let clock = test_mode::clock(ctx.remaining_accounts)?;
+    let references = payment_references(ctx.remaining_accounts)?;
+    ctx.accounts
+        .send
+        .process(lamports, &clock, references, None)
+}
+
+pub(crate) fn send_tokens_if_over_threshold(ctx: Context<SendTokens>, amount: u64) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_TOKEN_SEND)?;
+    let cfg = &ctx.accounts.config;
+    let violations = conditions::evaluate_tokens(
+        cfg,
+        amount,
+        &Clock::get()?,
+        ctx.accounts.from_token_account.amount,
+    );
+    if let Some(violation) = violations.first() {
+        return Err((*violation).into());
+    }
+    require_cpi_caller_allowed(cfg, ctx.accounts.instructions.as_ref())?;
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        token::Transfer {
+            from: ctx.accounts.from_token_account.to_account_info(),
+            to: ctx.accounts.to_token_account.to_account_info(),
+            authority: ctx.accounts.from.to_account_info(),
+        },
+    );
+    token::transfer(cpi_ctx, amount)?;
+    log_default_memo(cfg);
+
+    let cfg = &mut ctx.accounts.config;
+    emit!(TokenTransferExecuted {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        mint: ctx.accounts.mint.key(),
+        from: ctx.accounts.from.key(),
+        to: ctx.accounts.to.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub(crate) fn close_receipt(_ctx: Context<CloseReceipt>) -> Result<()> {
+    Ok(())
+}
+
+pub(crate) fn migrate_receipt(ctx: Context<MigrateReceipt>) -> Result<()> {
+    let receipt = ctx.accounts.receipt.to_account_info();
+    require_layout_owner(
+        &receipt,
+        &Receipt::DISCRIMINATOR,
+        RECEIPT_PAYER_OFFSET,
+        &ctx.accounts.payer.key(),
+    )?;
+    resize_account(
+        &receipt,
+        8 + Receipt::INIT_SPACE,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )
+}
+
+pub(crate) fn verify_payment(
+    ctx: Context<VerifyPayment>,
+    reference: [u8; 32],
+    min_lamports: u64,
+) -> Result<Receipt> {
+    let info = ctx.accounts.receipt.to_account_info();
+    require!(
+        info.owner == &crate::ID && !info.data_is_empty(),
+        ConditionalError::ReferenceNotPaid
+    );
+    let receipt = Receipt::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require!(
+        receipt.reference == reference,
+        ConditionalError::ReferenceNotPaid
+    );
+    require!(
+        receipt.lamports >= min_lamports,
+        ConditionalError::PaymentShortfall
+    );
+    Ok(receipt)
+}
+
+pub(crate) fn send_bridged(ctx: Context<SendBridged>, lamports: u64, nonce: u32) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_BRIDGED_SEND)?;
+    let accounts = &mut *ctx.accounts;
+    let route = accounts
+        .config
+        .bridge_route
+        .ok_or(ConditionalError::BridgeRouteNotSet)?;
+    require_keys_eq!(
+        accounts.wormhole_program.key(),
+        route.wormhole_program,
+        ConditionalError::InvalidBridgeRoute
+    );
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        lamports,
+        &Clock::get()?,
+        &accounts.from,
+        None,
+    )?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    check_velocity(
+        &mut accounts.config,
+        accounts.from.key(),
+        accounts.custody.key(),
+        lamports,
+        accounts.approver.as_ref(),
+    )?;
+
+    let cpi_ctx = CpiContext::new(
+        accounts.system_program.to_account_info(),
+        Transfer {
+            from: accounts.from.to_account_info(),
+            to: accounts.custody.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+
+    let fee = wormhole_message_fee(&accounts.wormhole_bridge)?;
+    if fee > 0 {
+        let cpi_ctx = CpiContext::new(
+            accounts.system_program.to_account_info(),
+            Transfer {
+                from: accounts.from.to_account_info(),
+                to: accounts.wormhole_fee_collector.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, fee)?;
+    }
+
+    let config_key = accounts.config.key();
+    let payload = bridge_payload(lamports, &config_key, &accounts.from.key(), &route);
+    let post_accounts = [
+        accounts.wormhole_bridge.to_account_info(),
+        accounts.wormhole_message.to_account_info(),
+        accounts.emitter.to_account_info(),
+        accounts.wormhole_sequence.to_account_info(),
+        accounts.from.to_account_info(),
+        accounts.wormhole_fee_collector.to_account_info(),
+        accounts.clock.to_account_info(),
+        accounts.rent.to_account_info(),
+        accounts.system_program.to_account_info(),
+    ];
+    let ix = Instruction {
+        program_id: route.wormhole_program,
+        accounts: post_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || account.key() == accounts.emitter.key(),
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: wormhole_post_message_data(nonce, &payload),
+    };
+    let bump = [ctx.bumps.emitter];
+    let signer_seeds: &[&[&[u8]]] = &[&[BRIDGE_EMITTER_SEED, &bump]];
+    invoke_signed(&ix, &post_accounts, signer_seeds)?;
+
+    let cfg = &mut ctx.accounts.config;
+    emit!(BridgedTransferInitiated {
+        seq: cfg.next_event_seq()?,
+        config: config_key,
+        from: ctx.accounts.from.key(),
+        lamports,
+        target_chain: route.chain,
+        recipient: route.recipient,
+        nonce,
+        message: ctx.accounts.wormhole_message.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[cfg(feature = "test-mode")]
+pub(crate) fn set_test_mocks(
+    ctx: Context<SetTestMocks>,
+    clock_offset_seconds: i64,
+    lamports_per_usd_cent: u64,
+    sol_usd_cents: u64,
+) -> Result<()> {
+    ctx.accounts.mocks.set_inner(test_mode::TestMocks {
+        clock_offset_seconds,
+        lamports_per_usd_cent,
+        bump: ctx.bumps.mocks,
+        sol_usd_cents,
+    });
+    Ok(())
+}
+
+pub(crate) fn deposit(ctx: Context<Deposit>, lamports: u64) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_ESCROW)?;
+    require!(lamports > 0, ConditionalError::InvalidAmount);
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    let cfg = &mut ctx.accounts.config;
+    emit!(EscrowDeposited {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        from: ctx.accounts.from.key(),
+        lamports,
+        escrowed: ctx.accounts.escrow.lamports(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub(crate) fn release(ctx: Context<Release>, lamports: u64) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_ESCROW)?;
+    require!(
+        !ctx.accounts.config.escrow_expired(Clock::get()?.slot),
+        ConditionalError::EscrowExpired
+    );
+    let accounts = &mut *ctx.accounts;
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        lamports,
+        &test_mode::clock(ctx.remaining_accounts)?,
+        &accounts.escrow,
+        Some(&accounts.to),
+    )?;
+    let recipient_token_account = accounts.recipient_token_account.as_deref();
+    accounts
+        .config
+        .beneficiary
+        .validate(&accounts.to, recipient_token_account)?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    check_velocity(
+        &mut accounts.config,
+        accounts.escrow.key(),
+        accounts.to.key(),
+        lamports,
+        accounts.approver.as_ref(),
+    )?;
+
+    let config_key = accounts.config.key();
+    let bump = [ctx.bumps.escrow];
+    let signer_seeds: &[&[&[u8]]] = &[&[ESCROW_SEED, config_key.as_ref(), &bump]];
+    let destination = accounts
+        .config
+        .beneficiary
+        .delivery_account(&accounts.to, recipient_token_account)?;
+    let cpi_ctx = CpiContext::new_with_signer(
+        accounts.system_program.to_account_info(),
+        Transfer {
+            from: accounts.escrow.to_account_info(),
+            to: destination.clone(),
+        },
+        signer_seeds,
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    accounts
+        .config
+        .beneficiary
+        .settle(destination, accounts.token_program.as_ref())?;
+    log_default_memo(&accounts.config);
+    let cfg = &mut accounts.config;
+    record_transfer(
+        cfg,
+        &accounts.escrow,
+        &accounts.to,
+        lamports,
+        Vec::new(),
+        None,
+    )?;
+    emit!(EscrowReleased {
+        seq: cfg.next_event_seq()?,
+        config: config_key,
+        to: accounts.to.key(),
+        lamports,
+        escrowed: accounts.escrow.lamports(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub(crate) fn refund(ctx: Context<Refund>) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    require!(
+        cfg.escrow_expired(Clock::get()?.slot),
+        ConditionalError::EscrowNotExpired
+    );
+    let lamports = ctx.accounts.escrow.lamports();
+    require!(lamports > 0, ConditionalError::InvalidAmount);
+    let config_key = cfg.key();
+    let bump = [ctx.bumps.escrow];
+    let signer_seeds: &[&[&[u8]]] = &[&[ESCROW_SEED, config_key.as_ref(), &bump]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.from.to_account_info(),
+        },
+        signer_seeds,
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    let cfg = &mut ctx.accounts.config;
+    emit!(EscrowRefunded {
+        seq: cfg.next_event_seq()?,
+        config: config_key,
+        from: ctx.accounts.from.key(),
+        lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub(crate) fn send_joint_if_over_threshold(ctx: Context<SendJoint>, lamports: u64) -> Result<()> {
+    require!(
+        get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT,
+        ConditionalError::JointSendViaCpi
+    );
+    require_feature_enabled(&ctx.accounts.global, FEATURE_JOINT_SEND)?;
+    let cfg = &mut ctx.accounts.config;
+    let total = joint_send_total(&ctx.accounts.instructions, &cfg.key())?;
+    let balances = conditions::Balances {
+        from_lamports: ctx.accounts.from.lamports(),
+        recipient_executable: ctx.accounts.to.executable,
+        recipient_owner: Some(*ctx.accounts.to.owner),
+        sol_usd_price: None,
+        quorum_approved: false,
+        range_proof_commitment: None,
+    };
+    let policy = ctx
+        .accounts
+        .policy
+        .as_ref()
+        .map(|policy| (policy.key(), &**policy));
+    // Every condition applies to the group's total, except the rate limit
+    // and the balance: each instruction draws its own part from those.
+    let violation = conditions::evaluate(cfg, policy, total, &Clock::get()?, &balances)
+        .into_iter()
+        .find(|violation| {
+            !matches!(
+                violation,
+                conditions::Violation::RateLimitExceeded { .. }
+                    | conditions::Violation::InsufficientFunds { .. }
+            )
+        });
+    if let Some(violation) = violation {
+        return Err(violation.into());
+    }
+    require_recipient_allowed(cfg, &ctx.accounts.to)?;
+    let recipient_token_account = ctx.accounts.recipient_token_account.as_deref();
+    cfg.beneficiary
+        .validate(&ctx.accounts.to, recipient_token_account)?;
+    check_velocity(
+        cfg,
+        ctx.accounts.from.key(),
+        ctx.accounts.to.key(),
+        lamports,
+        ctx.accounts.approver.as_ref(),
+    )?;
+
+    let destination = cfg
+        .beneficiary
+        .delivery_account(&ctx.accounts.to, recipient_token_account)?;
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: destination.clone(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    cfg.beneficiary
+        .settle(destination, ctx.accounts.token_program.as_ref())?;
+    msg!("Joint send: {} of {} lamports", lamports, total);
+    log_default_memo(cfg);
+    record_transfer(
+        cfg,
+        &ctx.accounts.from,
+        &ctx.accounts.to,
+        lamports,
+        Vec::new(),
+        None,
+    )
+}
+
+pub(crate) fn send_validator_payout<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SendValidatorPayout<'info>>,
+    total_lamports: u64,
+) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_VALIDATOR_PAYOUT)?;
+    require_conditions(
+        &ctx.accounts.config,
+        ctx.accounts.policy.as_ref(),
+        total_lamports,
+        &Clock::get()?,
+        &ctx.accounts.from,
+        None,
+    )?;
+    require_cpi_caller_allowed(&ctx.accounts.config, ctx.accounts.instructions.as_ref())?;
+    let cfg = &mut ctx.accounts.config;
+    let table = &ctx.accounts.payout_table;
+    let entries = &table.entries;
+    // Tables stored before canonical ordering must be rewritten first.
+    validate_payout_entries(entries)?;
+    let pairs = entries.len() * 2;
+    let dust_account = match table.remainder {
+        RemainderPolicy::DustAccount(account) => {
+            require!(
+                ctx.remaining_accounts.len() == pairs + 1,
+                ConditionalError::PayoutAccountsMismatch
+            );
+            let info = &ctx.remaining_accounts[pairs];
+            require_keys_eq!(
+                info.key(),
+                account,
+                ConditionalError::PayoutAccountsMismatch
+            );
+            require_recipient_allowed(cfg, info)?;
+            Some(info)
+        }
+        _ => {
+            require!(
+                ctx.remaining_accounts.len() == pairs,
+                ConditionalError::PayoutAccountsMismatch
+            );
+            None
+        }
+    };
+    check_velocity(
+        cfg,
+        ctx.accounts.from.key(),
+        table.key(),
+        total_lamports,
+        ctx.accounts.approver.as_ref(),
+    )?;
+
+    let shares_bps: Vec<u16> = entries.iter().map(|entry| entry.share_bps).collect();
+    let (shares, dust) = payout_shares(total_lamports, &shares_bps, table.remainder);
+    for (i, (entry, share)) in entries.iter().zip(shares).enumerate() {
+        let vote_account = &ctx.remaining_accounts[2 * i];
+        let withdrawer = &ctx.remaining_accounts[2 * i + 1];
+        require_keys_eq!(
+            vote_account.key(),
+            entry.vote_account,
+            ConditionalError::PayoutAccountsMismatch
+        );
+        require_keys_eq!(
+            withdrawer.key(),
+            vote_withdrawer(vote_account)?,
+            ConditionalError::PayoutAccountsMismatch
+        );
+        require_recipient_allowed(cfg, withdrawer)?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.from.to_account_info(),
+                to: withdrawer.clone(),
+            },
+        );
+        system_program::transfer(cpi_ctx, share)?;
+        record_transfer(cfg, &ctx.accounts.from, withdrawer, share, Vec::new(), None)?;
+    }
+    if let Some(dust_account) = dust_account.filter(|_| dust > 0) {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.from.to_account_info(),
+                to: dust_account.clone(),
+            },
+        );
+        system_program::transfer(cpi_ctx, dust)?;
+        record_transfer(
+            cfg,
+            &ctx.accounts.from,
+            dust_account,
+            dust,
+            Vec::new(),
+            None,
+        )?;
+    }
+    log_default_memo(cfg);
+    Ok(())
+}
+
+pub(crate) fn send_split_if_over_threshold<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SendSplit<'info>>,
+    lamports: u64,
+) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_SPLIT_SEND)?;
+    let accounts = &mut *ctx.accounts;
+    let entries = &accounts.split_table.entries;
+    // Tables stored before canonical ordering must be rewritten first.
+    validate_split_entries(entries)?;
+    require!(
+        ctx.remaining_accounts.len() == entries.len(),
+        ConditionalError::SplitAccountsMismatch
+    );
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        lamports,
+        &Clock::get()?,
+        &accounts.from,
+        None,
+    )?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    let cfg = &mut accounts.config;
+    check_velocity(
+        cfg,
+        accounts.from.key(),
+        accounts.split_table.key(),
+        lamports,
+        accounts.approver.as_ref(),
+    )?;
+
+    let shares_bps: Vec<u16> = entries.iter().map(|entry| entry.share_bps).collect();
+    let (shares, _) = payout_shares(lamports, &shares_bps, RemainderPolicy::LastRecipient);
+    for ((entry, share), recipient) in entries.iter().zip(shares).zip(ctx.remaining_accounts) {
+        require_keys_eq!(
+            recipient.key(),
+            entry.recipient,
+            ConditionalError::SplitAccountsMismatch
+        );
+        require_recipient_allowed(cfg, recipient)?;
+        let cpi_ctx = CpiContext::new(
+            accounts.system_program.to_account_info(),
+            Transfer {
+                from: accounts.from.to_account_info(),
+                to: recipient.clone(),
+            },
+        );
+        system_program::transfer(cpi_ctx, share)?;
+        record_transfer(cfg, &accounts.from, recipient, share, Vec::new(), None)?;
+    }
+    log_default_memo(cfg);
+    Ok(())
+}
+
+pub(crate) fn send_to_seeded_address(
+    ctx: Context<SendSeeded>,
+    lamports: u64,
+    seed: String,
+) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_SEEDED_SEND)?;
+    let accounts = &mut *ctx.accounts;
+    let recipient = accounts
+        .config
+        .seeded_recipient
+        .ok_or(ConditionalError::RecipientMismatch)?;
+    require!(
+        recipient.address(&seed) == Some(accounts.to.key()),
+        ConditionalError::SeededAddressMismatch
+    );
+    require!(
+        accounts.to.owner == &system_program::ID && accounts.to.data_is_empty(),
+        ConditionalError::SeededAddressInitialized
+    );
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        lamports,
+        &Clock::get()?,
+        &accounts.from,
+        Some(&accounts.to),
+    )?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    let cfg = &mut accounts.config;
+    check_velocity(
+        cfg,
+        accounts.from.key(),
+        accounts.to.key(),
+        lamports,
+        accounts.approver.as_ref(),
+    )?;
+
+    let cpi_ctx = CpiContext::new(
+        accounts.system_program.to_account_info(),
+        Transfer {
+            from: accounts.from.to_account_info(),
+            to: accounts.to.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    msg!("Seeded send to {} (seed {:?})", accounts.to.key(), seed);
+    log_default_memo(cfg);
+    record_transfer(
+        cfg,
+        &accounts.from,
+        &accounts.to,
+        lamports,
+        Vec::new(),
+        None,
+    )
+}
+
+pub(crate) fn create_match_pool(
+    ctx: Context<CreateMatchPool>,
+    ratio_bps: u16,
+    cap_lamports: u64,
+) -> Result<()> {
+    require!(ratio_bps > 0, ConditionalError::InvalidMatchRatio);
+    let pool = &mut ctx.accounts.match_pool;
+    pool.config = ctx.accounts.config.key();
+    pool.sponsor = ctx.accounts.sponsor.key();
+    pool.ratio_bps = ratio_bps;
+    pool.remaining_budget = cap_lamports;
+    pool.total_matched = 0;
+    pool.bump = ctx.bumps.match_pool;
+    pool.last_activity_ts = Clock::get()?.unix_timestamp;
+    Ok(())
+}
+
+pub(crate) fn fund_match_pool(ctx: Context<FundMatchPool>, lamports: u64) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.funder.to_account_info(),
+            to: ctx.accounts.match_pool.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    ctx.accounts.match_pool.last_activity_ts = Clock::get()?.unix_timestamp;
+    Ok(())
+}
+
+pub(crate) fn close_match_pool(_ctx: Context<CloseMatchPool>) -> Result<()> {
+    Ok(())
+}
+
+pub(crate) fn force_close_match_pool(ctx: Context<ForceCloseMatchPool>) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_CLOSE)?;
+    require_inactive(ctx.accounts.match_pool.last_activity_ts)
+}
+
+pub(crate) fn get_event_seq(ctx: Context<ReadConfig>) -> Result<u64> {
+    Ok(ctx.accounts.config.event_seq)
+}
+
+pub(crate) fn resolve_effective_policy(ctx: Context<ReadEffectivePolicy>) -> Result<PolicyTerms> {
+    ctx.accounts
+        .config
+        .resolve_policy(ctx.accounts.policy.as_ref())
+}
+
+pub(crate) fn get_config(ctx: Context<ReadEffectivePolicy>) -> Result<ConfigView> {
+    let cfg = &ctx.accounts.config;
+    let terms = cfg.resolve_policy(ctx.accounts.policy.as_ref())?;
+    Ok(cfg.view(terms))
+}
+
+pub(crate) fn create_milestone(
+    ctx: Context<CreateMilestone>,
+    index: u32,
+    amount: u64,
+    approver: Pubkey,
+) -> Result<()> {
+    let milestone = &mut ctx.accounts.milestone;
+    milestone.config = ctx.accounts.config.key();
+    milestone.payer = ctx.accounts.from.key();
+    milestone.approver = approver;
+    milestone.index = index;
+    milestone.amount = amount;
+    milestone.approved = false;
+    milestone.released = false;
+    milestone.bump = ctx.bumps.milestone;
+    milestone.last_activity_ts = Clock::get()?.unix_timestamp;
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.milestone.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, amount)?;
+    Ok(())
+}
+
+pub(crate) fn approve_milestone(ctx: Context<ApproveMilestone>) -> Result<()> {
+    let milestone = &mut ctx.accounts.milestone;
+    require!(
+        !milestone.released,
+        ConditionalError::MilestoneAlreadyReleased
+    );
+    milestone.approved = true;
+    milestone.last_activity_ts = Clock::get()?.unix_timestamp;
+    Ok(())
+}
+
+pub(crate) fn release_milestone(ctx: Context<ReleaseMilestone>) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let milestone = &accounts.milestone;
+    require!(milestone.approved, ConditionalError::MilestoneNotApproved);
+    require!(
+        !milestone.released,
+        ConditionalError::MilestoneAlreadyReleased
+    );
+    let amount = milestone.amount;
+    let clock = Clock::get()?;
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        amount,
+        &clock,
+        &accounts.milestone.to_account_info(),
+        Some(&accounts.to),
+    )?;
+    require_recipient_allowed(&accounts.config, &accounts.to)?;
+    let recipient_token_account = accounts.recipient_token_account.as_deref();
+    accounts
+        .config
+        .beneficiary
+        .validate(&accounts.to, recipient_token_account)?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    check_velocity(
+        &mut accounts.config,
+        accounts.milestone.payer,
+        accounts.to.key(),
+        amount,
+        accounts.approver.as_ref(),
+    )?;
+
+    // The milestone PDA is program-owned, so lamports can be moved directly.
+    let milestone = &mut accounts.milestone;
+    milestone.released = true;
+    milestone.last_activity_ts = clock.unix_timestamp;
+    milestone.sub_lamports(amount)?;
+    credit_beneficiary(
+        &accounts.config,
+        &accounts.to,
+        recipient_token_account,
+        accounts.token_program.as_ref(),
+        amount,
+    )?;
+    log_default_memo(&accounts.config);
+    record_transfer(
+        &mut accounts.config,
+        &accounts.milestone.to_account_info(),
+        &accounts.to,
+        amount,
+        Vec::new(),
+        None,
+    )
+}
+
+pub(crate) fn close_milestone(ctx: Context<CloseMilestone>) -> Result<()> {
+    require!(
+        ctx.accounts.milestone.released,
+        ConditionalError::MilestoneNotReleased
+    );
+    Ok(())
+}
+
+pub(crate) fn force_close_milestone(ctx: Context<ForceCloseMilestone>) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_CLOSE)?;
+    require_inactive(ctx.accounts.milestone.last_activity_ts)
+}
+
+pub(crate) fn create_stream(
+    ctx: Context<CreateStream>,
+    index: u32,
+    total: u64,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<()> {
+    require!(total > 0, ConditionalError::InvalidAmount);
+    require!(end_ts > start_ts, ConditionalError::InvalidStreamSchedule);
+    let stream = &mut ctx.accounts.stream;
+    stream.config = ctx.accounts.config.key();
+    stream.payer = ctx.accounts.from.key();
+    stream.index = index;
+    stream.total = total;
+    stream.withdrawn = 0;
+    stream.start_ts = start_ts;
+    stream.end_ts = end_ts;
+    stream.bump = ctx.bumps.stream;
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.stream.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, total)?;
+    Ok(())
+}
+
+pub(crate) fn withdraw_stream(ctx: Context<WithdrawStream>, lamports: u64) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let clock = test_mode::clock(ctx.remaining_accounts)?;
+    require!(
+        lamports <= accounts.stream.withdrawable(clock.unix_timestamp),
+        ConditionalError::StreamAmountUnavailable
+    );
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        lamports,
+        &clock,
+        &accounts.stream.to_account_info(),
+        Some(&accounts.to),
+    )?;
+    require_recipient_allowed(&accounts.config, &accounts.to)?;
+    let recipient_token_account = accounts.recipient_token_account.as_deref();
+    accounts
+        .config
+        .beneficiary
+        .validate(&accounts.to, recipient_token_account)?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    check_velocity(
+        &mut accounts.config,
+        accounts.stream.payer,
+        accounts.to.key(),
+        lamports,
+        accounts.approver.as_ref(),
+    )?;
+
+    // The stream PDA is program-owned, so lamports can be moved directly.
+    let stream = &mut accounts.stream;
+    stream.withdrawn += lamports;
+    stream.sub_lamports(lamports)?;
+    credit_beneficiary(
+        &accounts.config,
+        &accounts.to,
+        recipient_token_account,
+        accounts.token_program.as_ref(),
+        lamports,
+    )?;
+    log_default_memo(&accounts.config);
+    record_transfer(
+        &mut accounts.config,
+        &accounts.stream.to_account_info(),
+        &accounts.to,
+        lamports,
+        Vec::new(),
+        None,
+    )
+}
+
+pub(crate) fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    require_recipient_allowed(cfg, &ctx.accounts.to)?;
+    let recipient_token_account = ctx.accounts.recipient_token_account.as_deref();
+    let stream = &mut ctx.accounts.stream;
+    let owed = stream.withdrawable(Clock::get()?.unix_timestamp);
+    if owed > 0 {
+        cfg.beneficiary
+            .validate(&ctx.accounts.to, recipient_token_account)?;
+        stream.withdrawn += owed;
+        stream.sub_lamports(owed)?;
+        credit_beneficiary(
+            cfg,
+            &ctx.accounts.to,
+            recipient_token_account,
+            ctx.accounts.token_program.as_ref(),
+            owed,
+        )?;
+    }
+    Ok(())
+}
+
+pub(crate) fn create_delivery(
+    ctx: Context<CreateDelivery>,
+    index: u32,
+    amount: u64,
+    commitment: [u8; 32],
+    deadline: i64,
+) -> Result<()> {
+    let terms = ctx
+        .accounts
+        .config
+        .resolve_policy(ctx.accounts.policy.as_ref())?;
+    require_meets_threshold(&terms, amount)?;
+    let delivery = &mut ctx.accounts.delivery;
+    delivery.config = ctx.accounts.config.key();
+    delivery.payer = ctx.accounts.from.key();
+    delivery.index = index;
+    delivery.amount = amount;
+    delivery.commitment = commitment;
+    delivery.deadline = deadline;
+    delivery.acknowledged_at = None;
+    delivery.bump = ctx.bumps.delivery;
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.delivery.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, amount)?;
+    Ok(())
+}
+
+pub(crate) fn acknowledge_delivery(
+    ctx: Context<AcknowledgeDelivery>,
+    deliverable_hash: [u8; 32],
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let delivery = &accounts.delivery;
+    require!(
+        delivery.acknowledged_at.is_none(),
+        ConditionalError::DeliveryAlreadyAcknowledged
+    );
+    require!(
+        deliverable_hash == delivery.commitment,
+        ConditionalError::DeliveryHashMismatch
+    );
+    let amount = delivery.amount;
+    let clock = Clock::get()?;
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        amount,
+        &clock,
+        &accounts.delivery.to_account_info(),
+        Some(&accounts.to),
+    )?;
+    require_recipient_allowed(&accounts.config, &accounts.to)?;
+    let recipient_token_account = accounts.recipient_token_account.as_deref();
+    accounts
+        .config
+        .beneficiary
+        .validate(&accounts.to, recipient_token_account)?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    check_velocity(
+        &mut accounts.config,
+        accounts.delivery.payer,
+        accounts.to.key(),
+        amount,
+        accounts.approver.as_ref(),
+    )?;
+
+    let delivery = &mut accounts.delivery;
+    delivery.acknowledged_at = Some(clock.unix_timestamp);
+    delivery.sub_lamports(amount)?;
+    credit_beneficiary(
+        &accounts.config,
+        &accounts.to,
+        recipient_token_account,
+        accounts.token_program.as_ref(),
+        amount,
+    )?;
+    log_default_memo(&accounts.config);
+    record_transfer(
+        &mut accounts.config,
+        &accounts.delivery.to_account_info(),
+        &accounts.to,
+        amount,
+        Vec::new(),
+        None,
+    )
+}
+
+pub(crate) fn close_delivery(ctx: Context<CloseDelivery>) -> Result<()> {
+    let delivery = &ctx.accounts.delivery;
+    require!(
+        delivery.acknowledged_at.is_some() || Clock::get()?.unix_timestamp > delivery.deadline,
+        ConditionalError::DeliveryPending
+    );
+    Ok(())
+}
+
+pub(crate) fn schedule_transfer(
+    ctx: Context<ScheduleTransfer>,
+    id: u64,
+    lamports: u64,
+    unlock_slot: u64,
+    expiry_slot: Option<u64>,
+) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_SEND)?;
+    let cfg = &ctx.accounts.config;
+    let clock = Clock::get()?;
+    require!(
+        unlock_slot > clock.slot,
+        ConditionalError::InvalidUnlockSlot
+    );
+    require!(
+        !matches!(expiry_slot, Some(expiry) if expiry <= unlock_slot),
+        ConditionalError::InvalidExpirySlot
+    );
+    require!(lamports > 0, ConditionalError::InvalidAmount);
+    require_conditions(
+        cfg,
+        ctx.accounts.policy.as_ref(),
+        lamports,
+        &clock,
+        &ctx.accounts.from,
+        None,
+    )?;
+    require_cpi_caller_allowed(cfg, ctx.accounts.instructions.as_ref())?;
+    ctx.accounts.scheduled.set_inner(ScheduledTransfer {
+        config: cfg.key(),
+        id,
+        payer: ctx.accounts.from.key(),
+        lamports,
+        unlock_slot,
+        bump: ctx.bumps.scheduled,
+        expiry_slot,
+        reserved: [0; state::layout::RESERVED_LEN - 9],
+    });
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.scheduled.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    Ok(())
+}
+
+pub(crate) fn execute_scheduled(ctx: Context<ExecuteScheduled>) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_SEND)?;
+    let accounts = &mut *ctx.accounts;
+    let scheduled = &accounts.scheduled;
+    let clock = Clock::get()?;
+    require!(
+        scheduled.is_unlocked(clock.slot),
+        ConditionalError::ScheduledTransferLocked
+    );
+    require!(
+        !scheduled.is_expired(clock.slot),
+        ConditionalError::EscrowExpired
+    );
+    let (id, lamports) = (scheduled.id, scheduled.lamports);
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        lamports,
+        &clock,
+        &accounts.scheduled.to_account_info(),
+        Some(&accounts.to),
+    )?;
+    let recipient_token_account = accounts.recipient_token_account.as_deref();
+    accounts
+        .config
+        .beneficiary
+        .validate(&accounts.to, recipient_token_account)?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    check_velocity(
+        &mut accounts.config,
+        accounts.payer.key(),
+        accounts.to.key(),
+        lamports,
+        accounts.approver.as_ref(),
+    )?;
+
+    // The scheduled PDA is program-owned, so lamports can be moved directly.
+    accounts.scheduled.sub_lamports(lamports)?;
+    credit_beneficiary(
+        &accounts.config,
+        &accounts.to,
+        recipient_token_account,
+        accounts.token_program.as_ref(),
+        lamports,
+    )?;
+    log_default_memo(&accounts.config);
+    record_transfer(
+        &mut accounts.config,
+        &accounts.scheduled.to_account_info(),
+        &accounts.to,
+        lamports,
+        Vec::new(),
+        None,
+    )?;
+    let cfg = &mut accounts.config;
+    emit!(ScheduledTransferExecuted {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        id,
+        to: accounts.to.key(),
+        lamports,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub(crate) fn cancel_scheduled(ctx: Context<CancelScheduled>) -> Result<()> {
+    require!(
+        !ctx.accounts.scheduled.is_unlocked(Clock::get()?.slot),
+        ConditionalError::ScheduledTransferUnlocked
+    );
+    Ok(())
+}
+
+pub(crate) fn refund_scheduled(ctx: Context<CancelScheduled>) -> Result<()> {
+    require!(
+        ctx.accounts.scheduled.is_expired(Clock::get()?.slot),
+        ConditionalError::EscrowNotExpired
+    );
+    Ok(())
+}
+
+pub(crate) fn create_bounty(
+    ctx: Context<CreateBounty>,
+    target: Pubkey,
+    min_lamports: u64,
+    reward: u64,
+    expires_at: i64,
+) -> Result<()> {
+    require!(
+        min_lamports > 0 && reward > 0,
+        ConditionalError::InvalidAmount
+    );
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        ConditionalError::BountyExpired
+    );
+    let bounty = &mut ctx.accounts.bounty;
+    bounty.sponsor = ctx.accounts.sponsor.key();
+    bounty.target = target;
+    bounty.min_lamports = min_lamports;
+    bounty.reward = reward;
+    bounty.expires_at = expires_at;
+    bounty.winner = None;
+    bounty.bump = ctx.bumps.bounty;
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.sponsor.to_account_info(),
+            to: ctx.accounts.bounty.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, reward)?;
+    Ok(())
+}
+
+pub(crate) fn fund_bounty_target(ctx: Context<FundBountyTarget>, lamports: u64) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.sender.to_account_info(),
+            to: ctx.accounts.target.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+
+    let bounty = &mut ctx.accounts.bounty;
+    if bounty.winner.is_none()
+        && lamports >= bounty.min_lamports
+        && Clock::get()?.unix_timestamp < bounty.expires_at
+    {
+        bounty.winner = Some(ctx.accounts.sender.key());
+        bounty.sub_lamports(bounty.reward)?;
+        ctx.accounts.sender.add_lamports(bounty.reward)?;
+        msg!(
+            "Bounty won by {}: {} lamports",
+            ctx.accounts.sender.key(),
+            bounty.reward
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn close_bounty(ctx: Context<CloseBounty>) -> Result<()> {
+    let bounty = &ctx.accounts.bounty;
+    require!(
+        bounty.winner.is_some() || Clock::get()?.unix_timestamp >= bounty.expires_at,
+        ConditionalError::BountyActive
+    );
+    Ok(())
+}
+
+pub(crate) fn create_sweep(
+    ctx: Context<CreateSweep>,
+    cold_wallet: Pubkey,
+    threshold_lamports: u64,
+) -> Result<()> {
+    let sweep = &mut ctx.accounts.sweep;
+    sweep.owner = ctx.accounts.owner.key();
+    sweep.cold_wallet = cold_wallet;
+    sweep.threshold_lamports = threshold_lamports;
+    sweep.bump = ctx.bumps.sweep;
+    sweep.last_update_id = 0;
+    Ok(())
+}
+
+pub(crate) fn update_sweep(
+    ctx: Context<UpdateSweep>,
+    cold_wallet: Pubkey,
+    threshold_lamports: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let sweep = &mut ctx.accounts.sweep;
+    if !claim_update_id(&mut sweep.last_update_id, update_id)? {
+        return Ok(());
+    }
+    sweep.cold_wallet = cold_wallet;
+    sweep.threshold_lamports = threshold_lamports;
+    Ok(())
+}
+
+pub(crate) fn migrate_sweep(ctx: Context<MigrateSweep>) -> Result<()> {
+    let sweep = ctx.accounts.sweep.to_account_info();
+    require_layout_owner(&sweep, &Sweep::DISCRIMINATOR, 8, &ctx.accounts.owner.key())?;
+    resize_account(
+        &sweep,
+        8 + Sweep::INIT_SPACE,
+        &ctx.accounts.owner,
+        &ctx.accounts.system_program,
+    )
+}
+
+pub(crate) fn close_sweep(_ctx: Context<CloseSweep>) -> Result<()> {
+    Ok(())
+}
+
+pub(crate) fn fund_vault(ctx: Context<FundVault>, lamports: u64) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.funder.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    Ok(())
+}
+
+pub(crate) fn withdraw_vault(ctx: Context<WithdrawVault>) -> Result<()> {
+    require!(
+        ctx.accounts.config.close_requested_at.is_some(),
+        ConditionalError::CloseNotRequested
+    );
+    let lamports = ctx.accounts.vault.lamports();
+    require!(lamports > 0, ConditionalError::NothingToRecover);
+    let config_key = ctx.accounts.config.key();
+    let bump = [ctx.bumps.vault];
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, config_key.as_ref(), &bump]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.from.to_account_info(),
+        },
+        signer_seeds,
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    msg!("Withdrew {} lamports from the vault", lamports);
+    Ok(())
+}
+
+pub(crate) fn configure_vault_staking(
+    ctx: Context<ConfigureVaultStaking>,
+    stake_pool: Pubkey,
+    pool_mint: Pubkey,
+    beneficiary: Pubkey,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    let vault_stake = &mut ctx.accounts.vault_stake;
+    vault_stake.config = ctx.accounts.config.key();
+    vault_stake.stake_pool = stake_pool;
+    vault_stake.pool_mint = pool_mint;
+    vault_stake.beneficiary = beneficiary;
+    vault_stake.principal = 0;
+    vault_stake.bump = ctx.bumps.vault_stake;
+    Ok(())
+}
+
+pub(crate) fn stake_vault(ctx: Context<StakeVault>, lamports: u64) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_VAULT_STAKING)?;
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    require!(lamports > 0, ConditionalError::InvalidAmount);
+    require!(
+        ctx.accounts.vault.lamports() >= lamports,
+        ConditionalError::InsufficientFunds
+    );
+
+    let accounts = [
+        ctx.accounts.stake_pool.to_account_info(),
+        ctx.accounts.withdraw_authority.to_account_info(),
+        ctx.accounts.reserve_stake.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.pool_tokens.to_account_info(),
+        ctx.accounts.manager_fee_account.to_account_info(),
+        ctx.accounts.pool_tokens.to_account_info(),
+        ctx.accounts.pool_mint.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    ];
+    let ix = stake_pool_instruction(
+        STAKE_POOL_DEPOSIT_SOL,
+        lamports,
+        &accounts,
+        ctx.accounts.vault.key,
+    );
+    let config_key = ctx.accounts.config.key();
+    let bump = [ctx.bumps.vault];
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, config_key.as_ref(), &bump]];
+    let program = ctx.accounts.stake_pool_program.to_account_info();
+    invoke_signed(&ix, &[&accounts[..], &[program]].concat(), signer_seeds)?;
+
+    let vault_stake = &mut ctx.accounts.vault_stake;
+    vault_stake.principal = vault_stake
+        .principal
+        .checked_add(lamports)
+        .ok_or(ConditionalError::MathOverflow)?;
+    Ok(())
+}
+
+pub(crate) fn unstake_vault(ctx: Context<UnstakeVault>) -> Result<()> {
+    let pool_tokens = ctx.accounts.pool_tokens.amount;
+    require!(pool_tokens > 0, ConditionalError::NothingToRecover);
+
+    let before = ctx.accounts.vault.lamports();
+    let accounts = [
+        ctx.accounts.stake_pool.to_account_info(),
+        ctx.accounts.withdraw_authority.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.pool_tokens.to_account_info(),
+        ctx.accounts.reserve_stake.to_account_info(),
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.manager_fee_account.to_account_info(),
+        ctx.accounts.pool_mint.to_account_info(),
+        ctx.accounts.clock.to_account_info(),
+        ctx.accounts.stake_history.to_account_info(),
+        ctx.accounts.stake_program.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+    ];
+    let ix = stake_pool_instruction(
+        STAKE_POOL_WITHDRAW_SOL,
+        pool_tokens,
+        &accounts,
+        ctx.accounts.vault.key,
+    );
+    let config_key = ctx.accounts.config.key();
+    let bump = [ctx.bumps.vault];
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, config_key.as_ref(), &bump]];
+    let program = ctx.accounts.stake_pool_program.to_account_info();
+    invoke_signed(&ix, &[&accounts[..], &[program]].concat(), signer_seeds)?;
+
+    let received = ctx.accounts.vault.lamports().saturating_sub(before);
+    let vault_stake = &mut ctx.accounts.vault_stake;
+    let earned = received.saturating_sub(vault_stake.principal);
+    vault_stake.principal = 0;
+    if earned > 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.beneficiary.to_account_info(),
+            },
+            signer_seeds,
+        );
+        system_program::transfer(cpi_ctx, earned)?;
+    }
+    msg!("Unstaked {} lamports, {} yield", received, earned);
+    Ok(())
+}
+
+pub(crate) fn mint_allowance(
+    ctx: Context<MintAllowance>,
+    holder: Pubkey,
+    amount: u64,
+    expires_at: i64,
+    recipient: Option<Pubkey>,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_ALLOWANCES)?;
+    require!(
+        expires_at > Clock::get()?.unix_timestamp,
+        ConditionalError::AllowanceExpired
+    );
+
+    let allowance = &mut ctx.accounts.allowance;
+    allowance.config = ctx.accounts.config.key();
+    allowance.holder = holder;
+    allowance.remaining = amount;
+    allowance.expires_at = expires_at;
+    allowance.recipient = recipient;
+    allowance.bump = ctx.bumps.allowance;
+    Ok(())
+}
+
+pub(crate) fn redeem_allowance(ctx: Context<RedeemAllowance>, lamports: u64) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_ALLOWANCES)?;
+    let accounts = &mut *ctx.accounts;
+    let clock = Clock::get()?;
+    let allowance = &mut accounts.allowance;
+    require!(
+        clock.unix_timestamp < allowance.expires_at,
+        ConditionalError::AllowanceExpired
+    );
+    require!(
+        lamports <= allowance.remaining,
+        ConditionalError::AllowanceExceeded
+    );
+    let recipient = accounts.recipient.key();
+    require!(
+        !matches!(allowance.recipient, Some(allowed) if allowed != recipient),
+        ConditionalError::RecipientNotAllowed
+    );
+    require_conditions(
+        &accounts.config,
+        accounts.policy.as_ref(),
+        lamports,
+        &clock,
+        &accounts.vault,
+        Some(&accounts.recipient),
+    )?;
+    let recipient_token_account = accounts.recipient_token_account.as_deref();
+    accounts
+        .config
+        .beneficiary
+        .validate(&accounts.recipient, recipient_token_account)?;
+    require_cpi_caller_allowed(&accounts.config, accounts.instructions.as_ref())?;
+    check_velocity(
+        &mut accounts.config,
+        accounts.vault.key(),
+        recipient,
+        lamports,
+        accounts.approver.as_ref(),
+    )?;
+
+    allowance.remaining -= lamports;
+    let config_key = accounts.config.key();
+    let bump = [ctx.bumps.vault];
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, config_key.as_ref(), &bump]];
+    let destination = accounts
+        .config
+        .beneficiary
+        .delivery_account(&accounts.recipient, recipient_token_account)?;
+    let cpi_ctx = CpiContext::new_with_signer(
+        accounts.system_program.to_account_info(),
+        Transfer {
+            from: accounts.vault.to_account_info(),
+            to: destination.clone(),
+        },
+        signer_seeds,
+    );
+    system_program::transfer(cpi_ctx, lamports)?;
+    accounts
+        .config
+        .beneficiary
+        .settle(destination, accounts.token_program.as_ref())?;
+    log_default_memo(&accounts.config);
+    record_transfer(
+        &mut accounts.config,
+        &accounts.vault,
+        &accounts.recipient,
+        lamports,
+        Vec::new(),
+        None,
+    )
+}
+
+pub(crate) fn revoke_allowance(ctx: Context<RevokeAllowance>) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_ALLOWANCES)
+}
+
+pub(crate) fn open_epoch_summary(ctx: Context<OpenEpochSummary>) -> Result<()> {
+    let summary = &mut ctx.accounts.epoch_summary;
+    summary.config = ctx.accounts.config.key();
+    summary.epoch = Clock::get()?.epoch;
+    summary.volume = 0;
+    summary.count = 0;
+    summary.fees = 0;
+    summary.finalized = false;
+    summary.bump = ctx.bumps.epoch_summary;
+    Ok(())
+}
+
+pub(crate) fn finalize_epoch(ctx: Context<FinalizeEpoch>) -> Result<()> {
+    let summary = &mut ctx.accounts.epoch_summary;
+    require!(
+        Clock::get()?.epoch > summary.epoch,
+        ConditionalError::EpochNotOver
+    );
+    require!(!summary.finalized, ConditionalError::EpochAlreadyFinalized);
+    summary.finalized = true;
+
+    let cfg = &mut ctx.accounts.config;
+    emit!(EpochFinalized {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        epoch: summary.epoch,
+        volume: summary.volume,
+        count: summary.count,
+        fees: summary.fees,
+    });
+    Ok(())
+}
+
+pub(crate) fn initialize_global(ctx: Context<InitializeGlobal>, admin: Pubkey) -> Result<()> {
+    let global = &mut ctx.accounts.global;
+    global.admin = admin;
+    global.force_transfer_count = 0;
+    global.disabled_features = 0;
+    global.sunset_slot = 0;
+    global.bump = ctx.bumps.global;
+    global.last_update_id = 0;
+    Ok(())
+}
+
+pub(crate) fn migrate_global(ctx: Context<MigrateGlobal>) -> Result<()> {
+    let global = ctx.accounts.global.to_account_info();
+    require_layout_owner(
+        &global,
+        &GlobalState::DISCRIMINATOR,
+        8,
+        &ctx.accounts.admin.key(),
+    )?;
+    resize_account(
+        &global,
+        8 + GlobalState::INIT_SPACE,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )
+}
+
+pub(crate) fn update_admin(
+    ctx: Context<UpdateAdmin>,
+    new_admin: Pubkey,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let global = &mut ctx.accounts.global;
+    if !claim_update_id(&mut global.last_update_id, update_id)? {
+        return Ok(());
+    }
+    global.admin = new_admin;
+    Ok(())
+}
+
+pub(crate) fn set_disabled_features(
+    ctx: Context<UpdateAdmin>,
+    disabled_features: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let global = &mut ctx.accounts.global;
+    if !claim_update_id(&mut global.last_update_id, update_id)? {
+        return Ok(());
+    }
+    global.disabled_features = disabled_features;
+    msg!("Disabled features: {:#x}", disabled_features);
+    Ok(())
+}
+
+pub(crate) fn set_sunset_slot(
+    ctx: Context<UpdateAdmin>,
+    sunset_slot: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let global = &mut ctx.accounts.global;
+    if !claim_update_id(&mut global.last_update_id, update_id)? {
+        return Ok(());
+    }
+    global.sunset_slot = sunset_slot;
+    msg!("v1 instructions sunset at slot {}", sunset_slot);
+    Ok(())
+}
+
+pub(crate) fn admin_force_transfer(ctx: Context<AdminForceTransfer>, lamports: u64) -> Result<()> {
+    let source = &ctx.accounts.source;
+    require_keys_eq!(
+        *source.owner,
+        crate::ID,
+        ConditionalError::InvalidForceTransferSource
+    );
+    require_keys_neq!(
+        source.key(),
+        ctx.accounts.config.key(),
+        ConditionalError::InvalidForceTransferSource
+    );
+    {
+        // Escrow accounts store their owning config right after the discriminator.
+        let data = source.try_borrow_data()?;
+        require!(
+            data.len() >= 40 && data[8..40] == ctx.accounts.config.key().to_bytes(),
+            ConditionalError::InvalidForceTransferSource
+        );
+    }
+    let rent_floor = Rent::get()?.minimum_balance(source.data_len());
+    require!(
+        source.lamports().saturating_sub(rent_floor) >= lamports,
+        ConditionalError::InsufficientFunds
+    );
+
+    source.sub_lamports(lamports)?;
+    ctx.accounts.destination.add_lamports(lamports)?;
+
+    let global = &mut ctx.accounts.global;
+    global.force_transfer_count = global
+        .force_transfer_count
+        .checked_add(1)
+        .ok_or(ConditionalError::MathOverflow)?;
+
+    let cfg = &mut ctx.accounts.config;
+    emit!(AdminForceTransferExecuted {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        source: source.key(),
+        destination: ctx.accounts.destination.key(),
+        lamports,
+        admin: ctx.accounts.admin.key(),
+        authority: ctx.accounts.authority.key(),
+        use_count: global.force_transfer_count,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub(crate) fn initialize_event_schema(ctx: Context<InitializeEventSchema>) -> Result<()> {
+    let schema = &mut ctx.accounts.event_schema;
+    schema.version = EVENT_SCHEMA_VERSION;
+    schema.changelog = 0;
+    schema.updated_slot = Clock::get()?.slot;
+    schema.bump = ctx.bumps.event_schema;
+    Ok(())
+}
+
+pub(crate) fn sync_event_schema(ctx: Context<SyncEventSchema>) -> Result<()> {
+    let schema = &mut ctx.accounts.event_schema;
+    if schema.version < EVENT_SCHEMA_VERSION {
+        schema.version = EVENT_SCHEMA_VERSION;
+        schema.changelog = schema
+            .changelog
+            .checked_add(1)
+            .ok_or(ConditionalError::MathOverflow)?;
+        schema.updated_slot = Clock::get()?.slot;
+    }
+    Ok(())
+}
+
+pub(crate) fn update_threshold(
+    ctx: Context<Update>,
+    new_threshold_lamports: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    require!(
+        cfg.max_lamports == 0 || new_threshold_lamports <= cfg.max_lamports,
+        ConditionalError::InvalidLimits
+    );
+    require_threshold_not_lowered(cfg, ctx.accounts.authority.key(), new_threshold_lamports)?;
+    let old_threshold_lamports = cfg.threshold_lamports;
+    cfg.threshold_lamports = new_threshold_lamports;
+    emit_threshold_updated(cfg, ctx.accounts.authority.key(), old_threshold_lamports)?;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn update_limits(
+    ctx: Context<Update>,
+    threshold_lamports: u64,
+    max_lamports: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        max_lamports == 0 || threshold_lamports <= max_lamports,
+        ConditionalError::InvalidLimits
+    );
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    require_threshold_not_lowered(cfg, ctx.accounts.authority.key(), threshold_lamports)?;
+    let old_threshold_lamports = cfg.threshold_lamports;
+    cfg.threshold_lamports = threshold_lamports;
+    cfg.max_lamports = max_lamports;
+    emit_threshold_updated(cfg, ctx.accounts.authority.key(), old_threshold_lamports)?;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn update_rate_limit(
+    ctx: Context<Update>,
+    capacity: u64,
+    refill_per_second: u32,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        refill_per_second == 0 || capacity > 0,
+        ConditionalError::InvalidRateLimit
+    );
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.rate_limit.settle(Clock::get()?.unix_timestamp);
+    cfg.rate_limit.capacity = capacity;
+    cfg.rate_limit.refill_per_second = refill_per_second;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_paused(
+    ctx: Context<Update>,
+    paused: bool,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_PAUSE)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.paused = paused;
+    msg!("Config {}", if paused { "paused" } else { "unpaused" });
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn enable_monotonic_threshold(
+    ctx: Context<Update>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    require!(
+        cfg.comparison_mode.is_floor(),
+        ConditionalError::InvalidComparisonMode
+    );
+    require!(
+        cfg.auto_adjust.is_none(),
+        ConditionalError::InvalidAutoAdjust
+    );
+    cfg.monotonic_threshold = true;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn update_comparison_mode(
+    ctx: Context<Update>,
+    comparison_mode: ComparisonMode,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    require!(
+        !cfg.monotonic_threshold
+            || comparison_mode == cfg.comparison_mode
+            || comparison_mode == ComparisonMode::Gt,
+        ConditionalError::InvalidComparisonMode
+    );
+    cfg.comparison_mode = comparison_mode;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_usd_threshold(
+    ctx: Context<Update>,
+    threshold_usd_cents: Option<u64>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        threshold_usd_cents != Some(0),
+        ConditionalError::InvalidAmount
+    );
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.threshold_usd_cents = threshold_usd_cents;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_auto_adjust(
+    ctx: Context<Update>,
+    auto_adjust: Option<AutoAdjust>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    if let Some(terms) = &auto_adjust {
+        require!(
+            terms.is_valid()
+                && (cfg.max_lamports == 0 || terms.max_lamports <= cfg.max_lamports)
+                && !cfg.monotonic_threshold,
+            ConditionalError::InvalidAutoAdjust
+        );
+    }
+    cfg.auto_adjust = auto_adjust;
+    cfg.last_auto_adjust_unix = 0;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn adjust_threshold(ctx: Context<AdjustThreshold>) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_ORACLE)?;
+    let now = Clock::get()?.unix_timestamp;
+    let cfg = &mut ctx.accounts.config;
+    let terms = cfg
+        .auto_adjust
+        .ok_or(ConditionalError::AutoAdjustDisabled)?;
+    require!(
+        terms.is_due(cfg.last_auto_adjust_unix, now),
+        ConditionalError::AdjustmentTooEarly
+    );
+    let price = test_mode::sol_usd_price(&ctx.accounts.price_feed, now)?;
+    let threshold_lamports = terms
+        .threshold_lamports(&price)
+        .ok_or(ConditionalError::InvalidPriceFeed)?;
+    require!(
+        cfg.max_lamports == 0 || threshold_lamports <= cfg.max_lamports,
+        ConditionalError::InvalidLimits
+    );
+    cfg.last_auto_adjust_unix = now;
+    let old_threshold_lamports = cfg.threshold_lamports;
+    cfg.threshold_lamports = threshold_lamports;
+    emit_threshold_updated(cfg, ctx.accounts.cranker.key(), old_threshold_lamports)?;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.cranker.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_private_threshold(
+    ctx: Context<Update>,
+    commitment: Option<[u8; 32]>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        !matches!(commitment, Some(c) if !private_threshold::is_valid_commitment(&c)),
+        ConditionalError::InvalidCommitment
+    );
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.private_threshold = commitment;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_fee(
+    ctx: Context<Update>,
+    fee_bps: u16,
+    fee_collector: Pubkey,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        fee_bps <= MAX_FEE_BPS && (fee_bps == 0 || fee_collector != Pubkey::default()),
+        ConditionalError::InvalidFee
+    );
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.fee_bps = fee_bps;
+    cfg.fee_collector = fee_collector;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn update_time_window(
+    ctx: Context<Update>,
+    valid_from_unix: Option<i64>,
+    valid_until_unix: Option<i64>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    if let (Some(from), Some(until)) = (valid_from_unix, valid_until_unix) {
+        require!(from <= until, ConditionalError::InvalidTimeWindow);
+    }
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.valid_from_unix = valid_from_unix;
+    cfg.valid_until_unix = valid_until_unix;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_escrow_expiry(
+    ctx: Context<Update>,
+    expiry_slot: Option<u64>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.escrow_expiry_slot = expiry_slot;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn reset_stats(ctx: Context<Update>, update_id: u64, recent_slot: u64) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.stats = TransferStats::default();
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn propose_authority(
+    ctx: Context<Update>,
+    new_authority: Option<Pubkey>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.pending_authority = new_authority;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn accept_authority(
+    ctx: Context<AcceptAuthority>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    ctx.accounts
+        .config
+        .accept_authority(&ctx.accounts.new_authority.key())?;
+    if !ctx.accounts.config.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.new_authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_token_threshold(
+    ctx: Context<Update>,
+    token_threshold: Option<TokenThreshold>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    if let Some(terms) = &token_threshold {
+        require!(
+            cfg.mint_allowed(&terms.mint),
+            ConditionalError::MintNotAllowed
+        );
+    }
+    cfg.token_threshold = token_threshold;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn update_default_memo(
+    ctx: Context<Update>,
+    memo: String,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(memo.len() <= MAX_MEMO_LEN, ConditionalError::MemoTooLong);
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.default_memo = memo;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn apply_signed_proposal(
+    ctx: Context<ApplySignedProposal>,
+    proposal: ConfigProposal,
+) -> Result<()> {
+    require_feature_enabled(&ctx.accounts.global, FEATURE_SIGNED_PROPOSALS)?;
+    require_recent_slot(proposal.recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        proposal.config,
+        cfg.key(),
+        ConditionalError::InvalidProposal
+    );
+    require!(
+        proposal.nonce == cfg.proposal_nonce,
+        ConditionalError::InvalidProposal
+    );
+    require_ed25519_signature(
+        &ctx.accounts.instructions,
+        &cfg.authority,
+        &proposal.try_to_vec()?,
+    )?;
+    if !cfg.claim_update_id(proposal.update_id)? {
+        return Ok(());
+    }
+
+    if let Some(threshold_lamports) = proposal.threshold_lamports {
+        require!(
+            cfg.max_lamports == 0 || threshold_lamports <= cfg.max_lamports,
+            ConditionalError::InvalidLimits
+        );
+        let authority = cfg.authority;
+        require_threshold_not_lowered(cfg, authority, threshold_lamports)?;
+        let old_threshold_lamports = cfg.threshold_lamports;
+        cfg.threshold_lamports = threshold_lamports;
+        emit_threshold_updated(cfg, authority, old_threshold_lamports)?;
+    }
+    if let Some(to) = proposal.to {
+        let (from, old_to) = (cfg.from, cfg.to);
+        cfg.to = to;
+        cfg.recipient_hash = None;
+        let authority = cfg.authority;
+        emit_addresses_updated(cfg, authority, from, old_to)?;
+    }
+    if let Some(memo) = proposal.default_memo {
+        require!(memo.len() <= MAX_MEMO_LEN, ConditionalError::MemoTooLong);
+        cfg.default_memo = memo;
+    }
+    cfg.proposal_nonce = cfg
+        .proposal_nonce
+        .checked_add(1)
+        .ok_or(ConditionalError::MathOverflow)?;
+    let authority = ctx.accounts.config.authority;
+    let event = config_changed(&mut ctx.accounts.config, authority, ConfigChange::Updated)?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let config = ctx.accounts.config.to_account_info();
+    require_layout_owner(
+        &config,
+        &Config::DISCRIMINATOR,
+        8,
+        &ctx.accounts.authority.key(),
+    )?;
+    let mut cfg = Config::try_deserialize_versioned(&config.try_borrow_data()?)?;
+    let from_version = cfg.version;
+    cfg.upgrade()?;
+    let event = config_changed_at(
+        config.key(),
+        &mut cfg,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    resize_account(
+        &config,
+        8 + Config::INIT_SPACE,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+    )?;
+    cfg.try_serialize(&mut &mut config.try_borrow_mut_data()?[..])?;
+    msg!("Config version {} -> {}", from_version, cfg.version);
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn recover_lamports(ctx: Context<RecoverLamports>) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_RECOVER)?;
+    let config_info = ctx.accounts.config.to_account_info();
+    let rent_floor = Rent::get()?.minimum_balance(config_info.data_len());
+    let excess = config_info.lamports().saturating_sub(rent_floor);
+    require!(excess > 0, ConditionalError::NothingToRecover);
+
+    config_info.sub_lamports(excess)?;
+    ctx.accounts.destination.add_lamports(excess)?;
+    msg!("Recovered {} lamports from config", excess);
+    Ok(())
+}
+
+pub(crate) fn recover_tokens(ctx: Context<RecoverTokens>) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_RECOVER)?;
+    require!(
+        ctx.accounts.config.mint_allowed(&ctx.accounts.stray.mint),
+        ConditionalError::MintNotAllowed
+    );
+    let amount = ctx.accounts.stray.amount;
+    require!(amount > 0, ConditionalError::NothingToRecover);
+
+    let cfg = &ctx.accounts.config;
+    let bump = [cfg.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        CONFIG_SEED,
+        cfg.seed_from.as_ref(),
+        cfg.seed_route.as_ref(),
+        &bump,
+    ]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::Transfer {
+            from: ctx.accounts.stray.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority: ctx.accounts.config.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::transfer(cpi_ctx, amount)?;
+    msg!(
+        "Recovered {} tokens of mint {}",
+        amount,
+        ctx.accounts.stray.mint
+    );
+    Ok(())
+}
+
+pub(crate) fn set_allow_executable_recipient(
+    ctx: Context<Update>,
+    allow: bool,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.allow_executable_recipient = allow;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_below_threshold_behavior(
+    ctx: Context<Update>,
+    behavior: BelowThresholdBehavior,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.below_threshold_behavior = behavior;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_large_transfer_lamports(
+    ctx: Context<Update>,
+    lamports: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.large_transfer_lamports = lamports;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_bridge_route(
+    ctx: Context<Update>,
+    route: Option<BridgeRoute>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    if let Some(route) = &route {
+        route.validate()?;
+    }
+    cfg.bridge_route = route;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_anomaly_guard(
+    ctx: Context<Update>,
+    multiplier: u16,
+    require_approval: bool,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.anomaly_guard.multiplier = multiplier;
+    cfg.anomaly_guard.require_approval = require_approval;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_cpi_policy(
+    ctx: Context<Update>,
+    restrict: bool,
+    allowed_programs: Vec<Pubkey>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        allowed_programs.len() <= MAX_CPI_PROGRAMS,
+        ConditionalError::TooManyCpiPrograms
+    );
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.restrict_cpi = restrict;
+    cfg.cpi_allowlist = allowed_programs;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_mint_allowlist(
+    ctx: Context<Update>,
+    mints: Vec<Pubkey>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        mints.len() <= MAX_ALLOWED_MINTS,
+        ConditionalError::TooManyMints
+    );
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.mint_allowlist = mints;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_recipient_owners(
+    ctx: Context<Update>,
+    owners: Vec<Pubkey>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        owners.len() <= MAX_RECIPIENT_OWNERS,
+        ConditionalError::TooManyRecipientOwners
+    );
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.recipient_owners = owners;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_seeded_recipient(
+    ctx: Context<Update>,
+    seeded_recipient: Option<SeededRecipient>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.seeded_recipient = seeded_recipient;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_approvers(
+    ctx: Context<Update>,
+    members: Vec<Pubkey>,
+    quorum: u8,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    let approvers = Approvers { members, quorum };
+    require!(approvers.is_valid(), ConditionalError::InvalidApprovers);
+    cfg.approvers = approvers;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_log_rejections(
+    ctx: Context<Update>,
+    enabled: bool,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.log_rejections = enabled;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_epoch_reporting(
+    ctx: Context<Update>,
+    enabled: bool,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.epoch_reporting = enabled;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_max_config_age(
+    ctx: Context<Update>,
+    max_config_age_seconds: u64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.max_config_age_seconds = max_config_age_seconds;
+    cfg.last_attested_ts = Clock::get()?.unix_timestamp;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn attest(ctx: Context<Update>, update_id: u64, recent_slot: u64) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.last_attested_ts = Clock::get()?.unix_timestamp;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_beneficiary(
+    ctx: Context<Update>,
+    beneficiary: Beneficiary,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.beneficiary = beneficiary;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn create_payout_table(
+    ctx: Context<CreatePayoutTable>,
+    entries: Vec<PayoutEntry>,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    validate_payout_entries(&entries)?;
+    let table = &mut ctx.accounts.payout_table;
+    table.config = ctx.accounts.config.key();
+    table.entries = entries;
+    table.bump = ctx.bumps.payout_table;
+    table.remainder = RemainderPolicy::LastRecipient;
+    Ok(())
+}
+
+pub(crate) fn update_payout_table(
+    ctx: Context<UpdatePayoutTable>,
+    entries: Vec<PayoutEntry>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    validate_payout_entries(&entries)?;
+    ctx.accounts.payout_table.entries = entries;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_payout_remainder(
+    ctx: Context<UpdatePayoutTable>,
+    remainder: RemainderPolicy,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    ctx.accounts.payout_table.remainder = remainder;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_split_recipients(
+    ctx: Context<ManageSplitTable>,
+    entries: Vec<SplitEntry>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !ctx.accounts.config.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    validate_split_entries(&entries)?;
+    let table = &mut ctx.accounts.split_table;
+    table.config = ctx.accounts.config.key();
+    table.entries = entries;
+    table.bump = ctx.bumps.split_table;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn create_policy(
+    ctx: Context<CreatePolicy>,
+    id: u32,
+    threshold_lamports: u64,
+    below_threshold_behavior: BelowThresholdBehavior,
+    limits: PolicyLimits,
+) -> Result<()> {
+    limits.validate(threshold_lamports)?;
+    let policy = &mut ctx.accounts.policy;
+    policy.authority = ctx.accounts.authority.key();
+    policy.id = id;
+    policy.threshold_lamports = threshold_lamports;
+    policy.below_threshold_behavior = below_threshold_behavior;
+    policy.bump = ctx.bumps.policy;
+    policy.last_update_id = 0;
+    policy.limits = limits;
+    Ok(())
+}
+
+pub(crate) fn update_policy<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdatePolicy<'info>>,
+    threshold_lamports: u64,
+    below_threshold_behavior: BelowThresholdBehavior,
+    limits: PolicyLimits,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    limits.validate(threshold_lamports)?;
+    let policy = &mut ctx.accounts.policy;
+    if !claim_update_id(&mut policy.last_update_id, update_id)? {
+        return Ok(());
+    }
+    policy.threshold_lamports = threshold_lamports;
+    policy.below_threshold_behavior = below_threshold_behavior;
+    policy.limits = limits;
+    let policy = ctx.accounts.policy.key();
+    for info in ctx.remaining_accounts {
+        let mut cfg = Account::<Config>::try_from(info)?;
+        require!(
+            cfg.version == CONFIG_VERSION,
+            ConditionalError::ConfigMigrationRequired
+        );
+        require!(
+            cfg.policy == Some(policy),
+            ConditionalError::NotLinkedToPolicy
+        );
+        let event = config_changed(
+            &mut cfg,
+            ctx.accounts.authority.key(),
+            ConfigChange::Updated,
+        )?;
+        emit_cpi!(event);
+        cfg.exit(&crate::ID)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn migrate_policy(ctx: Context<MigratePolicy>) -> Result<()> {
+    let policy = ctx.accounts.policy.to_account_info();
+    require_layout_owner(
+        &policy,
+        &Policy::DISCRIMINATOR,
+        8,
+        &ctx.accounts.authority.key(),
+    )?;
+    resize_account(
+        &policy,
+        8 + Policy::INIT_SPACE,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+    )
+}
+
+pub(crate) fn set_policy(
+    ctx: Context<Update>,
+    policy: Option<Pubkey>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    cfg.policy = policy;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_policy_overrides(
+    ctx: Context<Update>,
+    overrides: PolicyOverrides,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_THRESHOLD)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    if let Some(threshold_lamports) = overrides.threshold_lamports {
+        require_threshold_not_lowered(cfg, ctx.accounts.authority.key(), threshold_lamports)?;
+    }
+    cfg.policy_overrides = overrides;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn update_addresses(
+    ctx: Context<UpdateAddresses>,
+    new_from: Pubkey,
+    new_to: Pubkey,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    if new_to != cfg.to || cfg.recipient_hash.is_some() {
+        if let Some(old_recipient_index) = ctx.accounts.old_recipient_index.as_mut() {
+            old_recipient_index.remove(&cfg.key());
+        }
+        add_to_recipient_index(
+            &ctx.accounts.new_recipient_index,
+            new_to,
+            cfg.key(),
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+    }
+    let (old_from, old_to) = (cfg.from, cfg.to);
+    cfg.from = new_from;
+    cfg.to = new_to;
+    cfg.recipient_hash = None;
+    emit_addresses_updated(cfg, ctx.accounts.authority.key(), old_from, old_to)?;
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_hashed_recipient(
+    ctx: Context<SetHashedRecipient>,
+    recipient_hash: [u8; 32],
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    if cfg.recipient_hash.is_none() {
+        let recipient_index = ctx
+            .accounts
+            .recipient_index
+            .as_mut()
+            .ok_or(ConditionalError::RecipientIndexRequired)?;
+        recipient_index.remove(&cfg.key());
+    }
+    cfg.to = Pubkey::default();
+    cfg.recipient_hash = Some(recipient_hash);
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_CLOSE)?;
+    require!(
+        ctx.accounts.escrow.lamports() == 0,
+        ConditionalError::ConfigHasFunds
+    );
+    let vault_funded = ctx.accounts.vault.lamports() > 0;
+    let now = Clock::get()?.unix_timestamp;
+    match cfg.close_requested_at {
+        Some(requested_at) => {
+            require!(
+                now >= requested_at.saturating_add(CLOSE_COOLING_SECONDS),
+                ConditionalError::CloseCoolingDown
+            );
+            require!(!vault_funded, ConditionalError::ConfigHasFunds);
+        }
+        None if vault_funded => {
+            cfg.close_requested_at = Some(now);
+            msg!(
+                "Close requested; final after {}",
+                now.saturating_add(CLOSE_COOLING_SECONDS)
+            );
+            let event = config_changed(
+                &mut ctx.accounts.config,
+                ctx.accounts.authority.key(),
+                ConfigChange::CloseRequested,
+            )?;
+            emit_cpi!(event);
+            return Ok(());
+        }
+        None => {}
+    }
+    if cfg.recipient_hash.is_none() {
+        ctx.accounts
+            .recipient_index
+            .as_mut()
+            .ok_or(ConditionalError::RecipientIndexRequired)?
+            .remove(&cfg.key());
+    }
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Closed,
+    )?;
+    emit_cpi!(event);
+    ctx.accounts
+        .config
+        .close(ctx.accounts.authority.to_account_info())
+}
+
+pub(crate) fn cancel_close_config(
+    ctx: Context<Update>,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_CLOSE)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    require!(
+        cfg.close_requested_at.take().is_some(),
+        ConditionalError::CloseNotRequested
+    );
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::CloseCancelled,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn set_authority_permissions(
+    ctx: Context<Update>,
+    key: Pubkey,
+    permissions: u8,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.authority.key(),
+        ConditionalError::Unauthorized
+    );
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    // Every bit of `permissions` is an assigned `PERM_*` flag, so any mask is valid.
+
+    cfg.authorities.retain(|entry| entry.key != key);
+    if permissions != 0 {
+        require!(
+            cfg.authorities.len() < MAX_AUTHORITIES,
+            ConditionalError::TooManyAuthorities
+        );
+        cfg.authorities.push(AuthorityEntry { key, permissions });
+    }
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn configure_recovery(
+    ctx: Context<ConfigureRecovery>,
+    committee: Vec<Pubkey>,
+    threshold: u8,
+    delay_seconds: i64,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    require!(
+        committee.len() <= MAX_RECOVERY_MEMBERS
+            && threshold > 0
+            && threshold as usize <= committee.len()
+            && delay_seconds > 0,
+        ConditionalError::InvalidRecoveryCommittee
+    );
+    if !ctx.accounts.config.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    ctx.accounts.recovery.set_inner(Recovery {
+        config: ctx.accounts.config.key(),
+        committee,
+        threshold,
+        delay_seconds,
+        pending: None,
+        bump: ctx.bumps.recovery,
+    });
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn register_viewer_key(
+    ctx: Context<ManageViewerKeys>,
+    key_hash: [u8; 32],
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !ctx.accounts.config.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    let viewer_keys = &mut ctx.accounts.viewer_keys;
+    viewer_keys.config = ctx.accounts.config.key();
+    viewer_keys.bump = ctx.bumps.viewer_keys;
+    if !viewer_keys.key_hashes.contains(&key_hash) {
+        require!(
+            viewer_keys.key_hashes.len() < MAX_VIEWER_KEYS,
+            ConditionalError::ViewerKeysFull
+        );
+        viewer_keys.key_hashes.push(key_hash);
+    }
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn revoke_viewer_key(
+    ctx: Context<ManageViewerKeys>,
+    key_hash: [u8; 32],
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_SETTINGS)?;
+    if !ctx.accounts.config.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    let key_hashes = &mut ctx.accounts.viewer_keys.key_hashes;
+    let index = key_hashes
+        .iter()
+        .position(|hash| *hash == key_hash)
+        .ok_or(ConditionalError::ViewerKeyNotFound)?;
+    key_hashes.swap_remove(index);
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn add_delegate(
+    ctx: Context<Update>,
+    delegate: Pubkey,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    if !cfg.delegates.contains(&delegate) {
+        require!(
+            cfg.delegates.len() < MAX_DELEGATES,
+            ConditionalError::DelegateListFull
+        );
+        cfg.delegates.push(delegate);
+    }
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn remove_delegate(
+    ctx: Context<Update>,
+    delegate: Pubkey,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    let cfg = &mut ctx.accounts.config;
+    cfg.require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !cfg.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    let index = cfg
+        .delegates
+        .iter()
+        .position(|listed| *listed == delegate)
+        .ok_or(ConditionalError::DelegateNotFound)?;
+    cfg.delegates.swap_remove(index);
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn add_recipient(
+    ctx: Context<ManageRecipientList>,
+    recipient: Pubkey,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !ctx.accounts.config.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    let list = &mut ctx.accounts.recipient_list;
+    list.config = ctx.accounts.config.key();
+    list.bump = ctx.bumps.recipient_list;
+    if !list.contains(&recipient) {
+        require!(
+            list.recipients.len() < MAX_LISTED_RECIPIENTS,
+            ConditionalError::RecipientListFull
+        );
+        list.recipients.push(recipient);
+    }
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn remove_recipient(
+    ctx: Context<ManageRecipientList>,
+    recipient: Pubkey,
+    update_id: u64,
+    recent_slot: u64,
+) -> Result<()> {
+    require_recent_slot(recent_slot)?;
+    ctx.accounts
+        .config
+        .require_permission(&ctx.accounts.authority.key(), PERM_UPDATE_ADDRESSES)?;
+    if !ctx.accounts.config.claim_update_id(update_id)? {
+        return Ok(());
+    }
+    let recipients = &mut ctx.accounts.recipient_list.recipients;
+    let index = recipients
+        .iter()
+        .position(|listed| *listed == recipient)
+        .ok_or(ConditionalError::RecipientNotListed)?;
+    recipients.swap_remove(index);
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.authority.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn propose_recovery(ctx: Context<RecoveryAction>, new_authority: Pubkey) -> Result<()> {
+    let recovery = &mut ctx.accounts.recovery;
+    let member = ctx.accounts.member.key();
+    recovery.require_member(&member)?;
+    let unlocks_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(recovery.delay_seconds)
+        .ok_or(ConditionalError::MathOverflow)?;
+    recovery.pending = Some(PendingRecovery {
+        new_authority,
+        approvals: vec![member],
+        unlocks_at,
+    });
+
+    let cfg = &mut ctx.accounts.config;
+    emit!(RecoveryProposed {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        proposer: member,
+        new_authority,
+        unlocks_at,
+    });
+    Ok(())
+}
+
+pub(crate) fn approve_recovery(ctx: Context<RecoveryAction>) -> Result<()> {
+    let recovery = &mut ctx.accounts.recovery;
+    let member = ctx.accounts.member.key();
+    recovery.require_member(&member)?;
+    let pending = recovery
+        .pending
+        .as_mut()
+        .ok_or(ConditionalError::NoPendingRecovery)?;
+    if !pending.approvals.contains(&member) {
+        pending.approvals.push(member);
+    }
+    Ok(())
+}
+
+pub(crate) fn veto_recovery(ctx: Context<RecoveryAction>) -> Result<()> {
+    let cfg = &mut ctx.accounts.config;
+    require_keys_eq!(
+        cfg.authority,
+        ctx.accounts.member.key(),
+        ConditionalError::Unauthorized
+    );
+    let pending = ctx
+        .accounts
+        .recovery
+        .pending
+        .take()
+        .ok_or(ConditionalError::NoPendingRecovery)?;
+    emit!(RecoveryVetoed {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        new_authority: pending.new_authority,
+    });
+    Ok(())
+}
+
+pub(crate) fn execute_recovery(ctx: Context<RecoveryAction>) -> Result<()> {
+    let recovery = &mut ctx.accounts.recovery;
+    recovery.require_member(&ctx.accounts.member.key())?;
+    let pending = recovery
+        .pending
+        .as_ref()
+        .ok_or(ConditionalError::NoPendingRecovery)?;
+    require!(
+        pending.approvals.len() >= recovery.threshold as usize
+            && Clock::get()?.unix_timestamp >= pending.unlocks_at,
+        ConditionalError::RecoveryNotReady
+    );
+    let new_authority = pending.new_authority;
+    recovery.pending = None;
+
+    let cfg = &mut ctx.accounts.config;
+    let old_authority = cfg.authority;
+    cfg.authority = new_authority;
+    cfg.pending_authority = None;
+    emit!(AuthorityRecovered {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        old_authority,
+        new_authority,
+    });
+    let event = config_changed(
+        &mut ctx.accounts.config,
+        ctx.accounts.member.key(),
+        ConfigChange::Updated,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub(crate) fn propose_transfer(
+    ctx: Context<ProposeTransfer>,
+    id: u64,
+    to: Pubkey,
+    lamports: u64,
+) -> Result<()> {
+    let cfg = &ctx.accounts.config;
+    let proposer = ctx.accounts.proposer.key();
+    let approver = cfg.approvers.members.contains(&proposer);
+    require!(
+        approver || proposer == cfg.from,
+        ConditionalError::NotApprover
+    );
+    require!(
+        cfg.is_recipient(&cfg.key(), &to),
+        ConditionalError::RecipientMismatch
+    );
+    require!(lamports > 0, ConditionalError::InvalidAmount);
+    ctx.accounts.pending_transfer.set_inner(PendingTransfer {
+        config: cfg.key(),
+        id,
+        proposer,
+        to,
+        lamports,
+        approvals: if approver { vec![proposer] } else { Vec::new() },
+        created_at: Clock::get()?.unix_timestamp,
+        bump: ctx.bumps.pending_transfer,
+        reserved: [0; state::layout::RESERVED_LEN],
+    });
+    Ok(())
+}
+
+pub(crate) fn approve_transfer(ctx: Context<ApproveTransfer>) -> Result<()> {
+    let approver = ctx.accounts.approver.key();
+    require!(
+        ctx.accounts.config.approvers.members.contains(&approver),
+        ConditionalError::NotApprover
+    );
+    let approvals = &mut ctx.accounts.pending_transfer.approvals;
+    if !approvals.contains(&approver) {
+        approvals.push(approver);
+    }
+    Ok(())
+}
+
+pub(crate) fn execute_transfer(ctx: Context<ExecuteTransfer>) -> Result<()> {
+    let pending = &ctx.accounts.pending_transfer;
+    let send = &mut ctx.accounts.send;
+    require!(
+        send.config.approvers.approved(&pending.approvals),
+        ConditionalError::QuorumNotReached
+    );
+    let lamports = pending.lamports;
+    let clock = test_mode::clock(ctx.remaining_accounts)?;
+    send.check_send(lamports, &clock, true)?;
+    send.execute(lamports, Vec::new(), None)
+}
+
+pub(crate) fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
+    let signer = ctx.accounts.signer.key();
+    require!(
+        signer == ctx.accounts.pending_transfer.proposer || signer == ctx.accounts.config.authority,
+        ConditionalError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Log the config's default memo (if any) so every transfer carries it.
+pub(crate) fn log_default_memo(cfg: &Config) {
+    if !cfg.default_memo.is_empty() {
+        msg!("memo: {}", cfg.default_memo);
+    }
+}
+
+/// Enforce the rate limit and run the anomaly guard for a send of `lamports`:
+/// fail if the current window cannot take it, emit `AnomalousTransfer` if it is
+/// an outlier (failing instead when approval is required and `approver` does
+/// not hold `PERM_APPROVE_ANOMALY`), then count it in the window and the
+/// average. Nothing is recorded when the send is rejected.
+pub(crate) fn check_velocity(
+    cfg: &mut Account<Config>,
+    from: Pubkey,
+    to: Pubkey,
+    lamports: u64,
+    approver: Option<&Signer>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        cfg.rate_limit.allows(lamports, now),
+        ConditionalError::RateLimitExceeded
+    );
+    let guard = cfg.anomaly_guard;
+    if guard.is_anomalous(lamports) {
+        let approver = approver.map(|approver| approver.key());
+        if guard.require_approval {
+            let approver = approver.ok_or(ConditionalError::AnomalyApprovalRequired)?;
+            cfg.require_permission(&approver, PERM_APPROVE_ANOMALY)?;
+        }
+        emit!(AnomalousTransfer {
+            seq: cfg.next_event_seq()?,
+            config: cfg.key(),
+            from,
+            to,
+            lamports,
+            average_lamports: guard.average_lamports,
+            multiplier: guard.multiplier,
+            approver,
+            timestamp: now,
+        });
+    }
+    cfg.anomaly_guard.record(lamports);
+    cfg.rate_limit.record(lamports, now);
+    Ok(())
+}
+
+/// Associated token account of `wallet` for `mint`.
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token::ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Build the `ConfigChanged` event for the config's current state.
+fn config_changed(
+    cfg: &mut Account<Config>,
+    changed_by: Pubkey,
+    change: ConfigChange,
+) -> Result<ConfigChanged> {
+    config_changed_at(cfg.key(), cfg, changed_by, change)
+}
+
+/// `config_changed` for a config decoded by hand at `address`.
+fn config_changed_at(
+    address: Pubkey,
+    cfg: &mut Config,
+    changed_by: Pubkey,
+    change: ConfigChange,
+) -> Result<ConfigChanged> {
+    Ok(ConfigChanged {
+        seq: cfg.next_event_seq()?,
+        config: address,
+        change,
+        changed_by,
+        authority: cfg.authority,
+        pending_authority: cfg.pending_authority,
+        from: cfg.from,
+        to: cfg.to,
+        hashed_recipient: cfg.recipient_hash.is_some(),
+        threshold_lamports: cfg.threshold_lamports,
+        max_lamports: cfg.max_lamports,
+        valid_from_unix: cfg.valid_from_unix,
+        valid_until_unix: cfg.valid_until_unix,
+        policy: cfg.policy,
+        threshold_override: cfg.policy_overrides.threshold_lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    })
+}
+
+/// Emit `ConfigInitialized` for a config just created.
+fn emit_config_initialized(cfg: &mut Account<Config>, cloned_from: Option<Pubkey>) -> Result<()> {
+    emit!(ConfigInitialized {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        authority: cfg.authority,
+        from: cfg.from,
+        to: cfg.to,
+        threshold_lamports: cfg.threshold_lamports,
+        cloned_from,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Emit `ThresholdUpdated` after the threshold moved off
+/// `old_threshold_lamports`.
+fn emit_threshold_updated(
+    cfg: &mut Account<Config>,
+    changed_by: Pubkey,
+    old_threshold_lamports: u64,
+) -> Result<()> {
+    emit!(ThresholdUpdated {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        changed_by,
+        old_threshold_lamports,
+        threshold_lamports: cfg.threshold_lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// `error` as the runtime reports it, as a program error code.
+pub(crate) fn error_code(error: &Error) -> u64 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number.into(),
+        Error::ProgramError(error) => error.program_error.clone().into(),
+    }
+}
+
+/// Fail with the first violation `conditions::evaluate` finds for a payout of
+/// `lamports` from `source` to `to`, for the paths outside
+/// `SendIfOverThreshold`. They take no price feed or range proof, so configs
+/// with a USD or private threshold fail with `PriceFeedRequired` or
+/// `RangeProofRequired`, and quorum configs with `ApprovalRequired`.
+fn require_conditions(
+    cfg: &Config,
+    policy: Option<&Account<Policy>>,
+    lamports: u64,
+    clock: &Clock,
+    source: &AccountInfo,
+    to: Option<&AccountInfo>,
+) -> Result<()> {
+    let balances = conditions::Balances {
+        from_lamports: source.lamports(),
+        recipient_executable: to.is_some_and(|to| to.executable),
+        recipient_owner: to.map(|to| *to.owner),
+        sol_usd_price: None,
+        quorum_approved: false,
+        range_proof_commitment: None,
+    };
+    let policy = policy.map(|policy| (policy.key(), &**policy));
+    match conditions::evaluate(cfg, policy, lamports, clock, &balances).first() {
+        Some(violation) => Err((*violation).into()),
+        None => Ok(()),
+    }
+}
+
+/// Fail with the error for the violation `amount` makes of `terms`, if any:
+/// `BelowThreshold` or `AboveThreshold`.
+fn require_meets_threshold(terms: &PolicyTerms, amount: u64) -> Result<()> {
+    match conditions::threshold_violation(terms, amount) {
+        Some(violation) => Err(violation.into()),
+        None => Ok(()),
+    }
+}
+
+/// Fail with `ThresholdDecreaseNotAllowed` if `threshold_lamports` would
+/// lower a monotonic config's threshold, emitting `ThresholdDecreaseRejected`
+/// first so the attempt shows in the failed transaction's logs.
+fn require_threshold_not_lowered(
+    cfg: &Account<Config>,
+    attempted_by: Pubkey,
+    threshold_lamports: u64,
+) -> Result<()> {
+    if cfg.allows_threshold(threshold_lamports) {
+        return Ok(());
+    }
+    emit!(ThresholdDecreaseRejected {
+        seq: cfg.event_seq,
+        config: cfg.key(),
+        attempted_by,
+        threshold_lamports: cfg.threshold_lamports,
+        rejected_threshold_lamports: threshold_lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    err!(ConditionalError::ThresholdDecreaseNotAllowed)
+}
+
+/// Emit `AddressesUpdated` after the route moved off `old_from` → `old_to`.
+fn emit_addresses_updated(
+    cfg: &mut Account<Config>,
+    changed_by: Pubkey,
+    old_from: Pubkey,
+    old_to: Pubkey,
+) -> Result<()> {
+    emit!(AddressesUpdated {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        changed_by,
+        old_from,
+        old_to,
+        from: cfg.from,
+        to: cfg.to,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Lamports held by a send's source and destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct BalanceSnapshot {
+    pub(crate) from: u64,
+    pub(crate) to: u64,
+}
+
+impl BalanceSnapshot {
+    pub(crate) fn take(from: &AccountInfo, to: &AccountInfo) -> Self {
+        Self {
+            from: from.lamports(),
+            to: to.lamports(),
+        }
+    }
+
+    /// Balances expected once `debited` left `from` and `credited` (the amount
+    /// plus any match) reached `to`. When both are the same account only the
+    /// net change applies. `None` if that is not representable.
+    fn expected(&self, debited: u64, credited: u64, same_account: bool) -> Option<Self> {
+        if same_account {
+            let lamports = self.to.checked_add(credited)?.checked_sub(debited)?;
+            return Some(Self {
+                from: lamports,
+                to: lamports,
+            });
+        }
+        Some(Self {
+            from: self.from.checked_sub(debited)?,
+            to: self.to.checked_add(credited)?,
+        })
+    }
+
+    /// Fail with `BalanceMismatch` unless `after` is exactly what the transfer
+    /// should have produced from `self`, guarding against exotic account
+    /// states (e.g. an aliased or concurrently credited destination).
+    pub(crate) fn require_moved(
+        &self,
+        after: &Self,
+        debited: u64,
+        credited: u64,
+        same_account: bool,
+    ) -> Result<()> {
+        require!(
+            self.expected(debited, credited, same_account) == Some(*after),
+            ConditionalError::BalanceMismatch
+        );
+        Ok(())
+    }
+}
+
+/// Count a completed transfer in the config's `stats` and emit
+/// `TransferExecuted` with the accounts' resulting balances, plus
+/// `LargeTransferExecuted` if `lamports` reaches the config's level. Without
+/// a send `memo`, the event carries the config's default memo.
+pub(crate) fn record_transfer(
+    cfg: &mut Account<Config>,
+    from: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    references: Vec<Pubkey>,
+    memo: Option<String>,
+) -> Result<()> {
+    cfg.stats.record(lamports, Clock::get()?.slot);
+    emit_transfer_executed(cfg, from, to, lamports, references, memo)?;
+    emit_if_large_transfer(cfg, from.key(), to.key(), lamports)
+}
+
+/// Emit `TransferExecuted` for a transfer without counting it in `stats`.
+pub(crate) fn emit_transfer_executed(
+    cfg: &mut Account<Config>,
+    from: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    references: Vec<Pubkey>,
+    memo: Option<String>,
+) -> Result<()> {
+    emit!(TransferExecuted {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        from: from.key(),
+        to: to.key(),
+        lamports,
+        references,
+        from_balance: from.lamports(),
+        to_balance: to.lamports(),
+        memo: memo.unwrap_or_else(|| cfg.default_memo.clone()),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Solana Pay reference keys among a send's remaining accounts: read-only,
+/// unsigned, and holding no lamports, so mocks and other real accounts are
+/// never mistaken for one. Point-of-sale systems find the transaction with
+/// `getSignaturesForAddress(reference)`.
+fn payment_references(remaining_accounts: &[AccountInfo]) -> Result<Vec<Pubkey>> {
+    let references: Vec<Pubkey> = remaining_accounts
+        .iter()
+        .filter(|account| !account.is_writable && !account.is_signer && account.lamports() == 0)
+        .map(|account| account.key())
+        .collect();
+    require!(
+        references.len() <= MAX_PAYMENT_REFERENCES,
+        ConditionalError::TooManyPaymentReferences
+    );
+    Ok(references)
+}
+
+/// Emit `LargeTransferExecuted` if `lamports` reaches the config's level.
+fn emit_if_large_transfer(
+    cfg: &mut Account<Config>,
+    from: Pubkey,
+    to: Pubkey,
+    lamports: u64,
+) -> Result<()> {
+    if cfg.large_transfer_lamports == 0 || lamports < cfg.large_transfer_lamports {
+        return Ok(());
+    }
+    emit!(LargeTransferExecuted {
+        seq: cfg.next_event_seq()?,
+        config: cfg.key(),
+        from,
+        to,
+        lamports,
+        large_transfer_lamports: cfg.large_transfer_lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Rent that must move to resize an account.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum RentDelta {
+    /// Payer must add this many lamports.
+    TopUp(u64),
+    /// This many lamports go back to the payer.
+    Refund(u64),
+    Unchanged,
+}
+
+/// Rent movement for resizing an account holding `lamports` from `old_len` to
+/// `new_len` bytes. Only the rent share of the balance moves: lamports above
+/// the old rent-exempt minimum (escrowed funds) are never refunded or consumed,
+/// and an underfunded account is brought up to the new minimum.
+pub(crate) fn rent_delta(rent: &Rent, lamports: u64, old_len: usize, new_len: usize) -> RentDelta {
+    let rent_held = lamports.min(rent.minimum_balance(old_len));
+    let rent_needed = rent.minimum_balance(new_len);
+    if rent_needed > rent_held {
+        RentDelta::TopUp(rent_needed - rent_held)
+    } else if rent_held > rent_needed {
+        RentDelta::Refund(rent_held - rent_needed)
+    } else {
+        RentDelta::Unchanged
+    }
+}
+
+/// Reallocate a program-owned account to `new_len`, charging `payer` for
+/// extra rent or refunding freed rent to it. Works for growth and shrinkage.
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    match rent_delta(
+        &Rent::get()?,
+        account.lamports(),
+        account.data_len(),
+        new_len,
+    ) {
+        RentDelta::TopUp(lamports) => {
+            let cpi_ctx = CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            );
+            system_program::transfer(cpi_ctx, lamports)?;
+        }
+        RentDelta::Refund(lamports) => {
+            account.sub_lamports(lamports)?;
+            payer.add_lamports(lamports)?;
+        }
+        RentDelta::Unchanged => {}
+    }
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+/// How `create_program_account` brings an address into use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AccountCreation {
+    /// Already a program account; nothing to do.
+    Exists,
+    /// Untouched address: a single `create_account` funded with `lamports`.
+    Create { lamports: u64 },
+    /// Address already holds lamports, so `create_account` would fail: transfer
+    /// `top_up`, then `allocate` and `assign`.
+    AllocateAndAssign { top_up: u64 },
+}
+
+/// Decide how to create a `space`-byte program account at an address that
+/// currently has `owner`, `lamports`, and `data_len`. Anything other than an
+/// existing program account or an empty system account is rejected, so a
+/// recipient-controlled address can never be adopted with foreign contents.
+pub(crate) fn plan_account_creation(
+    rent: &Rent,
+    owner: &Pubkey,
+    lamports: u64,
+    data_len: usize,
+    space: usize,
+) -> Result<AccountCreation> {
+    if *owner == crate::ID {
+        return Ok(AccountCreation::Exists);
+    }
+    require!(
+        *owner == system_program::ID && data_len == 0,
+        ConditionalError::UnexpectedAccountOwner
+    );
+    let rent_needed = rent.minimum_balance(space);
+    Ok(if lamports == 0 {
+        AccountCreation::Create {
+            lamports: rent_needed,
+        }
+    } else {
+        AccountCreation::AllocateAndAssign {
+            top_up: rent_needed.saturating_sub(lamports),
+        }
+    })
+}
+
+/// Create `account` as a `space`-byte account owned by this program, paid by
+/// `payer`, unless it already is one. `seeds` (without the bump) must derive
+/// `account`'s address. Returns the bump and whether the account was created.
+/// Every account this program creates outside an Anchor `init` goes through
+/// here, and the owner is always this program.
+pub(crate) fn create_program_account<'info>(
+    account: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<(u8, bool)> {
+    let (address, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(
+        account.key(),
+        address,
+        ConditionalError::InvalidAccountSeeds
+    );
+    let plan = plan_account_creation(
+        &Rent::get()?,
+        account.owner,
+        account.lamports(),
+        account.data_len(),
+        space,
+    )?;
+
+    let bump_seed = [bump];
+    let mut seeds_with_bump = seeds.to_vec();
+    seeds_with_bump.push(&bump_seed);
+    let signer_seeds: &[&[&[u8]]] = &[&seeds_with_bump];
+    let program = system_program.to_account_info();
+    match plan {
+        AccountCreation::Exists => return Ok((bump, false)),
+        AccountCreation::Create { lamports } => {
+            let cpi_ctx = CpiContext::new_with_signer(
+                program,
+                system_program::CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+                signer_seeds,
+            );
+            system_program::create_account(cpi_ctx, lamports, space as u64, &crate::ID)?;
+        }
+        AccountCreation::AllocateAndAssign { top_up } => {
+            if top_up > 0 {
+                let cpi_ctx = CpiContext::new(
+                    program.clone(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: account.clone(),
+                    },
+                );
+                system_program::transfer(cpi_ctx, top_up)?;
+            }
+            let cpi_ctx = CpiContext::new_with_signer(
+                program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: account.clone(),
+                },
+                signer_seeds,
+            );
+            system_program::allocate(cpi_ctx, space as u64)?;
+            let cpi_ctx = CpiContext::new_with_signer(
+                program,
+                system_program::Assign {
+                    account_to_assign: account.clone(),
+                },
+                signer_seeds,
+            );
+            system_program::assign(cpi_ctx, &crate::ID)?;
+        }
+    }
+    Ok((bump, true))
+}
+
+/// Record `config` in `recipient`'s index at `account`, creating the index on
+/// first use. A full index is left as it is; `ConfigInitialized` and
+/// `AddressesUpdated` still name the recipient for indexers.
+fn add_to_recipient_index<'info>(
+    account: &AccountInfo<'info>,
+    recipient: Pubkey,
+    config: Pubkey,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let (bump, created) = create_program_account(
+        account,
+        &[RECIPIENT_INDEX_SEED, recipient.as_ref()],
+        8 + RecipientIndex::INIT_SPACE,
+        payer,
+        system_program,
+    )?;
+    let mut index = if created {
+        RecipientIndex {
+            recipient,
+            configs: Vec::new(),
+            bump,
+        }
+    } else {
+        RecipientIndex::try_deserialize(&mut &account.try_borrow_data()?[..])?
+    };
+    if !index.add(recipient, config, bump) {
+        msg!("Recipient index full; {} not listed", config);
+    }
+    index.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Check a possibly outdated account is ours, has `discriminator`, and stores
+/// `signer` at `signer_offset` (the first field for the config authority and
+/// global admin, `RECEIPT_PAYER_OFFSET` for a receipt's payer).
+fn require_layout_owner(
+    account: &AccountInfo,
+    discriminator: &[u8],
+    signer_offset: usize,
+    signer: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        ConditionalError::InvalidAccountLayout
+    );
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= signer_offset + 32 && data[..8] == *discriminator,
+        ConditionalError::InvalidAccountLayout
+    );
+    require!(
+        data[signer_offset..signer_offset + 32] == signer.to_bytes(),
+        ConditionalError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Reject updates whose `recent_slot` is more than `RECENT_SLOT_TOLERANCE` from
+/// the current slot, so an old pre-signed admin transaction (e.g. held behind a
+/// leaked durable nonce) cannot be replayed long after it was signed.
+fn require_recent_slot(recent_slot: u64) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    require!(
+        slot.abs_diff(recent_slot) <= RECENT_SLOT_TOLERANCE,
+        ConditionalError::StaleSlot
+    );
+    Ok(())
+}
+
+/// Claim `update_id` against the last id an account applied. Ids must
+/// increase. Resubmitting the last one is a no-op (`Ok(false)`), so automation
+/// can retry blindly; a lower one was superseded and fails with
+/// `UpdateIdSuperseded`, so an old update cannot land late. `0` is untracked
+/// and always applies.
+pub(crate) fn claim_update_id(last_update_id: &mut u64, update_id: u64) -> Result<bool> {
+    if update_id == 0 {
+        return Ok(true);
+    }
+    if update_id == *last_update_id {
+        msg!("Update {} already applied", update_id);
+        return Ok(false);
+    }
+    require!(
+        update_id > *last_update_id,
+        ConditionalError::UpdateIdSuperseded
+    );
+    *last_update_id = update_id;
+    Ok(true)
+}
+
+/// Read the global state PDA, or `None` while it is uninitialized.
+fn load_global(global: &AccountInfo) -> Result<Option<GlobalState>> {
+    if global.owner != &crate::ID {
+        return Ok(None);
+    }
+    let state = GlobalState::try_deserialize(&mut &global.try_borrow_data()?[..])?;
+    Ok(Some(state))
+}
+
+/// Fail if the admin has disabled `feature`. An uninitialized global state
+/// disables nothing.
+pub(crate) fn require_feature_enabled(global: &AccountInfo, feature: u64) -> Result<()> {
+    if let Some(state) = load_global(global)? {
+        require!(
+            state.disabled_features & feature == 0,
+            ConditionalError::FeatureDisabled
+        );
+    }
+    Ok(())
+}
+
+/// Fail for a v1 instruction once the admin's sunset slot has been reached.
+fn require_not_sunset(global: &AccountInfo) -> Result<()> {
+    if let Some(state) = load_global(global)? {
+        require!(
+            state.sunset_slot == 0 || Clock::get()?.slot < state.sunset_slot,
+            ConditionalError::DeprecatedInstruction
+        );
+    }
+    Ok(())
+}
+
+/// If `to` is a `Sweep` PDA holding at least its threshold above rent, forward
+/// that whole surplus to the sweep's cold wallet. Other recipients are untouched.
+pub(crate) fn forward_sweep(
+    to: &AccountInfo,
+    destination: Option<&UncheckedAccount>,
+) -> Result<()> {
+    if to.owner != &crate::ID {
+        return Ok(());
+    }
+    let sweep = {
+        let data = to.try_borrow_data()?;
+        if !data.starts_with(&Sweep::DISCRIMINATOR) {
+            return Ok(());
+        }
+        Sweep::try_deserialize(&mut &data[..])?
+    };
+    let rent_floor = Rent::get()?.minimum_balance(to.data_len());
+    let surplus = to.lamports().saturating_sub(rent_floor);
+    if surplus == 0 || surplus < sweep.threshold_lamports {
+        return Ok(());
+    }
+    let destination = destination.ok_or(ConditionalError::SweepDestinationMismatch)?;
+    require_keys_eq!(
+        destination.key(),
+        sweep.cold_wallet,
+        ConditionalError::SweepDestinationMismatch
+    );
+    to.sub_lamports(surplus)?;
+    destination.add_lamports(surplus)?;
+    msg!("Swept {} lamports to {}", surplus, sweep.cold_wallet);
+    Ok(())
+}
+
+/// Commitment to `recipient` stored in a hashed-recipient config. The config
+/// address is mixed in so one payee's hash differs across configs, but anyone
+/// holding a list of candidate addresses can still test them against it.
+pub fn recipient_hash(config: &Pubkey, recipient: &Pubkey) -> [u8; 32] {
+    hashv(&[config.as_ref(), recipient.as_ref()]).to_bytes()
+}
+
+/// Reject executable recipients unless the config explicitly allows them, and
+/// recipients owned by a program outside `recipient_owners`.
+fn require_recipient_allowed(cfg: &Config, to: &AccountInfo) -> Result<()> {
+    require!(
+        !to.executable || cfg.allow_executable_recipient,
+        ConditionalError::ExecutableRecipient
+    );
+    require!(
+        cfg.recipient_owner_allowed(to.owner),
+        ConditionalError::RecipientOwnerNotAllowed
+    );
+    Ok(())
+}
+
+/// Credit `lamports`, already taken from a program-owned account, to `to`
+/// through the config's beneficiary mode: into `to` or its wSOL account, then
+/// synced. `to` must have passed `Beneficiary::validate`.
+fn credit_beneficiary<'info>(
+    cfg: &Config,
+    to: &AccountInfo<'info>,
+    recipient_token_account: Option<&AccountInfo<'info>>,
+    token_program: Option<&Program<'info, Token>>,
+    lamports: u64,
+) -> Result<()> {
+    let destination = cfg
+        .beneficiary
+        .delivery_account(to, recipient_token_account)?;
+    destination.add_lamports(lamports)?;
+    cfg.beneficiary.settle(destination, token_program)
+}
+
+/// Under a restricted CPI policy, reject CPI invocations unless the direct
+/// caller is an allowlisted program. The instructions sysvar only exposes
+/// top-level instructions, so the caller is known only one level down, where
+/// it is the top-level program. Deeper invocations are rejected: an
+/// allowlisted program there could be relaying for any other.
+pub(crate) fn require_cpi_caller_allowed(
+    cfg: &Config,
+    instructions: Option<&UncheckedAccount>,
+) -> Result<()> {
+    let height = get_stack_height();
+    if !cfg.restrict_cpi || height == TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+    require!(
+        height == TRANSACTION_LEVEL_STACK_HEIGHT + 1,
+        ConditionalError::CpiCallerNotAllowed
+    );
+    let instructions = instructions.ok_or(ConditionalError::CpiCallerNotAllowed)?;
+    let caller = get_instruction_relative(0, instructions)?.program_id;
+    require!(
+        cfg.cpi_allowlist.contains(&caller),
+        ConditionalError::CpiCallerNotAllowed
+    );
+    Ok(())
+}
+
+/// Sum `lamports` over all top-level `send_joint_if_over_threshold` instructions
+/// in the current transaction that target `config`.
+fn joint_send_total(instructions: &AccountInfo, config: &Pubkey) -> Result<u64> {
+    let discriminator = instruction::SendJointIfOverThreshold::DISCRIMINATOR;
+    let mut total: u64 = 0;
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        index += 1;
+        if ix.program_id != crate::ID
+            || ix.data.len() < 16
+            || ix.data[..8] != discriminator
+            || ix.accounts.first().map(|meta| meta.pubkey) != Some(*config)
+        {
+            continue;
+        }
+        let amount = u64::from_le_bytes(ix.data[8..16].try_into().unwrap());
+        total = total
+            .checked_add(amount)
+            .ok_or(ConditionalError::MathOverflow)?;
+    }
+    Ok(total)
+}
+
+/// Require the instruction before the current one to be an ed25519 program
+/// instruction verifying a single signature by `signer` over `message`, with
+/// all offsets pointing into that instruction's own data.
+fn require_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let ix = get_instruction_relative(-1, instructions)
+        .map_err(|_| ConditionalError::MissingSignatureVerification)?;
+    require_keys_eq!(
+        ix.program_id,
+        anchor_lang::solana_program::ed25519_program::ID,
+        ConditionalError::MissingSignatureVerification
+    );
+    let data = &ix.data;
+    // [num_signatures u8][padding u8][7 x u16 offsets]
+    require!(
+        data.len() >= 16 && data[0] == 1,
+        ConditionalError::InvalidSignatureVerification
+    );
+    let offset = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]);
+    let (signature_ix, pubkey_offset, pubkey_ix) = (offset(1), offset(2) as usize, offset(3));
+    let (message_offset, message_size, message_ix) =
+        (offset(4) as usize, offset(5) as usize, offset(6));
+    require!(
+        signature_ix == u16::MAX && pubkey_ix == u16::MAX && message_ix == u16::MAX,
+        ConditionalError::InvalidSignatureVerification
+    );
+    let verified_pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(ConditionalError::InvalidSignatureVerification)?;
+    let verified_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ConditionalError::InvalidSignatureVerification)?;
+    require!(
+        verified_pubkey == signer.as_ref() && verified_message == message,
+        ConditionalError::InvalidSignatureVerification
+    );
+    Ok(())
+}
+
+/// Payload `send_bridged` posts, big-endian as Wormhole payloads usually are:
+/// `BRIDGE_PAYLOAD_TRANSFER` (1), lamports (8), config (32), from (32), target
+/// chain (2), recipient (32).
+pub fn bridge_payload(
+    lamports: u64,
+    config: &Pubkey,
+    from: &Pubkey,
+    route: &BridgeRoute,
+) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(107);
+    payload.push(BRIDGE_PAYLOAD_TRANSFER);
+    payload.extend_from_slice(&lamports.to_be_bytes());
+    payload.extend_from_slice(config.as_ref());
+    payload.extend_from_slice(from.as_ref());
+    payload.extend_from_slice(&route.chain.to_be_bytes());
+    payload.extend_from_slice(&route.recipient);
+    payload
+}
+
+/// Data of a core bridge `PostMessage` at finalized consistency: the
+/// discriminant, then Borsh `{ nonce: u32, payload: Vec<u8>, consistency_level: u8 }`.
+pub(crate) fn wormhole_post_message_data(nonce: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(10 + payload.len());
+    data.push(WORMHOLE_POST_MESSAGE);
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(payload);
+    data.push(WORMHOLE_FINALIZED);
+    data
+}
+
+/// Message fee recorded in the core bridge's config account.
+fn wormhole_message_fee(bridge: &AccountInfo) -> Result<u64> {
+    let data = bridge.try_borrow_data()?;
+    let fee = data
+        .get(WORMHOLE_FEE_OFFSET..WORMHOLE_FEE_OFFSET + 8)
+        .ok_or(ConditionalError::InvalidBridgeRoute)?;
+    Ok(u64::from_le_bytes(fee.try_into().unwrap()))
+}
+
+/// Build an SPL stake pool `DepositSol`/`WithdrawSol` instruction, whose data is
+/// the one-byte discriminant followed by a little-endian `u64`. The `vault`
+/// PDA is the only signer, by `invoke_signed`: it never signs the outer
+/// transaction, so its `is_signer` is always false here. Everything else keeps
+/// its writability from `accounts`.
+fn stake_pool_instruction(
+    discriminant: u8,
+    amount: u64,
+    accounts: &[AccountInfo],
+    vault: &Pubkey,
+) -> Instruction {
+    let mut data = Vec::with_capacity(9);
+    data.push(discriminant);
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: SPL_STAKE_POOL_PROGRAM_ID,
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.key == vault,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    }
+}
+
+/// Whether `shares_bps` is 1..=`max_entries` non-zero shares summing to 10_000 bps.
+fn shares_are_valid(shares_bps: &[u16], max_entries: usize) -> bool {
+    !shares_bps.is_empty()
+        && shares_bps.len() <= max_entries
+        && shares_bps.iter().all(|&share_bps| share_bps > 0)
+        && shares_bps
+            .iter()
+            .map(|&share_bps| share_bps as u64)
+            .sum::<u64>()
+            == BPS_DENOMINATOR
+}
+
+/// Validate a payout table: 1..=MAX entries, non-zero shares summing to
+/// 10_000 bps, and vote accounts in canonical order.
+pub(crate) fn validate_payout_entries(entries: &[PayoutEntry]) -> Result<()> {
+    let shares_bps: Vec<u16> = entries.iter().map(|entry| entry.share_bps).collect();
+    require!(
+        shares_are_valid(&shares_bps, MAX_PAYOUT_RECIPIENTS)
+            && is_canonical_order(entries.iter().map(|entry| &entry.vote_account)),
+        ConditionalError::InvalidPayoutTable
+    );
+    Ok(())
+}
+
+/// Validate a split table: valid shares as for a payout table, and
+/// recipients in canonical order.
+pub(crate) fn validate_split_entries(entries: &[SplitEntry]) -> Result<()> {
+    let shares_bps: Vec<u16> = entries.iter().map(|entry| entry.share_bps).collect();
+    require!(
+        shares_are_valid(&shares_bps, MAX_SPLIT_RECIPIENTS)
+            && is_canonical_order(entries.iter().map(|entry| &entry.recipient)),
+        ConditionalError::InvalidSplit
+    );
+    Ok(())
+}
+
+/// Whether `keys` are strictly ascending by their bytes (not their base58
+/// text), the one order tables accept. Identical tables then always produce
+/// the same transfers and events in the same order, and no key repeats.
+pub fn is_canonical_order<'a>(keys: impl IntoIterator<Item = &'a Pubkey>) -> bool {
+    let mut keys = keys.into_iter().peekable();
+    while let Some(key) = keys.next() {
+        if matches!(keys.peek(), Some(next) if key >= *next) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Each share of `total` under `remainder`, plus the dust left for
+/// `RemainderPolicy::DustAccount` (zero otherwise). Shares and dust always sum
+/// to `total` for valid `shares_bps`.
+pub fn payout_shares(
+    total: u64,
+    shares_bps: &[u16],
+    remainder: RemainderPolicy,
+) -> (Vec<u64>, u64) {
+    let exact = |share_bps: u16| total as u128 * share_bps as u128;
+    let denominator = BPS_DENOMINATOR as u128;
+    let mut shares: Vec<u64> = shares_bps
+        .iter()
+        .map(|&share_bps| (exact(share_bps) / denominator) as u64)
+        .collect();
+    let mut dust = total.saturating_sub(shares.iter().sum());
+    match remainder {
+        RemainderPolicy::LastRecipient => {
+            if let Some(last) = shares.last_mut() {
+                *last += dust;
+                dust = 0;
+            }
+        }
+        RemainderPolicy::LargestRemainder => {
+            let mut order: Vec<usize> = (0..shares_bps.len()).collect();
+            order.sort_by_key(|&i| std::cmp::Reverse(exact(shares_bps[i]) % denominator));
+            for &i in order.iter().cycle().take(dust as usize) {
+                shares[i] += 1;
+            }
+            if !shares_bps.is_empty() {
+                dust = 0;
+            }
+        }
+        RemainderPolicy::DustAccount(_) => {}
+    }
+    (shares, dust)
+}
+
+/// Read the authorized withdrawer from a vote account. Supports the
+/// `V1_14_11` and `Current` layouts: `[u32 version][node_pubkey][authorized_withdrawer]..`.
+fn vote_withdrawer(vote_account: &AccountInfo) -> Result<Pubkey> {
+    require_keys_eq!(
+        *vote_account.owner,
+        anchor_lang::solana_program::vote::program::ID,
+        ConditionalError::InvalidVoteAccount
+    );
+    let data = vote_account.try_borrow_data()?;
+    require!(data.len() >= 68, ConditionalError::InvalidVoteAccount);
+    let version = u32::from_le_bytes(data[..4].try_into().unwrap());
+    require!(
+        version == 1 || version == 2,
+        ConditionalError::InvalidVoteAccount
+    );
+    Ok(Pubkey::new_from_array(data[36..68].try_into().unwrap()))
+}
+
+/// Fail unless `last_activity_ts` is at least `INACTIVITY_CLOSE_SECONDS` in the past.
+fn require_inactive(last_activity_ts: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now.saturating_sub(last_activity_ts) >= INACTIVITY_CLOSE_SECONDS,
+        ConditionalError::AccountStillActive
+    );
+    Ok(())
+}
@@ -0,0 +1,109 @@
+//! Cross-field consistency checks on `Config`, for catching state corruption.
+//!
+//! Every setter validates its own arguments, but nothing re-checks the config
+//! as a whole once several features have written to it. With the `invariants`
+//! feature the program is built with [`checked_entry`] as its entrypoint: it
+//! runs the Anchor dispatcher, then [`assert_invariants`] on every writable
+//! config the instruction was given, failing the transaction with
+//! `InvariantViolated` if one no longer holds. The checks cost compute (the bump check re-derives
+//! the PDA), so deployed builds leave the feature off.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{ConditionalError, Config, CONFIG_SEED, CONFIG_VERSION, MAX_DELEGATES, MAX_FEE_BPS};
+
+/// The first invariant `config` breaks, by name; `None` when all hold.
+pub fn broken_invariant(config: &Config) -> Option<&'static str> {
+    let window_ordered = match (config.valid_from_unix, config.valid_until_unix) {
+        (Some(from), Some(until)) => from <= until,
+        _ => true,
+    };
+    let checks = [
+        (
+            config.max_lamports == 0 || config.threshold_lamports <= config.max_lamports,
+            "max_lamports below threshold_lamports",
+        ),
+        (
+            config.rate_limit.refill_per_second == 0 || config.rate_limit.capacity > 0,
+            "rate limit enabled without capacity",
+        ),
+        (config.fee_bps <= MAX_FEE_BPS, "fee_bps above MAX_FEE_BPS"),
+        (
+            config.fee_bps == 0 || config.fee_collector != Pubkey::default(),
+            "fee without a fee_collector",
+        ),
+        (config.approvers.is_valid(), "approvers invalid"),
+        (
+            !config.monotonic_threshold || config.comparison_mode.is_floor(),
+            "monotonic threshold without a floor comparison mode",
+        ),
+        (window_ordered, "valid_from_unix after valid_until_unix"),
+        (
+            !matches!(config.auto_adjust, Some(terms) if !terms.is_valid()),
+            "auto_adjust terms invalid",
+        ),
+        (
+            !config.monotonic_threshold || config.auto_adjust.is_none(),
+            "monotonic threshold with an auto_adjust peg",
+        ),
+        (
+            config.delegates.len() <= MAX_DELEGATES,
+            "more than MAX_DELEGATES delegates",
+        ),
+        (
+            config.version <= CONFIG_VERSION,
+            "version newer than CONFIG_VERSION",
+        ),
+    ];
+    checks
+        .into_iter()
+        .find(|(holds, _)| !holds)
+        .map(|(_, invariant)| invariant)
+}
+
+/// Fail with `InvariantViolated` unless `config`, stored at `address`, is
+/// consistent: every [`broken_invariant`] check holds and its bump derives
+/// `address` from its seeds.
+pub fn assert_invariants(config: &Config, address: &Pubkey) -> Result<()> {
+    let invariant = broken_invariant(config).or_else(|| {
+        let seeds: &[&[u8]] = &[
+            CONFIG_SEED,
+            config.seed_from.as_ref(),
+            config.seed_route.as_ref(),
+            &[config.bump],
+        ];
+        let derived = Pubkey::create_program_address(seeds, &crate::ID);
+        (derived.ok() != Some(*address)).then_some("bump does not derive the config address")
+    });
+    match invariant {
+        Some(invariant) => {
+            msg!("Config {} invariant violated: {}", address, invariant);
+            err!(ConditionalError::InvariantViolated)
+        }
+        None => Ok(()),
+    }
+}
+
+/// The Anchor entrypoint followed by [`assert_invariants`] on each writable,
+/// current-layout config among `accounts`. Closed configs and ones still
+/// awaiting `migrate_config` are skipped.
+pub fn checked_entry<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+    crate::entry(program_id, accounts, data)?;
+    for account in accounts
+        .iter()
+        .filter(|account| account.is_writable && account.owner == program_id)
+    {
+        let data = account.try_borrow_data()?;
+        if data.len() != 8 + Config::INIT_SPACE || !data.starts_with(&Config::DISCRIMINATOR) {
+            continue;
+        }
+        let config = Config::try_deserialize(&mut &data[..])?;
+        assert_invariants(&config, account.key)?;
+    }
+    Ok(())
+}
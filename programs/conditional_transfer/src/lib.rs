@@ -16,158 +16,3903 @@
 //! 4) Initialize with TS script: `npx ts-node scripts/init.ts B_PUBKEY [thresholdLamports]`
 //! 5) Send with TS script: `npx ts-node scripts/send.ts 0.25`
 //!
+//! As a library: depend on this crate with `features = ["no-entrypoint"]` to
+//! reuse `conditions::evaluate`, `state`, and `processor` in-process; add `cpi`
+//! for the generated CPI client.
+//!
 //! Safety:
 //! - On-chain check uses **amount ≥ threshold**.
 //! - Authority may update threshold or addresses (consider multisig in production).
 
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{self, Transfer};
 
 // Paste your deployed program ID here and in Anchor.toml ([programs.devnet])
 declare_id!("REPLACE_WITH_YOUR_PROGRAM_ID");
 
+#[cfg(feature = "invariants")]
+use invariants::checked_entry;
+#[cfg(feature = "invariants")]
+anchor_lang::solana_program::entrypoint!(checked_entry);
+
+pub mod compute_budget;
+pub mod conditions;
+mod contexts;
+mod events;
+mod handlers;
+pub mod invariants;
+pub mod private_threshold;
+pub mod pyth;
+pub mod state;
+pub mod test_mode;
+#[cfg(test)]
+mod test_vectors;
+
+pub use contexts::*;
+pub use events::*;
+use handlers::*;
+pub use handlers::{
+    associated_token_address, bridge_payload, is_canonical_order, payout_shares, recipient_hash,
+};
+pub use state::*;
+
+/// Instruction handlers, for programs embedding this crate with the
+/// `no-entrypoint` feature instead of calling it by CPI.
+pub use crate::conditional_transfer as processor;
+
 const CONFIG_SEED: &[u8] = b"config";
+const MATCH_POOL_SEED: &[u8] = b"match_pool";
+const MILESTONE_SEED: &[u8] = b"milestone";
+const GLOBAL_SEED: &[u8] = b"global";
+const EVENT_SCHEMA_SEED: &[u8] = b"event_schema";
+const VAULT_SEED: &[u8] = b"vault";
+const ALLOWANCE_SEED: &[u8] = b"allowance";
+const EPOCH_SUMMARY_SEED: &[u8] = b"epoch_summary";
+const PAYOUT_TABLE_SEED: &[u8] = b"payout_table";
+const VAULT_STAKE_SEED: &[u8] = b"vault_stake";
+const DELIVERY_SEED: &[u8] = b"delivery";
+const BOUNTY_SEED: &[u8] = b"bounty";
+const SWEEP_SEED: &[u8] = b"sweep";
+const RECIPIENT_INDEX_SEED: &[u8] = b"recipient_index";
+const POLICY_SEED: &[u8] = b"policy";
+const RECEIPT_SEED: &[u8] = b"receipt";
+/// Offset of `Receipt::payer`, after the discriminator, config, and reference.
+const RECEIPT_PAYER_OFFSET: usize = 72;
+const RECOVERY_SEED: &[u8] = b"recovery";
+const VIEWER_KEYS_SEED: &[u8] = b"viewer_keys";
+const RECIPIENT_LIST_SEED: &[u8] = b"recipient_list";
+const SPLIT_TABLE_SEED: &[u8] = b"split_table";
+const BRIDGE_CUSTODY_SEED: &[u8] = b"bridge_custody";
+const ESCROW_SEED: &[u8] = b"escrow";
+const STREAM_SEED: &[u8] = b"stream";
+const PENDING_TRANSFER_SEED: &[u8] = b"pending_transfer";
+const SEND_NONCES_SEED: &[u8] = b"send_nonces";
+const SCHEDULED_TRANSFER_SEED: &[u8] = b"scheduled_transfer";
+/// Wormhole's conventional seed for a program's emitter PDA.
+const BRIDGE_EMITTER_SEED: &[u8] = b"emitter";
+
+/// SPL stake pool program, the only pool program vault staking will call.
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+/// SPL Associated Token Account program, used to derive recipients' wSOL accounts.
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hdZ4zDZx2Sma4j8Db4Gr8xRJu6");
+/// Wormhole core bridge deployments a bridge route may point at.
+pub const WORMHOLE_MAINNET_PROGRAM_ID: Pubkey =
+    pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+pub const WORMHOLE_DEVNET_PROGRAM_ID: Pubkey =
+    pubkey!("3u8hJUVTA4jH1wYAyUur7FFZVQ8H635K3tSHHF4ssjQ5");
+pub const WORMHOLE_LOCALNET_PROGRAM_ID: Pubkey =
+    pubkey!("Bridge1p5gheXUvJ6jGWGeCsgPKgnE3YgdGKRVCMY9o");
+/// Wormhole's own chain id for Solana, which a route may not target.
+const WORMHOLE_CHAIN_SOLANA: u16 = 1;
+/// Core bridge `PostMessage` discriminant and `ConsistencyLevel::Finalized`.
+const WORMHOLE_POST_MESSAGE: u8 = 1;
+const WORMHOLE_FINALIZED: u8 = 1;
+/// Offset of the message fee in the core bridge's `BridgeData` account
+/// (`guardian_set_index: u32, last_lamports: u64, guardian_set_expiration_time: u32, fee: u64`).
+const WORMHOLE_FEE_OFFSET: usize = 16;
+/// Leading byte of the payload `send_bridged` posts.
+pub const BRIDGE_PAYLOAD_TRANSFER: u8 = 1;
+/// `StakePoolInstruction::DepositSol` / `WithdrawSol` discriminants.
+const STAKE_POOL_DEPOSIT_SOL: u8 = 14;
+const STAKE_POOL_WITHDRAW_SOL: u8 = 16;
+
+/// Version of the `Config` layout written by this program. Bump it when an
+/// upgrade adds or reinterprets config fields, and teach `Config::upgrade` to
+/// bring the previous version forward; `migrate_config` runs it.
+pub const CONFIG_VERSION: u8 = 10;
+
+/// Version of the emitted event layouts. Bump this in any upgrade that changes an
+/// `#[event]` struct, then call `sync_event_schema` so indexers see the change.
+pub const EVENT_SCHEMA_VERSION: u16 = 5;
+
+/// Maximum length (bytes) of the config-level default memo and of a send's memo.
+pub const MAX_MEMO_LEN: usize = 64;
+
+/// Largest per-send fee a config may charge, in basis points (10%).
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Maximum number of programs a config may allow to CPI into the send instructions.
+pub const MAX_CPI_PROGRAMS: usize = 4;
+
+/// Maximum number of mints a config may allowlist for token transfers.
+pub const MAX_ALLOWED_MINTS: usize = 4;
+
+/// Maximum number of programs a config may allow to own its recipient.
+pub const MAX_RECIPIENT_OWNERS: usize = 4;
+
+/// Maximum number of members on a config's recovery committee.
+pub const MAX_RECOVERY_MEMBERS: usize = 7;
+
+/// Maximum number of delegate senders on a config.
+pub const MAX_DELEGATES: usize = 4;
+
+/// Maximum number of transfer approvers on a config.
+pub const MAX_APPROVERS: usize = 5;
 
+/// Maximum number of API key hashes in a config's `ViewerKeys`.
+pub const MAX_VIEWER_KEYS: usize = 8;
+
+/// Maximum number of addresses in a config's `RecipientList`.
+pub const MAX_LISTED_RECIPIENTS: usize = 16;
+
+/// Number of consecutive nonces a `SendNonces` account tracks.
+pub const NONCE_WINDOW: u64 = 256;
+
+/// Maximum number of Solana Pay reference keys a send records in its event.
+pub const MAX_PAYMENT_REFERENCES: usize = 4;
+
+/// Maximum number of vote accounts in a validator payout table.
+pub const MAX_PAYOUT_RECIPIENTS: usize = 8;
+
+/// Maximum number of recipients in a config's `SplitTable`.
+pub const MAX_SPLIT_RECIPIENTS: usize = 8;
+
+/// Maximum number of configs a single recipient index can list.
+pub const MAX_INDEXED_CONFIGS: usize = 16;
+
+/// How far (in slots) an update's `recent_slot` argument may be from the
+/// current slot, about a minute either side.
+pub const RECENT_SLOT_TOLERANCE: u64 = 150;
+
+/// Maximum number of additional authorities a config can grant permissions to.
+pub const MAX_AUTHORITIES: usize = 5;
+
+/// Permission bits for granted authorities. The root `authority` holds all of them.
+pub const PERM_UPDATE_THRESHOLD: u8 = 1 << 0;
+pub const PERM_UPDATE_ADDRESSES: u8 = 1 << 1;
+pub const PERM_PAUSE: u8 = 1 << 2;
+pub const PERM_CLOSE: u8 = 1 << 3;
+/// Memo and recipient-flag settings.
+pub const PERM_UPDATE_SETTINGS: u8 = 1 << 4;
+/// Sweeping stray lamports/tokens out of the config.
+pub const PERM_RECOVER: u8 = 1 << 5;
+/// Minting and revoking vault allowances.
+pub const PERM_ALLOWANCES: u8 = 1 << 6;
+/// Co-signing sends flagged by the anomaly guard.
+pub const PERM_APPROVE_ANOMALY: u8 = 1 << 7;
+/// Feature bits in `GlobalState::disabled_features`. A set bit makes the
+/// matching instructions fail with `FeatureDisabled`.
+pub const FEATURE_SEND: u64 = 1 << 0;
+pub const FEATURE_JOINT_SEND: u64 = 1 << 1;
+pub const FEATURE_VALIDATOR_PAYOUT: u64 = 1 << 2;
+pub const FEATURE_ALLOWANCES: u64 = 1 << 3;
+pub const FEATURE_VAULT_STAKING: u64 = 1 << 4;
+pub const FEATURE_SIGNED_PROPOSALS: u64 = 1 << 5;
+pub const FEATURE_BRIDGED_SEND: u64 = 1 << 6;
+pub const FEATURE_TOKEN_SEND: u64 = 1 << 7;
+pub const FEATURE_ESCROW: u64 = 1 << 8;
+pub const FEATURE_SPLIT_SEND: u64 = 1 << 9;
+pub const FEATURE_SEEDED_SEND: u64 = 1 << 10;
+pub const FEATURE_ORACLE: u64 = 1 << 11;
+
+pub const PERM_ALL: u8 = PERM_UPDATE_THRESHOLD
+    | PERM_UPDATE_ADDRESSES
+    | PERM_PAUSE
+    | PERM_CLOSE
+    | PERM_UPDATE_SETTINGS
+    | PERM_RECOVER
+    | PERM_ALLOWANCES
+    | PERM_APPROVE_ANOMALY;
+
+/// Sends the anomaly guard averages over before it starts flagging.
+const ANOMALY_MIN_SAMPLES: u32 = 8;
+/// Each send moves the rolling average 1/`ANOMALY_AVERAGE_WEIGHT` of the way.
+const ANOMALY_AVERAGE_WEIGHT: u128 = 8;
+
+/// How long `close_config` waits between the request and the close of a
+/// config whose vault holds lamports, so `from` can `withdraw_vault` first.
+pub const CLOSE_COOLING_SECONDS: i64 = 48 * 60 * 60;
+
+/// Auxiliary accounts untouched for this long may be force-closed by the config authority.
+const INACTIVITY_CLOSE_SECONDS: i64 = 180 * 24 * 60 * 60;
+
+/// Lamports per milli-SOL (0.001 SOL).
+const LAMPORTS_PER_MILLI_SOL: u64 = 1_000_000;
+
+/// Basis-point denominator used for ratios (10_000 bps = 1:1).
+const BPS_DENOMINATOR: u64 = 10_000;
 #[program]
 pub mod conditional_transfer {
     use super::*;
 
     /// Initialize the config PDA with: authority, from, to, and threshold.
     /// - `authority`: allowed to update config
-    /// - `from`: the only signer permitted to send funds
+    /// - `from`: the only signer permitted to send funds; must co-sign
+    /// - `route`: any 32 bytes telling `from`'s configs apart
     /// - `to`: recipient
     /// - `threshold_lamports`: minimal amount (lamports) required to allow transfer
     pub fn initialize(
         ctx: Context<Initialize>,
-        from: Pubkey,
+        route: Pubkey,
         to: Pubkey,
         threshold_lamports: u64,
     ) -> Result<()> {
-        let cfg = &mut ctx.accounts.config;
-        cfg.authority = ctx.accounts.authority.key();
-        cfg.from = from;
-        cfg.to = to;
-        cfg.threshold_lamports = threshold_lamports;
-        cfg.bump = *ctx.bumps.get("config").unwrap();
-        Ok(())
+        handlers::initialize(ctx, route, to, threshold_lamports)
+    }
+
+    /// Create the config for a new `route` of the co-signing `from`, paying
+    /// `to`, with `source`'s conditions and settings (root authority of
+    /// `source` only). Route
+    /// state is not copied: event sequence, proposal nonce, attestation,
+    /// anomaly statistics, recipient hash, and bridge route start fresh.
+    pub fn clone_config(
+        ctx: Context<CloneConfig>,
+        route: Pubkey,
+        to: Pubkey,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::clone_config(ctx, route, to, recent_slot)
     }
 
     /// Transfer lamports from `from` (must sign) to `to` if `lamports ≥ threshold`.
-    /// Uses a CPI to the System Program.
-    pub fn send_if_over_threshold(ctx: Context<SendIfOverThreshold>, lamports: u64) -> Result<()> {
-        let cfg = &ctx.accounts.config;
-        // NOTE: Behavior is "≥ threshold" (at least). Adjust here if you want different rules.
-        require!(
-            lamports >= cfg.threshold_lamports,
-            ConditionalError::BelowThreshold
-        );
-
-        // CPI to transfer SOL from `from` -> `to`
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.from.to_account_info(),
-                to: ctx.accounts.to.to_account_info(),
-            },
-        );
-        system_program::transfer(cpi_ctx, lamports)?;
-        Ok(())
+    /// Uses a CPI to the System Program. `memo` (at most `MAX_MEMO_LEN` bytes),
+    /// e.g. an invoice id, replaces the config's default memo for this send in
+    /// the log and in `TransferExecuted`.
+    /// Deprecated in favour of `send_if_over_threshold_v2`; fails once the
+    /// global `sunset_slot` is reached.
+    pub fn send_if_over_threshold(
+        ctx: Context<SendIfOverThreshold>,
+        lamports: u64,
+        memo: Option<String>,
+    ) -> Result<()> {
+        handlers::send_if_over_threshold(ctx, lamports, memo)
     }
 
-    /// Optional: Update threshold (authority only).
-    pub fn update_threshold(ctx: Context<Update>, new_threshold_lamports: u64) -> Result<()> {
-        let cfg = &mut ctx.accounts.config;
-        require_keys_eq!(cfg.authority, ctx.accounts.authority.key(), ConditionalError::Unauthorized);
-        cfg.threshold_lamports = new_threshold_lamports;
-        Ok(())
+    /// v2 of `send_if_over_threshold`: takes an amount tagged with its unit so
+    /// clients cannot confuse SOL with lamports. Converted and validated on-chain.
+    /// `memo` works as for v1.
+    pub fn send_if_over_threshold_v2(
+        ctx: Context<SendIfOverThreshold>,
+        amount: TypedAmount,
+        memo: Option<String>,
+    ) -> Result<()> {
+        handlers::send_if_over_threshold_v2(ctx, amount, memo)
     }
 
-    /// Optional: Update from/to addresses (authority only).
-    pub fn update_addresses(ctx: Context<Update>, new_from: Pubkey, new_to: Pubkey) -> Result<()> {
-        let cfg = &mut ctx.accounts.config;
-        require_keys_eq!(cfg.authority, ctx.accounts.authority.key(), ConditionalError::Unauthorized);
-        cfg.from = new_from;
-        cfg.to = new_to;
-        Ok(())
+    /// CPI-friendly variant of `send_if_over_threshold`: returns `false` (as
+    /// return data) instead of aborting when the send conditions are not met,
+    /// so composing programs can fall back. Transfer failures still abort.
+    pub fn try_send(ctx: Context<SendIfOverThreshold>, lamports: u64) -> Result<bool> {
+        handlers::try_send(ctx, lamports)
     }
-}
 
-/// Accounts context for initialization. Creates the config PDA.
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    /// Authority who can update the config parameters.
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    /// Program Derived Address storing config (created & paid by authority).
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 32 + 8 + 1, // discriminator + authority + from + to + threshold + bump
-        seeds = [CONFIG_SEED],
-        bump
-    )]
-    pub config: Account<'info, Config>,
-
-    /// Built-in System Program is needed for CPI transfer & allocation.
-    pub system_program: Program<'info, System>,
-}
+    /// Send like `send_if_over_threshold` and record it in a `Receipt` at
+    /// `[RECEIPT_SEED, config, reference]`, so a processor that knows the
+    /// invoice's `reference` can derive and fetch the receipt directly. A
+    /// reference can be paid only once per config. Below-threshold amounts
+    /// always fail here, whatever `below_threshold_behavior` says.
+    pub fn send_with_receipt(
+        ctx: Context<SendWithReceipt>,
+        lamports: u64,
+        reference: [u8; 32],
+    ) -> Result<()> {
+        handlers::send_with_receipt(ctx, lamports, reference)
+    }
 
-/// Accounts context for transfer call.
-#[derive(Accounts)]
-pub struct SendIfOverThreshold<'info> {
-    /// The config PDA.
-    #[account(
-        seeds = [CONFIG_SEED],
-        bump = config.bump
-    )]
-    pub config: Account<'info, Config>,
-
-    /// The `from` account must match config.from AND must sign the transaction.
-    #[account(mut, address = config.from)]
-    pub from: Signer<'info>,
-
-    /// The `to` account must match config.to and will receive lamports.
-    #[account(mut, address = config.to)]
-    pub to: SystemAccount<'info>,
-
-    pub system_program: Program<'info, System>,
-}
+    /// Send like `send_if_over_threshold`, at most once per `nonce`: the
+    /// sender's `SendNonces` account at `[SEND_NONCES_SEED, config, from]`
+    /// records the nonces used, and a repeat fails with `NonceAlreadyUsed`,
+    /// so a wallet can resubmit a send it is unsure landed. It tracks the
+    /// `NONCE_WINDOW` nonces below the highest used; older ones count as
+    /// used, so nonces should increase. A below-threshold send skipped under
+    /// `Noop` still uses its nonce.
+    pub fn send_with_nonce(ctx: Context<SendWithNonce>, lamports: u64, nonce: u64) -> Result<()> {
+        handlers::send_with_nonce(ctx, lamports, nonce)
+    }
 
-/// Accounts context for updates (authority-only).
-#[derive(Accounts)]
-pub struct Update<'info> {
-    /// Authority as set in config; must sign.
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    /// Config PDA with authority constraint enforced.
-    #[account(
-        mut,
-        seeds = [CONFIG_SEED],
-        bump = config.bump,
-        has_one = authority
-    )]
-    pub config: Account<'info, Config>,
-}
+    /// Transfer `amount` base units of the configured mint from `from`'s
+    /// associated token account to `to`'s if `amount ≥ token_threshold`. The
+    /// lamport-denominated rules (policy, anomaly guard, epoch summaries) do
+    /// not apply; recipient, freshness, CPI policy, and mint allowlist do.
+    pub fn send_tokens_if_over_threshold(ctx: Context<SendTokens>, amount: u64) -> Result<()> {
+        handlers::send_tokens_if_over_threshold(ctx, amount)
+    }
 
-/// On-chain config for the program.
-#[account]
-pub struct Config {
-    pub authority: Pubkey,
-    pub from: Pubkey,
-    pub to: Pubkey,
-    pub threshold_lamports: u64,
-    pub bump: u8,
-}
+    /// Close a receipt, returning its rent to whoever paid for it.
+    pub fn close_receipt(_ctx: Context<CloseReceipt>) -> Result<()> {
+        handlers::close_receipt(_ctx)
+    }
 
-/// Error types for the program.
-#[error_code]
-pub enum ConditionalError {
-    /// Amount provided was below the configured threshold.
-    #[msg("Amount must be at least the configured threshold.")]
-    BelowThreshold,
-    /// Caller attempted an unauthorized update.
-    #[msg("Only the authority may update config.")]
-    Unauthorized,
+    /// Resize a receipt to the current `Receipt` layout (its payer only), so
+    /// receipts from before an upgrade can be verified and closed again. Rent
+    /// is topped up from, or refunded to, the payer.
+    pub fn migrate_receipt(ctx: Context<MigrateReceipt>) -> Result<()> {
+        handlers::migrate_receipt(ctx)
+    }
+
+    /// Check that `reference` was paid through this config with at least
+    /// `min_lamports`, returning the receipt via return data. Merchants call it
+    /// (or simulate it) to settle a payment link; a CPI caller can gate its own
+    /// fulfilment on it.
+    pub fn verify_payment(
+        ctx: Context<VerifyPayment>,
+        reference: [u8; 32],
+        min_lamports: u64,
+    ) -> Result<Receipt> {
+        handlers::verify_payment(ctx, reference, min_lamports)
+    }
+
+    /// Guard a bridged payout with the config's conditions: lock `lamports` from
+    /// `from` in the config's bridge custody PDA and post a Wormhole message
+    /// (payload from `bridge_payload`) naming the route's foreign recipient, for
+    /// the receiving chain's contract to pay out once guardians sign it. `from`
+    /// also pays the bridge's message fee. Below-threshold amounts always fail.
+    pub fn send_bridged(ctx: Context<SendBridged>, lamports: u64, nonce: u32) -> Result<()> {
+        handlers::send_bridged(ctx, lamports, nonce)
+    }
+
+    /// Write the clock and price mocks read by the send paths (see
+    /// `test_mode`). Only built with the `test-mode` feature, so release
+    /// builds have no such instruction.
+    #[cfg(feature = "test-mode")]
+    pub fn set_test_mocks(
+        ctx: Context<SetTestMocks>,
+        clock_offset_seconds: i64,
+        lamports_per_usd_cent: u64,
+        sol_usd_cents: u64,
+    ) -> Result<()> {
+        handlers::set_test_mocks(
+            ctx,
+            clock_offset_seconds,
+            lamports_per_usd_cent,
+            sol_usd_cents,
+        )
+    }
+
+    /// Escrow step one: move `lamports` from `from` into the config's escrow
+    /// PDA. Nothing is checked against the conditions until `release`.
+    pub fn deposit(ctx: Context<Deposit>, lamports: u64) -> Result<()> {
+        handlers::deposit(ctx, lamports)
+    }
+
+    /// Escrow step two: pay `lamports` from the escrow PDA to `to`, checking
+    /// the threshold, window, and every other send condition now rather than
+    /// at deposit. Permissionless, since the funds can only reach the route's
+    /// recipient. Fails with `EscrowExpired` once `escrow_expiry_slot` passes.
+    pub fn release(ctx: Context<Release>, lamports: u64) -> Result<()> {
+        handlers::release(ctx, lamports)
+    }
+
+    /// Return the whole escrow to `from` once `escrow_expiry_slot` has passed
+    /// without the conditions letting it be released (`from` only), by a
+    /// transfer the escrow PDA signs. Not gated by `FEATURE_ESCROW`, so
+    /// disabling the feature can't trap deposits.
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        handlers::refund(ctx)
+    }
+
+    /// Joint variant of `send_if_over_threshold`: the threshold is checked against
+    /// the sum of every `send_joint_if_over_threshold` for this config in the same
+    /// transaction (e.g. 0.06 + 0.05 SOL against 0.1). Each instruction transfers
+    /// only its own `lamports`. Must be invoked at the top level, not via CPI.
+    pub fn send_joint_if_over_threshold(ctx: Context<SendJoint>, lamports: u64) -> Result<()> {
+        handlers::send_joint_if_over_threshold(ctx, lamports)
+    }
+
+    /// Distribute `total_lamports` from `from` across the payout table: each
+    /// vote account's current withdraw authority receives its share. The
+    /// threshold and every other send condition (maximum, rate limit, window,
+    /// balance, CPI allowlist) apply to the total. Remaining accounts are
+    /// `[vote_account, withdrawer]` pairs in table (vote account) order, followed by the dust
+    /// account under `RemainderPolicy::DustAccount`. The table's remainder
+    /// policy places the rounding dust, so the shares always sum to the total.
+    pub fn send_validator_payout<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SendValidatorPayout<'info>>,
+        total_lamports: u64,
+    ) -> Result<()> {
+        handlers::send_validator_payout(ctx, total_lamports)
+    }
+
+    /// Split `lamports` from `from` across the config's split table, one
+    /// System Program transfer per recipient, with the last recipient taking
+    /// the rounding dust. Every send condition (threshold, maximum, rate limit,
+    /// window, balance) applies to the total, and each recipient must pass the
+    /// recipient checks. Remaining accounts are the table's recipients,
+    /// writable, in table order (ascending by pubkey). Recipients are paid in
+    /// SOL; the config's beneficiary setting does not apply.
+    pub fn send_split_if_over_threshold<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SendSplit<'info>>,
+        lamports: u64,
+    ) -> Result<()> {
+        handlers::send_split_if_over_threshold(ctx, lamports)
+    }
+
+    /// Send `lamports` from `from` to the address derived from the config's
+    /// `seeded_recipient` and `seed` (`Pubkey::create_with_seed`), checked
+    /// on-chain so payers of seed-derived deposit addresses need not compute or
+    /// trust the key. `to` must still be uninitialized: system-owned with no
+    /// data. Every send condition applies; the beneficiary setting, matching,
+    /// and sweeps do not.
+    pub fn send_to_seeded_address(
+        ctx: Context<SendSeeded>,
+        lamports: u64,
+        seed: String,
+    ) -> Result<()> {
+        handlers::send_to_seeded_address(ctx, lamports, seed)
+    }
+
+    /// Create a matching pool for this config, owned by `sponsor`.
+    /// - `ratio_bps`: match per transferred lamport (10_000 = 1:1)
+    /// - `cap_lamports`: total lamports the pool will ever match
+    pub fn create_match_pool(
+        ctx: Context<CreateMatchPool>,
+        ratio_bps: u16,
+        cap_lamports: u64,
+    ) -> Result<()> {
+        handlers::create_match_pool(ctx, ratio_bps, cap_lamports)
+    }
+
+    /// Deposit lamports into a matching pool (any payer may fund it).
+    pub fn fund_match_pool(ctx: Context<FundMatchPool>, lamports: u64) -> Result<()> {
+        handlers::fund_match_pool(ctx, lamports)
+    }
+
+    /// Close a matching pool, returning rent and unmatched funds to the sponsor.
+    pub fn close_match_pool(_ctx: Context<CloseMatchPool>) -> Result<()> {
+        handlers::close_match_pool(_ctx)
+    }
+
+    /// Close a matching pool abandoned for `INACTIVITY_CLOSE_SECONDS` (requires `PERM_CLOSE`).
+    /// Funds still go back to the sponsor, never to the authority.
+    pub fn force_close_match_pool(ctx: Context<ForceCloseMatchPool>) -> Result<()> {
+        handlers::force_close_match_pool(ctx)
+    }
+
+    /// Read the config's current event sequence number via return data, so off-chain
+    /// consumers can compare it against the last event they observed.
+    pub fn get_event_seq(ctx: Context<ReadConfig>) -> Result<u64> {
+        handlers::get_event_seq(ctx)
+    }
+
+    /// Return the terms transfers under this config are evaluated against, after
+    /// applying the linked policy and per-config overrides (pass the policy if linked).
+    pub fn resolve_effective_policy(ctx: Context<ReadEffectivePolicy>) -> Result<PolicyTerms> {
+        handlers::resolve_effective_policy(ctx)
+    }
+
+    /// Return the config's send terms as a `ConfigView` via return data, so
+    /// CPI callers and simulations can query the policy without decoding the
+    /// account (pass the policy if linked).
+    pub fn get_config(ctx: Context<ReadEffectivePolicy>) -> Result<ConfigView> {
+        handlers::get_config(ctx)
+    }
+
+    /// Escrow one milestone tranche: `from` deposits `amount` into a milestone PDA
+    /// that `approver` must sign off before it can be released to `to`.
+    pub fn create_milestone(
+        ctx: Context<CreateMilestone>,
+        index: u32,
+        amount: u64,
+        approver: Pubkey,
+    ) -> Result<()> {
+        handlers::create_milestone(ctx, index, amount, approver)
+    }
+
+    /// Mark a milestone as complete (approver only).
+    pub fn approve_milestone(ctx: Context<ApproveMilestone>) -> Result<()> {
+        handlers::approve_milestone(ctx)
+    }
+
+    /// Release an approved milestone tranche to `to` if it meets the threshold.
+    /// Permissionless: anyone may crank the release once approved.
+    pub fn release_milestone(ctx: Context<ReleaseMilestone>) -> Result<()> {
+        handlers::release_milestone(ctx)
+    }
+
+    /// Close a released milestone, returning its rent to the payer.
+    pub fn close_milestone(ctx: Context<CloseMilestone>) -> Result<()> {
+        handlers::close_milestone(ctx)
+    }
+
+    /// Close a milestone abandoned for `INACTIVITY_CLOSE_SECONDS` (requires `PERM_CLOSE`).
+    /// Any unreleased tranche is refunded to the payer.
+    pub fn force_close_milestone(ctx: Context<ForceCloseMilestone>) -> Result<()> {
+        handlers::force_close_milestone(ctx)
+    }
+
+    /// Commit `total` lamports from `from` that vest linearly to `to` between
+    /// `start_ts` and `end_ts` (unix seconds).
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        index: u32,
+        total: u64,
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<()> {
+        handlers::create_stream(ctx, index, total, start_ts, end_ts)
+    }
+
+    /// Pay `lamports` of the vested, unwithdrawn balance to `to`. Each
+    /// withdrawal must pass every send condition (threshold, maximum, rate
+    /// limit, window). Permissionless: funds only reach the config's recipient.
+    pub fn withdraw_stream(ctx: Context<WithdrawStream>, lamports: u64) -> Result<()> {
+        handlers::withdraw_stream(ctx, lamports)
+    }
+
+    /// Stop a stream (payer only): the vested, unwithdrawn balance goes to
+    /// `to` whatever its size, and the unvested rest plus rent returns to the
+    /// payer.
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        handlers::cancel_stream(ctx)
+    }
+
+    /// Escrow `amount` from `from` until the recipient acknowledges delivery
+    /// by submitting `commitment` (e.g. the SHA-256 of the delivered file or
+    /// invoice). If unacknowledged by `deadline`, the payer may close it for a refund.
+    pub fn create_delivery(
+        ctx: Context<CreateDelivery>,
+        index: u32,
+        amount: u64,
+        commitment: [u8; 32],
+        deadline: i64,
+    ) -> Result<()> {
+        handlers::create_delivery(ctx, index, amount, commitment, deadline)
+    }
+
+    /// Recipient acknowledges delivery with the deliverable's hash; if it
+    /// matches the commitment the escrow is released to `to`, subject to every
+    /// send condition at that point.
+    pub fn acknowledge_delivery(
+        ctx: Context<AcknowledgeDelivery>,
+        deliverable_hash: [u8; 32],
+    ) -> Result<()> {
+        handlers::acknowledge_delivery(ctx, deliverable_hash)
+    }
+
+    /// Close a delivery, returning its lamports to the payer: rent once
+    /// acknowledged, or rent plus the escrow once past an unmet deadline.
+    pub fn close_delivery(ctx: Context<CloseDelivery>) -> Result<()> {
+        handlers::close_delivery(ctx)
+    }
+
+    /// Escrow `lamports` from `from` in a `ScheduledTransfer` at
+    /// `[SCHEDULED_TRANSFER_SEED, config, id]`, payable to `to` once the
+    /// chain reaches `unlock_slot`. The amount must pass every send condition
+    /// now and again when it executes. With an `expiry_slot` (after
+    /// `unlock_slot`), a transfer still unexecuted then can only be refunded.
+    pub fn schedule_transfer(
+        ctx: Context<ScheduleTransfer>,
+        id: u64,
+        lamports: u64,
+        unlock_slot: u64,
+        expiry_slot: Option<u64>,
+    ) -> Result<()> {
+        handlers::schedule_transfer(ctx, id, lamports, unlock_slot, expiry_slot)
+    }
+
+    /// Pay an unlocked scheduled transfer to `to` and return its rent to the
+    /// payer. Permissionless, for cranks: funds only reach the config's
+    /// recipient, and every send condition is checked against the current
+    /// config. A transfer that no longer meets them stays escrowed until it
+    /// does, or until its `expiry_slot`, after which only `refund_scheduled`
+    /// remains.
+    pub fn execute_scheduled(ctx: Context<ExecuteScheduled>) -> Result<()> {
+        handlers::execute_scheduled(ctx)
+    }
+
+    /// Take back a scheduled transfer before it unlocks (payer only); the
+    /// escrow and rent return to the payer. Works after the config is closed.
+    pub fn cancel_scheduled(ctx: Context<CancelScheduled>) -> Result<()> {
+        handlers::cancel_scheduled(ctx)
+    }
+
+    /// Reclaim a scheduled transfer that passed its `expiry_slot` unexecuted
+    /// (payer only); the escrow and rent return to the payer. Works after the
+    /// config is closed.
+    pub fn refund_scheduled(ctx: Context<CancelScheduled>) -> Result<()> {
+        handlers::refund_scheduled(ctx)
+    }
+
+    /// Open a bounty: the first signer to send at least `min_lamports` to
+    /// `target` through `fund_bounty_target` receives `reward` from this escrow.
+    /// Unclaimed rewards return to the sponsor after `expires_at`.
+    pub fn create_bounty(
+        ctx: Context<CreateBounty>,
+        target: Pubkey,
+        min_lamports: u64,
+        reward: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        handlers::create_bounty(ctx, target, min_lamports, reward, expires_at)
+    }
+
+    /// Send `lamports` from `sender` to the bounty target. If this is the first
+    /// send meeting `min_lamports` before expiry, the sender wins the reward in
+    /// the same instruction. Later sends still transfer but earn nothing.
+    pub fn fund_bounty_target(ctx: Context<FundBountyTarget>, lamports: u64) -> Result<()> {
+        handlers::fund_bounty_target(ctx, lamports)
+    }
+
+    /// Close a bounty, returning its lamports to the sponsor: rent once won,
+    /// or rent plus the reward once expired unclaimed.
+    pub fn close_bounty(ctx: Context<CloseBounty>) -> Result<()> {
+        handlers::close_bounty(ctx)
+    }
+
+    /// Register a recipient-side sweep: a PDA the owner can use as a config's
+    /// `to`. Whenever a send leaves it holding at least `threshold_lamports`
+    /// above rent, the whole surplus is forwarded to `cold_wallet` in the same instruction.
+    pub fn create_sweep(
+        ctx: Context<CreateSweep>,
+        cold_wallet: Pubkey,
+        threshold_lamports: u64,
+    ) -> Result<()> {
+        handlers::create_sweep(ctx, cold_wallet, threshold_lamports)
+    }
+
+    /// Change a sweep's cold wallet and threshold (owner only). `update_id`
+    /// works as for the config's `update_*` instructions, tracked per sweep.
+    pub fn update_sweep(
+        ctx: Context<UpdateSweep>,
+        cold_wallet: Pubkey,
+        threshold_lamports: u64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_sweep(ctx, cold_wallet, threshold_lamports, update_id, recent_slot)
+    }
+
+    /// Resize a sweep to the current `Sweep` layout (owner only). Rent is
+    /// topped up from, or refunded to, the owner.
+    pub fn migrate_sweep(ctx: Context<MigrateSweep>) -> Result<()> {
+        handlers::migrate_sweep(ctx)
+    }
+
+    /// Close a sweep, returning its rent and any unforwarded balance to the owner.
+    pub fn close_sweep(_ctx: Context<CloseSweep>) -> Result<()> {
+        handlers::close_sweep(_ctx)
+    }
+
+    /// Deposit lamports into the config's vault PDA (any payer may fund it).
+    pub fn fund_vault(ctx: Context<FundVault>, lamports: u64) -> Result<()> {
+        handlers::fund_vault(ctx, lamports)
+    }
+
+    /// Move the vault's whole balance to `from` while a close is pending
+    /// (`from` only), so a funded vault is never closed out from under it.
+    /// Unstake staked lamports with `unstake_vault` first.
+    pub fn withdraw_vault(ctx: Context<WithdrawVault>) -> Result<()> {
+        handlers::withdraw_vault(ctx)
+    }
+
+    /// Enable staking of idle vault lamports into an SPL stake pool, routing
+    /// yield to `beneficiary` on unstake (requires `PERM_UPDATE_SETTINGS`).
+    /// `pool_tokens` (see `stake_vault`) must be a `pool_mint` account owned by the vault.
+    pub fn configure_vault_staking(
+        ctx: Context<ConfigureVaultStaking>,
+        stake_pool: Pubkey,
+        pool_mint: Pubkey,
+        beneficiary: Pubkey,
+    ) -> Result<()> {
+        handlers::configure_vault_staking(ctx, stake_pool, pool_mint, beneficiary)
+    }
+
+    /// Deposit `lamports` of the vault into the stake pool (requires
+    /// `PERM_UPDATE_SETTINGS`). Staked lamports are unavailable to allowances
+    /// until `unstake_vault`.
+    pub fn stake_vault(ctx: Context<StakeVault>, lamports: u64) -> Result<()> {
+        handlers::stake_vault(ctx, lamports)
+    }
+
+    /// Redeem every pool token back into the vault (permissionless, so funds
+    /// can always be made available for release). Lamports received above the
+    /// staked principal are sent to the beneficiary.
+    pub fn unstake_vault(ctx: Context<UnstakeVault>) -> Result<()> {
+        handlers::unstake_vault(ctx)
+    }
+
+    /// Mint an allowance letting `holder` pull up to `amount` lamports from the
+    /// vault until `expires_at` (unix seconds), optionally only to `recipient`.
+    /// Either way redemptions may only pay the config's recipient or an address
+    /// on its recipient list. Requires `PERM_ALLOWANCES`.
+    pub fn mint_allowance(
+        ctx: Context<MintAllowance>,
+        holder: Pubkey,
+        amount: u64,
+        expires_at: i64,
+        recipient: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::mint_allowance(ctx, holder, amount, expires_at, recipient)
+    }
+
+    /// Redeem part of an allowance: transfer `lamports` from the vault to the
+    /// allowed recipient. Every send condition (pause, quorum, threshold,
+    /// maximum, rate limit, window) still applies to each redemption.
+    pub fn redeem_allowance(ctx: Context<RedeemAllowance>, lamports: u64) -> Result<()> {
+        handlers::redeem_allowance(ctx, lamports)
+    }
+
+    /// Revoke an allowance and reclaim its rent (requires `PERM_ALLOWANCES`).
+    pub fn revoke_allowance(ctx: Context<RevokeAllowance>) -> Result<()> {
+        handlers::revoke_allowance(ctx)
+    }
+
+    /// Create the summary account for the current epoch (permissionless; the
+    /// payer funds its rent).
+    pub fn open_epoch_summary(ctx: Context<OpenEpochSummary>) -> Result<()> {
+        handlers::open_epoch_summary(ctx)
+    }
+
+    /// Mark a past epoch's summary as final (permissionless crank).
+    pub fn finalize_epoch(ctx: Context<FinalizeEpoch>) -> Result<()> {
+        handlers::finalize_epoch(ctx)
+    }
+
+    /// Create the program-wide state and set its admin. Only the program's
+    /// upgrade authority may call this.
+    pub fn initialize_global(ctx: Context<InitializeGlobal>, admin: Pubkey) -> Result<()> {
+        handlers::initialize_global(ctx, admin)
+    }
+
+    /// Resize the global state PDA to the current `GlobalState` layout (admin
+    /// only). Rent is topped up from, or refunded to, the admin.
+    pub fn migrate_global(ctx: Context<MigrateGlobal>) -> Result<()> {
+        handlers::migrate_global(ctx)
+    }
+
+    /// Hand the program admin role to `new_admin` (current admin only). This
+    /// and the other admin updates take an `update_id` tracked by the global
+    /// state, working as for the config's `update_*` instructions.
+    pub fn update_admin(
+        ctx: Context<UpdateAdmin>,
+        new_admin: Pubkey,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_admin(ctx, new_admin, update_id, recent_slot)
+    }
+
+    /// Replace the protocol-wide disabled-feature mask (`FEATURE_*` bits; admin
+    /// only). Lets the admin switch off a compromised path without an upgrade.
+    pub fn set_disabled_features(
+        ctx: Context<UpdateAdmin>,
+        disabled_features: u64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_disabled_features(ctx, disabled_features, update_id, recent_slot)
+    }
+
+    /// Set the slot from which v1 instructions fail with `DeprecatedInstruction`
+    /// (admin only). `0` cancels the sunset. The deadline is announced on-chain
+    /// so integrators can migrate to the v2 instructions in time.
+    pub fn set_sunset_slot(
+        ctx: Context<UpdateAdmin>,
+        sunset_slot: u64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_sunset_slot(ctx, sunset_slot, update_id, recent_slot)
+    }
+
+    /// Emergency recovery of escrowed lamports stuck in a program-owned account
+    /// (milestone, match pool) belonging to `config`. Requires BOTH the program
+    /// admin and the config authority to sign; every use is counted and emitted.
+    pub fn admin_force_transfer(ctx: Context<AdminForceTransfer>, lamports: u64) -> Result<()> {
+        handlers::admin_force_transfer(ctx, lamports)
+    }
+
+    /// Create the event schema registry at the current `EVENT_SCHEMA_VERSION`.
+    pub fn initialize_event_schema(ctx: Context<InitializeEventSchema>) -> Result<()> {
+        handlers::initialize_event_schema(ctx)
+    }
+
+    /// Record a newer `EVENT_SCHEMA_VERSION` after an upgrade (permissionless).
+    /// No-op when the registry is already current.
+    pub fn sync_event_schema(ctx: Context<SyncEventSchema>) -> Result<()> {
+        handlers::sync_event_schema(ctx)
+    }
+
+    /// Optional: Update threshold (requires `PERM_UPDATE_THRESHOLD`).
+    pub fn update_threshold(
+        ctx: Context<Update>,
+        new_threshold_lamports: u64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_threshold(ctx, new_threshold_lamports, update_id, recent_slot)
+    }
+
+    /// Optional: Set both bounds of a send, `threshold_lamports ≤ lamports ≤
+    /// max_lamports` (requires `PERM_UPDATE_THRESHOLD`). `max_lamports = 0`
+    /// removes the cap. A linked policy that sets a maximum replaces this one.
+    pub fn update_limits(
+        ctx: Context<Update>,
+        threshold_lamports: u64,
+        max_lamports: u64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_limits(
+            ctx,
+            threshold_lamports,
+            max_lamports,
+            update_id,
+            recent_slot,
+        )
+    }
+
+    /// Optional: Rate-limit sends with a token bucket of `capacity` lamports
+    /// refilling at `refill_per_second` (requires `PERM_UPDATE_THRESHOLD`):
+    /// bursts up to `capacity` pass, sustained outflow is held to the refill
+    /// rate, and sends the bucket can't cover fail with `RateLimitExceeded`.
+    /// `refill_per_second = 0` removes the limit. What has been drawn from
+    /// the bucket carries over, refilled at the old rate up to now.
+    pub fn update_rate_limit(
+        ctx: Context<Update>,
+        capacity: u64,
+        refill_per_second: u32,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_rate_limit(ctx, capacity, refill_per_second, update_id, recent_slot)
+    }
+
+    /// Stop (or resume) every send from this config, e.g. while the `from` key
+    /// is suspected compromised (requires `PERM_PAUSE`). Config updates still work.
+    pub fn set_paused(
+        ctx: Context<Update>,
+        paused: bool,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_paused(ctx, paused, update_id, recent_slot)
+    }
+
+    /// Turn `threshold_lamports` into a floor (root only): from now on
+    /// `update_threshold`, `update_limits`, and signed proposals may only raise
+    /// it, and a lower value fails with `ThresholdDecreaseNotAllowed`. There is
+    /// no way back. The floor also holds under a linked policy or policy
+    /// overrides: a lower override is rejected, and a lower policy threshold
+    /// resolves to the floor. Needs a `Gte` or `Gt` comparison mode, the
+    /// only ones where a higher threshold is stricter, and no `auto_adjust` peg.
+    pub fn enable_monotonic_threshold(
+        ctx: Context<Update>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::enable_monotonic_threshold(ctx, update_id, recent_slot)
+    }
+
+    /// Optional: Choose how send amounts are compared with the threshold
+    /// (requires `PERM_UPDATE_THRESHOLD`); see [`ComparisonMode`]. A monotonic
+    /// config may only tighten `Gte` to `Gt`.
+    pub fn update_comparison_mode(
+        ctx: Context<Update>,
+        comparison_mode: ComparisonMode,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_comparison_mode(ctx, comparison_mode, update_id, recent_slot)
+    }
+
+    /// Optional: Require sends to be worth at least `threshold_usd_cents` at the
+    /// Pyth SOL/USD price when they execute (requires `PERM_UPDATE_THRESHOLD`),
+    /// in addition to `threshold_lamports`. Sends must then pass the price feed;
+    /// paths that take none fail with `PriceFeedRequired`, and all fail with
+    /// `FeatureDisabled` while `FEATURE_ORACLE` is switched off. `None` removes it.
+    pub fn set_usd_threshold(
+        ctx: Context<Update>,
+        threshold_usd_cents: Option<u64>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_usd_threshold(ctx, threshold_usd_cents, update_id, recent_slot)
+    }
+
+    /// Optional: Peg `threshold_lamports` to a US dollar value (requires
+    /// `PERM_UPDATE_THRESHOLD`). A permissionless crank then calls
+    /// `adjust_threshold` to reprice it at most every `interval_seconds`, so
+    /// a "at least $20" config stays accurate as SOL moves. Unlike
+    /// `set_usd_threshold`, sends need no price feed; between adjustments the
+    /// last lamport value applies. Bounds must fit `max_lamports`, and a
+    /// monotonic config can't be pegged, as the peg lowers the threshold when
+    /// SOL rises. Only the config's own threshold moves; a linked policy's or
+    /// an override's does not. `None` stops adjusting and keeps the current value.
+    pub fn set_auto_adjust(
+        ctx: Context<Update>,
+        auto_adjust: Option<AutoAdjust>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_auto_adjust(ctx, auto_adjust, update_id, recent_slot)
+    }
+
+    /// Reprice a pegged threshold from the Pyth SOL/USD feed; see
+    /// `set_auto_adjust`. Permissionless crank: the price feed is checked as
+    /// for sends, and the result is bounded by the config's terms. Fails with
+    /// `AutoAdjustDisabled` on an unpegged config, `AdjustmentTooEarly`
+    /// within `interval_seconds` of the last adjustment, and `FeatureDisabled`
+    /// while `FEATURE_ORACLE` is switched off.
+    pub fn adjust_threshold(ctx: Context<AdjustThreshold>) -> Result<()> {
+        handlers::adjust_threshold(ctx)
+    }
+
+    /// Experimental: Keep the threshold private (requires
+    /// `PERM_UPDATE_THRESHOLD`). `commitment` is a Pedersen commitment to the
+    /// threshold; sends must then pass a `RangeProofU64` proof context account
+    /// showing the amount meets it (see `private_threshold`), and paths that
+    /// take none fail with `RangeProofRequired`. `threshold_lamports` still
+    /// applies, as a public floor. `None` removes it.
+    pub fn set_private_threshold(
+        ctx: Context<Update>,
+        commitment: Option<[u8; 32]>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_private_threshold(ctx, commitment, update_id, recent_slot)
+    }
+
+    /// Charge `fee_bps` of every `SendIfOverThreshold` send to `fee_collector`
+    /// (root only). The recipient gets the remainder; thresholds and limits
+    /// still apply to the full amount. At most `MAX_FEE_BPS`; `0` removes the
+    /// fee. The collector should already be rent exempt, or small fees fail.
+    /// A linked policy that sets a fee replaces this one and its collector.
+    pub fn set_fee(
+        ctx: Context<Update>,
+        fee_bps: u16,
+        fee_collector: Pubkey,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_fee(ctx, fee_bps, fee_collector, update_id, recent_slot)
+    }
+
+    /// Optional: Restrict sends to `[valid_from_unix, valid_until_unix]`
+    /// (root only). Either bound may be unset; clearing both lifts the window.
+    /// A linked policy that sets a window replaces this one.
+    pub fn update_time_window(
+        ctx: Context<Update>,
+        valid_from_unix: Option<i64>,
+        valid_until_unix: Option<i64>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_time_window(
+            ctx,
+            valid_from_unix,
+            valid_until_unix,
+            update_id,
+            recent_slot,
+        )
+    }
+
+    /// Optional: Let `from` `refund` the escrow from `expiry_slot` on, and stop
+    /// `release` then (root only). A past slot makes it refundable at once;
+    /// `None` removes the expiry.
+    pub fn set_escrow_expiry(
+        ctx: Context<Update>,
+        expiry_slot: Option<u64>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_escrow_expiry(ctx, expiry_slot, update_id, recent_slot)
+    }
+
+    /// Optional: Zero the lifetime transfer statistics (root only), e.g. at
+    /// the start of a budgeting period.
+    pub fn reset_stats(ctx: Context<Update>, update_id: u64, recent_slot: u64) -> Result<()> {
+        handlers::reset_stats(ctx, update_id, recent_slot)
+    }
+
+    /// Optional: Propose `new_authority` as the config's root authority, or
+    /// withdraw the proposal with `None` (root authority only). Nothing changes
+    /// until the proposed key signs `accept_authority`, so a mistyped key
+    /// cannot lock the config.
+    pub fn propose_authority(
+        ctx: Context<Update>,
+        new_authority: Option<Pubkey>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::propose_authority(ctx, new_authority, update_id, recent_slot)
+    }
+
+    /// Optional: Take over as root authority; the signer must be the
+    /// `pending_authority` proposed with `propose_authority`.
+    pub fn accept_authority(
+        ctx: Context<AcceptAuthority>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::accept_authority(ctx, update_id, recent_slot)
+    }
+
+    /// Optional: Set or clear the mint and token-amount threshold gating
+    /// `send_tokens_if_over_threshold` (requires `PERM_UPDATE_THRESHOLD`). The
+    /// mint must pass the config's mint allowlist.
+    pub fn set_token_threshold(
+        ctx: Context<Update>,
+        token_threshold: Option<TokenThreshold>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_token_threshold(ctx, token_threshold, update_id, recent_slot)
+    }
+
+    /// Optional: Set the default memo attached to every transfer (requires
+    /// `PERM_UPDATE_SETTINGS`). An empty string clears it.
+    pub fn update_default_memo(
+        ctx: Context<Update>,
+        memo: String,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_default_memo(ctx, memo, update_id, recent_slot)
+    }
+
+    /// Apply a `ConfigProposal` signed offline by the root authority. The
+    /// preceding instruction must be an ed25519 program instruction verifying
+    /// the authority's signature over the proposal's Borsh bytes. Anyone may
+    /// relay it; the proposal nonce prevents replay, and its signed
+    /// `recent_slot` keeps a proposal that was never relayed from landing late.
+    pub fn apply_signed_proposal(
+        ctx: Context<ApplySignedProposal>,
+        proposal: ConfigProposal,
+    ) -> Result<()> {
+        handlers::apply_signed_proposal(ctx, proposal)
+    }
+
+    /// Upgrade the config PDA in place to the current layout and
+    /// `CONFIG_VERSION` after a program upgrade (root authority only). The
+    /// config is decoded in the layout of its stored version (see
+    /// `Config::try_deserialize_versioned`) and brought forward by
+    /// `Config::upgrade`, then the account is resized and rewritten in the
+    /// current layout. Rent is topped up from, or refunded to, the authority.
+    /// Safe to repeat.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        handlers::migrate_config(ctx)
+    }
+
+    /// Sweep lamports sent directly to the config PDA (anything above its
+    /// rent-exempt minimum) to `destination` (requires `PERM_RECOVER`).
+    pub fn recover_lamports(ctx: Context<RecoverLamports>) -> Result<()> {
+        handlers::recover_lamports(ctx)
+    }
+
+    /// Sweep the full balance of a token account owned by the config PDA to
+    /// `destination` (requires `PERM_RECOVER`). Covers tokens sent to the config's ATA.
+    pub fn recover_tokens(ctx: Context<RecoverTokens>) -> Result<()> {
+        handlers::recover_tokens(ctx)
+    }
+
+    /// Optional: Allow or forbid `to` being an executable (program) account
+    /// (requires `PERM_UPDATE_SETTINGS`).
+    pub fn set_allow_executable_recipient(
+        ctx: Context<Update>,
+        allow: bool,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_allow_executable_recipient(ctx, allow, update_id, recent_slot)
+    }
+
+    /// Optional: Choose whether below-threshold sends fail or are skipped
+    /// (requires `PERM_UPDATE_SETTINGS`).
+    pub fn set_below_threshold_behavior(
+        ctx: Context<Update>,
+        behavior: BelowThresholdBehavior,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_below_threshold_behavior(ctx, behavior, update_id, recent_slot)
+    }
+
+    /// Optional: Set the amount at or above which sends also emit
+    /// `LargeTransferExecuted` (requires `PERM_UPDATE_SETTINGS`). `0` disables.
+    pub fn set_large_transfer_lamports(
+        ctx: Context<Update>,
+        lamports: u64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_large_transfer_lamports(ctx, lamports, update_id, recent_slot)
+    }
+
+    /// Optional: Set or clear the foreign recipient `send_bridged` pays
+    /// (requires `PERM_UPDATE_ADDRESSES`). The route must name a known Wormhole
+    /// core bridge deployment and a chain other than Solana.
+    pub fn set_bridge_route(
+        ctx: Context<Update>,
+        route: Option<BridgeRoute>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_bridge_route(ctx, route, update_id, recent_slot)
+    }
+
+    /// Optional: Flag sends above `multiplier` times the rolling average send
+    /// size with `AnomalousTransfer` (requires `PERM_UPDATE_SETTINGS`). With
+    /// `require_approval`, flagged sends also need an `approver` co-signer holding
+    /// `PERM_APPROVE_ANOMALY`. `multiplier = 0` disables; the average keeps
+    /// updating either way.
+    pub fn set_anomaly_guard(
+        ctx: Context<Update>,
+        multiplier: u16,
+        require_approval: bool,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_anomaly_guard(ctx, multiplier, require_approval, update_id, recent_slot)
+    }
+
+    /// Optional: Restrict which programs may CPI into the send instructions
+    /// (requires `PERM_UPDATE_SETTINGS`). With `restrict = true` and an empty
+    /// list, only direct (top-level) invocations are accepted; otherwise the
+    /// listed programs may call in directly, never through a further CPI.
+    pub fn set_cpi_policy(
+        ctx: Context<Update>,
+        restrict: bool,
+        allowed_programs: Vec<Pubkey>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_cpi_policy(ctx, restrict, allowed_programs, update_id, recent_slot)
+    }
+
+    /// Optional: Restrict which mints the config may move, covering token-mode
+    /// delivery and `recover_tokens` (requires `PERM_UPDATE_SETTINGS`). An empty
+    /// list allows every mint.
+    pub fn set_mint_allowlist(
+        ctx: Context<Update>,
+        mints: Vec<Pubkey>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_mint_allowlist(ctx, mints, update_id, recent_slot)
+    }
+
+    /// Optional: Restrict the programs that may own `to`, so sends cannot
+    /// land in accounts whose owner would strand the lamports (requires
+    /// `PERM_UPDATE_SETTINGS`). An empty list lifts the restriction.
+    pub fn set_recipient_owners(
+        ctx: Context<Update>,
+        owners: Vec<Pubkey>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_recipient_owners(ctx, owners, update_id, recent_slot)
+    }
+
+    /// Optional: Let `send_to_seeded_address` pay any address derived from
+    /// `base` and `owner` with a seed, such as an exchange's per-user deposit
+    /// addresses (requires `PERM_UPDATE_ADDRESSES`). `None` turns it off.
+    pub fn set_seeded_recipient(
+        ctx: Context<Update>,
+        seeded_recipient: Option<SeededRecipient>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_seeded_recipient(ctx, seeded_recipient, update_id, recent_slot)
+    }
+
+    /// Require `quorum` of `members` to approve every send (root authority
+    /// only): go through `propose_transfer`, `approve_transfer`, and
+    /// `execute_transfer`, as direct sends then fail with `ApprovalRequired`.
+    /// An empty list with quorum `0` removes the requirement.
+    pub fn set_approvers(
+        ctx: Context<Update>,
+        members: Vec<Pubkey>,
+        quorum: u8,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_approvers(ctx, members, quorum, update_id, recent_slot)
+    }
+
+    /// Optional: Log every rejected `SendIfOverThreshold` send as a
+    /// `TransferRejected` event before it fails (requires
+    /// `PERM_UPDATE_SETTINGS`), so probing attempts show up in transaction
+    /// logs. Costs the event's compute on each rejection.
+    pub fn set_log_rejections(
+        ctx: Context<Update>,
+        enabled: bool,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_log_rejections(ctx, enabled, update_id, recent_slot)
+    }
+
+    /// Optional: Require every send to update the current epoch's summary
+    /// (requires `PERM_UPDATE_SETTINGS`).
+    pub fn set_epoch_reporting(
+        ctx: Context<Update>,
+        enabled: bool,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_epoch_reporting(ctx, enabled, update_id, recent_slot)
+    }
+
+    /// Optional: Require the root authority to `attest` at least every
+    /// `max_config_age_seconds`, or sends revert (requires `PERM_UPDATE_SETTINGS`).
+    /// `0` disables the check. Setting it also counts as an attestation.
+    pub fn set_max_config_age(
+        ctx: Context<Update>,
+        max_config_age_seconds: u64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_max_config_age(ctx, max_config_age_seconds, update_id, recent_slot)
+    }
+
+    /// Re-attest the config as reviewed, restarting the max-age window (root only).
+    pub fn attest(ctx: Context<Update>, update_id: u64, recent_slot: u64) -> Result<()> {
+        handlers::attest(ctx, update_id, recent_slot)
+    }
+
+    /// Optional: Choose how `to` receives sends (requires `PERM_UPDATE_ADDRESSES`).
+    /// Applies to every payout of the config's conditions: sends, escrow
+    /// releases, milestones, streams, deliveries, scheduled transfers, and
+    /// allowance redemptions. Split, validator, and seeded sends stay SOL.
+    pub fn set_beneficiary(
+        ctx: Context<Update>,
+        beneficiary: Beneficiary,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_beneficiary(ctx, beneficiary, update_id, recent_slot)
+    }
+
+    /// Create the validator payout table (requires `PERM_UPDATE_ADDRESSES`).
+    /// Shares are in basis points and must sum to 10_000; entries must be
+    /// sorted by vote account (see `is_canonical_order`).
+    pub fn create_payout_table(
+        ctx: Context<CreatePayoutTable>,
+        entries: Vec<PayoutEntry>,
+    ) -> Result<()> {
+        handlers::create_payout_table(ctx, entries)
+    }
+
+    /// Replace the validator payout table entries (requires `PERM_UPDATE_ADDRESSES`).
+    pub fn update_payout_table(
+        ctx: Context<UpdatePayoutTable>,
+        entries: Vec<PayoutEntry>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_payout_table(ctx, entries, update_id, recent_slot)
+    }
+
+    /// Choose where a payout's rounding dust goes (requires
+    /// `PERM_UPDATE_ADDRESSES`).
+    pub fn set_payout_remainder(
+        ctx: Context<UpdatePayoutTable>,
+        remainder: RemainderPolicy,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_payout_remainder(ctx, remainder, update_id, recent_slot)
+    }
+
+    /// Replace the recipients of `send_split_if_over_threshold` (requires
+    /// `PERM_UPDATE_ADDRESSES`). Shares are in basis points, must sum to 10_000,
+    /// and entries must be sorted by recipient (see `is_canonical_order`), so
+    /// each appears once. The table is created on first use.
+    pub fn set_split_recipients(
+        ctx: Context<ManageSplitTable>,
+        entries: Vec<SplitEntry>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_split_recipients(ctx, entries, update_id, recent_slot)
+    }
+
+    /// Create a shared policy that many configs can reference with
+    /// `set_policy`; updating it applies to all of them at once. Besides the
+    /// threshold, `limits` can set the maximum, send window, and fee of every
+    /// linked config (see [`PolicyLimits`]).
+    pub fn create_policy(
+        ctx: Context<CreatePolicy>,
+        id: u32,
+        threshold_lamports: u64,
+        below_threshold_behavior: BelowThresholdBehavior,
+        limits: PolicyLimits,
+    ) -> Result<()> {
+        handlers::create_policy(
+            ctx,
+            id,
+            threshold_lamports,
+            below_threshold_behavior,
+            limits,
+        )
+    }
+
+    /// Change a shared policy's terms (policy authority only). `update_id`
+    /// works as for the config's `update_*` instructions, tracked per policy.
+    /// The terms of every linked config change with it: pass those configs as
+    /// writable remaining accounts to emit their `ConfigChanged`.
+    pub fn update_policy<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdatePolicy<'info>>,
+        threshold_lamports: u64,
+        below_threshold_behavior: BelowThresholdBehavior,
+        limits: PolicyLimits,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_policy(
+            ctx,
+            threshold_lamports,
+            below_threshold_behavior,
+            limits,
+            update_id,
+            recent_slot,
+        )
+    }
+
+    /// Resize a shared policy to the current `Policy` layout (policy authority
+    /// only). Rent is topped up from, or refunded to, the authority.
+    pub fn migrate_policy(ctx: Context<MigratePolicy>) -> Result<()> {
+        handlers::migrate_policy(ctx)
+    }
+
+    /// Link the config to a shared policy, or unlink with `None` (requires
+    /// `PERM_UPDATE_THRESHOLD`). While linked, the policy's terms replace the
+    /// config's own threshold and below-threshold behavior, and transfers must
+    /// pass the policy account.
+    pub fn set_policy(
+        ctx: Context<Update>,
+        policy: Option<Pubkey>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_policy(ctx, policy, update_id, recent_slot)
+    }
+
+    /// Override individual fields of the resolved policy for this config only
+    /// (requires `PERM_UPDATE_THRESHOLD`). `None` fields inherit. A monotonic
+    /// config's threshold override may not go below its floor.
+    pub fn set_policy_overrides(
+        ctx: Context<Update>,
+        overrides: PolicyOverrides,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_policy_overrides(ctx, overrides, update_id, recent_slot)
+    }
+
+    /// Optional: Update from/to addresses (requires `PERM_UPDATE_ADDRESSES`).
+    /// Moves the config from the old recipient's index to the new one's.
+    pub fn update_addresses(
+        ctx: Context<UpdateAddresses>,
+        new_from: Pubkey,
+        new_to: Pubkey,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::update_addresses(ctx, new_from, new_to, update_id, recent_slot)
+    }
+
+    /// Replace the recipient with one known only by its hash (requires
+    /// `PERM_UPDATE_ADDRESSES`). `recipient_hash` is `recipient_hash(config, to)`;
+    /// sends must pass the matching plaintext `to`, which the first payment then
+    /// reveals. The config leaves its old recipient's index, since listing it
+    /// there would give the payee away. `update_addresses` returns to a
+    /// plaintext recipient.
+    pub fn set_hashed_recipient(
+        ctx: Context<SetHashedRecipient>,
+        recipient_hash: [u8; 32],
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_hashed_recipient(ctx, recipient_hash, update_id, recent_slot)
+    }
+
+    /// Close the config, returning its rent to the signer and removing it from
+    /// the recipient's index (requires `PERM_CLOSE`). Refuses with
+    /// `ConfigHasFunds` while the escrow holds lamports, which would otherwise
+    /// only be reachable again by re-initializing the route.
+    ///
+    /// While the vault holds lamports the close takes two calls: the first
+    /// only records the request, after which sends fail with `ConfigClosing`
+    /// and `from` may `withdraw_vault`; the second, at least
+    /// `CLOSE_COOLING_SECONDS` later and once the vault is empty, closes the
+    /// config. Once requested, the cooling period applies even if the vault
+    /// has since been emptied.
+    pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
+        handlers::close_config(ctx)
+    }
+
+    /// Withdraw a pending close (requires `PERM_CLOSE`), letting sends resume.
+    pub fn cancel_close_config(
+        ctx: Context<Update>,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::cancel_close_config(ctx, update_id, recent_slot)
+    }
+
+    /// Grant `key` the given permission bitmask, replacing any previous grant.
+    /// A zero mask removes the entry. Only the root `authority` may manage grants.
+    pub fn set_authority_permissions(
+        ctx: Context<Update>,
+        key: Pubkey,
+        permissions: u8,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::set_authority_permissions(ctx, key, permissions, update_id, recent_slot)
+    }
+
+    /// Set the recovery committee that can replace a lost root authority:
+    /// `threshold` of `committee` must approve a proposal, and it can only be
+    /// executed `delay_seconds` after it was announced. Root authority only;
+    /// any pending proposal is discarded.
+    pub fn configure_recovery(
+        ctx: Context<ConfigureRecovery>,
+        committee: Vec<Pubkey>,
+        threshold: u8,
+        delay_seconds: i64,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::configure_recovery(
+            ctx,
+            committee,
+            threshold,
+            delay_seconds,
+            update_id,
+            recent_slot,
+        )
+    }
+
+    /// Register an API key for off-chain exporters serving this config's event
+    /// stream (requires `PERM_UPDATE_SETTINGS`). Only `key_hash` is stored:
+    /// `HMAC-SHA256(key = api_key, message = config address)`, so the same key
+    /// hashes differently under every config and the secret never goes on-chain.
+    pub fn register_viewer_key(
+        ctx: Context<ManageViewerKeys>,
+        key_hash: [u8; 32],
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::register_viewer_key(ctx, key_hash, update_id, recent_slot)
+    }
+
+    /// Revoke a registered API key (requires `PERM_UPDATE_SETTINGS`).
+    /// Exporters stop serving it once they observe the change.
+    pub fn revoke_viewer_key(
+        ctx: Context<ManageViewerKeys>,
+        key_hash: [u8; 32],
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::revoke_viewer_key(ctx, key_hash, update_id, recent_slot)
+    }
+
+    /// Let `delegate` sign and fund `send_if_over_threshold` in place of
+    /// `config.from` (requires `PERM_UPDATE_ADDRESSES`), e.g. a rotated hot
+    /// wallet. Adding a listed delegate is a no-op.
+    pub fn add_delegate(
+        ctx: Context<Update>,
+        delegate: Pubkey,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::add_delegate(ctx, delegate, update_id, recent_slot)
+    }
+
+    /// Stop `delegate` from sending for this config (requires
+    /// `PERM_UPDATE_ADDRESSES`).
+    pub fn remove_delegate(
+        ctx: Context<Update>,
+        delegate: Pubkey,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::remove_delegate(ctx, delegate, update_id, recent_slot)
+    }
+
+    /// Let sends pay `recipient` as well as `config.to` (requires
+    /// `PERM_UPDATE_ADDRESSES`). Adding a listed address is a no-op.
+    pub fn add_recipient(
+        ctx: Context<ManageRecipientList>,
+        recipient: Pubkey,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::add_recipient(ctx, recipient, update_id, recent_slot)
+    }
+
+    /// Stop sends to a listed `recipient` (requires `PERM_UPDATE_ADDRESSES`).
+    /// `config.to` itself is changed with `update_addresses`.
+    pub fn remove_recipient(
+        ctx: Context<ManageRecipientList>,
+        recipient: Pubkey,
+        update_id: u64,
+        recent_slot: u64,
+    ) -> Result<()> {
+        handlers::remove_recipient(ctx, recipient, update_id, recent_slot)
+    }
+
+    /// Announce a proposal to make `new_authority` the root authority. The
+    /// proposer's approval counts; a new proposal replaces any pending one and
+    /// restarts the wait.
+    pub fn propose_recovery(ctx: Context<RecoveryAction>, new_authority: Pubkey) -> Result<()> {
+        handlers::propose_recovery(ctx, new_authority)
+    }
+
+    /// Add the signing committee member's approval to the pending proposal.
+    pub fn approve_recovery(ctx: Context<RecoveryAction>) -> Result<()> {
+        handlers::approve_recovery(ctx)
+    }
+
+    /// Cancel the pending proposal. Only the current root authority may veto,
+    /// which is what makes the wait a safeguard against a rogue committee.
+    pub fn veto_recovery(ctx: Context<RecoveryAction>) -> Result<()> {
+        handlers::veto_recovery(ctx)
+    }
+
+    /// Replace the root authority once the pending proposal has `threshold`
+    /// approvals and its wait has elapsed. Any committee member may execute.
+    pub fn execute_recovery(ctx: Context<RecoveryAction>) -> Result<()> {
+        handlers::execute_recovery(ctx)
+    }
+
+    /// Propose sending `lamports` to `to` under the config's approvers, in a
+    /// `PendingTransfer` at `[PENDING_TRANSFER_SEED, config, id]`. The
+    /// proposer must be `from` or an approver, pays the rent, and has its
+    /// approval counted if it is an approver.
+    pub fn propose_transfer(
+        ctx: Context<ProposeTransfer>,
+        id: u64,
+        to: Pubkey,
+        lamports: u64,
+    ) -> Result<()> {
+        handlers::propose_transfer(ctx, id, to, lamports)
+    }
+
+    /// Add the signing approver's approval to a pending transfer.
+    pub fn approve_transfer(ctx: Context<ApproveTransfer>) -> Result<()> {
+        handlers::approve_transfer(ctx)
+    }
+
+    /// Run a pending transfer once approvers currently on the config reach
+    /// the quorum; `from` signs as for any send. Every send condition is
+    /// checked at execution, and the pending transfer is closed to its
+    /// proposer.
+    pub fn execute_transfer(ctx: Context<ExecuteTransfer>) -> Result<()> {
+        handlers::execute_transfer(ctx)
+    }
+
+    /// Drop a pending transfer, returning its rent to the proposer. The
+    /// proposer or the root authority may cancel.
+    pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
+        handlers::cancel_transfer(ctx)
+    }
+}
+
+/// Error types for the program.
+///
+/// Clients see `6000 + discriminant` and match on it, so the numbers are
+/// frozen: a new error takes the next unused number, and no error is ever
+/// renumbered or its number reused (`error_codes_are_stable` holds them).
+#[error_code]
+pub enum ConditionalError {
+    /// Amount provided was below the configured threshold.
+    #[msg("Amount must be at least the configured threshold.")]
+    BelowThreshold = 0,
+    /// Caller attempted an unauthorized update.
+    #[msg("Signer lacks the permission required for this update.")]
+    Unauthorized = 1,
+    /// Matching pool ratio must be non-zero.
+    #[msg("Match ratio must be greater than zero.")]
+    InvalidMatchRatio = 2,
+    /// Arithmetic overflowed.
+    #[msg("Arithmetic overflow.")]
+    MathOverflow = 3,
+    /// Milestone has not been approved yet.
+    #[msg("Milestone has not been approved.")]
+    MilestoneNotApproved = 4,
+    /// Milestone tranche was already released.
+    #[msg("Milestone has already been released.")]
+    MilestoneAlreadyReleased = 5,
+    /// Memo exceeds the maximum length.
+    #[msg("Memo is too long.")]
+    MemoTooLong = 6,
+    /// Milestone must be released before it can be closed.
+    #[msg("Milestone has not been released.")]
+    MilestoneNotReleased = 7,
+    /// Account was active too recently to be force-closed.
+    #[msg("Account has not been inactive long enough to force-close.")]
+    AccountStillActive = 8,
+    /// There were no stray funds to recover.
+    #[msg("Nothing to recover.")]
+    NothingToRecover = 9,
+    /// Force-transfer source is not an escrow account of this config.
+    #[msg("Source is not a program-owned escrow account of this config.")]
+    InvalidForceTransferSource = 10,
+    /// Account does not hold enough spendable lamports.
+    #[msg("Insufficient funds.")]
+    InsufficientFunds = 11,
+    /// Recipient is an executable account and the config does not allow it.
+    #[msg("Recipient is an executable account.")]
+    ExecutableRecipient = 12,
+    /// Amount must be non-zero.
+    #[msg("Amount must be greater than zero.")]
+    InvalidAmount = 13,
+    /// Amount unit cannot be converted on-chain.
+    #[msg("Amount unit is not supported.")]
+    UnsupportedAmountUnit = 14,
+    /// Permission mask contains unknown bits.
+    #[msg("Invalid permission bits.")]
+    InvalidPermissions = 15,
+    /// Authority list is full.
+    #[msg("Too many authorities.")]
+    TooManyAuthorities = 16,
+    /// Joint sends must be top-level instructions.
+    #[msg("Joint send cannot be invoked via CPI.")]
+    JointSendViaCpi = 17,
+    /// Allowance has expired.
+    #[msg("Allowance has expired.")]
+    AllowanceExpired = 18,
+    /// Redemption exceeds the remaining allowance.
+    #[msg("Amount exceeds the remaining allowance.")]
+    AllowanceExceeded = 19,
+    /// Recipient is not permitted by the allowance.
+    #[msg("Recipient is not allowed.")]
+    RecipientNotAllowed = 20,
+    /// CPI allowlist is full.
+    #[msg("Too many CPI programs.")]
+    TooManyCpiPrograms = 21,
+    /// Invoked via CPI from a program not on the config's allowlist, or
+    /// through more than one level of CPI.
+    #[msg("CPI caller is not allowed by this config.")]
+    CpiCallerNotAllowed = 22,
+    /// The current, unfinalized epoch summary must be passed.
+    #[msg("Current epoch summary is required.")]
+    EpochSummaryRequired = 23,
+    /// Epoch has not ended yet.
+    #[msg("Epoch is not over yet.")]
+    EpochNotOver = 24,
+    /// Epoch summary was already finalized.
+    #[msg("Epoch summary is already finalized.")]
+    EpochAlreadyFinalized = 25,
+    /// Payout table entries are empty, too many, or shares do not sum to 10_000 bps.
+    #[msg("Invalid payout table.")]
+    InvalidPayoutTable = 26,
+    /// Remaining accounts do not match the payout table.
+    #[msg("Payout accounts do not match the payout table.")]
+    PayoutAccountsMismatch = 27,
+    /// Account is not a readable vote account.
+    #[msg("Invalid vote account.")]
+    InvalidVoteAccount = 28,
+    /// Proposal targets another config or carries a stale nonce.
+    #[msg("Proposal config or nonce does not match.")]
+    InvalidProposal = 29,
+    /// No ed25519 verification instruction precedes this one.
+    #[msg("Missing ed25519 signature verification instruction.")]
+    MissingSignatureVerification = 30,
+    /// The ed25519 instruction does not verify the authority's signature over the proposal.
+    #[msg("Signature verification does not cover the authority and proposal.")]
+    InvalidSignatureVerification = 31,
+    /// Config has not been attested within `max_config_age_seconds`.
+    #[msg("Config must be re-attested by the authority.")]
+    ConfigStale = 32,
+    /// Submitted deliverable hash does not match the sender's commitment.
+    #[msg("Deliverable hash does not match the commitment.")]
+    DeliveryHashMismatch = 33,
+    /// Delivery has already been acknowledged and released.
+    #[msg("Delivery already acknowledged.")]
+    DeliveryAlreadyAcknowledged = 34,
+    /// Delivery is unacknowledged and its deadline has not passed.
+    #[msg("Delivery is still pending.")]
+    DeliveryPending = 35,
+    /// `to` does not match the configured beneficiary kind, or a required program is missing.
+    #[msg("Recipient does not match the configured beneficiary kind.")]
+    InvalidBeneficiary = 36,
+    /// The program admin has disabled this instruction protocol-wide.
+    #[msg("This feature is currently disabled by the program admin.")]
+    FeatureDisabled = 37,
+    /// v1 instruction called at or after the global sunset slot.
+    #[msg("This instruction version has been sunset; use the v2 instruction.")]
+    DeprecatedInstruction = 38,
+    /// Account is not a program account of the expected type.
+    #[msg("Account does not have the expected owner or discriminator.")]
+    InvalidAccountLayout = 39,
+    /// Bounty expiry is in the past.
+    #[msg("Bounty has expired.")]
+    BountyExpired = 40,
+    /// Bounty is still open and unexpired.
+    #[msg("Bounty is still active.")]
+    BountyActive = 41,
+    /// Sweep reached its threshold but the cold wallet was missing or wrong.
+    #[msg("Sweep destination must be the sweep's cold wallet.")]
+    SweepDestinationMismatch = 42,
+    /// Recipient index already lists `MAX_INDEXED_CONFIGS` configs. No longer
+    /// returned: a full index now skips the listing instead.
+    #[msg("Recipient index is full.")]
+    RecipientIndexFull = 43,
+    /// `recent_slot` is too far from the current slot.
+    #[msg("recent_slot is outside the allowed tolerance of the current slot.")]
+    StaleSlot = 44,
+    /// Config is linked to a policy but that policy account was not passed.
+    #[msg("The config's linked policy account is required.")]
+    PolicyRequired = 45,
+    /// `to` is not the config's recipient (or does not match its hash).
+    #[msg("Recipient does not match the config.")]
+    RecipientMismatch = 46,
+    /// The config's recipient is in plaintext but its index was not passed.
+    #[msg("The recipient's index account is required.")]
+    RecipientIndexRequired = 47,
+    /// An account to be created already has data or a foreign owner.
+    #[msg("Account to create is already in use by another owner.")]
+    UnexpectedAccountOwner = 48,
+    /// An account to be created is not at the address its seeds derive.
+    #[msg("Account address does not match its seeds.")]
+    InvalidAccountSeeds = 49,
+    /// Send was flagged as anomalous and the config requires a co-signed approval.
+    #[msg("Anomalous transfer requires an approver holding PERM_APPROVE_ANOMALY.")]
+    AnomalyApprovalRequired = 50,
+    /// Mint allowlist is full.
+    #[msg("Too many allowed mints.")]
+    TooManyMints = 51,
+    /// Token transfer of a mint missing from the config's allowlist.
+    #[msg("Mint is not allowed for this config.")]
+    MintNotAllowed = 52,
+    /// Committee is empty, too large, or its threshold or delay is out of range.
+    #[msg("Invalid recovery committee, threshold, or delay.")]
+    InvalidRecoveryCommittee = 53,
+    /// Signer is not on the config's recovery committee.
+    #[msg("Signer is not a recovery committee member.")]
+    NotRecoveryMember = 54,
+    /// There is no pending recovery proposal.
+    #[msg("No recovery is pending.")]
+    NoPendingRecovery = 55,
+    /// The pending recovery lacks approvals or its wait has not elapsed.
+    #[msg("Recovery is not yet approved or still in its waiting period.")]
+    RecoveryNotReady = 56,
+    /// Balances after the transfer do not reflect exactly the amount sent.
+    #[msg("Post-transfer balances do not match the amount sent.")]
+    BalanceMismatch = 57,
+    /// The config has no bridge route.
+    #[msg("No bridge route is configured.")]
+    BridgeRouteNotSet = 58,
+    /// Unknown bridge program, Solana or zero target, empty recipient, or a
+    /// bridge account that does not match the route.
+    #[msg("Invalid bridge route.")]
+    InvalidBridgeRoute = 59,
+    /// The config has no token mint and threshold.
+    #[msg("No token threshold is configured.")]
+    TokenThresholdNotSet = 60,
+    /// A token account or mint does not match the config's mint and route.
+    #[msg("Token account does not match the configured mint and route.")]
+    InvalidTokenAccount = 61,
+    /// The config already has `MAX_VIEWER_KEYS` API keys.
+    #[msg("Too many viewer keys.")]
+    ViewerKeysFull = 62,
+    /// The API key hash is not registered for this config.
+    #[msg("Viewer key not found.")]
+    ViewerKeyNotFound = 63,
+    /// Amount is over the config's `max_lamports`.
+    #[msg("Amount is above the configured maximum.")]
+    AboveMaximum = 64,
+    /// Threshold would exceed a non-zero maximum.
+    #[msg("Threshold must not exceed the maximum.")]
+    InvalidLimits = 65,
+    /// No longer returned: `set_test_mocks` is compiled out without the
+    /// `test-mode` feature. Kept so later codes do not shift.
+    #[msg("Test mode is not compiled into this program.")]
+    TestModeDisabled = 66,
+    /// Stream must end after it starts.
+    #[msg("Stream end must be after its start.")]
+    InvalidStreamSchedule = 67,
+    /// Withdrawal exceeds the stream's vested, unwithdrawn balance.
+    #[msg("Amount exceeds the stream's withdrawable balance.")]
+    StreamAmountUnavailable = 68,
+    /// Send is before `valid_from_unix` or after `valid_until_unix`.
+    #[msg("Transfer is outside the configured time window.")]
+    OutsideTimeWindow = 69,
+    /// `valid_from_unix` is after `valid_until_unix`.
+    #[msg("Time window must not end before it starts.")]
+    InvalidTimeWindow = 70,
+    /// Signer of `accept_authority` is not the config's `pending_authority`.
+    #[msg("Signer is not the pending authority.")]
+    NotPendingAuthority = 71,
+    /// `to` is owned by a program missing from the config's `recipient_owners`.
+    #[msg("Recipient is owned by a program the config does not allow.")]
+    RecipientOwnerNotAllowed = 72,
+    /// Recipient owner list is over `MAX_RECIPIENT_OWNERS`.
+    #[msg("Too many allowed recipient owners.")]
+    TooManyRecipientOwners = 73,
+    /// Send would take the current window over `max_per_period`.
+    #[msg("Transfer exceeds the rate limit for the current period.")]
+    RateLimitExceeded = 74,
+    /// Rate limit refills but has no capacity.
+    #[msg("Rate limit must have a non-zero capacity.")]
+    InvalidRateLimit = 75,
+    /// Recipient list is at `MAX_LISTED_RECIPIENTS`.
+    #[msg("Recipient list is full.")]
+    RecipientListFull = 76,
+    /// `remove_recipient` names an address that is not listed.
+    #[msg("Recipient is not in the list.")]
+    RecipientNotListed = 77,
+    /// `verify_payment` found no receipt for the reference under this config.
+    #[msg("No payment recorded for this reference.")]
+    ReferenceNotPaid = 78,
+    /// `verify_payment` found a receipt for less than the requested amount.
+    #[msg("Payment is less than the requested amount.")]
+    PaymentShortfall = 79,
+    /// `close_config` found lamports left in the escrow or vault.
+    #[msg("Escrow or vault still holds lamports.")]
+    ConfigHasFunds = 80,
+    /// A send was passed more than `MAX_PAYMENT_REFERENCES` reference keys.
+    #[msg("Too many payment reference keys.")]
+    TooManyPaymentReferences = 81,
+    /// Split entries are empty, too many, repeat a recipient, or do not sum to 10_000 bps.
+    #[msg("Invalid split table.")]
+    InvalidSplit = 82,
+    /// Remaining accounts do not match the split table.
+    #[msg("Split accounts do not match the split table.")]
+    SplitAccountsMismatch = 83,
+    /// Config has a USD threshold and the send has no price feed to apply it.
+    #[msg("A SOL/USD price feed is required for this config.")]
+    PriceFeedRequired = 84,
+    /// Price feed is not a fully verified Pyth SOL/USD update.
+    #[msg("Invalid SOL/USD price feed.")]
+    InvalidPriceFeed = 85,
+    /// Price was published more than `MAX_PRICE_AGE_SECONDS` ago.
+    #[msg("SOL/USD price is stale.")]
+    StalePrice = 86,
+    /// `to` is not the address derived from the seeded recipient and seed.
+    #[msg("Recipient does not match the seed-derived address.")]
+    SeededAddressMismatch = 87,
+    /// Seed-derived `to` is already a program account.
+    #[msg("Seed-derived recipient is already initialized.")]
+    SeededAddressInitialized = 88,
+    /// Config is paused with `set_paused`.
+    #[msg("Config is paused.")]
+    ProgramPaused = 89,
+    /// Approver list has duplicates, too many members, or a quorum out of range.
+    #[msg("Invalid approvers or quorum.")]
+    InvalidApprovers = 90,
+    /// Config has approvers; sends go through `propose_transfer`.
+    #[msg("Sends from this config need approval; use propose_transfer.")]
+    ApprovalRequired = 91,
+    /// Signer is neither an approver nor, for proposals, `from`.
+    #[msg("Signer is not an approver of this config.")]
+    NotApprover = 92,
+    /// The pending transfer lacks approvals from current approvers.
+    #[msg("Pending transfer has not reached the approval quorum.")]
+    QuorumNotReached = 93,
+    /// Config has a private threshold and the send has no range proof meeting it.
+    #[msg("A range proof that the amount meets the private threshold is required.")]
+    RangeProofRequired = 94,
+    /// Range proof account is not a ZK Token proof `RangeProofU64` context.
+    #[msg("Invalid range proof context account.")]
+    InvalidRangeProof = 95,
+    /// Threshold commitment is not a valid Ristretto point.
+    #[msg("Invalid threshold commitment.")]
+    InvalidCommitment = 96,
+    /// Send owes a fee and its resolved fee collector was not passed.
+    #[msg("The config's fee collector is required.")]
+    FeeCollectorRequired = 97,
+    /// Fee above `MAX_FEE_BPS`, or a fee without a collector.
+    #[msg("Invalid fee.")]
+    InvalidFee = 98,
+    /// Config has a pending close; see `close_config`.
+    #[msg("Config is closing.")]
+    ConfigClosing = 99,
+    /// `close_config` was called again before `CLOSE_COOLING_SECONDS` passed.
+    #[msg("Config close is still cooling down.")]
+    CloseCoolingDown = 100,
+    /// The config has no pending close to cancel or withdraw under.
+    #[msg("No close is pending for this config.")]
+    CloseNotRequested = 101,
+    /// `send_with_nonce` was given a nonce the sender already used, or one
+    /// below its window.
+    #[msg("Nonce already used.")]
+    NonceAlreadyUsed = 102,
+    /// A monotonic config was asked to lower its threshold.
+    #[msg("Threshold may only increase on this config.")]
+    ThresholdDecreaseNotAllowed = 103,
+    /// Amount is over the threshold of an `Lte`, `Lt`, or `Eq` config.
+    #[msg("Amount must not exceed the configured threshold.")]
+    AboveThreshold = 104,
+    /// Comparison mode change would loosen a monotonic threshold, or the
+    /// mode does not make the threshold a floor.
+    #[msg("Comparison mode is not allowed for this config.")]
+    InvalidComparisonMode = 105,
+    /// The send's signer is neither `config.from` nor one of its delegates.
+    #[msg("Signer is not the config's sender or a delegate.")]
+    SenderNotAllowed = 106,
+    /// Config already has `MAX_DELEGATES` delegates.
+    #[msg("Delegate list is full.")]
+    DelegateListFull = 107,
+    /// `remove_delegate` named a key that is not a delegate.
+    #[msg("Delegate not found.")]
+    DelegateNotFound = 108,
+    /// A config failed its end-of-instruction consistency check; see
+    /// `invariants`. Only raised by `invariants` builds.
+    #[msg("Config invariant violated.")]
+    InvariantViolated = 109,
+    /// `schedule_transfer` was given an unlock slot that is not in the future.
+    #[msg("Unlock slot must be in the future.")]
+    InvalidUnlockSlot = 110,
+    /// `execute_scheduled` ran before the transfer's unlock slot.
+    #[msg("Scheduled transfer is still locked.")]
+    ScheduledTransferLocked = 111,
+    /// `cancel_scheduled` ran once the transfer had unlocked.
+    #[msg("Scheduled transfer has unlocked and can no longer be cancelled.")]
+    ScheduledTransferUnlocked = 112,
+    /// `set_auto_adjust` terms with a zero target, unordered bounds, or a
+    /// maximum above `max_lamports`; or a peg on a monotonic config.
+    #[msg("Invalid auto-adjust terms.")]
+    InvalidAutoAdjust = 113,
+    /// `adjust_threshold` on a config without an `auto_adjust` peg.
+    #[msg("Threshold auto-adjust is disabled.")]
+    AutoAdjustDisabled = 114,
+    /// `adjust_threshold` within `interval_seconds` of the last adjustment.
+    #[msg("Threshold was adjusted too recently.")]
+    AdjustmentTooEarly = 115,
+    /// `release` or `execute_scheduled` after the escrow's expiry slot.
+    #[msg("Escrow has expired and can only be refunded.")]
+    EscrowExpired = 116,
+    /// `refund` or `refund_scheduled` before the escrow's expiry slot.
+    #[msg("Escrow has not expired.")]
+    EscrowNotExpired = 117,
+    /// `schedule_transfer` was given an expiry slot not after its unlock slot.
+    #[msg("Expiry slot must be after the unlock slot.")]
+    InvalidExpirySlot = 118,
+    /// An `update_id` below the last one applied; a newer update superseded it.
+    #[msg("Update id is older than the last applied update.")]
+    UpdateIdSuperseded = 119,
+    /// `update_policy` was passed a config that does not link the policy.
+    #[msg("Config is not linked to this policy.")]
+    NotLinkedToPolicy = 120,
+    /// The config was written under an older `CONFIG_VERSION`; run
+    /// `migrate_config` first.
+    #[msg("Config must be migrated to the current version.")]
+    ConfigMigrationRequired = 121,
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::hash::hashv;
+    use anchor_lang::system_program;
+    use anchor_lang::Discriminator;
+    use anchor_spl::token;
+
+    use super::*;
+
+    fn rent() -> Rent {
+        Rent::default()
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        use ConditionalError::*;
+        let codes = [
+            (BelowThreshold, 6000),
+            (Unauthorized, 6001),
+            (InvalidMatchRatio, 6002),
+            (MathOverflow, 6003),
+            (MilestoneNotApproved, 6004),
+            (MilestoneAlreadyReleased, 6005),
+            (MemoTooLong, 6006),
+            (MilestoneNotReleased, 6007),
+            (AccountStillActive, 6008),
+            (NothingToRecover, 6009),
+            (InvalidForceTransferSource, 6010),
+            (InsufficientFunds, 6011),
+            (ExecutableRecipient, 6012),
+            (InvalidAmount, 6013),
+            (UnsupportedAmountUnit, 6014),
+            (InvalidPermissions, 6015),
+            (TooManyAuthorities, 6016),
+            (JointSendViaCpi, 6017),
+            (AllowanceExpired, 6018),
+            (AllowanceExceeded, 6019),
+            (RecipientNotAllowed, 6020),
+            (TooManyCpiPrograms, 6021),
+            (CpiCallerNotAllowed, 6022),
+            (EpochSummaryRequired, 6023),
+            (EpochNotOver, 6024),
+            (EpochAlreadyFinalized, 6025),
+            (InvalidPayoutTable, 6026),
+            (PayoutAccountsMismatch, 6027),
+            (InvalidVoteAccount, 6028),
+            (InvalidProposal, 6029),
+            (MissingSignatureVerification, 6030),
+            (InvalidSignatureVerification, 6031),
+            (ConfigStale, 6032),
+            (DeliveryHashMismatch, 6033),
+            (DeliveryAlreadyAcknowledged, 6034),
+            (DeliveryPending, 6035),
+            (InvalidBeneficiary, 6036),
+            (FeatureDisabled, 6037),
+            (DeprecatedInstruction, 6038),
+            (InvalidAccountLayout, 6039),
+            (BountyExpired, 6040),
+            (BountyActive, 6041),
+            (SweepDestinationMismatch, 6042),
+            (RecipientIndexFull, 6043),
+            (StaleSlot, 6044),
+            (PolicyRequired, 6045),
+            (RecipientMismatch, 6046),
+            (RecipientIndexRequired, 6047),
+            (UnexpectedAccountOwner, 6048),
+            (InvalidAccountSeeds, 6049),
+            (AnomalyApprovalRequired, 6050),
+            (TooManyMints, 6051),
+            (MintNotAllowed, 6052),
+            (InvalidRecoveryCommittee, 6053),
+            (NotRecoveryMember, 6054),
+            (NoPendingRecovery, 6055),
+            (RecoveryNotReady, 6056),
+            (BalanceMismatch, 6057),
+            (BridgeRouteNotSet, 6058),
+            (InvalidBridgeRoute, 6059),
+            (TokenThresholdNotSet, 6060),
+            (InvalidTokenAccount, 6061),
+            (ViewerKeysFull, 6062),
+            (ViewerKeyNotFound, 6063),
+            (AboveMaximum, 6064),
+            (InvalidLimits, 6065),
+            (TestModeDisabled, 6066),
+            (InvalidStreamSchedule, 6067),
+            (StreamAmountUnavailable, 6068),
+            (OutsideTimeWindow, 6069),
+            (InvalidTimeWindow, 6070),
+            (NotPendingAuthority, 6071),
+            (RecipientOwnerNotAllowed, 6072),
+            (TooManyRecipientOwners, 6073),
+            (RateLimitExceeded, 6074),
+            (InvalidRateLimit, 6075),
+            (RecipientListFull, 6076),
+            (RecipientNotListed, 6077),
+            (ReferenceNotPaid, 6078),
+            (PaymentShortfall, 6079),
+            (ConfigHasFunds, 6080),
+            (TooManyPaymentReferences, 6081),
+            (InvalidSplit, 6082),
+            (SplitAccountsMismatch, 6083),
+            (PriceFeedRequired, 6084),
+            (InvalidPriceFeed, 6085),
+            (StalePrice, 6086),
+            (SeededAddressMismatch, 6087),
+            (SeededAddressInitialized, 6088),
+            (ProgramPaused, 6089),
+            (InvalidApprovers, 6090),
+            (ApprovalRequired, 6091),
+            (NotApprover, 6092),
+            (QuorumNotReached, 6093),
+            (RangeProofRequired, 6094),
+            (InvalidRangeProof, 6095),
+            (InvalidCommitment, 6096),
+            (FeeCollectorRequired, 6097),
+            (InvalidFee, 6098),
+            (ConfigClosing, 6099),
+            (CloseCoolingDown, 6100),
+            (CloseNotRequested, 6101),
+            (NonceAlreadyUsed, 6102),
+            (ThresholdDecreaseNotAllowed, 6103),
+            (AboveThreshold, 6104),
+            (InvalidComparisonMode, 6105),
+            (SenderNotAllowed, 6106),
+            (DelegateListFull, 6107),
+            (DelegateNotFound, 6108),
+            (InvariantViolated, 6109),
+            (InvalidUnlockSlot, 6110),
+            (ScheduledTransferLocked, 6111),
+            (ScheduledTransferUnlocked, 6112),
+            (InvalidAutoAdjust, 6113),
+            (AutoAdjustDisabled, 6114),
+            (AdjustmentTooEarly, 6115),
+            (EscrowExpired, 6116),
+            (EscrowNotExpired, 6117),
+            (InvalidExpirySlot, 6118),
+            (UpdateIdSuperseded, 6119),
+            (NotLinkedToPolicy, 6120),
+            (ConfigMigrationRequired, 6121),
+        ];
+        for (error, code) in codes {
+            assert_eq!(u32::from(error), code, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn rent_delta_grow_tops_up_difference() {
+        let rent = rent();
+        let lamports = rent.minimum_balance(100);
+        assert_eq!(
+            rent_delta(&rent, lamports, 100, 200),
+            RentDelta::TopUp(rent.minimum_balance(200) - lamports)
+        );
+    }
+
+    #[test]
+    fn rent_delta_same_size_is_unchanged() {
+        let rent = rent();
+        assert_eq!(
+            rent_delta(&rent, rent.minimum_balance(100), 100, 100),
+            RentDelta::Unchanged
+        );
+    }
+
+    #[test]
+    fn rent_delta_shrink_refunds_freed_rent() {
+        let rent = rent();
+        assert_eq!(
+            rent_delta(&rent, rent.minimum_balance(200), 200, 100),
+            RentDelta::Refund(rent.minimum_balance(200) - rent.minimum_balance(100))
+        );
+    }
+
+    #[test]
+    fn rent_delta_shrink_keeps_escrowed_lamports() {
+        let rent = rent();
+        let escrow = 5_000_000;
+        assert_eq!(
+            rent_delta(&rent, rent.minimum_balance(200) + escrow, 200, 100),
+            RentDelta::Refund(rent.minimum_balance(200) - rent.minimum_balance(100))
+        );
+    }
+
+    #[test]
+    fn rent_delta_grow_does_not_spend_escrowed_lamports() {
+        let rent = rent();
+        let escrow = 5_000_000;
+        assert_eq!(
+            rent_delta(&rent, rent.minimum_balance(100) + escrow, 100, 200),
+            RentDelta::TopUp(rent.minimum_balance(200) - rent.minimum_balance(100))
+        );
+    }
+
+    #[test]
+    fn rent_delta_shrink_underfunded_refunds_only_surplus() {
+        let rent = rent();
+        let lamports = rent.minimum_balance(100) + 10;
+        assert_eq!(rent_delta(&rent, lamports, 200, 100), RentDelta::Refund(10));
+    }
+
+    #[test]
+    fn rent_delta_shrink_underfunded_below_new_minimum_tops_up() {
+        let rent = rent();
+        let lamports = rent.minimum_balance(100) - 10;
+        assert_eq!(rent_delta(&rent, lamports, 200, 100), RentDelta::TopUp(10));
+    }
+
+    #[test]
+    fn rent_delta_shrink_to_zero_refunds_all_rent() {
+        let rent = rent();
+        assert_eq!(
+            rent_delta(&rent, rent.minimum_balance(200), 200, 0),
+            RentDelta::Refund(rent.minimum_balance(200) - rent.minimum_balance(0))
+        );
+    }
+
+    #[test]
+    fn balance_snapshot_accepts_exact_transfer_and_match() {
+        let before = BalanceSnapshot {
+            from: 1_000,
+            to: 50,
+        };
+        let after = BalanceSnapshot { from: 700, to: 400 };
+        assert!(before.require_moved(&after, 300, 350, false).is_ok());
+    }
+
+    #[test]
+    fn balance_snapshot_rejects_unexpected_delta() {
+        let before = BalanceSnapshot {
+            from: 1_000,
+            to: 50,
+        };
+        let after = BalanceSnapshot { from: 700, to: 351 };
+        assert_eq!(
+            before.require_moved(&after, 300, 300, false).unwrap_err(),
+            ConditionalError::BalanceMismatch.into()
+        );
+    }
+
+    #[test]
+    fn balance_snapshot_self_send_nets_out() {
+        let before = BalanceSnapshot {
+            from: 1_000,
+            to: 1_000,
+        };
+        let after = BalanceSnapshot {
+            from: 1_000,
+            to: 1_000,
+        };
+        assert!(before.require_moved(&after, 300, 300, true).is_ok());
+        assert!(before.require_moved(&after, 300, 350, true).is_err());
+    }
+
+    fn route() -> BridgeRoute {
+        BridgeRoute {
+            wormhole_program: WORMHOLE_DEVNET_PROGRAM_ID,
+            chain: 2,
+            recipient: [7; 32],
+        }
+    }
+
+    #[test]
+    fn bridge_route_rejects_unknown_program_and_solana_target() {
+        assert!(route().validate().is_ok());
+        let unknown = BridgeRoute {
+            wormhole_program: Pubkey::new_unique(),
+            ..route()
+        };
+        let solana = BridgeRoute {
+            chain: WORMHOLE_CHAIN_SOLANA,
+            ..route()
+        };
+        let empty = BridgeRoute {
+            recipient: [0; 32],
+            ..route()
+        };
+        for bad in [unknown, solana, empty] {
+            assert_eq!(
+                bad.validate().unwrap_err(),
+                ConditionalError::InvalidBridgeRoute.into()
+            );
+        }
+    }
+
+    #[test]
+    fn wormhole_post_message_wraps_bridge_payload() {
+        let (config, from) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payload = bridge_payload(0x0102, &config, &from, &route());
+        assert_eq!(payload.len(), 107);
+        assert_eq!(
+            payload[..9],
+            [BRIDGE_PAYLOAD_TRANSFER, 0, 0, 0, 0, 0, 0, 1, 2]
+        );
+        assert_eq!(payload[73..75], [0, 2]);
+
+        let data = wormhole_post_message_data(5, &payload);
+        assert_eq!(data[..9], [WORMHOLE_POST_MESSAGE, 5, 0, 0, 0, 107, 0, 0, 0]);
+        assert_eq!(data[9..116], payload[..]);
+        assert_eq!(data[116], WORMHOLE_FINALIZED);
+    }
+
+    #[test]
+    fn evaluate_rejects_amount_above_maximum() {
+        let mut cfg = max_config();
+        cfg.rate_limit = RateLimit::default();
+        cfg.threshold_usd_cents = None;
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        cfg.policy = None;
+        cfg.policy_overrides = PolicyOverrides::default();
+        cfg.max_config_age_seconds = 0;
+        cfg.beneficiary = Beneficiary::Sol;
+        cfg.threshold_lamports = 10;
+        cfg.max_lamports = 20;
+        let (clock, balances) = (
+            Clock::default(),
+            conditions::Balances {
+                from_lamports: 100,
+                recipient_executable: false,
+                recipient_owner: None,
+                sol_usd_price: None,
+                quorum_approved: false,
+                range_proof_commitment: None,
+            },
+        );
+        assert!(conditions::evaluate(&cfg, None, 20, &clock, &balances).is_empty());
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 21, &clock, &balances),
+            vec![conditions::Violation::AboveMaximum {
+                amount: 21,
+                max_lamports: 20,
+            }]
+        );
+        cfg.max_lamports = 0;
+        assert!(conditions::evaluate(&cfg, None, 21, &clock, &balances).is_empty());
+    }
+
+    #[test]
+    fn cloned_config_keeps_settings_and_resets_route_state() {
+        let source = max_config();
+        let (from, route, to) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let cloned = source.cloned_for(from, route, to, 7, 1_234);
+        assert_eq!((cloned.from, cloned.to), (from, to));
+        assert_eq!((cloned.seed_from, cloned.seed_route), (from, route));
+        assert_eq!(
+            (
+                cloned.bump,
+                cloned.event_seq,
+                cloned.proposal_nonce,
+                cloned.last_update_id
+            ),
+            (7, 0, 0, 0)
+        );
+        assert_eq!(cloned.last_attested_ts, 1_234);
+        assert_eq!(cloned.recipient_hash, None);
+        assert_eq!(cloned.bridge_route, None);
+        assert_eq!(cloned.anomaly_guard.samples, 0);
+        assert_eq!(cloned.stats, TransferStats::default());
+        assert_eq!(
+            (cloned.rate_limit.updated_at, cloned.rate_limit.drained),
+            (0, 0)
+        );
+        assert_eq!(cloned.rate_limit.capacity, source.rate_limit.capacity);
+        assert_eq!(
+            cloned.anomaly_guard.multiplier,
+            source.anomaly_guard.multiplier
+        );
+        assert_eq!(cloned.authority, source.authority);
+        assert_eq!(cloned.threshold_lamports, source.threshold_lamports);
+        assert_eq!(cloned.authorities, source.authorities);
+        assert_eq!(cloned.policy, source.policy);
+        assert_eq!(cloned.cpi_allowlist, source.cpi_allowlist);
+        assert_eq!(cloned.valid_until_unix, source.valid_until_unix);
+        assert_eq!(cloned.pending_authority, None);
+    }
+
+    #[test]
+    fn payout_shares_always_sum_to_the_total() {
+        let tables: &[&[u16]] = &[
+            &[10_000],
+            &[5_000, 5_000],
+            &[3_333, 3_333, 3_334],
+            &[1, 9_999],
+            &[9_999, 1],
+            &[1_250; 8],
+            &[100, 200, 300, 400, 9_000],
+            &[1, 1, 1, 1, 1, 1, 1, 9_993],
+        ];
+        let totals = (0..=2_000u64).chain([
+            9_999,
+            10_001,
+            1_000_000_007,
+            u64::MAX / 3,
+            u64::MAX - 1,
+            u64::MAX,
+        ]);
+        for total in totals {
+            for bps in tables {
+                let floors: Vec<u64> = bps
+                    .iter()
+                    .map(|&b| (total as u128 * b as u128 / 10_000) as u64)
+                    .collect();
+                for remainder in [
+                    RemainderPolicy::LastRecipient,
+                    RemainderPolicy::LargestRemainder,
+                    RemainderPolicy::DustAccount(Pubkey::default()),
+                ] {
+                    let (shares, dust) = payout_shares(total, bps, remainder);
+                    let paid: u128 = shares.iter().map(|&s| s as u128).sum();
+                    assert_eq!(paid + dust as u128, total as u128, "{total} {bps:?}");
+                    assert!(dust < bps.len() as u64);
+                    let bumped = shares.iter().zip(&floors).filter(|(s, f)| s != f);
+                    match remainder {
+                        RemainderPolicy::LargestRemainder => {
+                            assert!(bumped.clone().all(|(s, f)| *s == f + 1));
+                        }
+                        RemainderPolicy::DustAccount(_) => assert_eq!(shares, floors),
+                        RemainderPolicy::LastRecipient => {}
+                    }
+                    if remainder != RemainderPolicy::DustAccount(Pubkey::default()) {
+                        assert_eq!(dust, 0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn largest_remainder_goes_to_the_largest_fractions_first() {
+        let entries = [3_333, 3_333, 3_334];
+        // 100 lamports: floors 33, 33, 33; fractions .33, .33, .34.
+        let last = payout_shares(100, &entries, RemainderPolicy::LastRecipient);
+        assert_eq!(last, (vec![33, 33, 34], 0));
+        let largest = payout_shares(100, &entries, RemainderPolicy::LargestRemainder);
+        assert_eq!(largest, (vec![33, 33, 34], 0));
+        // 200 lamports: floors 66, 66, 66; fractions .66, .66, .68.
+        let largest = payout_shares(200, &entries, RemainderPolicy::LargestRemainder);
+        assert_eq!(largest, (vec![67, 66, 67], 0));
+        let last = payout_shares(200, &entries, RemainderPolicy::LastRecipient);
+        assert_eq!(last, (vec![66, 66, 68], 0));
+        let dust = payout_shares(
+            200,
+            &entries,
+            RemainderPolicy::DustAccount(Pubkey::default()),
+        );
+        assert_eq!(dust, (vec![66, 66, 66], 2));
+    }
+
+    #[test]
+    fn split_entries_need_unique_recipients_and_full_shares() {
+        let entry = |recipient, share_bps| SplitEntry {
+            recipient,
+            share_bps,
+        };
+        let mut keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        keys.sort();
+        let [a, b] = keys;
+        assert!(validate_split_entries(&[entry(a, 6_000), entry(b, 4_000)]).is_ok());
+        assert!(validate_split_entries(&[entry(a, 6_000), entry(a, 4_000)]).is_err());
+        // Recipients must be in canonical (ascending) order.
+        assert!(validate_split_entries(&[entry(b, 6_000), entry(a, 4_000)]).is_err());
+        assert!(validate_split_entries(&[entry(a, 6_000), entry(b, 3_999)]).is_err());
+        assert!(validate_split_entries(&[entry(a, 10_000), entry(b, 0)]).is_err());
+        assert!(validate_split_entries(&[]).is_err());
+        let full: Vec<SplitEntry> = (0..=MAX_SPLIT_RECIPIENTS)
+            .map(|_| entry(Pubkey::new_unique(), 1))
+            .collect();
+        assert!(validate_split_entries(&full).is_err());
+    }
+
+    #[test]
+    fn tables_accept_only_canonical_order() {
+        let low = Pubkey::new_from_array([1; 32]);
+        let mut high_bytes = [1; 32];
+        high_bytes[31] = 2;
+        let high = Pubkey::new_from_array(high_bytes);
+        assert!(is_canonical_order([&low, &high]));
+        assert!(!is_canonical_order([&high, &low]));
+        assert!(!is_canonical_order([&low, &low]));
+        assert!(is_canonical_order([&low]));
+
+        let entry = |vote_account, share_bps| PayoutEntry {
+            vote_account,
+            share_bps,
+        };
+        assert!(validate_payout_entries(&[entry(low, 5_000), entry(high, 5_000)]).is_ok());
+        assert!(validate_payout_entries(&[entry(high, 5_000), entry(low, 5_000)]).is_err());
+        assert!(validate_payout_entries(&[entry(low, 5_000), entry(low, 5_000)]).is_err());
+    }
+
+    #[test]
+    fn auto_adjust_pegs_the_threshold_within_bounds() {
+        let terms = AutoAdjust {
+            target_usd_cents: 2_000,
+            min_lamports: 100_000_000,
+            max_lamports: 200_000_000,
+            interval_seconds: 60,
+        };
+        let at = |price| conditions::SolUsdPrice {
+            price,
+            exponent: -8,
+        };
+        // $20 at $150 is 0.1333… SOL, rounded up.
+        assert_eq!(
+            terms.threshold_lamports(&at(15_000_000_000)),
+            Some(133_333_334)
+        );
+        // Past the bounds the threshold holds at them.
+        assert_eq!(
+            terms.threshold_lamports(&at(50_000_000_000)),
+            Some(100_000_000)
+        );
+        assert_eq!(
+            terms.threshold_lamports(&at(5_000_000_000)),
+            Some(200_000_000)
+        );
+        assert_eq!(terms.threshold_lamports(&at(0)), None);
+
+        assert!(terms.is_due(0, 1_700_000_000));
+        assert!(!terms.is_due(1_700_000_000, 1_700_000_059));
+        assert!(terms.is_due(1_700_000_000, 1_700_000_060));
+
+        assert!(terms.is_valid());
+        assert!(!AutoAdjust {
+            target_usd_cents: 0,
+            ..terms
+        }
+        .is_valid());
+        assert!(!AutoAdjust {
+            min_lamports: 300_000_000,
+            ..terms
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn usd_threshold_is_priced_in_lamports_rounding_up() {
+        // $150.00000000 per SOL.
+        let price = conditions::SolUsdPrice {
+            price: 15_000_000_000,
+            exponent: -8,
+        };
+        // $20 = 0.1333… SOL.
+        assert_eq!(price.lamports_for_usd_cents(2_000), Some(133_333_334));
+        assert_eq!(price.lamports_for_usd_cents(15_000), Some(1_000_000_000));
+        let coarse = conditions::SolUsdPrice {
+            price: 150,
+            exponent: 0,
+        };
+        assert_eq!(coarse.lamports_for_usd_cents(2_000), Some(133_333_334));
+        let huge_exponent = conditions::SolUsdPrice {
+            price: 15,
+            exponent: 10,
+        };
+        assert_eq!(huge_exponent.lamports_for_usd_cents(100), Some(1));
+        for price in [0, -1] {
+            let invalid = conditions::SolUsdPrice {
+                price,
+                exponent: -8,
+            };
+            assert_eq!(invalid.lamports_for_usd_cents(2_000), None);
+        }
+        let tiny = conditions::SolUsdPrice {
+            price: 1,
+            exponent: -30,
+        };
+        assert_eq!(tiny.lamports_for_usd_cents(u64::MAX), None);
+
+        let mut cfg = max_config();
+        cfg.threshold_usd_cents = Some(2_000);
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        cfg.threshold_lamports = 0;
+        cfg.policy = None;
+        cfg.policy_overrides = PolicyOverrides::default();
+        cfg.beneficiary = Beneficiary::Sol;
+        cfg.max_lamports = 0;
+        cfg.rate_limit = RateLimit::default();
+        cfg.max_config_age_seconds = 0;
+        cfg.valid_from_unix = None;
+        cfg.valid_until_unix = None;
+        cfg.recipient_owners.clear();
+        let priced = conditions::Balances {
+            from_lamports: 1_000_000_000,
+            sol_usd_price: Some(price),
+            ..Default::default()
+        };
+        let clock = Clock::default();
+        assert!(conditions::evaluate(&cfg, None, 133_333_334, &clock, &priced).is_empty());
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 133_333_333, &clock, &priced),
+            vec![conditions::Violation::BelowThreshold {
+                amount: 133_333_333,
+                threshold_lamports: 133_333_334,
+            }]
+        );
+        let unpriced = conditions::Balances {
+            sol_usd_price: None,
+            ..priced
+        };
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 133_333_334, &clock, &unpriced),
+            vec![conditions::Violation::PriceFeedRequired]
+        );
+    }
+
+    #[test]
+    fn pyth_price_updates_must_be_full_sol_usd() {
+        let update = |verification: u8, feed_id: [u8; 32]| {
+            let mut data = hashv(&[b"account:PriceUpdateV2"]).to_bytes()[..8].to_vec();
+            data.extend_from_slice(&[0; 32]);
+            data.push(verification);
+            data.extend_from_slice(&feed_id);
+            data.extend_from_slice(&15_000_000_000i64.to_le_bytes());
+            data.extend_from_slice(&0u64.to_le_bytes());
+            data.extend_from_slice(&(-8i32).to_le_bytes());
+            data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+            data
+        };
+        let price = conditions::SolUsdPrice {
+            price: 15_000_000_000,
+            exponent: -8,
+        };
+        let full = update(1, pyth::SOL_USD_FEED_ID);
+        assert_eq!(
+            pyth::parse_price_update(&full),
+            Some((price, 1_700_000_000))
+        );
+        assert_eq!(pyth::parse_price_update(&full[..full.len() - 1]), None);
+        // `Partial { num_signatures }` shifts the message by a byte and is rejected.
+        assert_eq!(
+            pyth::parse_price_update(&update(0, pyth::SOL_USD_FEED_ID)),
+            None
+        );
+        assert_eq!(pyth::parse_price_update(&update(1, [1; 32])), None);
+        let mut wrong_account = full.clone();
+        wrong_account[0] ^= 1;
+        assert_eq!(pyth::parse_price_update(&wrong_account), None);
+    }
+
+    #[test]
+    fn private_thresholds_need_a_range_proof_bound_to_the_amount() {
+        use solana_zk_token_sdk::encryption::pedersen::PedersenOpening;
+        use solana_zk_token_sdk::instruction::{ProofType, ZkProofData};
+        use solana_zk_token_sdk::zk_token_proof_state::ProofContextState;
+
+        let opening = PedersenOpening::new_rand();
+        let commitment = private_threshold::commit_threshold(1_000, &opening);
+        assert!(private_threshold::is_valid_commitment(&commitment));
+        assert!(!private_threshold::is_valid_commitment(&[u8::MAX; 32]));
+        assert!(private_threshold::threshold_range_proof(999, 1_000, &opening).is_none());
+        let proof = private_threshold::threshold_range_proof(1_500, 1_000, &opening).unwrap();
+        proof.verify_proof().unwrap();
+        let context = ProofContextState::encode(
+            &Pubkey::new_unique(),
+            ProofType::RangeProofU64,
+            proof.context_data(),
+        );
+        let proven = private_threshold::parse_range_proof_context(&context).unwrap();
+        let mut other_proof = context.clone();
+        other_proof[32] = ProofType::BatchedRangeProofU64 as u8;
+        assert_eq!(
+            private_threshold::parse_range_proof_context(&other_proof),
+            None
+        );
+
+        let mut cfg = max_config();
+        cfg.threshold_usd_cents = None;
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = Some(commitment);
+        cfg.close_requested_at = None;
+        cfg.threshold_lamports = 0;
+        cfg.policy = None;
+        cfg.policy_overrides = PolicyOverrides::default();
+        cfg.beneficiary = Beneficiary::Sol;
+        cfg.max_lamports = 0;
+        cfg.rate_limit = RateLimit::default();
+        cfg.max_config_age_seconds = 0;
+        cfg.valid_from_unix = None;
+        cfg.valid_until_unix = None;
+        cfg.recipient_owners.clear();
+        let balances = |range_proof_commitment| conditions::Balances {
+            from_lamports: 10_000,
+            range_proof_commitment,
+            ..Default::default()
+        };
+        let clock = Clock::default();
+        let unproven = vec![conditions::Violation::RangeProofRequired];
+        assert!(
+            conditions::evaluate(&cfg, None, 1_500, &clock, &balances(Some(proven))).is_empty()
+        );
+        // The proof covers `amount − threshold`, so it fails for any other amount.
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 1_499, &clock, &balances(Some(proven))),
+            unproven
+        );
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 1_500, &clock, &balances(None)),
+            unproven
+        );
+    }
+
+    #[test]
+    fn receipt_payer_offset_matches_layout() {
+        let payer = Pubkey::new_unique();
+        let receipt = Receipt {
+            config: Pubkey::new_unique(),
+            reference: [7; 32],
+            payer,
+            from: Pubkey::new_unique(),
+            to: Pubkey::new_unique(),
+            lamports: 1,
+            slot: 2,
+            timestamp: 3,
+            bump: 255,
+            from_balance: 4,
+            to_balance: 5,
+        };
+        let mut data = Vec::new();
+        receipt.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + Receipt::INIT_SPACE);
+        assert_eq!(
+            data[RECEIPT_PAYER_OFFSET..RECEIPT_PAYER_OFFSET + 32],
+            payer.to_bytes()
+        );
+    }
+
+    #[test]
+    fn seeded_recipients_derive_with_seed() {
+        let recipient = SeededRecipient {
+            base: Pubkey::new_unique(),
+            owner: system_program::ID,
+        };
+        assert_eq!(
+            recipient.address("user-42"),
+            Some(
+                Pubkey::create_with_seed(&recipient.base, "user-42", &system_program::ID).unwrap()
+            )
+        );
+        assert_ne!(recipient.address("user-42"), recipient.address("user-43"));
+        let max_seed = "s".repeat(anchor_lang::solana_program::pubkey::MAX_SEED_LEN);
+        assert!(recipient.address(&max_seed).is_some());
+        assert_eq!(recipient.address(&format!("{max_seed}s")), None);
+    }
+
+    #[test]
+    fn paused_configs_reject_every_send() {
+        let mut cfg = max_config();
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        cfg.policy = None;
+        cfg.policy_overrides = PolicyOverrides::default();
+        cfg.beneficiary = Beneficiary::Sol;
+        cfg.threshold_lamports = 10;
+        cfg.threshold_usd_cents = None;
+        cfg.max_lamports = 0;
+        cfg.rate_limit = RateLimit::default();
+        cfg.max_config_age_seconds = 0;
+        cfg.valid_from_unix = None;
+        cfg.valid_until_unix = None;
+        cfg.recipient_owners.clear();
+        cfg.mint_allowlist.clear();
+        let balances = conditions::Balances {
+            from_lamports: 1_000,
+            ..Default::default()
+        };
+        let clock = Clock::default();
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 100, &clock, &balances),
+            vec![conditions::Violation::Paused]
+        );
+        assert_eq!(
+            conditions::evaluate_tokens(&cfg, u64::MAX, &clock, u64::MAX),
+            vec![conditions::Violation::Paused]
+        );
+        assert_eq!(
+            u32::from(conditions::Violation::Paused.error()),
+            u32::from(ConditionalError::ProgramPaused)
+        );
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        assert!(conditions::evaluate(&cfg, None, 100, &clock, &balances).is_empty());
+
+        cfg.close_requested_at = Some(0);
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 100, &clock, &balances),
+            vec![conditions::Violation::ClosePending]
+        );
+        assert_eq!(
+            u32::from(conditions::Violation::ClosePending.error()),
+            u32::from(ConditionalError::ConfigClosing)
+        );
+    }
+
+    #[test]
+    fn approvers_need_distinct_members_and_a_reachable_quorum() {
+        let [a, b, c] = [(); 3].map(|_| Pubkey::new_unique());
+        let approvers = |members: Vec<Pubkey>, quorum| Approvers { members, quorum };
+        assert!(Approvers::default().is_valid());
+        assert!(approvers(vec![a, b, c], 2).is_valid());
+        assert!(approvers(vec![a], 1).is_valid());
+        assert!(!approvers(vec![a, b], 0).is_valid());
+        assert!(!approvers(vec![a, b], 3).is_valid());
+        assert!(!approvers(vec![a, a], 1).is_valid());
+        assert!(!approvers(Vec::new(), 1).is_valid());
+        let six = (0..=MAX_APPROVERS).map(|_| Pubkey::new_unique()).collect();
+        assert!(!approvers(six, 1).is_valid());
+
+        let two_of_three = approvers(vec![a, b, c], 2);
+        assert!(!two_of_three.approved(&[a]));
+        assert!(two_of_three.approved(&[a, c]));
+        // Approvals from removed members no longer count.
+        assert!(!two_of_three.approved(&[a, Pubkey::new_unique()]));
+        assert!(Approvers::default().approved(&[]));
+
+        let mut cfg = max_config();
+        cfg.paused = false;
+        cfg.approvers = two_of_three;
+        let violations = |quorum_approved| {
+            let balances = conditions::Balances {
+                quorum_approved,
+                ..Default::default()
+            };
+            conditions::evaluate(&cfg, None, 0, &Clock::default(), &balances)
+        };
+        assert!(violations(false).contains(&conditions::Violation::ApprovalRequired));
+        assert!(!violations(true).contains(&conditions::Violation::ApprovalRequired));
+    }
+
+    #[test]
+    fn update_ids_apply_once_and_in_order() {
+        let mut cfg = max_config();
+        cfg.last_update_id = 0;
+        assert!(cfg.claim_update_id(5).unwrap());
+        assert!(!cfg.claim_update_id(5).unwrap(), "a retry is a no-op");
+        assert_eq!(
+            cfg.claim_update_id(4).unwrap_err(),
+            ConditionalError::UpdateIdSuperseded.into(),
+            "an older update cannot land late"
+        );
+        assert!(cfg.claim_update_id(6).unwrap());
+        assert!(cfg.claim_update_id(0).unwrap() && cfg.claim_update_id(0).unwrap());
+        assert_eq!(cfg.last_update_id, 6);
+    }
+
+    #[test]
+    fn rate_limit_bucket_allows_bursts_and_refills_over_time() {
+        let mut limit = RateLimit {
+            refill_per_second: 10,
+            capacity: 1_000,
+            ..RateLimit::default()
+        };
+        // A full bucket takes a burst of its whole capacity at once.
+        assert!(limit.allows(1_000, 5_000));
+        assert!(!limit.allows(1_001, 5_000));
+        limit.record(600, 5_000);
+        assert_eq!((limit.updated_at, limit.remaining(5_000)), (5_000, 400));
+        limit.record(400, 5_000);
+        assert!(!limit.allows(1, 5_000));
+        // It refills at 10 lamports a second, never past capacity.
+        assert_eq!(limit.remaining(5_001), 10);
+        assert_eq!(limit.remaining(5_030), 300);
+        assert_eq!(limit.remaining(5_100), 1_000);
+        assert_eq!(limit.remaining(9_000), 1_000);
+        // Sustained outflow is held to the refill rate.
+        for second in 5_001..5_011 {
+            assert!(limit.allows(10, second) && !limit.allows(11, second));
+            limit.record(10, second);
+        }
+        assert_eq!((limit.updated_at, limit.drained), (5_010, 1_000));
+        // A clock running backwards refills nothing.
+        assert_eq!(limit.remaining(4_000), 0);
+        limit.record(0, 4_000);
+        assert_eq!(limit.updated_at, 5_010);
+        // Settling before a rate change keeps the refill earned so far.
+        limit.settle(5_040);
+        limit.refill_per_second = 1;
+        assert_eq!(limit.remaining(5_050), 310);
+        limit.refill_per_second = 10;
+        limit.record(300, 5_050);
+        assert_eq!(limit.remaining(5_050), 100);
+
+        let mut cfg = max_config();
+        cfg.policy = None;
+        cfg.policy_overrides = PolicyOverrides::default();
+        cfg.max_config_age_seconds = 0;
+        cfg.beneficiary = Beneficiary::Sol;
+        cfg.threshold_lamports = 10;
+        cfg.max_lamports = 0;
+        cfg.valid_from_unix = None;
+        cfg.valid_until_unix = None;
+        cfg.rate_limit = limit;
+        cfg.threshold_usd_cents = None;
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        let clock = Clock {
+            unix_timestamp: 5_070,
+            ..Clock::default()
+        };
+        let balances = conditions::Balances {
+            from_lamports: 1_000,
+            recipient_executable: false,
+            recipient_owner: None,
+            sol_usd_price: None,
+            quorum_approved: false,
+            range_proof_commitment: None,
+        };
+        assert!(conditions::evaluate(&cfg, None, 300, &clock, &balances).is_empty());
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 301, &clock, &balances),
+            vec![conditions::Violation::RateLimitExceeded {
+                amount: 301,
+                remaining: 300,
+            }]
+        );
+        cfg.rate_limit.refill_per_second = 0;
+        assert!(!limit.allows(301, 5_070));
+        assert!(conditions::evaluate(&cfg, None, 1_000, &clock, &balances).is_empty());
+    }
+
+    #[test]
+    fn transfer_stats_accumulate_and_saturate() {
+        let mut stats = TransferStats::default();
+        stats.record(100, 7);
+        stats.record(250, 9);
+        assert_eq!(
+            stats,
+            TransferStats {
+                total_transferred: 350,
+                transfer_count: 2,
+                last_transfer_slot: 9,
+            }
+        );
+        stats.record(u64::MAX, 10);
+        assert_eq!(stats.total_transferred, u64::MAX);
+        assert_eq!(stats.transfer_count, 3);
+    }
+
+    #[test]
+    fn authority_changes_only_when_the_pending_authority_accepts() {
+        let mut cfg = max_config();
+        let (root, proposed) = (cfg.authority, Pubkey::new_unique());
+        cfg.pending_authority = None;
+        assert!(cfg.accept_authority(&proposed).is_err());
+        cfg.pending_authority = Some(proposed);
+        assert!(cfg.accept_authority(&Pubkey::new_unique()).is_err());
+        assert_eq!(cfg.authority, root);
+        cfg.accept_authority(&proposed).unwrap();
+        assert_eq!(cfg.authority, proposed);
+        assert_eq!(cfg.pending_authority, None);
+    }
+
+    #[test]
+    fn test_mocks_shift_clock_and_price_usd_cents() {
+        let mocks = test_mode::TestMocks {
+            clock_offset_seconds: 3_600,
+            lamports_per_usd_cent: 60_000,
+            bump: 255,
+            sol_usd_cents: 15_000,
+        };
+        let clock = Clock {
+            unix_timestamp: 1_000,
+            ..Clock::default()
+        };
+        assert_eq!(mocks.apply(clock).unix_timestamp, 4_600);
+        let cents = TypedAmount {
+            unit: AmountUnit::UsdCents,
+            value: 250,
+        };
+        assert_eq!(mocks.to_lamports(&cents).unwrap(), 15_000_000);
+        let unpriced = test_mode::TestMocks {
+            lamports_per_usd_cent: 0,
+            ..mocks
+        };
+        assert_eq!(
+            unpriced.to_lamports(&cents).unwrap_err(),
+            ConditionalError::UnsupportedAmountUnit.into()
+        );
+        let price = mocks.sol_usd_price().unwrap();
+        assert_eq!(price.lamports_for_usd_cents(2_000), Some(133_333_334));
+        let unpriced = test_mode::TestMocks {
+            sol_usd_cents: 0,
+            ..mocks
+        };
+        assert_eq!(unpriced.sol_usd_price(), None);
+    }
+
+    #[test]
+    fn stream_vests_linearly_between_start_and_end() {
+        let stream = Stream {
+            config: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            index: 0,
+            total: 1_000,
+            withdrawn: 250,
+            start_ts: 100,
+            end_ts: 200,
+            bump: 255,
+        };
+        assert_eq!(stream.vested(100), 0);
+        assert_eq!(stream.vested(133), 330);
+        assert_eq!(stream.vested(200), 1_000);
+        assert_eq!(stream.vested(i64::MAX), 1_000);
+        assert_eq!(stream.withdrawable(110), 0);
+        assert_eq!(stream.withdrawable(150), 250);
+        assert_eq!(stream.withdrawable(250), 750);
+    }
+
+    #[test]
+    fn evaluate_rejects_sends_outside_time_window() {
+        let mut cfg = max_config();
+        cfg.rate_limit = RateLimit::default();
+        cfg.threshold_usd_cents = None;
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        cfg.policy = None;
+        cfg.policy_overrides = PolicyOverrides::default();
+        cfg.max_config_age_seconds = 0;
+        cfg.beneficiary = Beneficiary::Sol;
+        cfg.threshold_lamports = 10;
+        cfg.valid_from_unix = Some(1_000);
+        cfg.valid_until_unix = Some(2_000);
+        let balances = conditions::Balances {
+            from_lamports: 100,
+            recipient_executable: false,
+            recipient_owner: None,
+            sol_usd_price: None,
+            quorum_approved: false,
+            range_proof_commitment: None,
+        };
+        let at = |unix_timestamp| Clock {
+            unix_timestamp,
+            ..Clock::default()
+        };
+        for now in [1_000, 2_000] {
+            assert!(conditions::evaluate(&cfg, None, 10, &at(now), &balances).is_empty());
+        }
+        for now in [999, 2_001] {
+            assert_eq!(
+                conditions::evaluate(&cfg, None, 10, &at(now), &balances),
+                vec![conditions::Violation::OutsideTimeWindow { now }]
+            );
+        }
+        cfg.valid_until_unix = None;
+        assert!(conditions::evaluate(&cfg, None, 10, &at(i64::MAX), &balances).is_empty());
+    }
+
+    #[test]
+    fn evaluate_rejects_recipients_owned_by_unlisted_programs() {
+        let mut cfg = max_config();
+        cfg.rate_limit = RateLimit::default();
+        cfg.threshold_usd_cents = None;
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        cfg.policy = None;
+        cfg.policy_overrides = PolicyOverrides::default();
+        cfg.max_config_age_seconds = 0;
+        cfg.beneficiary = Beneficiary::Sol;
+        cfg.threshold_lamports = 10;
+        cfg.valid_from_unix = None;
+        cfg.valid_until_unix = None;
+        cfg.recipient_owners = vec![system_program::ID];
+        let clock = Clock::default();
+        let owned_by = |owner| conditions::Balances {
+            from_lamports: 100,
+            recipient_executable: false,
+            recipient_owner: Some(owner),
+            sol_usd_price: None,
+            quorum_approved: false,
+            range_proof_commitment: None,
+        };
+        let balances = owned_by(system_program::ID);
+        assert!(conditions::evaluate(&cfg, None, 10, &clock, &balances).is_empty());
+        let balances = owned_by(token::ID);
+        assert_eq!(
+            conditions::evaluate(&cfg, None, 10, &clock, &balances),
+            vec![conditions::Violation::RecipientOwnerNotAllowed { owner: token::ID }]
+        );
+        cfg.recipient_owners.clear();
+        assert!(conditions::evaluate(&cfg, None, 10, &clock, &balances).is_empty());
+    }
+
+    #[test]
+    fn evaluate_tokens_checks_threshold_allowlist_and_balance() {
+        let mut cfg = max_config();
+        cfg.paused = false;
+        cfg.approvers = Approvers::default();
+        cfg.private_threshold = None;
+        cfg.close_requested_at = None;
+        cfg.max_config_age_seconds = 0;
+        cfg.mint_allowlist.clear();
+        let mint = Pubkey::new_unique();
+        let clock = Clock::default();
+        cfg.token_threshold = None;
+        assert_eq!(
+            conditions::evaluate_tokens(&cfg, 10, &clock, 10),
+            vec![conditions::Violation::TokenThresholdNotSet]
+        );
+
+        cfg.token_threshold = Some(TokenThreshold {
+            mint,
+            threshold_amount: 5,
+        });
+        assert!(conditions::evaluate_tokens(&cfg, 5, &clock, 5).is_empty());
+        assert_eq!(
+            conditions::evaluate_tokens(&cfg, 4, &clock, 3),
+            vec![
+                conditions::Violation::TokenBelowThreshold {
+                    amount: 4,
+                    threshold_amount: 5,
+                },
+                conditions::Violation::InsufficientFunds {
+                    available: 3,
+                    required: 4,
+                },
+            ]
+        );
+
+        cfg.mint_allowlist = vec![Pubkey::new_unique()];
+        assert_eq!(
+            conditions::evaluate_tokens(&cfg, 5, &clock, 5),
+            vec![conditions::Violation::MintNotAllowed]
+        );
+    }
+
+    const CONFIG_SPACE: usize = 8 + Config::INIT_SPACE;
+
+    /// A config with every `Vec` and `String` at its `max_len` and every
+    /// `Option` set: the largest value `Config` can serialize to.
+    fn max_config() -> Config {
+        let key = Pubkey::new_unique;
+        Config {
+            authority: key(),
+            from: key(),
+            to: key(),
+            threshold_lamports: u64::MAX,
+            bump: 255,
+            event_seq: u64::MAX,
+            default_memo: "m".repeat(MAX_MEMO_LEN),
+            allow_executable_recipient: true,
+            authorities: (0..MAX_AUTHORITIES)
+                .map(|_| AuthorityEntry {
+                    key: key(),
+                    permissions: PERM_ALL,
+                })
+                .collect(),
+            below_threshold_behavior: BelowThresholdBehavior::Noop,
+            restrict_cpi: true,
+            cpi_allowlist: (0..MAX_CPI_PROGRAMS).map(|_| key()).collect(),
+            epoch_reporting: true,
+            proposal_nonce: u64::MAX,
+            max_config_age_seconds: u64::MAX,
+            last_attested_ts: i64::MAX,
+            beneficiary: Beneficiary::AssociatedTokenAccount,
+            policy: Some(key()),
+            policy_overrides: PolicyOverrides {
+                threshold_lamports: Some(u64::MAX),
+                below_threshold_behavior: Some(BelowThresholdBehavior::Noop),
+            },
+            recipient_hash: Some([0xff; 32]),
+            large_transfer_lamports: u64::MAX,
+            anomaly_guard: AnomalyGuard {
+                multiplier: u16::MAX,
+                require_approval: true,
+                average_lamports: u64::MAX,
+                samples: u32::MAX,
+            },
+            mint_allowlist: (0..MAX_ALLOWED_MINTS).map(|_| key()).collect(),
+            bridge_route: Some(BridgeRoute {
+                wormhole_program: WORMHOLE_MAINNET_PROGRAM_ID,
+                chain: u16::MAX,
+                recipient: [0xff; 32],
+            }),
+            seed_from: key(),
+            seed_route: key(),
+            token_threshold: Some(TokenThreshold {
+                mint: key(),
+                threshold_amount: u64::MAX,
+            }),
+            max_lamports: u64::MAX,
+            valid_from_unix: Some(i64::MIN),
+            valid_until_unix: Some(i64::MAX),
+            pending_authority: Some(key()),
+            recipient_owners: (0..MAX_RECIPIENT_OWNERS).map(|_| key()).collect(),
+            stats: TransferStats {
+                total_transferred: u64::MAX,
+                transfer_count: u64::MAX,
+                last_transfer_slot: u64::MAX,
+            },
+            rate_limit: RateLimit {
+                refill_per_second: u32::MAX,
+                capacity: u64::MAX,
+                updated_at: i64::MAX,
+                drained: u64::MAX,
+            },
+            last_update_id: u64::MAX,
+            threshold_usd_cents: Some(u64::MAX),
+            seeded_recipient: Some(SeededRecipient {
+                base: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+            }),
+            paused: true,
+            approvers: Approvers {
+                members: (0..MAX_APPROVERS).map(|_| Pubkey::new_unique()).collect(),
+                quorum: u8::MAX,
+            },
+            private_threshold: Some([u8::MAX; 32]),
+            fee_bps: MAX_FEE_BPS,
+            fee_collector: key(),
+            close_requested_at: Some(i64::MAX),
+            monotonic_threshold: true,
+            comparison_mode: ComparisonMode::Gte,
+            log_rejections: true,
+            last_auto_adjust_unix: i64::MAX,
+            escrow_expiry_slot: Some(u64::MAX),
+            version: CONFIG_VERSION,
+            reserved: [0; state::layout::RESERVED_LEN - 64],
+            delegates: (0..MAX_DELEGATES).map(|_| key()).collect(),
+            auto_adjust: Some(AutoAdjust {
+                target_usd_cents: u64::MAX,
+                min_lamports: u64::MAX,
+                max_lamports: u64::MAX,
+                interval_seconds: u32::MAX,
+            }),
+        }
+    }
+
+    #[test]
+    fn config_upgrade_turns_the_fixed_window_into_a_bucket() {
+        let mut cfg = max_config();
+        // Version 3 stored `period_seconds` where `refill_per_second` is now.
+        cfg.version = 3;
+        cfg.rate_limit = RateLimit {
+            refill_per_second: 86_400,
+            capacity: 864_000_000,
+            updated_at: 1_000,
+            drained: 500,
+        };
+        cfg.upgrade().unwrap();
+        assert_eq!(
+            cfg.rate_limit,
+            RateLimit {
+                refill_per_second: 10_000,
+                capacity: 864_000_000,
+                updated_at: 1_000,
+                drained: 500,
+            }
+        );
+        // Caps below one lamport a second round up to it; disabled stays so.
+        cfg.version = 3;
+        cfg.rate_limit.refill_per_second = 3_600;
+        cfg.rate_limit.capacity = 60;
+        cfg.upgrade().unwrap();
+        assert_eq!(cfg.rate_limit.refill_per_second, 1);
+        cfg.version = 3;
+        cfg.rate_limit.refill_per_second = 0;
+        cfg.upgrade().unwrap();
+        assert_eq!(cfg.rate_limit.refill_per_second, 0);
+        // Current configs are left alone.
+        cfg.rate_limit.refill_per_second = 7;
+        cfg.upgrade().unwrap();
+        assert_eq!(cfg.rate_limit.refill_per_second, 7);
+    }
+
+    #[test]
+    fn config_upgrade_walks_to_the_current_version() {
+        let mut cfg = max_config();
+        cfg.version = 0;
+        cfg.upgrade().unwrap();
+        assert_eq!(cfg.version, CONFIG_VERSION);
+        cfg.upgrade().unwrap();
+        assert_eq!(cfg.version, CONFIG_VERSION);
+        cfg.version = CONFIG_VERSION + 1;
+        assert!(cfg.upgrade().is_err());
+        let cloned = Config {
+            version: 0,
+            ..max_config()
+        }
+        .cloned_for(Pubkey::new_unique(), Pubkey::new_unique(), 255, 0);
+        assert_eq!(cloned.version, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn versioned_decode_reads_delegates_where_version_8_wrote_them() {
+        let delegates: Vec<Pubkey> = (0..MAX_DELEGATES).map(|_| Pubkey::new_unique()).collect();
+        let v8 = Config {
+            version: 8,
+            last_auto_adjust_unix: 0,
+            escrow_expiry_slot: None,
+            delegates: Vec::new(),
+            auto_adjust: None,
+            ..max_config()
+        };
+        // Version 8 ended its reserve after `log_rejections`, with 17 zero
+        // bytes for the fields carved since, then wrote `delegates`. Here the
+        // current layout spends 9 of them on `last_auto_adjust_unix` and
+        // `escrow_expiry_slot`, then an empty `delegates` and `None` take 5.
+        let current = v8.try_to_vec().unwrap();
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&current[..current.len() - 5]);
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&delegates.try_to_vec().unwrap());
+        // Stale bytes, as a longer authority list once left.
+        data.extend_from_slice(&[0xab; 64]);
+
+        let mut cfg = Config::try_deserialize_versioned(&data).unwrap();
+        assert_eq!(cfg.version, 8);
+        assert_eq!(cfg.delegates, delegates);
+        assert_eq!(cfg.authorities, v8.authorities);
+        cfg.upgrade().unwrap();
+        let mut migrated = Vec::new();
+        cfg.try_serialize(&mut migrated).unwrap();
+        let decoded = Config::try_deserialize_versioned(&migrated).unwrap();
+        assert_eq!(decoded.version, CONFIG_VERSION);
+        assert_eq!(decoded.delegates, delegates);
+
+        // Configs from before `delegates` have none, whatever follows them.
+        let v6 = Config {
+            version: 6,
+            log_rejections: false,
+            ..v8
+        };
+        let current = v6.try_to_vec().unwrap();
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&current[..current.len() - 5]);
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&[0xab; 64]);
+        let cfg = Config::try_deserialize_versioned(&data).unwrap();
+        assert!(cfg.delegates.is_empty());
+    }
+
+    #[test]
+    fn versioned_decode_reads_auto_adjust_where_version_9_wrote_it() {
+        let delegates: Vec<Pubkey> = (0..MAX_DELEGATES).map(|_| Pubkey::new_unique()).collect();
+        let auto_adjust = Some(AutoAdjust {
+            target_usd_cents: 2_000,
+            min_lamports: 1,
+            max_lamports: 2,
+            interval_seconds: 60,
+        });
+        let v9 = Config {
+            version: 9,
+            escrow_expiry_slot: None,
+            delegates: Vec::new(),
+            auto_adjust: None,
+            ..max_config()
+        };
+        // Version 9 left 9 zero bytes of reserve for `escrow_expiry_slot`,
+        // which the current layout reads as one byte of `None`.
+        let current = v9.try_to_vec().unwrap();
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&current[..current.len() - 5]);
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&delegates.try_to_vec().unwrap());
+        data.extend_from_slice(&auto_adjust.try_to_vec().unwrap());
+        data.extend_from_slice(&[0xab; 64]);
+
+        let mut cfg = Config::try_deserialize_versioned(&data).unwrap();
+        assert_eq!(cfg.delegates, delegates);
+        assert_eq!(cfg.auto_adjust, auto_adjust);
+        assert_eq!(cfg.escrow_expiry_slot, None);
+        cfg.upgrade().unwrap();
+        assert_eq!(cfg.version, CONFIG_VERSION);
+        assert_eq!(cfg.auto_adjust, auto_adjust);
+    }
+
+    #[test]
+    fn fees_round_down_and_never_exceed_the_cap() {
+        let mut cfg = max_config();
+        assert_eq!(cfg.fee_for(1_000_000), 100_000);
+        assert_eq!(cfg.fee_for(9), 0);
+        assert_eq!(cfg.fee_for(u64::MAX), u64::MAX / 10);
+        cfg.fee_bps = 25;
+        assert_eq!(cfg.fee_for(1_000_000), 2_500);
+        assert_eq!(cfg.fee_for(399), 0);
+        cfg.fee_bps = 0;
+        assert_eq!(cfg.fee_for(u64::MAX), 0);
+    }
+
+    #[test]
+    fn monotonic_thresholds_only_rise() {
+        let mut cfg = max_config();
+        cfg.threshold_lamports = 1_000;
+        assert!(cfg.allows_threshold(1_000));
+        assert!(cfg.allows_threshold(1_001));
+        assert!(!cfg.allows_threshold(999));
+        assert!(!cfg.allows_threshold(0));
+        cfg.monotonic_threshold = false;
+        assert!(cfg.allows_threshold(0));
+    }
+
+    #[test]
+    fn monotonic_floor_bounds_resolved_threshold() {
+        let mut cfg = max_config();
+        cfg.threshold_lamports = 1_000;
+        let address = Pubkey::new_unique();
+        let policy = Policy {
+            authority: Pubkey::new_unique(),
+            id: 0,
+            threshold_lamports: 10,
+            below_threshold_behavior: BelowThresholdBehavior::Fail,
+            bump: 255,
+            last_update_id: 0,
+            limits: PolicyLimits::default(),
+        };
+        cfg.policy = Some(address);
+        cfg.policy_overrides = PolicyOverrides::default();
+        let threshold = |cfg: &Config| {
+            cfg.resolve_terms(Some((address, &policy)))
+                .unwrap()
+                .threshold_lamports
+        };
+        assert_eq!(threshold(&cfg), 1_000);
+        cfg.policy_overrides.threshold_lamports = Some(500);
+        assert_eq!(threshold(&cfg), 1_000);
+        cfg.policy_overrides.threshold_lamports = Some(2_000);
+        assert_eq!(threshold(&cfg), 2_000);
+        cfg.monotonic_threshold = false;
+        cfg.policy_overrides.threshold_lamports = None;
+        assert_eq!(threshold(&cfg), 10);
+    }
+
+    #[test]
+    fn policy_limits_replace_the_configs_own() {
+        let cfg = Config {
+            policy: Some(Pubkey::new_unique()),
+            ..max_config()
+        };
+        let mut policy = Policy {
+            authority: Pubkey::new_unique(),
+            id: 0,
+            threshold_lamports: 10,
+            below_threshold_behavior: BelowThresholdBehavior::Fail,
+            bump: 255,
+            last_update_id: 0,
+            limits: PolicyLimits::default(),
+        };
+        let terms = |policy: &Policy| {
+            cfg.resolve_terms(Some((cfg.policy.unwrap(), policy)))
+                .unwrap()
+        };
+        let own = cfg.own_terms();
+        let unset = terms(&policy);
+        assert_eq!(unset.max_lamports, own.max_lamports);
+        assert_eq!(unset.valid_from_unix, own.valid_from_unix);
+        assert_eq!(unset.valid_until_unix, own.valid_until_unix);
+        assert_eq!(
+            (unset.fee_bps, unset.fee_collector),
+            (own.fee_bps, own.fee_collector)
+        );
+
+        let collector = Pubkey::new_unique();
+        policy.limits = PolicyLimits {
+            max_lamports: 1_000,
+            valid_from_unix: None,
+            valid_until_unix: Some(50),
+            fee_bps: 100,
+            fee_collector: collector,
+        };
+        let set = terms(&policy);
+        assert_eq!(set.max_lamports, 1_000);
+        // One bound replaces the whole window, so the config's start is gone.
+        assert_eq!(
+            (set.valid_from_unix, set.valid_until_unix),
+            (None, Some(50))
+        );
+        assert!(!set.within_time_window(51));
+        assert_eq!((set.fee_bps, set.fee_collector), (100, collector));
+        assert_eq!(set.fee_for(10_000), 100);
+
+        assert!(policy.limits.validate(1_000).is_ok());
+        assert!(policy.limits.validate(1_001).is_err());
+        policy.limits.fee_collector = Pubkey::default();
+        assert!(policy.limits.validate(10).is_err());
+    }
+
+    #[test]
+    fn invariants_flag_inconsistent_configs() {
+        // `max_config` sizes its approvers and peg, it does not make them
+        // valid or compatible.
+        let consistent = || Config {
+            approvers: Approvers::default(),
+            auto_adjust: None,
+            ..max_config()
+        };
+        let cfg = consistent();
+        assert_eq!(invariants::broken_invariant(&cfg), None);
+        let broken = |change: fn(&mut Config)| {
+            let mut cfg = consistent();
+            change(&mut cfg);
+            invariants::broken_invariant(&cfg)
+        };
+        assert_eq!(
+            broken(|cfg| cfg.max_lamports = cfg.threshold_lamports - 1),
+            Some("max_lamports below threshold_lamports")
+        );
+        assert_eq!(
+            broken(|cfg| cfg.fee_collector = Pubkey::default()),
+            Some("fee without a fee_collector")
+        );
+        assert_eq!(
+            broken(|cfg| cfg.comparison_mode = ComparisonMode::Lt),
+            Some("monotonic threshold without a floor comparison mode")
+        );
+        assert_eq!(
+            broken(|cfg| {
+                cfg.monotonic_threshold = false;
+                cfg.auto_adjust = Some(AutoAdjust {
+                    target_usd_cents: 2_000,
+                    min_lamports: 2,
+                    max_lamports: 1,
+                    interval_seconds: 0,
+                });
+            }),
+            Some("auto_adjust terms invalid")
+        );
+        assert_eq!(
+            broken(|cfg| {
+                cfg.auto_adjust = Some(AutoAdjust {
+                    target_usd_cents: 2_000,
+                    min_lamports: 0,
+                    max_lamports: 1,
+                    interval_seconds: 0,
+                });
+            }),
+            Some("monotonic threshold with an auto_adjust peg")
+        );
+        assert_eq!(
+            broken(|cfg| cfg.version = CONFIG_VERSION + 1),
+            Some("version newer than CONFIG_VERSION")
+        );
+
+        let (address, bump) = Pubkey::find_program_address(
+            &[CONFIG_SEED, cfg.seed_from.as_ref(), cfg.seed_route.as_ref()],
+            &ID,
+        );
+        let cfg = Config { bump, ..cfg };
+        assert!(invariants::assert_invariants(&cfg, &address).is_ok());
+        assert_eq!(
+            invariants::assert_invariants(&cfg, &Pubkey::new_unique()).unwrap_err(),
+            ConditionalError::InvariantViolated.into()
+        );
+    }
+
+    #[test]
+    fn rejections_report_the_runtime_error_code() {
+        assert_eq!(error_code(&ConditionalError::BelowThreshold.into()), 6000);
+        assert_eq!(error_code(&ConditionalError::AboveThreshold.into()), 6104);
+        assert_eq!(
+            error_code(&ProgramError::InsufficientFunds.into()),
+            u64::from(ProgramError::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn delegates_send_alongside_from() {
+        let mut cfg = max_config();
+        let delegate = cfg.delegates[0];
+        assert!(cfg.is_sender(&cfg.from));
+        assert!(cfg.is_sender(&delegate));
+        assert!(!cfg.is_sender(&Pubkey::new_unique()));
+        cfg.delegates.clear();
+        assert!(!cfg.is_sender(&delegate));
+        let cloned = max_config().cloned_for(cfg.from, cfg.to, 255, 0);
+        assert!(cloned.delegates.is_empty());
+    }
+
+    #[test]
+    fn comparison_modes_bound_the_amount_from_either_side() {
+        let mut terms = PolicyTerms {
+            threshold_lamports: 1_000,
+            below_threshold_behavior: BelowThresholdBehavior::Fail,
+            comparison_mode: ComparisonMode::Gte,
+            ..max_config().own_terms()
+        };
+        let below = |amount| {
+            Some(conditions::Violation::BelowThreshold {
+                amount,
+                threshold_lamports: 1_000,
+            })
+        };
+        let above = |amount| {
+            Some(conditions::Violation::AboveThreshold {
+                amount,
+                threshold_lamports: 1_000,
+            })
+        };
+        let cases = [
+            (ComparisonMode::Gte, [below(999), None, None]),
+            (ComparisonMode::Gt, [below(999), below(1_000), None]),
+            (ComparisonMode::Eq, [below(999), None, above(1_001)]),
+            (ComparisonMode::Lte, [None, None, above(1_001)]),
+            (ComparisonMode::Lt, [None, above(1_000), above(1_001)]),
+        ];
+        for (mode, expected) in cases {
+            terms.comparison_mode = mode;
+            for (amount, violation) in [999, 1_000, 1_001].into_iter().zip(expected) {
+                assert_eq!(
+                    conditions::threshold_violation(&terms, amount),
+                    violation,
+                    "{mode:?} {amount}"
+                );
+                assert_eq!(terms.admits(amount), violation.is_none());
+            }
+        }
+        assert!(ComparisonMode::Gt.is_floor());
+        assert!(!ComparisonMode::Lte.is_floor());
+    }
+
+    #[test]
+    fn reserved_layouts_keep_their_size() {
+        // Carving a field from `reserved` must leave these unchanged; see
+        // `state::layout`.
+        assert_eq!(8 + Config::INIT_SPACE, 1_672);
+        assert_eq!(8 + PendingTransfer::INIT_SPACE, 357);
+        assert_eq!(8 + SendNonces::INIT_SPACE, 177);
+        assert_eq!(8 + ScheduledTransfer::INIT_SPACE, 161);
+    }
+
+    #[test]
+    fn scheduled_transfers_unlock_at_their_slot() {
+        let scheduled = ScheduledTransfer {
+            config: Pubkey::new_unique(),
+            id: 7,
+            payer: Pubkey::new_unique(),
+            lamports: 1_000,
+            unlock_slot: 500,
+            bump: 255,
+            expiry_slot: None,
+            reserved: [0; state::layout::RESERVED_LEN - 9],
+        };
+        assert!(!scheduled.is_unlocked(0));
+        assert!(!scheduled.is_unlocked(499));
+        assert!(scheduled.is_unlocked(500));
+        assert!(scheduled.is_unlocked(u64::MAX));
+        assert!(!scheduled.is_expired(u64::MAX));
+        let expiring = ScheduledTransfer {
+            expiry_slot: Some(600),
+            ..scheduled
+        };
+        assert!(!expiring.is_expired(599));
+        assert!(expiring.is_expired(600));
+    }
+
+    #[test]
+    fn config_view_fits_in_return_data() {
+        let cfg = max_config();
+        let len = cfg.view(cfg.own_terms()).try_to_vec().unwrap().len();
+        assert!(len <= anchor_lang::solana_program::program::MAX_RETURN_DATA);
+    }
+
+    #[test]
+    fn escrow_expires_at_its_slot() {
+        let mut cfg = max_config();
+        cfg.escrow_expiry_slot = None;
+        assert!(!cfg.escrow_expired(u64::MAX));
+        cfg.escrow_expiry_slot = Some(1_000);
+        assert!(!cfg.escrow_expired(999));
+        assert!(cfg.escrow_expired(1_000));
+    }
+
+    #[test]
+    fn send_nonces_reject_repeats_and_slide_forward() {
+        let mut nonces = SendNonces {
+            config: Pubkey::new_unique(),
+            sender: Pubkey::new_unique(),
+            base: 0,
+            used: [0; NONCE_WINDOW as usize / 64],
+            bump: 255,
+            reserved: [0; state::layout::RESERVED_LEN],
+        };
+        assert!(nonces.claim(0) && !nonces.claim(0));
+        // Any order within the window.
+        assert!(nonces.claim(200) && nonces.claim(64) && nonces.claim(63));
+        assert!(!nonces.claim(200) && !nonces.claim(64));
+        assert_eq!(nonces.base, 0);
+        // Past the window it slides up, keeping the nonces still in range.
+        assert!(nonces.claim(300));
+        assert_eq!(nonces.base, 300 - (NONCE_WINDOW - 1));
+        assert!(!nonces.claim(300) && !nonces.claim(200));
+        assert!(nonces.claim(199));
+        // Kept nonces stay used; those below the window count as used.
+        assert!(!nonces.claim(63) && !nonces.claim(0) && !nonces.claim(44));
+        assert!(nonces.claim(45));
+        // A far jump clears the window.
+        assert!(nonces.claim(u64::MAX));
+        assert_eq!(nonces.base, u64::MAX - (NONCE_WINDOW - 1));
+        assert_eq!(nonces.used, [0, 0, 0, 1 << 63]);
+        assert!(!nonces.claim(u64::MAX) && nonces.claim(u64::MAX - 1));
+    }
+
+    #[test]
+    fn config_space_matches_max_serialized_size() {
+        let len = max_config().try_to_vec().unwrap().len();
+        assert_eq!(
+            CONFIG_SPACE,
+            Config::DISCRIMINATOR.len() + len,
+            "Config::INIT_SPACE is out of sync with its Borsh layout; check the #[max_len] of new fields"
+        );
+    }
+
+    #[test]
+    fn config_version_stays_first_in_the_reserve() {
+        // `version` was the first field carved from `reserved`; later fields
+        // go after it so older configs still decode their version.
+        let bytes = max_config().try_to_vec().unwrap();
+        let appended_space = 4 + 32 * MAX_DELEGATES + 1 + AutoAdjust::INIT_SPACE;
+        assert_eq!(
+            bytes[Config::INIT_SPACE - appended_space - state::layout::RESERVED_LEN],
+            CONFIG_VERSION
+        );
+    }
+
+    #[test]
+    fn config_at_max_size_round_trips() {
+        let cfg = max_config();
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        cfg.serialize(&mut data).unwrap();
+        assert_eq!(data.len(), CONFIG_SPACE);
+        let decoded = Config::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.try_to_vec().unwrap(), cfg.try_to_vec().unwrap());
+    }
+
+    const INDEX_SPACE: usize = 8 + RecipientIndex::INIT_SPACE;
+
+    #[test]
+    fn full_recipient_index_skips_new_configs() {
+        let recipient = Pubkey::new_unique();
+        let mut index = RecipientIndex {
+            recipient,
+            configs: Vec::new(),
+            bump: 255,
+        };
+        let listed: Vec<Pubkey> = (0..MAX_INDEXED_CONFIGS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        for config in &listed {
+            assert!(index.add(recipient, *config, 255));
+        }
+        assert!(!index.add(recipient, Pubkey::new_unique(), 255));
+        assert!(index.add(recipient, listed[0], 255));
+        assert_eq!(index.configs, listed);
+        index.remove(&listed[0]);
+        assert!(index.add(recipient, Pubkey::new_unique(), 255));
+    }
+
+    #[test]
+    fn plan_creation_untouched_address_creates_account() {
+        let rent = rent();
+        assert_eq!(
+            plan_account_creation(&rent, &system_program::ID, 0, 0, INDEX_SPACE).unwrap(),
+            AccountCreation::Create {
+                lamports: rent.minimum_balance(INDEX_SPACE)
+            }
+        );
+    }
+
+    #[test]
+    fn plan_creation_prefunded_address_tops_up_then_allocates() {
+        let rent = rent();
+        assert_eq!(
+            plan_account_creation(&rent, &system_program::ID, 1_000, 0, INDEX_SPACE).unwrap(),
+            AccountCreation::AllocateAndAssign {
+                top_up: rent.minimum_balance(INDEX_SPACE) - 1_000
+            }
+        );
+    }
+
+    #[test]
+    fn plan_creation_overfunded_address_allocates_without_top_up() {
+        let rent = rent();
+        let lamports = rent.minimum_balance(INDEX_SPACE) + 1;
+        assert_eq!(
+            plan_account_creation(&rent, &system_program::ID, lamports, 0, INDEX_SPACE).unwrap(),
+            AccountCreation::AllocateAndAssign { top_up: 0 }
+        );
+    }
+
+    #[test]
+    fn plan_creation_existing_program_account_is_reused() {
+        let rent = rent();
+        assert_eq!(
+            plan_account_creation(&rent, &crate::ID, 1, INDEX_SPACE, INDEX_SPACE).unwrap(),
+            AccountCreation::Exists
+        );
+    }
+
+    #[test]
+    fn plan_creation_rejects_foreign_owner() {
+        assert_eq!(
+            plan_account_creation(&rent(), &token::ID, 1, 0, INDEX_SPACE).unwrap_err(),
+            ConditionalError::UnexpectedAccountOwner.into()
+        );
+    }
+
+    #[test]
+    fn plan_creation_rejects_system_account_with_data() {
+        assert_eq!(
+            plan_account_creation(&rent(), &system_program::ID, 1, 16, INDEX_SPACE).unwrap_err(),
+            ConditionalError::UnexpectedAccountOwner.into()
+        );
+    }
+
+    #[test]
+    fn create_program_account_rejects_address_off_seeds() {
+        let recipient = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let (wrong_address, _) =
+            Pubkey::find_program_address(&[RECIPIENT_INDEX_SEED, other.as_ref()], &crate::ID);
+        let payer_key = Pubkey::new_unique();
+        let system_id = system_program::ID;
+        let (mut account_lamports, mut payer_lamports, mut program_lamports) =
+            (0, 1_000_000_000, 1);
+        let (mut account_data, mut payer_data, mut program_data) = ([0u8; 0], [0u8; 0], [0u8; 0]);
+        let account = AccountInfo::new(
+            &wrong_address,
+            false,
+            true,
+            &mut account_lamports,
+            &mut account_data,
+            &system_id,
+            false,
+            0,
+        );
+        let payer_info = AccountInfo::new(
+            &payer_key,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system_id,
+            false,
+            0,
+        );
+        let program_info = AccountInfo::new(
+            &system_id,
+            false,
+            false,
+            &mut program_lamports,
+            &mut program_data,
+            &system_id,
+            true,
+            0,
+        );
+        let payer = Signer::try_from(&payer_info).unwrap();
+        let program = Program::<System>::try_from(&program_info).unwrap();
+        assert_eq!(
+            create_program_account(
+                &account,
+                &[RECIPIENT_INDEX_SEED, recipient.as_ref()],
+                INDEX_SPACE,
+                &payer,
+                &program,
+            )
+            .unwrap_err(),
+            ConditionalError::InvalidAccountSeeds.into()
+        );
+    }
+
+    #[test]
+    fn compute_budget_hints_name_real_instructions() {
+        use compute_budget::{recommended_compute_budget, COMPUTE_BUDGET_HINTS};
+
+        let discriminators: Vec<[u8; 8]> = vec![
+            instruction::Initialize::DISCRIMINATOR,
+            instruction::CloneConfig::DISCRIMINATOR,
+            instruction::SendIfOverThreshold::DISCRIMINATOR,
+            instruction::SendIfOverThresholdV2::DISCRIMINATOR,
+            instruction::TrySend::DISCRIMINATOR,
+            instruction::SendWithReceipt::DISCRIMINATOR,
+            instruction::SendWithNonce::DISCRIMINATOR,
+            instruction::SendTokensIfOverThreshold::DISCRIMINATOR,
+            instruction::SendBridged::DISCRIMINATOR,
+            instruction::SendJointIfOverThreshold::DISCRIMINATOR,
+            instruction::SendValidatorPayout::DISCRIMINATOR,
+            instruction::SendSplitIfOverThreshold::DISCRIMINATOR,
+            instruction::SendToSeededAddress::DISCRIMINATOR,
+            instruction::ExecuteTransfer::DISCRIMINATOR,
+            instruction::Release::DISCRIMINATOR,
+            instruction::FundBountyTarget::DISCRIMINATOR,
+            instruction::ReleaseMilestone::DISCRIMINATOR,
+            instruction::ExecuteScheduled::DISCRIMINATOR,
+            instruction::RedeemAllowance::DISCRIMINATOR,
+            instruction::OpenEpochSummary::DISCRIMINATOR,
+            instruction::ApplySignedProposal::DISCRIMINATOR,
+            instruction::UpdateAddresses::DISCRIMINATOR,
+            instruction::ExecuteRecovery::DISCRIMINATOR,
+        ];
+        assert_eq!(COMPUTE_BUDGET_HINTS.len(), discriminators.len());
+        for (hint, discriminator) in COMPUTE_BUDGET_HINTS.iter().zip(discriminators) {
+            let preimage = format!("global:{}", hint.instruction);
+            assert_eq!(
+                hashv(&[preimage.as_bytes()]).to_bytes()[..8],
+                discriminator,
+                "{}",
+                hint.instruction
+            );
+            assert_eq!(recommended_compute_budget(hint.instruction), *hint);
+        }
+
+        let fallback = recommended_compute_budget("update_threshold");
+        assert_eq!(
+            fallback.compute_units,
+            compute_budget::DEFAULT_COMPUTE_UNITS
+        );
+        assert_eq!(
+            recommended_compute_budget("send_joint_if_over_threshold").compute_unit_limit(100),
+            compute_budget::MAX_COMPUTE_UNIT_LIMIT
+        );
+    }
 }
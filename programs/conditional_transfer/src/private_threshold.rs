@@ -0,0 +1,94 @@
+//! Experimental private thresholds, for configs with a `private_threshold`.
+//!
+//! The config stores a Pedersen commitment `C = t·G + r·H` to its threshold
+//! `t` rather than the value. A send of `amount` passes a proof context
+//! account of the ZK Token proof program holding a verified `RangeProofU64`
+//! for the commitment `amount·G − C`, which commits to `amount − t` under the
+//! opening `−r`. The proof shows that value lies in `[0, 2^64)`, so
+//! `amount ≥ t`, without revealing `t`; only holders of `r` can produce it.
+//!
+//! The ZK Token proof program is the predecessor of the ZK ElGamal proof
+//! program; it is the one the 1.18 toolchain builds against. Context accounts
+//! have the layout `[context_state_authority: 32][proof_type: 1][commitment: 32]`.
+
+use anchor_lang::prelude::*;
+use solana_zk_token_sdk::curve25519::ristretto::{
+    multiply_ristretto, subtract_ristretto, validate_ristretto, PodRistrettoPoint,
+};
+use solana_zk_token_sdk::curve25519::scalar::PodScalar;
+use solana_zk_token_sdk::instruction::ProofType;
+
+use crate::ConditionalError;
+
+/// ZK Token proof program, the owner of proof context accounts.
+pub const ZK_PROOF_PROGRAM_ID: Pubkey = solana_zk_token_sdk::zk_token_proof_program::ID;
+
+/// Compressed Ristretto basepoint `G`, the value generator of the commitment.
+const RISTRETTO_BASEPOINT: [u8; 32] = [
+    0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71, 0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00, 0x51, 0x5f,
+    0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d, 0xb6, 0xa6, 0x59, 0x45, 0xe0, 0x8d, 0x2d, 0x76,
+];
+/// Offset of the proof type, after the context state authority.
+const PROOF_TYPE_OFFSET: usize = 32;
+
+/// The commitment `account` proves to hold a 64-bit value, if it is a
+/// `RangeProofU64` context account of the ZK Token proof program.
+pub fn range_proof_commitment(account: &AccountInfo) -> Result<[u8; 32]> {
+    require_keys_eq!(
+        *account.owner,
+        ZK_PROOF_PROGRAM_ID,
+        ConditionalError::InvalidRangeProof
+    );
+    let data = account.try_borrow_data()?;
+    parse_range_proof_context(&data).ok_or_else(|| ConditionalError::InvalidRangeProof.into())
+}
+
+/// The commitment in `RangeProofU64` proof context data.
+pub fn parse_range_proof_context(data: &[u8]) -> Option<[u8; 32]> {
+    if *data.get(PROOF_TYPE_OFFSET)? != ProofType::RangeProofU64 as u8 {
+        return None;
+    }
+    data.get(PROOF_TYPE_OFFSET + 1..PROOF_TYPE_OFFSET + 33)?
+        .try_into()
+        .ok()
+}
+
+/// Whether `commitment` is a valid compressed Ristretto point.
+pub fn is_valid_commitment(commitment: &[u8; 32]) -> bool {
+    validate_ristretto(&PodRistrettoPoint(*commitment))
+}
+
+/// Whether a range proof for `proven` shows `amount` meets the threshold in
+/// `commitment`, i.e. `proven == amount·G − commitment`.
+pub fn proves_threshold(commitment: &[u8; 32], amount: u64, proven: &[u8; 32]) -> bool {
+    let mut scalar = [0u8; 32];
+    scalar[..8].copy_from_slice(&amount.to_le_bytes());
+    multiply_ristretto(&PodScalar(scalar), &PodRistrettoPoint(RISTRETTO_BASEPOINT))
+        .and_then(|value| subtract_ristretto(&value, &PodRistrettoPoint(*commitment)))
+        .is_some_and(|difference| difference.0 == *proven)
+}
+
+/// The commitment to store for `threshold` under `opening`.
+#[cfg(not(target_os = "solana"))]
+pub fn commit_threshold(
+    threshold: u64,
+    opening: &solana_zk_token_sdk::encryption::pedersen::PedersenOpening,
+) -> [u8; 32] {
+    solana_zk_token_sdk::encryption::pedersen::Pedersen::with(threshold, opening).to_bytes()
+}
+
+/// Proof data for a `VerifyRangeProofU64` showing `amount` meets the
+/// threshold committed with [`commit_threshold`]; `None` when it does not.
+#[cfg(not(target_os = "solana"))]
+pub fn threshold_range_proof(
+    amount: u64,
+    threshold: u64,
+    opening: &solana_zk_token_sdk::encryption::pedersen::PedersenOpening,
+) -> Option<solana_zk_token_sdk::instruction::RangeProofU64Data> {
+    use solana_zk_token_sdk::encryption::pedersen::{Pedersen, PedersenOpening};
+
+    let value = amount.checked_sub(threshold)?;
+    let opening = PedersenOpening::new(-opening.get_scalar());
+    let commitment = Pedersen::with(value, &opening);
+    solana_zk_token_sdk::instruction::RangeProofU64Data::new(&commitment, value, &opening).ok()
+}
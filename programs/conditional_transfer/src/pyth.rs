@@ -0,0 +1,68 @@
+//! SOL/USD prices from Pyth, for configs with a `threshold_usd_cents`.
+//!
+//! The price comes from a Pyth Solana Receiver `PriceUpdateV2` account, read
+//! by hand rather than through the Pyth SDK:
+//! `[discriminator: 8][write_authority: 32][verification_level: 1 or 2]`
+//! followed by the price message
+//! `[feed_id: 32][price: i64][conf: u64][exponent: i32][publish_time: i64]..`.
+//! Only fully verified updates of the SOL/USD feed published within
+//! `MAX_PRICE_AGE_SECONDS` are accepted.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::conditions::SolUsdPrice;
+use crate::ConditionalError;
+
+/// Pyth Solana Receiver, the owner of `PriceUpdateV2` accounts.
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Pyth feed id of SOL/USD.
+pub const SOL_USD_FEED_ID: [u8; 32] = [
+    0xef, 0x0d, 0x8b, 0x6f, 0xda, 0x2c, 0xeb, 0xa4, 0x1d, 0xa1, 0x5d, 0x40, 0x95, 0xd1, 0xda, 0x39,
+    0x2a, 0x0d, 0x2f, 0x8e, 0xd0, 0xc6, 0xc7, 0xbc, 0x0f, 0x4c, 0xfa, 0xc8, 0xc2, 0x80, 0xb5, 0x6d,
+];
+
+/// Oldest price, in seconds before the current clock, a send may use.
+#[constant]
+pub const MAX_PRICE_AGE_SECONDS: i64 = 60;
+
+/// `VerificationLevel::Full`; `Partial` (0) carries a signature count.
+const VERIFICATION_FULL: u8 = 1;
+/// Offset of the verification level, after the discriminator and write authority.
+const VERIFICATION_OFFSET: usize = 40;
+/// Length of the price message fields read here.
+const MESSAGE_LEN: usize = 32 + 8 + 8 + 4 + 8;
+
+/// The SOL/USD price in `account`, if it is a fresh, fully verified update.
+pub fn sol_usd_price(account: &AccountInfo, now: i64) -> Result<SolUsdPrice> {
+    require_keys_eq!(
+        *account.owner,
+        PYTH_RECEIVER_PROGRAM_ID,
+        ConditionalError::InvalidPriceFeed
+    );
+    let data = account.try_borrow_data()?;
+    let (price, publish_time) =
+        parse_price_update(&data).ok_or(ConditionalError::InvalidPriceFeed)?;
+    require!(
+        now.saturating_sub(publish_time) <= MAX_PRICE_AGE_SECONDS,
+        ConditionalError::StalePrice
+    );
+    Ok(price)
+}
+
+/// The price and publish time of a fully verified SOL/USD `PriceUpdateV2`.
+pub fn parse_price_update(data: &[u8]) -> Option<(SolUsdPrice, i64)> {
+    let discriminator = &hashv(&[b"account:PriceUpdateV2"]).to_bytes()[..8];
+    if data.get(..8)? != discriminator || *data.get(VERIFICATION_OFFSET)? != VERIFICATION_FULL {
+        return None;
+    }
+    let message = data.get(VERIFICATION_OFFSET + 1..VERIFICATION_OFFSET + 1 + MESSAGE_LEN)?;
+    if message[..32] != SOL_USD_FEED_ID {
+        return None;
+    }
+    let price = i64::from_le_bytes(message[32..40].try_into().ok()?);
+    let exponent = i32::from_le_bytes(message[48..52].try_into().ok()?);
+    let publish_time = i64::from_le_bytes(message[52..60].try_into().ok()?);
+    Some((SolUsdPrice { price, exponent }, publish_time))
+}
@@ -0,0 +1,1579 @@
+//! Account and state types, for programs embedding this crate.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+use crate::*;
+
+pub mod layout;
+
+/// On-chain config for the program.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub authority: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub threshold_lamports: u64,
+    pub bump: u8,
+    /// Sequence number of the last event emitted under this config. Every event
+    /// carries the next value, so gaps reveal missed events.
+    pub event_seq: u64,
+    /// Reference string attached to every transfer under this config.
+    #[max_len(MAX_MEMO_LEN)]
+    pub default_memo: String,
+    /// Permit `to` to be an executable account. Off by default, since lamports
+    /// sent to a program are almost always unrecoverable.
+    pub allow_executable_recipient: bool,
+    /// Additional authorities and the permissions each holds.
+    #[max_len(MAX_AUTHORITIES)]
+    pub authorities: Vec<AuthorityEntry>,
+    /// What `send_if_over_threshold` does with a below-threshold amount.
+    pub below_threshold_behavior: BelowThresholdBehavior,
+    /// When set, CPI invocations of the send instructions must come directly
+    /// from a top-level program in `cpi_allowlist`.
+    pub restrict_cpi: bool,
+    #[max_len(MAX_CPI_PROGRAMS)]
+    pub cpi_allowlist: Vec<Pubkey>,
+    /// When set, sends must pass the current epoch's `EpochSummary`.
+    pub epoch_reporting: bool,
+    /// Nonce the next `ConfigProposal` must carry; bumped on each apply.
+    pub proposal_nonce: u64,
+    /// Sends revert once this long has passed since `last_attested_ts`; `0` disables.
+    pub max_config_age_seconds: u64,
+    /// Last time the root authority attested the config.
+    pub last_attested_ts: i64,
+    /// What kind of account `to` is, which decides how sends are delivered.
+    pub beneficiary: Beneficiary,
+    /// Shared `Policy` whose terms replace this config's own, if linked.
+    pub policy: Option<Pubkey>,
+    /// Per-config values that take precedence over the resolved base terms.
+    pub policy_overrides: PolicyOverrides,
+    /// When set, `to` is zeroed and the recipient is whoever hashes to this
+    /// under `recipient_hash`.
+    pub recipient_hash: Option<[u8; 32]>,
+    /// Sends of at least this many lamports also emit `LargeTransferExecuted`;
+    /// `0` disables.
+    pub large_transfer_lamports: u64,
+    /// Rolling send-size statistics and the rule for flagging outliers.
+    pub anomaly_guard: AnomalyGuard,
+    /// Mints the config may move; empty allows every mint.
+    #[max_len(MAX_ALLOWED_MINTS)]
+    pub mint_allowlist: Vec<Pubkey>,
+    /// Foreign recipient for `send_bridged`; unset disables bridged payouts.
+    pub bridge_route: Option<BridgeRoute>,
+    /// The `from` and `route` the PDA was derived from at `initialize`, as
+    /// `[CONFIG_SEED, seed_from, seed_route]`. They never change, so the address
+    /// stays put when `update_addresses` changes `from` or `to`.
+    pub seed_from: Pubkey,
+    pub seed_route: Pubkey,
+    /// Mint and threshold for `send_tokens_if_over_threshold`; unset disables it.
+    pub token_threshold: Option<TokenThreshold>,
+    /// Sends above this many lamports fail with `AboveMaximum`; `0` disables.
+    pub max_lamports: u64,
+    /// Sends before this unix time fail with `OutsideTimeWindow`; unset means
+    /// no lower bound.
+    pub valid_from_unix: Option<i64>,
+    /// Sends after this unix time fail with `OutsideTimeWindow`; unset means
+    /// no upper bound.
+    pub valid_until_unix: Option<i64>,
+    /// Authority proposed by `propose_authority`, awaiting its
+    /// `accept_authority`.
+    pub pending_authority: Option<Pubkey>,
+    /// Programs `to` may be owned by (e.g. the System Program, or the Token
+    /// program for `Beneficiary::TokenAccount`); empty allows any owner.
+    #[max_len(MAX_RECIPIENT_OWNERS)]
+    pub recipient_owners: Vec<Pubkey>,
+    /// Lifetime transfer totals; cleared by `reset_stats`.
+    pub stats: TransferStats,
+    /// Token bucket limiting outflow; see `update_rate_limit`.
+    pub rate_limit: RateLimit,
+    /// Highest `update_id` applied by a config setter; see
+    /// `Config::claim_update_id`.
+    pub last_update_id: u64,
+    /// Minimum send in US cents, priced by Pyth SOL/USD at execution time, on
+    /// top of `threshold_lamports`; see `set_usd_threshold`.
+    pub threshold_usd_cents: Option<u64>,
+    /// Base and owner of the seed-derived addresses `send_to_seeded_address`
+    /// may pay; see `set_seeded_recipient`.
+    pub seeded_recipient: Option<SeededRecipient>,
+    /// Emergency stop set by `set_paused`: sends fail with `ProgramPaused`.
+    pub paused: bool,
+    /// Keys that must approve sends, and how many; see `set_approvers`.
+    pub approvers: Approvers,
+    /// Pedersen commitment to a hidden threshold sends must prove they meet;
+    /// see `set_private_threshold`.
+    pub private_threshold: Option<[u8; 32]>,
+    /// `CONFIG_VERSION` the account was last written under; `0` for configs
+    /// created before versioning that have not been migrated. Every
+    /// instruction but `migrate_config` rejects older versions with
+    /// `ConfigMigrationRequired`, since they decode in the wrong layout.
+    pub version: u8,
+    /// Share of each `SendIfOverThreshold` send routed to `fee_collector`, in
+    /// basis points; `0` charges nothing. See `set_fee`.
+    pub fee_bps: u16,
+    /// Receives the fee; unused while `fee_bps` is `0`.
+    pub fee_collector: Pubkey,
+    /// When `close_config` was first called on a funded vault; sends fail
+    /// with `ConfigClosing` until the close completes or is cancelled.
+    pub close_requested_at: Option<i64>,
+    /// Set by `enable_monotonic_threshold`: `threshold_lamports` may only rise.
+    pub monotonic_threshold: bool,
+    /// How sends are compared with the threshold; see `update_comparison_mode`.
+    pub comparison_mode: ComparisonMode,
+    /// Log rejected sends as `TransferRejected`; see `set_log_rejections`.
+    pub log_rejections: bool,
+    /// When `adjust_threshold` last re-pegged the threshold; `0` if it has
+    /// not run since `set_auto_adjust`.
+    pub last_auto_adjust_unix: i64,
+    /// From this slot `release` fails with `EscrowExpired` and `refund`
+    /// returns the escrow to `from`; see `set_escrow_expiry`.
+    pub escrow_expiry_slot: Option<u64>,
+    /// Space for future fields; see `state::layout`.
+    pub reserved: [u8; state::layout::RESERVED_LEN - 64],
+    /// Signers besides `from` that may fund `send_if_over_threshold`; see
+    /// `add_delegate`. Too large for the reserve, so appended after it.
+    #[max_len(MAX_DELEGATES)]
+    pub delegates: Vec<Pubkey>,
+    /// US dollar value `adjust_threshold` keeps `threshold_lamports` at; see
+    /// `set_auto_adjust`. Appended after `delegates`, as the reserve is too
+    /// small for it.
+    pub auto_adjust: Option<AutoAdjust>,
+}
+
+/// The fixed parts of `Pubkey::create_with_seed(base, seed, owner)`
+/// addresses; each send supplies the seed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct SeededRecipient {
+    pub base: Pubkey,
+    pub owner: Pubkey,
+}
+
+impl SeededRecipient {
+    /// The address derived with `seed`; `None` if the seed exceeds
+    /// `MAX_SEED_LEN` bytes.
+    pub fn address(&self, seed: &str) -> Option<Pubkey> {
+        Pubkey::create_with_seed(&self.base, seed, &self.owner).ok()
+    }
+}
+
+/// Terms for pegging `threshold_lamports` to a US dollar value: every
+/// `interval_seconds`, `adjust_threshold` reprices `target_usd_cents` at the
+/// Pyth SOL/USD price and clamps it to `[min_lamports, max_lamports]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct AutoAdjust {
+    pub target_usd_cents: u64,
+    pub min_lamports: u64,
+    pub max_lamports: u64,
+    /// Minimum time between adjustments, so cranks can't churn the threshold
+    /// on every price tick.
+    pub interval_seconds: u32,
+}
+
+impl AutoAdjust {
+    /// Whether the terms are usable: a non-zero target and ordered bounds.
+    pub fn is_valid(&self) -> bool {
+        self.target_usd_cents > 0 && self.min_lamports <= self.max_lamports
+    }
+
+    /// The threshold for `price`: the lamports worth `target_usd_cents`,
+    /// rounded up and clamped to the bounds. `None` for an unusable price.
+    pub fn threshold_lamports(&self, price: &conditions::SolUsdPrice) -> Option<u64> {
+        price
+            .lamports_for_usd_cents(self.target_usd_cents)
+            .map(|lamports| lamports.clamp(self.min_lamports, self.max_lamports))
+    }
+
+    /// Whether an adjustment is allowed at `now`, the last one having run at
+    /// `last_adjusted_unix`.
+    pub fn is_due(&self, last_adjusted_unix: i64, now: i64) -> bool {
+        now.saturating_sub(last_adjusted_unix) >= i64::from(self.interval_seconds)
+    }
+}
+
+/// A config's transfer approvers: with a non-zero `quorum`, sends only run
+/// through a `PendingTransfer` approved by `quorum` of `members`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct Approvers {
+    #[max_len(MAX_APPROVERS)]
+    pub members: Vec<Pubkey>,
+    pub quorum: u8,
+}
+
+impl Approvers {
+    /// At most `MAX_APPROVERS` distinct members and `1 ≤ quorum ≤ members`, or
+    /// no members and quorum `0` (approval off).
+    pub fn is_valid(&self) -> bool {
+        let members = &self.members;
+        let distinct = members
+            .iter()
+            .enumerate()
+            .all(|(i, member)| !members[..i].contains(member));
+        members.len() <= MAX_APPROVERS
+            && distinct
+            && (self.quorum as usize) <= members.len()
+            && (self.quorum == 0) == members.is_empty()
+    }
+
+    /// Whether `approvals` from current members reach the quorum. Approvals
+    /// from keys removed since they were given do not count.
+    pub fn approved(&self, approvals: &[Pubkey]) -> bool {
+        let current = approvals
+            .iter()
+            .filter(|approval| self.members.contains(approval))
+            .count();
+        current >= self.quorum as usize
+    }
+}
+
+/// Token-side counterpart of `threshold_lamports`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct TokenThreshold {
+    pub mint: Pubkey,
+    /// Minimum transfer, in the mint's base units.
+    pub threshold_amount: u64,
+}
+
+/// Where `send_bridged` pays out on another chain.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct BridgeRoute {
+    /// Wormhole core bridge to post through, one of the `WORMHOLE_*_PROGRAM_ID`s.
+    pub wormhole_program: Pubkey,
+    /// Wormhole chain id of the target chain.
+    pub chain: u16,
+    /// Recipient on the target chain, left-padded to 32 bytes.
+    pub recipient: [u8; 32],
+}
+
+impl BridgeRoute {
+    pub(crate) fn validate(&self) -> Result<()> {
+        require!(
+            [
+                WORMHOLE_MAINNET_PROGRAM_ID,
+                WORMHOLE_DEVNET_PROGRAM_ID,
+                WORMHOLE_LOCALNET_PROGRAM_ID,
+            ]
+            .contains(&self.wormhole_program)
+                && self.chain != 0
+                && self.chain != WORMHOLE_CHAIN_SOLANA
+                && self.recipient != [0; 32],
+            ConditionalError::InvalidBridgeRoute
+        );
+        Ok(())
+    }
+}
+
+/// Circuit breaker against drain patterns: sends far above the recent average
+/// are flagged and can be made to need a second signature.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct AnomalyGuard {
+    /// Flag sends above this multiple of `average_lamports`; `0` disables.
+    pub multiplier: u16,
+    /// Reject flagged sends unless an approver co-signs.
+    pub require_approval: bool,
+    /// Exponentially weighted average send size.
+    pub average_lamports: u64,
+    /// Sends folded into the average (saturating).
+    pub samples: u32,
+}
+
+impl AnomalyGuard {
+    /// Whether a send of `lamports` is an outlier against the current average.
+    /// Nothing is flagged until `ANOMALY_MIN_SAMPLES` sends have been seen.
+    pub fn is_anomalous(&self, lamports: u64) -> bool {
+        self.multiplier != 0
+            && self.samples >= ANOMALY_MIN_SAMPLES
+            && lamports as u128 > self.average_lamports as u128 * self.multiplier as u128
+    }
+
+    /// Fold a send of `lamports` into the rolling average.
+    pub(crate) fn record(&mut self, lamports: u64) {
+        self.average_lamports = if self.samples == 0 {
+            lamports
+        } else {
+            ((self.average_lamports as u128 * (ANOMALY_AVERAGE_WEIGHT - 1) + lamports as u128)
+                / ANOMALY_AVERAGE_WEIGHT) as u64
+        };
+        self.samples = self.samples.saturating_add(1);
+    }
+}
+
+/// Lifetime send statistics, counted wherever `TransferExecuted` is emitted
+/// (per share for validator payouts), except for a send's fee leg. Per-epoch totals live in the opt-in
+/// `EpochSummary`.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct TransferStats {
+    /// Lamports sent since creation or the last `reset_stats` (saturating).
+    pub total_transferred: u64,
+    pub transfer_count: u64,
+    /// Slot of the latest transfer; `0` before the first.
+    pub last_transfer_slot: u64,
+}
+
+impl TransferStats {
+    /// Fold in a transfer of `lamports` at `slot`. Saturates rather than
+    /// failing, so statistics can never block a send.
+    pub fn record(&mut self, lamports: u64, slot: u64) {
+        self.total_transferred = self.total_transferred.saturating_add(lamports);
+        self.transfer_count = self.transfer_count.saturating_add(1);
+        self.last_transfer_slot = slot;
+    }
+}
+
+/// Token bucket limiting outflow. Sends draw their lamports from a bucket
+/// holding up to `capacity`, which refills continuously at
+/// `refill_per_second`; a send the bucket can't cover is refused. The level
+/// is stored as what has been drawn, so a zeroed bucket is full.
+///
+/// Field types follow the fixed-window limit this replaced, so the layout is
+/// unchanged; `Config::upgrade` converts the old values.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct RateLimit {
+    /// Lamports returned to the bucket per second; `0` disables the limit.
+    pub refill_per_second: u32,
+    /// Lamports a full bucket holds: the largest burst.
+    pub capacity: u64,
+    /// Unix time `drained` was last brought up to date.
+    pub updated_at: i64,
+    /// Lamports drawn and not yet refilled as of `updated_at`.
+    pub drained: u64,
+}
+
+impl RateLimit {
+    /// Lamports still drawn at `now`, after refilling since `updated_at`. A
+    /// clock earlier than `updated_at` refills nothing.
+    fn drained_at(&self, now: i64) -> u64 {
+        let elapsed = u64::try_from(now.saturating_sub(self.updated_at)).unwrap_or(0);
+        self.drained
+            .saturating_sub(elapsed.saturating_mul(self.refill_per_second as u64))
+    }
+
+    /// Lamports a send at `now` may still move; `u64::MAX` when disabled.
+    pub fn remaining(&self, now: i64) -> u64 {
+        if self.refill_per_second == 0 {
+            return u64::MAX;
+        }
+        self.capacity.saturating_sub(self.drained_at(now))
+    }
+
+    /// Whether a send of `lamports` at `now` fits the limit.
+    pub fn allows(&self, lamports: u64, now: i64) -> bool {
+        lamports <= self.remaining(now)
+    }
+
+    /// The bucket for a version 3 fixed-window limit read in this layout:
+    /// `refill_per_second` holds the window length and `capacity` the
+    /// per-window cap, which becomes the refill rate that sends the same
+    /// average. The window's usage is taken as drawn when it opened.
+    fn fixed_window_as_bucket(self) -> Self {
+        let period_seconds = self.refill_per_second as u64;
+        if period_seconds == 0 {
+            return self;
+        }
+        let refill = (self.capacity / period_seconds).max(1);
+        Self {
+            refill_per_second: u32::try_from(refill).unwrap_or(u32::MAX),
+            ..self
+        }
+    }
+
+    /// Apply the refill up to `now` at the current rate, e.g. before the
+    /// rate changes.
+    pub fn settle(&mut self, now: i64) {
+        self.drained = self.drained_at(now);
+        self.updated_at = self.updated_at.max(now);
+    }
+
+    /// Draw a send of `lamports` at `now` from the bucket.
+    pub fn record(&mut self, lamports: u64, now: i64) {
+        if self.refill_per_second == 0 {
+            return;
+        }
+        self.settle(now);
+        self.drained = self.drained.saturating_add(lamports);
+    }
+}
+
+/// Delivery mode for the config's `to` account.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum Beneficiary {
+    /// A plain address receiving SOL.
+    #[default]
+    Sol,
+    /// A wrapped-SOL token account; its token balance is synced after each send.
+    TokenAccount,
+    /// A stake account; lamports land as undelegated excess the withdrawer can
+    /// withdraw or merge.
+    StakeAccount,
+    /// A wallet paid through its wrapped-SOL associated token account. The
+    /// account is derived from `to` on-chain, not trusted from the caller.
+    AssociatedTokenAccount,
+}
+
+impl Beneficiary {
+    /// Mint delivered in token modes; `None` when sends stay native SOL.
+    pub fn mint(&self) -> Option<Pubkey> {
+        match self {
+            Beneficiary::TokenAccount | Beneficiary::AssociatedTokenAccount => {
+                Some(token::spl_token::native_mint::ID)
+            }
+            Beneficiary::Sol | Beneficiary::StakeAccount => None,
+        }
+    }
+
+    /// The account sends are paid into: `to` itself, or in
+    /// `AssociatedTokenAccount` mode the passed `recipient_token_account`.
+    pub(crate) fn delivery_account<'a, 'info>(
+        &self,
+        to: &'a AccountInfo<'info>,
+        recipient_token_account: Option<&'a AccountInfo<'info>>,
+    ) -> Result<&'a AccountInfo<'info>> {
+        match self {
+            Beneficiary::AssociatedTokenAccount => {
+                Ok(recipient_token_account.ok_or(ConditionalError::InvalidBeneficiary)?)
+            }
+            _ => Ok(to),
+        }
+    }
+
+    /// Check the delivery account is the kind of account this mode pays into.
+    pub(crate) fn validate<'info>(
+        &self,
+        to: &AccountInfo<'info>,
+        recipient_token_account: Option<&AccountInfo<'info>>,
+    ) -> Result<()> {
+        match self {
+            Beneficiary::Sol => {}
+            Beneficiary::AssociatedTokenAccount => {
+                let ata = self.delivery_account(to, recipient_token_account)?;
+                require_keys_eq!(
+                    ata.key(),
+                    associated_token_address(&to.key(), &token::spl_token::native_mint::ID),
+                    ConditionalError::InvalidBeneficiary
+                );
+                require_keys_eq!(*ata.owner, token::ID, ConditionalError::InvalidBeneficiary);
+                let account = TokenAccount::try_deserialize(&mut &ata.try_borrow_data()?[..])?;
+                require_keys_eq!(
+                    account.owner,
+                    to.key(),
+                    ConditionalError::InvalidBeneficiary
+                );
+            }
+            Beneficiary::TokenAccount => {
+                require_keys_eq!(*to.owner, token::ID, ConditionalError::InvalidBeneficiary);
+                let account = TokenAccount::try_deserialize(&mut &to.try_borrow_data()?[..])?;
+                require_keys_eq!(
+                    account.mint,
+                    token::spl_token::native_mint::ID,
+                    ConditionalError::InvalidBeneficiary
+                );
+            }
+            Beneficiary::StakeAccount => {
+                require_keys_eq!(
+                    *to.owner,
+                    anchor_lang::solana_program::stake::program::ID,
+                    ConditionalError::InvalidBeneficiary
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Finish delivery after lamports have been moved into the delivery account `to`.
+    pub(crate) fn settle<'info>(
+        &self,
+        to: &AccountInfo<'info>,
+        token_program: Option<&Program<'info, Token>>,
+    ) -> Result<()> {
+        if matches!(
+            self,
+            Beneficiary::TokenAccount | Beneficiary::AssociatedTokenAccount
+        ) {
+            let token_program = token_program.ok_or(ConditionalError::InvalidBeneficiary)?;
+            let cpi_ctx = CpiContext::new(
+                token_program.to_account_info(),
+                token::SyncNative {
+                    account: to.clone(),
+                },
+            );
+            token::sync_native(cpi_ctx)?;
+        }
+        Ok(())
+    }
+}
+
+/// Config change signed offline by the root authority and applied with
+/// `apply_signed_proposal`. The signed message is this struct's Borsh
+/// encoding, so clients write exactly these bytes to the proposal file.
+/// `None` fields are left unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigProposal {
+    pub config: Pubkey,
+    pub nonce: u64,
+    /// Slot the proposal was written at; it must be applied within
+    /// `RECENT_SLOT_TOLERANCE` slots of it.
+    pub recent_slot: u64,
+    /// Claimed like an `update_*` instruction's `update_id`; `0` opts out.
+    pub update_id: u64,
+    pub threshold_lamports: Option<u64>,
+    pub to: Option<Pubkey>,
+    pub default_memo: Option<String>,
+}
+
+/// Outcome of a send whose amount is below the threshold.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum BelowThresholdBehavior {
+    /// Abort the instruction (and so the transaction) with `BelowThreshold`.
+    #[default]
+    Fail,
+    /// Skip the transfer, succeed, and emit `TransferSkipped`.
+    Noop,
+}
+
+/// How a send amount is compared with the lamport threshold. The USD, private,
+/// and token thresholds stay minimums whatever the mode.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum ComparisonMode {
+    /// `amount >= threshold`; the zero-byte default.
+    #[default]
+    Gte,
+    /// `amount > threshold`.
+    Gt,
+    /// `amount == threshold`.
+    Eq,
+    /// `amount <= threshold`.
+    Lte,
+    /// `amount < threshold`.
+    Lt,
+}
+
+impl ComparisonMode {
+    /// Whether `amount` passes `threshold` under this mode.
+    pub fn admits(self, amount: u64, threshold: u64) -> bool {
+        match self {
+            ComparisonMode::Gte => amount >= threshold,
+            ComparisonMode::Gt => amount > threshold,
+            ComparisonMode::Eq => amount == threshold,
+            ComparisonMode::Lte => amount <= threshold,
+            ComparisonMode::Lt => amount < threshold,
+        }
+    }
+
+    /// Whether the threshold is a lower bound, so raising it is stricter.
+    pub fn is_floor(self) -> bool {
+        matches!(self, ComparisonMode::Gte | ComparisonMode::Gt)
+    }
+}
+
+/// A granted authority and its permission bitmask (`PERM_*`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct AuthorityEntry {
+    pub key: Pubkey,
+    pub permissions: u8,
+}
+
+impl Config {
+    /// This config's conditions and settings on `from`'s `route`, paying `to`:
+    /// per-route state (sequence numbers, attestation, anomaly statistics,
+    /// recipient hash, bridge route, delegates) is reset.
+    pub fn cloned_for(
+        &self,
+        from: Pubkey,
+        route: Pubkey,
+        to: Pubkey,
+        bump: u8,
+        now: i64,
+    ) -> Config {
+        Config {
+            from,
+            to,
+            seed_from: from,
+            seed_route: route,
+            bump,
+            event_seq: 0,
+            proposal_nonce: 0,
+            last_attested_ts: now,
+            recipient_hash: None,
+            bridge_route: None,
+            anomaly_guard: AnomalyGuard {
+                average_lamports: 0,
+                samples: 0,
+                ..self.anomaly_guard
+            },
+            pending_authority: None,
+            stats: TransferStats::default(),
+            rate_limit: RateLimit {
+                updated_at: 0,
+                drained: 0,
+                ..self.rate_limit
+            },
+            last_update_id: 0,
+            seeded_recipient: None,
+            close_requested_at: None,
+            version: CONFIG_VERSION,
+            delegates: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Whether `key` may sign and fund sends: `from` or a delegate.
+    pub fn is_sender(&self, key: &Pubkey) -> bool {
+        *key == self.from || self.delegates.contains(key)
+    }
+
+    /// Whether `threshold_lamports` may replace the current threshold: always,
+    /// unless the config is monotonic and it would lower it.
+    pub fn allows_threshold(&self, threshold_lamports: u64) -> bool {
+        !self.monotonic_threshold || threshold_lamports >= self.threshold_lamports
+    }
+
+    /// This config as `get_config` returns it, with `terms` already resolved.
+    pub fn view(&self, terms: PolicyTerms) -> ConfigView {
+        ConfigView {
+            version: self.version,
+            event_seq: self.event_seq,
+            authority: self.authority,
+            from: self.from,
+            to: self.to,
+            hashed_recipient: self.recipient_hash.is_some(),
+            terms: terms.threshold_terms(),
+            max_lamports: terms.max_lamports,
+            threshold_usd_cents: self.threshold_usd_cents,
+            private_threshold: self.private_threshold.is_some(),
+            valid_from_unix: terms.valid_from_unix,
+            valid_until_unix: terms.valid_until_unix,
+            paused: self.paused,
+            closing: self.close_requested_at.is_some(),
+            approval_quorum: self.approvers.quorum,
+            fee_bps: terms.fee_bps,
+            fee_collector: terms.fee_collector,
+            policy: self.policy,
+            monotonic_threshold: self.monotonic_threshold,
+            auto_adjust: self.auto_adjust,
+            escrow_expiry_slot: self.escrow_expiry_slot,
+        }
+    }
+
+    /// Whether the escrow has passed its `escrow_expiry_slot` at `slot`.
+    pub fn escrow_expired(&self, slot: u64) -> bool {
+        matches!(self.escrow_expiry_slot, Some(expiry) if slot >= expiry)
+    }
+
+    /// The fee the config's own `fee_bps` charges on a send of `lamports`,
+    /// rounded down; a linked policy's fee is in `PolicyTerms::fee_for`.
+    pub fn fee_for(&self, lamports: u64) -> u64 {
+        self.own_terms().fee_for(lamports)
+    }
+
+    /// Decode config data written under any `version`. Fields carved from the
+    /// reserve are zeros until their version wrote them, so the current layout
+    /// reads them as their defaults. `delegates` and `auto_adjust`, appended
+    /// after the reserve, are not where the current layout looks for them:
+    /// older reserves still spanned the full `INIT_SPACE` of the `Option`s
+    /// carved since, which decode as `None` from a single zero byte. They are
+    /// re-read from where the stored version wrote them, and left empty for
+    /// versions that predate them, instead of decoding whatever bytes follow.
+    pub fn try_deserialize_versioned(data: &[u8]) -> Result<Self> {
+        let mut cfg = Self::try_deserialize(&mut &data[..])?;
+        if cfg.version >= CONFIG_VERSION {
+            return Ok(cfg);
+        }
+        let appended = cfg.delegates.try_to_vec()?.len() + cfg.auto_adjust.try_to_vec()?.len();
+        let mut offset = Self::DISCRIMINATOR.len() + cfg.try_to_vec()?.len() - appended;
+        // `close_requested_at` (version 3) and `escrow_expiry_slot` (version
+        // 10) each held 9 bytes of the older reserve, not the 1 read above.
+        if cfg.version < 3 {
+            offset += 8;
+        }
+        if cfg.version < 10 {
+            offset += 8;
+        }
+        let mut tail = data
+            .get(offset..)
+            .ok_or(ConditionalError::InvalidAccountLayout)?;
+        cfg.delegates = if cfg.version >= 7 {
+            AnchorDeserialize::deserialize(&mut tail)
+                .map_err(|_| ConditionalError::InvalidAccountLayout)?
+        } else {
+            Vec::new()
+        };
+        cfg.auto_adjust = if cfg.version >= 9 {
+            AnchorDeserialize::deserialize(&mut tail)
+                .map_err(|_| ConditionalError::InvalidAccountLayout)?
+        } else {
+            None
+        };
+        Ok(cfg)
+    }
+
+    /// Bring a config written under an older `version` up to
+    /// `CONFIG_VERSION`, one version at a time. Fields added since read as
+    /// their zero-byte defaults (see `try_deserialize_versioned`); a step only
+    /// has to fill in those that need another value. Fails for versions newer
+    /// than this program.
+    pub fn upgrade(&mut self) -> Result<()> {
+        require!(
+            self.version <= CONFIG_VERSION,
+            ConditionalError::InvalidAccountLayout
+        );
+        while self.version < CONFIG_VERSION {
+            // 0 → 1 introduced `version` itself; nothing else to fill in.
+            // 1 → 2 added `fee_bps` and `fee_collector`; zero means no fee.
+            // 2 → 3 added `close_requested_at`; `None` means no close pending.
+            if self.version == 3 {
+                self.rate_limit = self.rate_limit.fixed_window_as_bucket();
+            }
+            // 4 → 5 added `monotonic_threshold`; `false` keeps the old behavior.
+            // 5 → 6 added `comparison_mode`; zeros read as `Gte`, the old rule.
+            // 6 → 7 appended `delegates` after the reserve; older configs
+            // decode it as empty in `try_deserialize_versioned`.
+            // 7 → 8 added `log_rejections`; `false` keeps rejections silent.
+            // 8 → 9 appended `auto_adjust` and added `last_auto_adjust_unix`;
+            // older configs decode `None` in `try_deserialize_versioned`, so
+            // the threshold stays manual.
+            // 9 → 10 added `escrow_expiry_slot`; `None` never expires.
+            self.version += 1;
+        }
+        Ok(())
+    }
+
+    /// Claim `update_id` for a config setter; see [`claim_update_id`].
+    pub fn claim_update_id(&mut self, update_id: u64) -> Result<bool> {
+        claim_update_id(&mut self.last_update_id, update_id)
+    }
+
+    /// Hand root authority to `signer` if it is the pending authority.
+    pub fn accept_authority(&mut self, signer: &Pubkey) -> Result<()> {
+        require!(
+            self.pending_authority == Some(*signer),
+            ConditionalError::NotPendingAuthority
+        );
+        self.authority = *signer;
+        self.pending_authority = None;
+        Ok(())
+    }
+
+    /// Whether `to` may be an account owned by `owner`.
+    pub fn recipient_owner_allowed(&self, owner: &Pubkey) -> bool {
+        self.recipient_owners.is_empty() || self.recipient_owners.contains(owner)
+    }
+
+    /// Whether the config may move tokens of `mint`.
+    pub fn mint_allowed(&self, mint: &Pubkey) -> bool {
+        self.mint_allowlist.is_empty() || self.mint_allowlist.contains(mint)
+    }
+
+    /// Whether `candidate` is this config's recipient; `config` is its address.
+    pub fn is_recipient(&self, config: &Pubkey, candidate: &Pubkey) -> bool {
+        match self.recipient_hash {
+            Some(hash) => recipient_hash(config, candidate) == hash,
+            None => *candidate == self.to,
+        }
+    }
+
+    /// Require `signer` to be the root authority or hold every bit in `permission`.
+    pub fn require_permission(&self, signer: &Pubkey, permission: u8) -> Result<()> {
+        let granted = *signer == self.authority
+            || self
+                .authorities
+                .iter()
+                .any(|entry| entry.key == *signer && entry.permissions & permission == permission);
+        require!(granted, ConditionalError::Unauthorized);
+        Ok(())
+    }
+
+    /// Resolve the terms governing transfers. Precedence, highest first:
+    /// 1. `policy_overrides` fields that are set,
+    /// 2. the linked `Policy` (which must then be passed),
+    /// 3. the config's own fields, when no policy is linked.
+    ///
+    /// The maximum, window, and fee come from the policy's `limits` where it
+    /// sets them and from the config otherwise; overrides do not cover them.
+    /// On a monotonic config the resolved threshold never drops below
+    /// `threshold_lamports`, whatever the policy or overrides say.
+    pub fn resolve_policy(&self, policy: Option<&Account<Policy>>) -> Result<PolicyTerms> {
+        let terms = self.resolve_terms(policy.map(|policy| (policy.key(), &**policy)));
+        Ok(terms.ok_or(ConditionalError::PolicyRequired)?)
+    }
+
+    /// `resolve_policy` over a plain `(address, Policy)`, for off-chain use.
+    /// `None` when a linked policy is missing or does not match.
+    pub fn resolve_terms(&self, policy: Option<(Pubkey, &Policy)>) -> Option<PolicyTerms> {
+        let own = self.own_terms();
+        let base = match (self.policy, policy) {
+            (None, _) => own,
+            (Some(linked), Some((key, policy))) if key == linked => {
+                let limits = &policy.limits;
+                let window = limits.valid_from_unix.is_some() || limits.valid_until_unix.is_some();
+                let (fee_bps, fee_collector) = if limits.fee_bps > 0 {
+                    (limits.fee_bps, limits.fee_collector)
+                } else {
+                    (own.fee_bps, own.fee_collector)
+                };
+                PolicyTerms {
+                    threshold_lamports: policy.threshold_lamports,
+                    below_threshold_behavior: policy.below_threshold_behavior,
+                    max_lamports: match limits.max_lamports {
+                        0 => own.max_lamports,
+                        max_lamports => max_lamports,
+                    },
+                    valid_from_unix: if window {
+                        limits.valid_from_unix
+                    } else {
+                        own.valid_from_unix
+                    },
+                    valid_until_unix: if window {
+                        limits.valid_until_unix
+                    } else {
+                        own.valid_until_unix
+                    },
+                    fee_bps,
+                    fee_collector,
+                    ..own
+                }
+            }
+            (Some(_), _) => return None,
+        };
+        let overrides = &self.policy_overrides;
+        let threshold_lamports = overrides
+            .threshold_lamports
+            .unwrap_or(base.threshold_lamports);
+        Some(PolicyTerms {
+            // A monotonic threshold is a floor under the policy and overrides too.
+            threshold_lamports: if self.monotonic_threshold {
+                threshold_lamports.max(self.threshold_lamports)
+            } else {
+                threshold_lamports
+            },
+            below_threshold_behavior: overrides
+                .below_threshold_behavior
+                .unwrap_or(base.below_threshold_behavior),
+            ..base
+        })
+    }
+
+    /// The config's own terms, ignoring any linked policy and overrides.
+    pub fn own_terms(&self) -> PolicyTerms {
+        PolicyTerms {
+            threshold_lamports: self.threshold_lamports,
+            below_threshold_behavior: self.below_threshold_behavior,
+            comparison_mode: self.comparison_mode,
+            max_lamports: self.max_lamports,
+            valid_from_unix: self.valid_from_unix,
+            valid_until_unix: self.valid_until_unix,
+            fee_bps: self.fee_bps,
+            fee_collector: self.fee_collector,
+        }
+    }
+
+    /// Advance and return the sequence number for the next emitted event.
+    pub fn next_event_seq(&mut self) -> Result<u64> {
+        self.event_seq = self
+            .event_seq
+            .checked_add(1)
+            .ok_or(ConditionalError::MathOverflow)?;
+        Ok(self.event_seq)
+    }
+}
+
+/// Reusable transfer terms shared by any number of configs.
+#[account]
+#[derive(InitSpace)]
+pub struct Policy {
+    pub authority: Pubkey,
+    pub id: u32,
+    pub threshold_lamports: u64,
+    pub below_threshold_behavior: BelowThresholdBehavior,
+    pub bump: u8,
+    /// Highest `update_id` applied by `update_policy`.
+    pub last_update_id: u64,
+    pub limits: PolicyLimits,
+}
+
+/// Limits a policy sets for every linked config. Each one that is set
+/// replaces the config's own; unset ones (`0`, no window, no fee) leave it in
+/// force.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct PolicyLimits {
+    /// Largest send; `0` leaves the config's `max_lamports`.
+    pub max_lamports: u64,
+    /// Send window, replacing the config's whole window when either bound is set.
+    pub valid_from_unix: Option<i64>,
+    pub valid_until_unix: Option<i64>,
+    /// Fee on `SendIfOverThreshold` sends; `0` leaves the config's fee.
+    pub fee_bps: u16,
+    /// Receives the fee; unused while `fee_bps` is `0`.
+    pub fee_collector: Pubkey,
+}
+
+impl PolicyLimits {
+    /// Check the limits against each other and the policy's threshold, with
+    /// the errors `update_limits`, `update_time_window`, and `set_fee` use.
+    pub fn validate(&self, threshold_lamports: u64) -> Result<()> {
+        require!(
+            self.max_lamports == 0 || threshold_lamports <= self.max_lamports,
+            ConditionalError::InvalidLimits
+        );
+        if let (Some(from), Some(until)) = (self.valid_from_unix, self.valid_until_unix) {
+            require!(from <= until, ConditionalError::InvalidTimeWindow);
+        }
+        require!(
+            self.fee_bps <= MAX_FEE_BPS
+                && (self.fee_bps == 0 || self.fee_collector != Pubkey::default()),
+            ConditionalError::InvalidFee
+        );
+        Ok(())
+    }
+}
+
+/// Optional per-config replacements for individual policy fields.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct PolicyOverrides {
+    pub threshold_lamports: Option<u64>,
+    pub below_threshold_behavior: Option<BelowThresholdBehavior>,
+}
+
+/// Terms a transfer is evaluated against, after resolving any linked policy.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolicyTerms {
+    pub threshold_lamports: u64,
+    pub below_threshold_behavior: BelowThresholdBehavior,
+    /// Always the config's own; policies do not carry one.
+    pub comparison_mode: ComparisonMode,
+    /// `0` means no maximum.
+    pub max_lamports: u64,
+    pub valid_from_unix: Option<i64>,
+    pub valid_until_unix: Option<i64>,
+    pub fee_bps: u16,
+    pub fee_collector: Pubkey,
+}
+
+impl PolicyTerms {
+    /// Whether a send of `amount` meets the threshold under these terms.
+    pub fn admits(&self, amount: u64) -> bool {
+        self.comparison_mode.admits(amount, self.threshold_lamports)
+    }
+
+    /// Whether unix time `now` is inside the send window (bounds inclusive).
+    pub fn within_time_window(&self, now: i64) -> bool {
+        !matches!(self.valid_from_unix, Some(from) if now < from)
+            && !matches!(self.valid_until_unix, Some(until) if now > until)
+    }
+
+    /// The fee owed on a send of `lamports`, rounded down.
+    pub fn fee_for(&self, lamports: u64) -> u64 {
+        (lamports as u128 * self.fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    /// The threshold part, as `ConfigView` reports it.
+    pub fn threshold_terms(&self) -> ThresholdTerms {
+        ThresholdTerms {
+            threshold_lamports: self.threshold_lamports,
+            below_threshold_behavior: self.below_threshold_behavior,
+            comparison_mode: self.comparison_mode,
+        }
+    }
+}
+
+/// The threshold part of [`PolicyTerms`].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThresholdTerms {
+    pub threshold_lamports: u64,
+    pub below_threshold_behavior: BelowThresholdBehavior,
+    pub comparison_mode: ComparisonMode,
+}
+
+/// The parts of a config that decide whether a send goes through, returned
+/// by `get_config`. Stable across config layout changes; fields are only
+/// ever appended.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigView {
+    pub version: u8,
+    pub event_seq: u64,
+    pub authority: Pubkey,
+    pub from: Pubkey,
+    /// Zeroed while the recipient is hashed.
+    pub to: Pubkey,
+    pub hashed_recipient: bool,
+    /// Effective threshold terms, after the linked policy and overrides. The
+    /// limits, window, and fee below are effective ones too.
+    pub terms: ThresholdTerms,
+    pub max_lamports: u64,
+    pub threshold_usd_cents: Option<u64>,
+    /// Whether sends must also prove they meet a hidden threshold.
+    pub private_threshold: bool,
+    pub valid_from_unix: Option<i64>,
+    pub valid_until_unix: Option<i64>,
+    pub paused: bool,
+    /// Whether a `close_config` is pending.
+    pub closing: bool,
+    /// Approvals a send needs; `0` when none.
+    pub approval_quorum: u8,
+    pub fee_bps: u16,
+    pub fee_collector: Pubkey,
+    pub policy: Option<Pubkey>,
+    pub monotonic_threshold: bool,
+    pub auto_adjust: Option<AutoAdjust>,
+    pub escrow_expiry_slot: Option<u64>,
+}
+
+/// Quota granted to a third party to trigger transfers out of the config vault.
+#[account]
+#[derive(InitSpace)]
+pub struct Allowance {
+    pub config: Pubkey,
+    /// Signer allowed to redeem.
+    pub holder: Pubkey,
+    /// Lamports still redeemable.
+    pub remaining: u64,
+    /// Unix timestamp after which redemptions fail.
+    pub expires_at: i64,
+    /// Only recipient redemptions may pay; `None` means config.to.
+    pub recipient: Option<Pubkey>,
+    pub bump: u8,
+}
+
+/// Stake pool position for a config's idle vault lamports.
+#[account]
+#[derive(InitSpace)]
+pub struct VaultStake {
+    pub config: Pubkey,
+    pub stake_pool: Pubkey,
+    pub pool_mint: Pubkey,
+    /// Receives yield above `principal` on unstake.
+    pub beneficiary: Pubkey,
+    /// Lamports deposited since the last unstake.
+    pub principal: u64,
+    pub bump: u8,
+}
+
+/// Per-epoch activity totals for a config, so reports read O(1) accounts per epoch.
+#[account]
+#[derive(InitSpace)]
+pub struct EpochSummary {
+    pub config: Pubkey,
+    pub epoch: u64,
+    /// Lamports transferred to recipients during the epoch, fees excluded.
+    pub volume: u64,
+    /// Number of transfers during the epoch.
+    pub count: u64,
+    /// Fees collected during the epoch.
+    pub fees: u64,
+    /// Set by `finalize_epoch` once the epoch has passed.
+    pub finalized: bool,
+    pub bump: u8,
+}
+
+impl EpochSummary {
+    /// Add one transfer of `lamports` (with `fee`) to the totals.
+    pub(crate) fn record(&mut self, lamports: u64, fee: u64) -> Result<()> {
+        self.volume = self
+            .volume
+            .checked_add(lamports)
+            .ok_or(ConditionalError::MathOverflow)?;
+        self.count = self
+            .count
+            .checked_add(1)
+            .ok_or(ConditionalError::MathOverflow)?;
+        self.fees = self
+            .fees
+            .checked_add(fee)
+            .ok_or(ConditionalError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Validator commission-sharing table: each vote account's withdraw authority
+/// receives `share_bps` of a `send_validator_payout`.
+#[account]
+#[derive(InitSpace)]
+pub struct PayoutTable {
+    pub config: Pubkey,
+    #[max_len(MAX_PAYOUT_RECIPIENTS)]
+    pub entries: Vec<PayoutEntry>,
+    pub bump: u8,
+    /// Where the lamports lost to flooring each share go. Tables created
+    /// before this field read the zeroed default, `LastRecipient`.
+    pub remainder: RemainderPolicy,
+}
+
+/// Where `send_validator_payout` puts the lamports that flooring each
+/// entry's `total * share_bps / 10_000` leaves over (fewer than one per
+/// entry).
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum RemainderPolicy {
+    /// All of it to the last entry.
+    #[default]
+    LastRecipient,
+    /// One lamport each to the entries with the largest fractional parts,
+    /// earlier entries first on ties (largest-remainder method).
+    LargestRemainder,
+    /// All of it to this account, passed after the payout pairs.
+    DustAccount(Pubkey),
+}
+
+/// One validator in a `PayoutTable`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct PayoutEntry {
+    pub vote_account: Pubkey,
+    /// Share of the distribution in basis points.
+    pub share_bps: u16,
+}
+
+/// Recipients of `send_split_if_over_threshold`, each receiving `share_bps`
+/// of the amount sent.
+#[account]
+#[derive(InitSpace)]
+pub struct SplitTable {
+    pub config: Pubkey,
+    #[max_len(MAX_SPLIT_RECIPIENTS)]
+    pub entries: Vec<SplitEntry>,
+    pub bump: u8,
+}
+
+/// One recipient in a `SplitTable`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct SplitEntry {
+    pub recipient: Pubkey,
+    /// Share of the amount in basis points.
+    pub share_bps: u16,
+}
+
+/// Program-wide state controlled by the program admin.
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalState {
+    pub admin: Pubkey,
+    /// Number of times `admin_force_transfer` has ever been used.
+    pub force_transfer_count: u64,
+    /// `FEATURE_*` bits switched off protocol-wide.
+    pub disabled_features: u64,
+    /// Slot from which v1 instructions fail; `0` means no sunset is scheduled.
+    pub sunset_slot: u64,
+    pub bump: u8,
+    /// Highest `update_id` applied by an admin update.
+    pub last_update_id: u64,
+}
+
+/// Registry of the event format, so indexers can detect layout changes.
+#[account]
+#[derive(InitSpace)]
+pub struct EventSchema {
+    /// `EVENT_SCHEMA_VERSION` of the deployed program.
+    pub version: u16,
+    /// Number of version changes recorded since creation.
+    pub changelog: u64,
+    /// Slot of the last version change.
+    pub updated_slot: u64,
+    pub bump: u8,
+}
+
+/// Unit of a `TypedAmount`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmountUnit {
+    Lamports,
+    /// 1 milli-SOL = 1_000_000 lamports.
+    MilliSol,
+    /// US cents; requires a price source, not yet supported.
+    UsdCents,
+}
+
+/// Amount argument tagged with its unit, used by `send_if_over_threshold_v2`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TypedAmount {
+    pub unit: AmountUnit,
+    pub value: u64,
+}
+
+impl TypedAmount {
+    /// Convert to lamports, rejecting zero values and overflow.
+    pub fn to_lamports(&self) -> Result<u64> {
+        require!(self.value > 0, ConditionalError::InvalidAmount);
+        match self.unit {
+            AmountUnit::Lamports => Ok(self.value),
+            AmountUnit::MilliSol => self
+                .value
+                .checked_mul(LAMPORTS_PER_MILLI_SOL)
+                .ok_or_else(|| error!(ConditionalError::MathOverflow)),
+            AmountUnit::UsdCents => err!(ConditionalError::UnsupportedAmountUnit),
+        }
+    }
+}
+
+/// Sponsor-funded pool that matches qualifying transfers under a config.
+/// Lamports above rent-exemption held by this account are the matchable funds.
+#[account]
+#[derive(InitSpace)]
+pub struct MatchPool {
+    pub config: Pubkey,
+    pub sponsor: Pubkey,
+    /// Match ratio in basis points (10_000 = 1:1).
+    pub ratio_bps: u16,
+    /// Lamports the pool may still match before reaching its cap.
+    pub remaining_budget: u64,
+    /// Lamports matched so far.
+    pub total_matched: u64,
+    pub bump: u8,
+    /// Unix timestamp of the last create/fund/match.
+    pub last_activity_ts: i64,
+}
+
+impl MatchPool {
+    /// Compute the match owed for `lamports` and record it against the budget.
+    /// Never exceeds the remaining budget or the pool's spendable balance (`available`).
+    pub(crate) fn apply_match(&mut self, lamports: u64, available: u64) -> Result<u64> {
+        let owed = (lamports as u128 * self.ratio_bps as u128 / BPS_DENOMINATOR as u128)
+            .min(u64::MAX as u128) as u64;
+        let matched = owed.min(self.remaining_budget).min(available);
+
+        self.remaining_budget -= matched;
+        self.total_matched = self
+            .total_matched
+            .checked_add(matched)
+            .ok_or(ConditionalError::MathOverflow)?;
+        Ok(matched)
+    }
+}
+
+/// One escrowed tranche of a milestone-based payment.
+#[account]
+#[derive(InitSpace)]
+pub struct Milestone {
+    pub config: Pubkey,
+    /// Account that funded the tranche.
+    pub payer: Pubkey,
+    /// Signer allowed to mark the milestone complete.
+    pub approver: Pubkey,
+    pub index: u32,
+    pub amount: u64,
+    pub approved: bool,
+    pub released: bool,
+    pub bump: u8,
+    /// Unix timestamp of the last create/approve/release.
+    pub last_activity_ts: i64,
+}
+
+/// Lamports vesting linearly to the config's recipient over `[start_ts, end_ts]`.
+#[account]
+#[derive(InitSpace)]
+pub struct Stream {
+    pub config: Pubkey,
+    /// Account that funded the stream; receives the unvested rest on cancel.
+    pub payer: Pubkey,
+    pub index: u32,
+    pub total: u64,
+    pub withdrawn: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub bump: u8,
+}
+
+impl Stream {
+    /// Lamports vested at unix time `now`.
+    pub fn vested(&self, now: i64) -> u64 {
+        if now <= self.start_ts {
+            return 0;
+        }
+        if now >= self.end_ts {
+            return self.total;
+        }
+        let elapsed = (now - self.start_ts) as u128;
+        let duration = (self.end_ts - self.start_ts) as u128;
+        (self.total as u128 * elapsed / duration) as u64
+    }
+
+    /// Vested lamports not yet withdrawn at unix time `now`.
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.withdrawn)
+    }
+}
+
+/// Escrowed transfer released when the recipient submits a hash matching `commitment`.
+#[account]
+#[derive(InitSpace)]
+pub struct Delivery {
+    pub config: Pubkey,
+    /// Account that funded the escrow.
+    pub payer: Pubkey,
+    pub index: u32,
+    pub amount: u64,
+    /// Hash of the expected deliverable, set by the sender.
+    pub commitment: [u8; 32],
+    /// Unix timestamp after which an unacknowledged delivery may be refunded.
+    pub deadline: i64,
+    /// When the recipient acknowledged delivery, if they have.
+    pub acknowledged_at: Option<i64>,
+    pub bump: u8,
+}
+
+/// Escrowed transfer payable to the config's recipient from `unlock_slot`;
+/// see `schedule_transfer`.
+#[account]
+#[derive(InitSpace)]
+pub struct ScheduledTransfer {
+    pub config: Pubkey,
+    /// Payer-chosen id, part of the PDA seeds.
+    pub id: u64,
+    /// Funded the escrow and paid the rent, which returns to it.
+    pub payer: Pubkey,
+    pub lamports: u64,
+    /// First slot `execute_scheduled` may run in; `cancel_scheduled` works
+    /// only before it.
+    pub unlock_slot: u64,
+    pub bump: u8,
+    /// First slot `execute_scheduled` may no longer run in and
+    /// `refund_scheduled` may; `None` never expires.
+    pub expiry_slot: Option<u64>,
+    /// Space for future fields; see `state::layout`.
+    pub reserved: [u8; state::layout::RESERVED_LEN - 9],
+}
+
+impl ScheduledTransfer {
+    /// Whether the transfer may execute, rather than be cancelled, at `slot`.
+    pub fn is_unlocked(&self, slot: u64) -> bool {
+        slot >= self.unlock_slot
+    }
+
+    /// Whether the transfer may only be refunded at `slot`.
+    pub fn is_expired(&self, slot: u64) -> bool {
+        matches!(self.expiry_slot, Some(expiry) if slot >= expiry)
+    }
+}
+
+/// "First to fund the target wins" incentive, escrowing the reward.
+#[account]
+#[derive(InitSpace)]
+pub struct Bounty {
+    pub sponsor: Pubkey,
+    /// Recipient the qualifying send must go to.
+    pub target: Pubkey,
+    /// Smallest single send that wins.
+    pub min_lamports: u64,
+    pub reward: u64,
+    /// Unix timestamp after which the bounty can no longer be won.
+    pub expires_at: i64,
+    /// First qualifying sender, once there is one.
+    pub winner: Option<Pubkey>,
+    pub bump: u8,
+}
+
+/// Recipient-side auto-forwarding rule; the PDA itself receives the funds.
+#[account]
+#[derive(InitSpace)]
+pub struct Sweep {
+    pub owner: Pubkey,
+    /// Where balances at or above the threshold are forwarded.
+    pub cold_wallet: Pubkey,
+    /// Balance above rent that triggers forwarding.
+    pub threshold_lamports: u64,
+    pub bump: u8,
+    /// Highest `update_id` applied by `update_sweep`.
+    pub last_update_id: u64,
+}
+
+/// Reverse index from a recipient to the configs naming it as `to`, so payees
+/// can discover them without scanning program accounts.
+#[account]
+#[derive(InitSpace)]
+pub struct RecipientIndex {
+    pub recipient: Pubkey,
+    #[max_len(MAX_INDEXED_CONFIGS)]
+    pub configs: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl RecipientIndex {
+    /// Record `config` under `recipient`; also stamps a freshly created index.
+    /// Listing is best-effort: anyone can create configs paying any address,
+    /// so a full index leaves `config` out rather than blocking it. Returns
+    /// whether `config` is listed.
+    pub(crate) fn add(&mut self, recipient: Pubkey, config: Pubkey, bump: u8) -> bool {
+        self.recipient = recipient;
+        self.bump = bump;
+        if self.configs.contains(&config) {
+            return true;
+        }
+        if self.configs.len() >= MAX_INDEXED_CONFIGS {
+            return false;
+        }
+        self.configs.push(config);
+        true
+    }
+
+    pub(crate) fn remove(&mut self, config: &Pubkey) {
+        self.configs.retain(|entry| entry != config);
+    }
+}
+
+/// Proof of a `send_with_receipt` payment, addressable by its external reference.
+#[account]
+#[derive(InitSpace)]
+pub struct Receipt {
+    pub config: Pubkey,
+    /// Payer-supplied id (e.g. an invoice number), part of the PDA seeds.
+    pub reference: [u8; 32],
+    /// Account that paid the receipt's rent.
+    pub payer: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub bump: u8,
+    /// Balances of `from` and `to` right after the payment; zero in receipts
+    /// written before they were recorded and brought over by `migrate_receipt`.
+    pub from_balance: u64,
+    pub to_balance: u64,
+}
+
+/// Recovery committee for a config's root authority.
+#[account]
+#[derive(InitSpace)]
+pub struct Recovery {
+    pub config: Pubkey,
+    #[max_len(MAX_RECOVERY_MEMBERS)]
+    pub committee: Vec<Pubkey>,
+    /// Approvals needed to execute a proposal.
+    pub threshold: u8,
+    /// Wait between announcing a proposal and executing it.
+    pub delay_seconds: i64,
+    pub pending: Option<PendingRecovery>,
+    pub bump: u8,
+}
+
+/// API key hashes an exporter accepts for a config's event stream.
+#[account]
+#[derive(InitSpace)]
+pub struct ViewerKeys {
+    pub config: Pubkey,
+    /// `HMAC-SHA256(key = api_key, message = config address)` per key.
+    #[max_len(MAX_VIEWER_KEYS)]
+    pub key_hashes: Vec<[u8; 32]>,
+    pub bump: u8,
+}
+
+/// Addresses besides `config.to` that `send_if_over_threshold` may pay, for
+/// payroll-style configs with many payees.
+#[account]
+#[derive(InitSpace)]
+pub struct RecipientList {
+    pub config: Pubkey,
+    #[max_len(MAX_LISTED_RECIPIENTS)]
+    pub recipients: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl RecipientList {
+    pub fn contains(&self, recipient: &Pubkey) -> bool {
+        self.recipients.contains(recipient)
+    }
+}
+
+/// An announced proposal to replace the root authority.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub struct PendingRecovery {
+    pub new_authority: Pubkey,
+    #[max_len(MAX_RECOVERY_MEMBERS)]
+    pub approvals: Vec<Pubkey>,
+    /// Earliest unix time the proposal may be executed.
+    pub unlocks_at: i64,
+}
+
+impl Recovery {
+    pub(crate) fn require_member(&self, key: &Pubkey) -> Result<()> {
+        require!(
+            self.committee.contains(key),
+            ConditionalError::NotRecoveryMember
+        );
+        Ok(())
+    }
+}
+
+/// Nonces a sender has used with `send_with_nonce`: a window of
+/// `NONCE_WINDOW` nonces starting at `base`. Using a nonce past the window
+/// slides it up so that nonce is the last one tracked.
+#[account]
+#[derive(InitSpace)]
+pub struct SendNonces {
+    pub config: Pubkey,
+    /// The config's `from` when the nonces were used.
+    pub sender: Pubkey,
+    /// Lowest nonce tracked; every nonce below it counts as used.
+    pub base: u64,
+    /// Bit `i % 64` of word `i / 64` is set once `base + i` has been used.
+    pub used: [u64; NONCE_WINDOW as usize / 64],
+    pub bump: u8,
+    /// Space for future fields; see `state::layout`.
+    pub reserved: [u8; state::layout::RESERVED_LEN],
+}
+
+impl SendNonces {
+    fn is_used(&self, offset: u64) -> bool {
+        self.used[(offset / 64) as usize] & (1 << (offset % 64)) != 0
+    }
+
+    /// Mark `nonce` used, sliding the window up if it lies past it. `false`
+    /// if it was already used or is below the window.
+    pub fn claim(&mut self, nonce: u64) -> bool {
+        let Some(offset) = nonce.checked_sub(self.base) else {
+            return false;
+        };
+        let offset = if offset < NONCE_WINDOW {
+            offset
+        } else {
+            let shift = offset - (NONCE_WINDOW - 1);
+            let mut used = [0; NONCE_WINDOW as usize / 64];
+            for kept in 0..NONCE_WINDOW.saturating_sub(shift) {
+                if self.is_used(kept + shift) {
+                    used[(kept / 64) as usize] |= 1 << (kept % 64);
+                }
+            }
+            self.used = used;
+            self.base += shift;
+            NONCE_WINDOW - 1
+        };
+        if self.is_used(offset) {
+            return false;
+        }
+        self.used[(offset / 64) as usize] |= 1 << (offset % 64);
+        true
+    }
+}
+
+/// A send awaiting the config's approvers; see `propose_transfer`.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingTransfer {
+    pub config: Pubkey,
+    /// Proposer-chosen id, part of the PDA seeds.
+    pub id: u64,
+    /// Paid the rent, which returns to it on execute or cancel.
+    pub proposer: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    #[max_len(MAX_APPROVERS)]
+    pub approvals: Vec<Pubkey>,
+    pub created_at: i64,
+    pub bump: u8,
+    /// Space for future fields; see `state::layout`.
+    pub reserved: [u8; state::layout::RESERVED_LEN],
+}
@@ -0,0 +1,35 @@
+//! Reserved space in account layouts, so upgrades can add fields in place.
+//!
+//! Accounts are Borsh structs sized by `InitSpace`; appending a field makes
+//! existing accounts too short to deserialize until they are resized
+//! (`migrate_config`, `migrate_global`, `migrate_receipt`). New layouts end in
+//! `reserved: [u8; RESERVED_LEN]` instead, and later fields are carved from it:
+//!
+//! - A new field goes immediately before `reserved`, which shrinks by the
+//!   field's `INIT_SPACE`: `reserved: [u8; RESERVED_LEN - 9]` after adding an
+//!   `Option<u64>`. The account size stays the same; the
+//!   `reserved_layouts_keep_their_size` test pins it.
+//! - The program never writes `reserved`, so it is all zeros on every account.
+//!   A carved field must decode from zeros as its default (`0`, `false`,
+//!   `None`, empty `Vec`); give it an explicit "unset" meaning if not.
+//! - Carve a variable-size field (`Option`, `Vec`, `String`) only while
+//!   nothing follows `reserved`. Borsh reads `None` from one zero byte, not its
+//!   `INIT_SPACE`, so every field after the reserve shifts by the difference
+//!   against accounts written before the carve. `Config` carved two
+//!   `Option<_>` fields (`close_requested_at`, `escrow_expiry_slot`) ahead of
+//!   its appended `delegates` and `auto_adjust`, so
+//!   `Config::try_deserialize_versioned` adds back the 8 bytes each leaves
+//!   unread when decoding older versions. Prefer a fixed-size encoding with a
+//!   zero "unset" value, e.g. a `u64` slot where `0` means none.
+//! - Once the reserve is spent, or for a field larger than what is left of it,
+//!   the field is appended after `reserved` and the account needs a migrate
+//!   instruction again; update the pinned size.
+//!
+//! `PendingTransfer`, `SendNonces`, and `ScheduledTransfer` carry a reserve.
+//! `Config` had one, but it is spent (`reserved: [u8; RESERVED_LEN - 64]` is
+//! empty), so new `Config` fields are appended and need `migrate_config`. The
+//! other layouts predate this policy and keep their append-and-migrate
+//! upgrades.
+
+/// Bytes reserved at the end of each new account layout.
+pub const RESERVED_LEN: usize = 64;
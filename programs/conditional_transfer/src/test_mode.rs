@@ -0,0 +1,110 @@
+//! Clock and price mocks for deterministic tests under `solana-program-test`.
+//!
+//! With the `test-mode` feature, `set_test_mocks` writes a [`TestMocks`] PDA
+//! and the send paths read it when it is passed as a remaining account: the
+//! clock is shifted by `clock_offset_seconds` before the time window,
+//! freshness, and vesting checks, and `AmountUnit::UsdCents` converts at
+//! `lamports_per_usd_cent`. Passed in place of the Pyth price feed, the PDA
+//! also prices `threshold_usd_cents` and `adjust_threshold` at
+//! `sol_usd_cents`. Without the feature the mocks are compiled out:
+//! there is no `set_test_mocks` instruction and the real clock and feed are
+//! used.
+
+use anchor_lang::prelude::*;
+
+use crate::conditions::SolUsdPrice;
+use crate::{pyth, AmountUnit, ConditionalError, TypedAmount};
+
+pub const TEST_MOCKS_SEED: &[u8] = b"test_mocks";
+
+/// Mocked sysvar and oracle values.
+#[account]
+#[derive(InitSpace)]
+pub struct TestMocks {
+    /// Seconds added to `Clock::unix_timestamp`.
+    pub clock_offset_seconds: i64,
+    /// Price for `AmountUnit::UsdCents`; `0` leaves the unit unsupported.
+    pub lamports_per_usd_cent: u64,
+    pub bump: u8,
+    /// SOL/USD price in US cents standing in for the Pyth feed; `0` leaves
+    /// the feed required.
+    pub sol_usd_cents: u64,
+}
+
+impl TestMocks {
+    /// `clock` with the mocked offset applied.
+    pub fn apply(&self, mut clock: Clock) -> Clock {
+        clock.unix_timestamp = clock
+            .unix_timestamp
+            .saturating_add(self.clock_offset_seconds);
+        clock
+    }
+
+    /// The mocked SOL/USD price, if one is set.
+    pub fn sol_usd_price(&self) -> Option<SolUsdPrice> {
+        let price = i64::try_from(self.sol_usd_cents).ok()?;
+        (price > 0).then_some(SolUsdPrice {
+            price,
+            exponent: -2,
+        })
+    }
+
+    /// `amount` in lamports at the mocked USD price.
+    pub fn to_lamports(&self, amount: &TypedAmount) -> Result<u64> {
+        match amount.unit {
+            AmountUnit::UsdCents if self.lamports_per_usd_cent > 0 => {
+                require!(amount.value > 0, ConditionalError::InvalidAmount);
+                amount
+                    .value
+                    .checked_mul(self.lamports_per_usd_cent)
+                    .ok_or_else(|| error!(ConditionalError::MathOverflow))
+            }
+            _ => amount.to_lamports(),
+        }
+    }
+}
+
+/// The mocks among `remaining_accounts`, if any.
+#[cfg(feature = "test-mode")]
+fn find(remaining_accounts: &[AccountInfo]) -> Result<Option<TestMocks>> {
+    let address = Pubkey::find_program_address(&[TEST_MOCKS_SEED], &crate::ID).0;
+    remaining_accounts
+        .iter()
+        .find(|account| account.key() == address && account.owner == &crate::ID)
+        .map(|account| TestMocks::try_deserialize(&mut &account.try_borrow_data()?[..]))
+        .transpose()
+}
+
+/// The clock the send conditions see.
+pub fn clock(remaining_accounts: &[AccountInfo]) -> Result<Clock> {
+    let clock = Clock::get()?;
+    #[cfg(feature = "test-mode")]
+    if let Some(mocks) = find(remaining_accounts)? {
+        return Ok(mocks.apply(clock));
+    }
+    let _ = remaining_accounts;
+    Ok(clock)
+}
+
+/// `amount` in lamports, priced by the mocks when present.
+pub fn to_lamports(amount: &TypedAmount, remaining_accounts: &[AccountInfo]) -> Result<u64> {
+    #[cfg(feature = "test-mode")]
+    if let Some(mocks) = find(remaining_accounts)? {
+        return mocks.to_lamports(amount);
+    }
+    let _ = remaining_accounts;
+    amount.to_lamports()
+}
+
+/// The SOL/USD price from `price_feed`: the mocked price when the feed is the
+/// mocks PDA, else the Pyth update checked by `pyth::sol_usd_price`.
+pub fn sol_usd_price(price_feed: &AccountInfo, now: i64) -> Result<SolUsdPrice> {
+    #[cfg(feature = "test-mode")]
+    if let Some(price) = find(std::slice::from_ref(price_feed))?
+        .as_ref()
+        .and_then(TestMocks::sol_usd_price)
+    {
+        return Ok(price);
+    }
+    pyth::sol_usd_price(price_feed, now)
+}
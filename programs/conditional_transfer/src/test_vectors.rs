@@ -0,0 +1,756 @@
+//! Canonical test vectors for client implementations.
+//!
+//! `cargo test dump_test_vectors` writes `test-vectors/vectors.json` with
+//! instruction data, PDA addresses, account layouts, and event bytes built from
+//! fixed inputs. TS/Python/Go clients can check their serializers against it.
+//! PDAs depend on the program id in `declare_id!`, so regenerate after changing it.
+
+use anchor_lang::{system_program, AccountSerialize, Event, InstructionData};
+use anchor_spl::token;
+use serde_json::{json, Value};
+
+use crate::*;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+fn ix_vector(name: &str, data: Vec<u8>) -> Value {
+    json!({ "name": name, "discriminator": hex(&data[..8]), "data": hex(&data) })
+}
+
+fn pda_vector(name: &str, seeds: &[&[u8]]) -> Value {
+    let (address, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    json!({
+        "name": name,
+        "seeds": seeds.iter().map(|s| hex(s)).collect::<Vec<_>>(),
+        "address": address.to_string(),
+        "bump": bump,
+    })
+}
+
+/// `space` is the allocated account size; `size` is the serialized length of the sample.
+fn account_vector<T: AccountSerialize + Space>(name: &str, account: &T) -> Value {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    json!({
+        "name": name,
+        "space": 8 + T::INIT_SPACE,
+        "size": data.len(),
+        "data": hex(&data),
+    })
+}
+
+/// Fixed opening for the sample private threshold.
+fn sample_opening() -> solana_zk_token_sdk::encryption::pedersen::PedersenOpening {
+    solana_zk_token_sdk::encryption::pedersen::PedersenOpening::from_bytes(&[7; 32]).unwrap()
+}
+
+fn sample_config() -> Config {
+    Config {
+        authority: key(1),
+        from: key(2),
+        to: key(3),
+        threshold_lamports: 100_000_000,
+        bump: 254,
+        event_seq: 7,
+        default_memo: "invoice".to_string(),
+        allow_executable_recipient: false,
+        authorities: vec![AuthorityEntry {
+            key: key(4),
+            permissions: PERM_UPDATE_THRESHOLD | PERM_PAUSE,
+        }],
+        below_threshold_behavior: BelowThresholdBehavior::Noop,
+        restrict_cpi: true,
+        cpi_allowlist: vec![key(12)],
+        epoch_reporting: true,
+        proposal_nonce: 3,
+        max_config_age_seconds: 7_776_000,
+        last_attested_ts: 1_700_000_000,
+        beneficiary: Beneficiary::TokenAccount,
+        policy: Some(key(13)),
+        policy_overrides: PolicyOverrides {
+            threshold_lamports: Some(300_000_000),
+            below_threshold_behavior: None,
+        },
+        recipient_hash: None,
+        large_transfer_lamports: 10_000_000_000,
+        anomaly_guard: AnomalyGuard {
+            multiplier: 10,
+            require_approval: true,
+            average_lamports: 150_000_000,
+            samples: 42,
+        },
+        seed_from: key(2),
+        seed_route: key(4),
+        max_lamports: 50_000_000_000,
+        valid_from_unix: Some(1_735_689_600),
+        valid_until_unix: Some(1_751_241_600),
+        pending_authority: Some(key(16)),
+        recipient_owners: vec![token::ID],
+        stats: TransferStats {
+            total_transferred: 12_500_000_000,
+            transfer_count: 50,
+            last_transfer_slot: 249_999_000,
+        },
+        rate_limit: RateLimit {
+            refill_per_second: 115_740,
+            capacity: 10_000_000_000,
+            updated_at: 1_699_990_000,
+            drained: 2_500_000_000,
+        },
+        last_update_id: 17,
+        threshold_usd_cents: Some(2_000),
+        seeded_recipient: Some(SeededRecipient {
+            base: key(20),
+            owner: system_program::ID,
+        }),
+        paused: false,
+        approvers: Approvers {
+            members: vec![key(21), key(22), key(23)],
+            quorum: 2,
+        },
+        private_threshold: Some(private_threshold::commit_threshold(
+            5_000_000_000,
+            &sample_opening(),
+        )),
+        fee_bps: 25,
+        fee_collector: key(24),
+        close_requested_at: None,
+        monotonic_threshold: true,
+        comparison_mode: ComparisonMode::Gt,
+        log_rejections: true,
+        last_auto_adjust_unix: 1_700_000_000,
+        escrow_expiry_slot: Some(300_000_000),
+        version: CONFIG_VERSION,
+        reserved: [0; state::layout::RESERVED_LEN - 64],
+        delegates: vec![key(25)],
+        auto_adjust: Some(AutoAdjust {
+            target_usd_cents: 2_000,
+            min_lamports: 50_000_000,
+            max_lamports: 500_000_000,
+            interval_seconds: 3_600,
+        }),
+        token_threshold: Some(TokenThreshold {
+            mint: key(14),
+            threshold_amount: 5_000_000,
+        }),
+        bridge_route: Some(BridgeRoute {
+            wormhole_program: WORMHOLE_MAINNET_PROGRAM_ID,
+            chain: 2,
+            recipient: [0xaa; 32],
+        }),
+        mint_allowlist: vec![token::spl_token::native_mint::ID],
+    }
+}
+
+/// Build every vector from fixed inputs.
+fn build() -> Value {
+    let config =
+        Pubkey::find_program_address(&[CONFIG_SEED, key(2).as_ref(), key(4).as_ref()], &crate::ID)
+            .0;
+    let sponsor = key(5);
+
+    let instructions = vec![
+        ix_vector(
+            "initialize",
+            instruction::Initialize {
+                route: key(4),
+                to: key(3),
+                threshold_lamports: 100_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "clone_config",
+            instruction::CloneConfig {
+                route: key(16),
+                to: key(15),
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "send_if_over_threshold",
+            instruction::SendIfOverThreshold {
+                lamports: 250_000_000,
+                memo: Some("INV-2024-0042".to_string()),
+            }
+            .data(),
+        ),
+        ix_vector(
+            "send_if_over_threshold_v2",
+            instruction::SendIfOverThresholdV2 {
+                amount: TypedAmount {
+                    unit: AmountUnit::MilliSol,
+                    value: 250,
+                },
+                memo: Some("INV-2024-0043".to_string()),
+            }
+            .data(),
+        ),
+        ix_vector(
+            "update_threshold",
+            instruction::UpdateThreshold {
+                new_threshold_lamports: 200_000_000,
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "update_limits",
+            instruction::UpdateLimits {
+                threshold_lamports: 100_000_000,
+                max_lamports: 50_000_000_000,
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "deposit",
+            instruction::Deposit {
+                lamports: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "release",
+            instruction::Release {
+                lamports: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_split_recipients",
+            instruction::SetSplitRecipients {
+                entries: vec![
+                    SplitEntry {
+                        recipient: key(3),
+                        share_bps: 7_000,
+                    },
+                    SplitEntry {
+                        recipient: key(19),
+                        share_bps: 3_000,
+                    },
+                ],
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "send_split_if_over_threshold",
+            instruction::SendSplitIfOverThreshold {
+                lamports: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_seeded_recipient",
+            instruction::SetSeededRecipient {
+                seeded_recipient: Some(SeededRecipient {
+                    base: key(20),
+                    owner: system_program::ID,
+                }),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "send_to_seeded_address",
+            instruction::SendToSeededAddress {
+                lamports: 250_000_000,
+                seed: "deposit-0001".to_string(),
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_payout_remainder",
+            instruction::SetPayoutRemainder {
+                remainder: RemainderPolicy::DustAccount(key(17)),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_recipient_owners",
+            instruction::SetRecipientOwners {
+                owners: vec![system_program::ID, token::ID],
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "reset_stats",
+            instruction::ResetStats {
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "verify_payment",
+            instruction::VerifyPayment {
+                reference: [9u8; 32],
+                min_lamports: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "add_recipient",
+            instruction::AddRecipient {
+                recipient: key(18),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "propose_authority",
+            instruction::ProposeAuthority {
+                new_authority: Some(key(16)),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "accept_authority",
+            instruction::AcceptAuthority {
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "update_rate_limit",
+            instruction::UpdateRateLimit {
+                capacity: 10_000_000_000,
+                refill_per_second: 115_740,
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_approvers",
+            instruction::SetApprovers {
+                members: vec![key(21), key(22), key(23)],
+                quorum: 2,
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "propose_transfer",
+            instruction::ProposeTransfer {
+                id: 1,
+                to: key(3),
+                lamports: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_paused",
+            instruction::SetPaused {
+                paused: true,
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_usd_threshold",
+            instruction::SetUsdThreshold {
+                threshold_usd_cents: Some(2_000),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_auto_adjust",
+            instruction::SetAutoAdjust {
+                auto_adjust: Some(AutoAdjust {
+                    target_usd_cents: 2_000,
+                    min_lamports: 50_000_000,
+                    max_lamports: 500_000_000,
+                    interval_seconds: 3_600,
+                }),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector("adjust_threshold", instruction::AdjustThreshold {}.data()),
+        ix_vector("get_config", instruction::GetConfig {}.data()),
+        ix_vector(
+            "set_private_threshold",
+            instruction::SetPrivateThreshold {
+                commitment: Some(private_threshold::commit_threshold(
+                    5_000_000_000,
+                    &sample_opening(),
+                )),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_fee",
+            instruction::SetFee {
+                fee_bps: 25,
+                fee_collector: key(24),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "add_delegate",
+            instruction::AddDelegate {
+                delegate: key(25),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "update_comparison_mode",
+            instruction::UpdateComparisonMode {
+                comparison_mode: ComparisonMode::Eq,
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "update_time_window",
+            instruction::UpdateTimeWindow {
+                valid_from_unix: Some(1_735_689_600),
+                valid_until_unix: Some(1_751_241_600),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "update_addresses",
+            instruction::UpdateAddresses {
+                new_from: key(6),
+                new_to: key(7),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_hashed_recipient",
+            instruction::SetHashedRecipient {
+                recipient_hash: recipient_hash(&config, &key(7)),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "update_default_memo",
+            instruction::UpdateDefaultMemo {
+                memo: "invoice".to_string(),
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "set_authority_permissions",
+            instruction::SetAuthorityPermissions {
+                key: key(4),
+                permissions: PERM_ALL,
+                update_id: 18,
+                recent_slot: 250_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "send_tokens_if_over_threshold",
+            instruction::SendTokensIfOverThreshold { amount: 5_000_000 }.data(),
+        ),
+        ix_vector(
+            "send_bridged",
+            instruction::SendBridged {
+                lamports: 250_000_000,
+                nonce: 7,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "create_match_pool",
+            instruction::CreateMatchPool {
+                ratio_bps: 10_000,
+                cap_lamports: 1_000_000_000,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "create_milestone",
+            instruction::CreateMilestone {
+                index: 3,
+                amount: 500_000_000,
+                approver: key(8),
+            }
+            .data(),
+        ),
+        ix_vector(
+            "create_stream",
+            instruction::CreateStream {
+                index: 4,
+                total: 3_000_000_000,
+                start_ts: 1_735_689_600,
+                end_ts: 1_767_225_600,
+            }
+            .data(),
+        ),
+        ix_vector(
+            "withdraw_stream",
+            instruction::WithdrawStream {
+                lamports: 250_000_000,
+            }
+            .data(),
+        ),
+    ];
+    // `set_test_mocks` only exists in `test-mode` builds.
+    #[cfg(feature = "test-mode")]
+    let instructions = {
+        let mut instructions = instructions;
+        instructions.push(ix_vector(
+            "set_test_mocks",
+            instruction::SetTestMocks {
+                clock_offset_seconds: 86_400,
+                lamports_per_usd_cent: 60_000,
+                sol_usd_cents: 15_000,
+            }
+            .data(),
+        ));
+        instructions
+    };
+
+    let pdas = vec![
+        pda_vector("config", &[CONFIG_SEED, key(2).as_ref(), key(4).as_ref()]),
+        pda_vector(
+            "match_pool",
+            &[MATCH_POOL_SEED, config.as_ref(), sponsor.as_ref()],
+        ),
+        pda_vector(
+            "milestone",
+            &[MILESTONE_SEED, config.as_ref(), &3u32.to_le_bytes()],
+        ),
+        pda_vector(
+            "stream",
+            &[STREAM_SEED, config.as_ref(), &4u32.to_le_bytes()],
+        ),
+        pda_vector("global", &[GLOBAL_SEED]),
+        pda_vector("recipient_index", &[RECIPIENT_INDEX_SEED, key(3).as_ref()]),
+        pda_vector("receipt", &[RECEIPT_SEED, config.as_ref(), &[9u8; 32]]),
+        pda_vector("recovery", &[RECOVERY_SEED, config.as_ref()]),
+        pda_vector("viewer_keys", &[VIEWER_KEYS_SEED, config.as_ref()]),
+        pda_vector("recipient_list", &[RECIPIENT_LIST_SEED, config.as_ref()]),
+        pda_vector("split_table", &[SPLIT_TABLE_SEED, config.as_ref()]),
+        pda_vector("bridge_custody", &[BRIDGE_CUSTODY_SEED, config.as_ref()]),
+        pda_vector("emitter", &[BRIDGE_EMITTER_SEED]),
+        pda_vector("escrow", &[ESCROW_SEED, config.as_ref()]),
+        pda_vector("event_schema", &[EVENT_SCHEMA_SEED]),
+    ];
+
+    let accounts = vec![
+        account_vector("Config", &sample_config()),
+        account_vector(
+            "MatchPool",
+            &MatchPool {
+                config,
+                sponsor,
+                ratio_bps: 5_000,
+                remaining_budget: 1_000_000_000,
+                total_matched: 50_000_000,
+                bump: 253,
+                last_activity_ts: 1_700_000_000,
+            },
+        ),
+        account_vector(
+            "Milestone",
+            &Milestone {
+                config,
+                payer: key(2),
+                approver: key(8),
+                index: 3,
+                amount: 500_000_000,
+                approved: true,
+                released: false,
+                bump: 252,
+                last_activity_ts: 1_700_000_000,
+            },
+        ),
+    ];
+
+    let events = vec![
+        json!({
+            "name": "AdminForceTransferExecuted",
+            "data": hex(
+                &AdminForceTransferExecuted {
+                    seq: 8,
+                    config,
+                    source: key(9),
+                    destination: key(10),
+                    lamports: 1_000,
+                    admin: key(11),
+                    authority: key(1),
+                    use_count: 1,
+                    timestamp: 1_700_000_000,
+                }
+                .data()
+            ),
+        }),
+        json!({
+            "name": "TransferSkipped",
+            "data": hex(
+                &TransferSkipped {
+                    seq: 9,
+                    config,
+                    from: key(2),
+                    to: key(3),
+                    lamports: 1_000,
+                    threshold_lamports: 100_000_000,
+                    timestamp: 1_700_000_000,
+                }
+                .data()
+            ),
+        }),
+        json!({
+            "name": "TransferRejected",
+            "data": hex(
+                &TransferRejected {
+                    seq: 9,
+                    config,
+                    from: key(2),
+                    to: key(3),
+                    lamports: 1_000,
+                    error_code: 6000,
+                    timestamp: 1_700_000_000,
+                }
+                .data()
+            ),
+        }),
+        json!({
+            "name": "TransferExecuted",
+            "data": hex(
+                &TransferExecuted {
+                    seq: 10,
+                    config,
+                    from: key(2),
+                    to: key(3),
+                    lamports: 250_000_000,
+                    references: vec![key(4)],
+                    from_balance: 750_000_000,
+                    to_balance: 1_250_000_000,
+                    memo: "INV-2024-0042".to_string(),
+                    timestamp: 1_700_000_000,
+                }
+                .data()
+            ),
+        }),
+        json!({
+            "name": "ConfigInitialized",
+            "data": hex(
+                &ConfigInitialized {
+                    seq: 0,
+                    config,
+                    authority: key(1),
+                    from: key(2),
+                    to: key(3),
+                    threshold_lamports: 100_000_000,
+                    cloned_from: None,
+                    timestamp: 1_700_000_000,
+                }
+                .data()
+            ),
+        }),
+        json!({
+            "name": "ThresholdUpdated",
+            "data": hex(
+                &ThresholdUpdated {
+                    seq: 11,
+                    config,
+                    changed_by: key(1),
+                    old_threshold_lamports: 100_000_000,
+                    threshold_lamports: 200_000_000,
+                    timestamp: 1_700_000_000,
+                }
+                .data()
+            ),
+        }),
+        json!({
+            "name": "AddressesUpdated",
+            "data": hex(
+                &AddressesUpdated {
+                    seq: 12,
+                    config,
+                    changed_by: key(1),
+                    old_from: key(2),
+                    old_to: key(3),
+                    from: key(2),
+                    to: key(15),
+                    timestamp: 1_700_000_000,
+                }
+                .data()
+            ),
+        }),
+    ];
+
+    let proposals = vec![json!({
+        "name": "ConfigProposal",
+        "data": hex(
+            &ConfigProposal {
+                config,
+                nonce: 3,
+                recent_slot: 250_000_000,
+                update_id: 18,
+                threshold_lamports: Some(200_000_000),
+                to: None,
+                default_memo: Some("payroll".to_string()),
+            }
+            .try_to_vec()
+            .unwrap()
+        ),
+    })];
+
+    json!({
+        "program_id": crate::ID.to_string(),
+        "event_schema_version": EVENT_SCHEMA_VERSION,
+        "instructions": instructions,
+        "pdas": pdas,
+        "accounts": accounts,
+        "events": events,
+        "proposals": proposals,
+    })
+}
+
+#[test]
+fn dump_test_vectors() {
+    let vectors = build();
+    assert_eq!(vectors, build(), "test vectors must be deterministic");
+
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test-vectors");
+    std::fs::create_dir_all(&dir).unwrap();
+    let json = serde_json::to_string_pretty(&vectors).unwrap();
+    std::fs::write(dir.join("vectors.json"), json + "\n").unwrap();
+}
@@ -0,0 +1,152 @@
+//! Runs `conditional_transfer` natively under `solana-program-test` to check
+//! that a requested close waits out `CLOSE_COOLING_SECONDS` even once the
+//! vault has been withdrawn.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use conditional_transfer::{accounts, instruction, ConditionalError, CLOSE_COOLING_SECONDS};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+const THRESHOLD: u64 = 100_000_000;
+
+// Anchor's `entry` ties the account slice to the accounts' own lifetime, which
+// `processor!` cannot express; program-test keeps both alive for the whole call.
+fn conditional_transfer_entry<'a, 'b, 'c>(
+    program_id: &'a Pubkey,
+    accounts: &'b [AccountInfo<'c>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'c [AccountInfo<'c>] = unsafe { std::mem::transmute(accounts) };
+    conditional_transfer::entry(program_id, accounts, data)
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ix: Instruction,
+) -> Result<(), TransactionError> {
+    // A fresh blockhash each time, so resending the same close is a new
+    // transaction rather than a duplicate.
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let payer = &context.payer;
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn withdrawn_vault_does_not_skip_cooling_period() {
+    let program_test = ProgramTest::new(
+        "conditional_transfer",
+        conditional_transfer::ID,
+        processor!(conditional_transfer_entry),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let route = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let config = pda(&[b"config", payer.as_ref(), route.as_ref()]);
+    let recipient_index = pda(&[b"recipient_index", to.as_ref()]);
+    let vault = pda(&[b"vault", config.as_ref()]);
+    let event_authority = pda(&[b"__event_authority"]);
+
+    let initialize = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::Initialize {
+            authority: payer,
+            from: payer,
+            config,
+            recipient_index,
+            system_program: system_program::ID,
+            event_authority,
+            program: conditional_transfer::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Initialize {
+            route,
+            to,
+            threshold_lamports: THRESHOLD,
+        }
+        .data(),
+    };
+    process(&mut context, initialize).await.unwrap();
+    let fund = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::FundVault {
+            funder: payer,
+            config,
+            vault,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::FundVault {
+            lamports: THRESHOLD,
+        }
+        .data(),
+    };
+    process(&mut context, fund).await.unwrap();
+
+    let close = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::CloseConfig {
+            authority: payer,
+            config,
+            recipient_index: Some(recipient_index),
+            escrow: pda(&[b"escrow", config.as_ref()]),
+            vault,
+            event_authority,
+            program: conditional_transfer::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::CloseConfig {}.data(),
+    };
+    // With the vault funded, the first call only records the request.
+    process(&mut context, close.clone()).await.unwrap();
+    let withdraw = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::WithdrawVault {
+            from: payer,
+            config,
+            vault,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::WithdrawVault {}.data(),
+    };
+    process(&mut context, withdraw).await.unwrap();
+
+    let err = process(&mut context, close.clone()).await.unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ConditionalError::CloseCoolingDown.into())
+        )
+    );
+
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += CLOSE_COOLING_SECONDS;
+    context.set_sysvar(&clock);
+    process(&mut context, close).await.unwrap();
+    assert!(context
+        .banks_client
+        .get_account(config)
+        .await
+        .unwrap()
+        .is_none());
+}
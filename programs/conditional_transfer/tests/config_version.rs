@@ -0,0 +1,125 @@
+//! Runs `conditional_transfer` natively under `solana-program-test` to check
+//! that instructions other than `migrate_config` reject a config written under
+//! an older `CONFIG_VERSION`.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use conditional_transfer::{accounts, instruction, ConditionalError, Config, CONFIG_VERSION};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::AccountSharedData,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+const THRESHOLD: u64 = 100_000_000;
+
+// Anchor's `entry` ties the account slice to the accounts' own lifetime, which
+// `processor!` cannot express; program-test keeps both alive for the whole call.
+fn conditional_transfer_entry<'a, 'b, 'c>(
+    program_id: &'a Pubkey,
+    accounts: &'b [AccountInfo<'c>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'c [AccountInfo<'c>] = unsafe { std::mem::transmute(accounts) };
+    conditional_transfer::entry(program_id, accounts, data)
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ix: Instruction,
+) -> Result<(), TransactionError> {
+    let payer = &context.payer;
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn rejects_unmigrated_config() {
+    let program_test = ProgramTest::new(
+        "conditional_transfer",
+        conditional_transfer::ID,
+        processor!(conditional_transfer_entry),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let route = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let config = pda(&[b"config", payer.as_ref(), route.as_ref()]);
+    let event_authority = pda(&[b"__event_authority"]);
+    let initialize = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::Initialize {
+            authority: payer,
+            from: payer,
+            config,
+            recipient_index: pda(&[b"recipient_index", to.as_ref()]),
+            system_program: system_program::ID,
+            event_authority,
+            program: conditional_transfer::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Initialize {
+            route,
+            to,
+            threshold_lamports: THRESHOLD,
+        }
+        .data(),
+    };
+    process(&mut context, initialize).await.unwrap();
+
+    // Stamp the config with the previous version, as a config created before
+    // the last upgrade and not yet migrated would be.
+    let mut account = context
+        .banks_client
+        .get_account(config)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut cfg = Config::try_deserialize(&mut account.data.as_slice()).unwrap();
+    cfg.version = CONFIG_VERSION - 1;
+    let mut data = Vec::new();
+    cfg.try_serialize(&mut data).unwrap();
+    account.data[..data.len()].copy_from_slice(&data);
+    context.set_account(&config, &AccountSharedData::from(account));
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let pause = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::Update {
+            authority: payer,
+            config,
+            event_authority,
+            program: conditional_transfer::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SetPaused {
+            paused: true,
+            update_id: 0,
+            recent_slot: clock.slot,
+        }
+        .data(),
+    };
+    let err = process(&mut context, pause).await.unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ConditionalError::ConfigMigrationRequired.into())
+        )
+    );
+}
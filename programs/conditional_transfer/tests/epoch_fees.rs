@@ -0,0 +1,181 @@
+//! Runs `conditional_transfer` natively under `solana-program-test` to check
+//! that an epoch summary reports the recipient's leg as volume and the fee
+//! leg as fees, each counted once, and that the config's `stats` count only
+//! the recipient's leg.
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use conditional_transfer::{accounts, instruction, Config, EpochSummary};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, instruction::Instruction,
+    pubkey::Pubkey, signature::Signer, system_program, transaction::Transaction,
+};
+
+const THRESHOLD: u64 = 100_000_000;
+const FEE_BPS: u16 = 250;
+
+// Anchor's `entry` ties the account slice to the accounts' own lifetime, which
+// `processor!` cannot express; program-test keeps both alive for the whole call.
+fn conditional_transfer_entry<'a, 'b, 'c>(
+    program_id: &'a Pubkey,
+    accounts: &'b [AccountInfo<'c>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'c [AccountInfo<'c>] = unsafe { std::mem::transmute(accounts) };
+    conditional_transfer::entry(program_id, accounts, data)
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+async fn process(context: &mut ProgramTestContext, ixs: &[Instruction]) {
+    let payer = &context.payer;
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &[payer], blockhash);
+    context.banks_client.process_transaction(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn summary_counts_fees_separately_from_volume() {
+    let program_test = ProgramTest::new(
+        "conditional_transfer",
+        conditional_transfer::ID,
+        processor!(conditional_transfer_entry),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let route = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let collector = Pubkey::new_unique();
+    let config = pda(&[b"config", payer.as_ref(), route.as_ref()]);
+    let event_authority = pda(&[b"__event_authority"]);
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let epoch_summary = pda(&[
+        b"epoch_summary",
+        config.as_ref(),
+        &clock.epoch.to_le_bytes(),
+    ]);
+    let update = |data: Vec<u8>| Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::Update {
+            authority: payer,
+            config,
+            event_authority,
+            program: conditional_transfer::ID,
+        }
+        .to_account_metas(None),
+        data,
+    };
+
+    process(
+        &mut context,
+        &[
+            Instruction {
+                program_id: conditional_transfer::ID,
+                accounts: accounts::Initialize {
+                    authority: payer,
+                    from: payer,
+                    config,
+                    recipient_index: pda(&[b"recipient_index", to.as_ref()]),
+                    system_program: system_program::ID,
+                    event_authority,
+                    program: conditional_transfer::ID,
+                }
+                .to_account_metas(None),
+                data: instruction::Initialize {
+                    route,
+                    to,
+                    threshold_lamports: THRESHOLD,
+                }
+                .data(),
+            },
+            update(
+                instruction::SetFee {
+                    fee_bps: FEE_BPS,
+                    fee_collector: collector,
+                    update_id: 0,
+                    recent_slot: clock.slot,
+                }
+                .data(),
+            ),
+            update(
+                instruction::SetEpochReporting {
+                    enabled: true,
+                    update_id: 0,
+                    recent_slot: clock.slot,
+                }
+                .data(),
+            ),
+            Instruction {
+                program_id: conditional_transfer::ID,
+                accounts: accounts::OpenEpochSummary {
+                    payer,
+                    config,
+                    epoch_summary,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: instruction::OpenEpochSummary {}.data(),
+            },
+        ],
+    )
+    .await;
+
+    let send = |lamports: u64| Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: accounts::SendIfOverThreshold {
+            config,
+            from: payer,
+            to,
+            instructions: None,
+            match_pool: None,
+            epoch_summary: Some(epoch_summary),
+            system_program: system_program::ID,
+            token_program: None,
+            recipient_token_account: None,
+            global: pda(&[b"global"]),
+            sweep_destination: None,
+            policy: None,
+            approver: None,
+            recipient_list: None,
+            price_feed: None,
+            range_proof: None,
+            fee_collector: Some(collector),
+        }
+        .to_account_metas(None),
+        data: instruction::SendIfOverThreshold {
+            lamports,
+            memo: None,
+        }
+        .data(),
+    };
+    let sends = [THRESHOLD, 3 * THRESHOLD];
+    process(&mut context, &[send(sends[0]), send(sends[1])]).await;
+
+    let fee = |lamports: u64| lamports * FEE_BPS as u64 / 10_000;
+    let fees: u64 = sends.iter().map(|&lamports| fee(lamports)).sum();
+    let total: u64 = sends.iter().sum();
+    let account = context
+        .banks_client
+        .get_account(epoch_summary)
+        .await
+        .unwrap()
+        .unwrap();
+    let summary = EpochSummary::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(summary.count, 2);
+    assert_eq!(summary.fees, fees);
+    assert_eq!(summary.volume, total - fees);
+    let account = context
+        .banks_client
+        .get_account(config)
+        .await
+        .unwrap()
+        .unwrap();
+    let cfg = Config::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(cfg.stats.transfer_count, 2);
+    assert_eq!(cfg.stats.total_transferred, total - fees);
+    let banks = &mut context.banks_client;
+    assert_eq!(banks.get_balance(collector).await.unwrap(), fees);
+    assert_eq!(banks.get_balance(to).await.unwrap(), total - fees);
+}
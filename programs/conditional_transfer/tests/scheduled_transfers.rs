@@ -0,0 +1,243 @@
+//! Runs `conditional_transfer` natively under `solana-program-test` to check
+//! that scheduled transfers pass the send conditions when scheduled and again
+//! when executed.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use conditional_transfer::{accounts, instruction, ConditionalError};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+const THRESHOLD: u64 = 100_000_000;
+
+// Anchor's `entry` ties the account slice to the accounts' own lifetime, which
+// `processor!` cannot express; program-test keeps both alive for the whole call.
+fn conditional_transfer_entry<'a, 'b, 'c>(
+    program_id: &'a Pubkey,
+    accounts: &'b [AccountInfo<'c>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'c [AccountInfo<'c>] = unsafe { std::mem::transmute(accounts) };
+    conditional_transfer::entry(program_id, accounts, data)
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+/// A config paying `to` from the payer, who is also its authority.
+struct Route {
+    context: ProgramTestContext,
+    config: Pubkey,
+    to: Pubkey,
+}
+
+impl Route {
+    async fn new() -> Self {
+        let program_test = ProgramTest::new(
+            "conditional_transfer",
+            conditional_transfer::ID,
+            processor!(conditional_transfer_entry),
+        );
+        let context = program_test.start_with_context().await;
+        let payer = context.payer.pubkey();
+        let route = Pubkey::new_unique();
+        let mut this = Route {
+            config: pda(&[b"config", payer.as_ref(), route.as_ref()]),
+            to: Pubkey::new_unique(),
+            context,
+        };
+        let initialize = Instruction {
+            program_id: conditional_transfer::ID,
+            accounts: accounts::Initialize {
+                authority: payer,
+                from: payer,
+                config: this.config,
+                recipient_index: pda(&[b"recipient_index", this.to.as_ref()]),
+                system_program: system_program::ID,
+                event_authority: pda(&[b"__event_authority"]),
+                program: conditional_transfer::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::Initialize {
+                route,
+                to: this.to,
+                threshold_lamports: THRESHOLD,
+            }
+            .data(),
+        };
+        this.process(initialize).await.unwrap();
+        this
+    }
+
+    async fn process(&mut self, ix: Instruction) -> Result<(), TransactionError> {
+        let payer = &self.context.payer;
+        let blockhash = self
+            .context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+        self.context
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .map_err(|err| err.unwrap())
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    /// An `Update`-context setter signed by the authority.
+    fn update(&self, data: Vec<u8>) -> Instruction {
+        Instruction {
+            program_id: conditional_transfer::ID,
+            accounts: accounts::Update {
+                authority: self.context.payer.pubkey(),
+                config: self.config,
+                event_authority: pda(&[b"__event_authority"]),
+                program: conditional_transfer::ID,
+            }
+            .to_account_metas(None),
+            data,
+        }
+    }
+
+    fn scheduled(&self, id: u64) -> Pubkey {
+        pda(&[
+            b"scheduled_transfer",
+            self.config.as_ref(),
+            &id.to_le_bytes(),
+        ])
+    }
+
+    fn schedule(&self, id: u64, lamports: u64, unlock_slot: u64) -> Instruction {
+        Instruction {
+            program_id: conditional_transfer::ID,
+            accounts: accounts::ScheduleTransfer {
+                config: self.config,
+                from: self.context.payer.pubkey(),
+                scheduled: self.scheduled(id),
+                policy: None,
+                system_program: system_program::ID,
+                instructions: None,
+                global: pda(&[b"global"]),
+            }
+            .to_account_metas(None),
+            data: instruction::ScheduleTransfer {
+                id,
+                lamports,
+                unlock_slot,
+                expiry_slot: None,
+            }
+            .data(),
+        }
+    }
+
+    fn execute(&self, id: u64) -> Instruction {
+        Instruction {
+            program_id: conditional_transfer::ID,
+            accounts: accounts::ExecuteScheduled {
+                config: self.config,
+                scheduled: self.scheduled(id),
+                payer: self.context.payer.pubkey(),
+                to: self.to,
+                policy: None,
+                instructions: None,
+                global: pda(&[b"global"]),
+                approver: None,
+                token_program: None,
+                recipient_token_account: None,
+            }
+            .to_account_metas(None),
+            data: instruction::ExecuteScheduled {}.data(),
+        }
+    }
+}
+
+fn program_error(error: ConditionalError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error.into()))
+}
+
+#[tokio::test]
+async fn rejects_schedule_while_paused() {
+    let mut route = Route::new().await;
+    let slot = route.clock().await.slot;
+    let pause = route.update(
+        instruction::SetPaused {
+            paused: true,
+            update_id: 0,
+            recent_slot: slot,
+        }
+        .data(),
+    );
+    route.process(pause).await.unwrap();
+
+    let err = route
+        .process(route.schedule(0, THRESHOLD, slot + 10))
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(ConditionalError::ProgramPaused));
+}
+
+#[tokio::test]
+async fn rejects_schedule_above_maximum() {
+    let mut route = Route::new().await;
+    let slot = route.clock().await.slot;
+    let limits = route.update(
+        instruction::UpdateLimits {
+            threshold_lamports: THRESHOLD,
+            max_lamports: 2 * THRESHOLD,
+            update_id: 0,
+            recent_slot: slot,
+        }
+        .data(),
+    );
+    route.process(limits).await.unwrap();
+
+    let err = route
+        .process(route.schedule(0, 2 * THRESHOLD + 1, slot + 10))
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(ConditionalError::AboveMaximum));
+}
+
+#[tokio::test]
+async fn rejects_execution_outside_time_window() {
+    let mut route = Route::new().await;
+    let clock = route.clock().await;
+    let unlock_slot = clock.slot + 2;
+    route
+        .process(route.schedule(0, THRESHOLD, unlock_slot))
+        .await
+        .unwrap();
+
+    // The window opens an hour from now, after the transfer unlocks.
+    let window = route.update(
+        instruction::UpdateTimeWindow {
+            valid_from_unix: Some(clock.unix_timestamp + 3_600),
+            valid_until_unix: None,
+            update_id: 0,
+            recent_slot: clock.slot,
+        }
+        .data(),
+    );
+    route.process(window).await.unwrap();
+    route.context.warp_to_slot(unlock_slot + 1).unwrap();
+
+    let err = route.process(route.execute(0)).await.unwrap_err();
+    assert_eq!(err, program_error(ConditionalError::OutsideTimeWindow));
+    let to = route.to;
+    assert_eq!(route.context.banks_client.get_balance(to).await.unwrap(), 0);
+}
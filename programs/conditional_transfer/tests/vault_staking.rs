@@ -0,0 +1,354 @@
+//! Runs `conditional_transfer` next to the SPL stake pool program under
+//! `solana-program-test`: the vault deposits into a fresh pool and withdraws
+//! its whole position, signing both CPIs as the vault PDA.
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use conditional_transfer::{VaultStake, SPL_STAKE_POOL_PROGRAM_ID};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account_info::AccountInfo,
+    borsh1::{get_instance_packed_len, get_packed_len},
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    stake::{
+        self,
+        state::{Authorized, Lockup, StakeStateV2},
+    },
+    system_instruction, system_program, sysvar,
+    transaction::Transaction,
+};
+use spl_stake_pool::state::{Fee, StakePool, ValidatorList};
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const THRESHOLD: u64 = LAMPORTS_PER_SOL / 10;
+const STAKED: u64 = LAMPORTS_PER_SOL;
+
+// Anchor's `entry` ties the account slice to the accounts' own lifetime, which
+// `processor!` cannot express; program-test keeps both alive for the whole call.
+fn conditional_transfer_entry<'a, 'b, 'c>(
+    program_id: &'a Pubkey,
+    accounts: &'b [AccountInfo<'c>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'c [AccountInfo<'c>] = unsafe { std::mem::transmute(accounts) };
+    conditional_transfer::entry(program_id, accounts, data)
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &conditional_transfer::ID).0
+}
+
+async fn process(
+    banks: &mut BanksClient,
+    payer: &Keypair,
+    ixs: &[Instruction],
+    signers: &[&Keypair],
+) {
+    let blockhash = banks.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx =
+        Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &all_signers, blockhash);
+    banks.process_transaction(tx).await.unwrap();
+}
+
+/// Accounts of a stake pool with no validators and zero fees, so SOL goes in
+/// and out of its reserve one-for-one.
+struct Pool {
+    stake_pool: Pubkey,
+    withdraw_authority: Pubkey,
+    reserve_stake: Pubkey,
+    pool_mint: Pubkey,
+    manager_fee_account: Pubkey,
+}
+
+async fn create_pool(banks: &mut BanksClient, payer: &Keypair) -> Pool {
+    let rent = Rent::default();
+    let stake_pool = Keypair::new();
+    let validator_list = Keypair::new();
+    let reserve_stake = Keypair::new();
+    let pool_mint = Keypair::new();
+    let manager_fee_account = Keypair::new();
+    let (withdraw_authority, _) = spl_stake_pool::find_withdraw_authority_program_address(
+        &SPL_STAKE_POOL_PROGRAM_ID,
+        &stake_pool.pubkey(),
+    );
+
+    let mint_len = spl_token::state::Mint::LEN;
+    let token_len = spl_token::state::Account::LEN;
+    process(
+        banks,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &pool_mint.pubkey(),
+                rent.minimum_balance(mint_len),
+                mint_len as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::ID,
+                &pool_mint.pubkey(),
+                &withdraw_authority,
+                None,
+                9,
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &manager_fee_account.pubkey(),
+                rent.minimum_balance(token_len),
+                token_len as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::ID,
+                &manager_fee_account.pubkey(),
+                &pool_mint.pubkey(),
+                &payer.pubkey(),
+            )
+            .unwrap(),
+        ],
+        &[&pool_mint, &manager_fee_account],
+    )
+    .await;
+
+    let stake_len = StakeStateV2::size_of();
+    let reserve_lamports = rent.minimum_balance(stake_len) + LAMPORTS_PER_SOL;
+    let mut reserve = stake::instruction::create_account(
+        &payer.pubkey(),
+        &reserve_stake.pubkey(),
+        &Authorized {
+            staker: withdraw_authority,
+            withdrawer: withdraw_authority,
+        },
+        &Lockup::default(),
+        reserve_lamports,
+    );
+    let list_len = get_instance_packed_len(&ValidatorList::new(1)).unwrap();
+    let pool_len = get_packed_len::<StakePool>();
+    reserve.extend([
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &validator_list.pubkey(),
+            rent.minimum_balance(list_len),
+            list_len as u64,
+            &SPL_STAKE_POOL_PROGRAM_ID,
+        ),
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &stake_pool.pubkey(),
+            rent.minimum_balance(pool_len),
+            pool_len as u64,
+            &SPL_STAKE_POOL_PROGRAM_ID,
+        ),
+        spl_stake_pool::instruction::initialize(
+            &SPL_STAKE_POOL_PROGRAM_ID,
+            &stake_pool.pubkey(),
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &withdraw_authority,
+            &validator_list.pubkey(),
+            &reserve_stake.pubkey(),
+            &pool_mint.pubkey(),
+            &manager_fee_account.pubkey(),
+            &spl_token::ID,
+            None,
+            Fee::default(),
+            Fee::default(),
+            Fee::default(),
+            0,
+            1,
+        ),
+    ]);
+    process(
+        banks,
+        payer,
+        &reserve,
+        &[&reserve_stake, &validator_list, &stake_pool],
+    )
+    .await;
+
+    Pool {
+        stake_pool: stake_pool.pubkey(),
+        withdraw_authority,
+        reserve_stake: reserve_stake.pubkey(),
+        pool_mint: pool_mint.pubkey(),
+        manager_fee_account: manager_fee_account.pubkey(),
+    }
+}
+
+async fn token_amount(banks: &mut BanksClient, account: Pubkey) -> u64 {
+    let account = banks.get_account(account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+async fn principal(banks: &mut BanksClient, vault_stake: Pubkey) -> u64 {
+    let account = banks.get_account(vault_stake).await.unwrap().unwrap();
+    VaultStake::try_deserialize(&mut account.data.as_slice())
+        .unwrap()
+        .principal
+}
+
+#[tokio::test]
+async fn stakes_and_unstakes_vault() {
+    let mut program_test = ProgramTest::new(
+        "conditional_transfer",
+        conditional_transfer::ID,
+        processor!(conditional_transfer_entry),
+    );
+    program_test.add_program(
+        "spl_stake_pool",
+        SPL_STAKE_POOL_PROGRAM_ID,
+        processor!(spl_stake_pool::processor::Processor::process),
+    );
+    let (mut banks, payer, _) = program_test.start().await;
+    let pool = create_pool(&mut banks, &payer).await;
+
+    let to = Pubkey::new_unique();
+    let beneficiary = Pubkey::new_unique();
+    let route = Pubkey::new_unique();
+    let config = pda(&[b"config", payer.pubkey().as_ref(), route.as_ref()]);
+    let vault = pda(&[b"vault", config.as_ref()]);
+    let vault_stake = pda(&[b"vault_stake", config.as_ref()]);
+    let event_authority = pda(&[b"__event_authority"]);
+    let pool_tokens = Keypair::new();
+    let token_len = spl_token::state::Account::LEN;
+
+    process(
+        &mut banks,
+        &payer,
+        &[
+            Instruction {
+                program_id: conditional_transfer::ID,
+                accounts: conditional_transfer::accounts::Initialize {
+                    authority: payer.pubkey(),
+                    from: payer.pubkey(),
+                    config,
+                    recipient_index: pda(&[b"recipient_index", to.as_ref()]),
+                    system_program: system_program::ID,
+                    event_authority,
+                    program: conditional_transfer::ID,
+                }
+                .to_account_metas(None),
+                data: conditional_transfer::instruction::Initialize {
+                    route,
+                    to,
+                    threshold_lamports: THRESHOLD,
+                }
+                .data(),
+            },
+            Instruction {
+                program_id: conditional_transfer::ID,
+                accounts: conditional_transfer::accounts::FundVault {
+                    funder: payer.pubkey(),
+                    config,
+                    vault,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: conditional_transfer::instruction::FundVault {
+                    lamports: 2 * STAKED,
+                }
+                .data(),
+            },
+            Instruction {
+                program_id: conditional_transfer::ID,
+                accounts: conditional_transfer::accounts::ConfigureVaultStaking {
+                    authority: payer.pubkey(),
+                    config,
+                    vault_stake,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: conditional_transfer::instruction::ConfigureVaultStaking {
+                    stake_pool: pool.stake_pool,
+                    pool_mint: pool.pool_mint,
+                    beneficiary,
+                }
+                .data(),
+            },
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &pool_tokens.pubkey(),
+                Rent::default().minimum_balance(token_len),
+                token_len as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::ID,
+                &pool_tokens.pubkey(),
+                &pool.pool_mint,
+                &vault,
+            )
+            .unwrap(),
+        ],
+        &[&pool_tokens],
+    )
+    .await;
+    let funded = banks.get_balance(vault).await.unwrap();
+
+    let stake = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: conditional_transfer::accounts::StakeVault {
+            authority: payer.pubkey(),
+            config,
+            vault_stake,
+            vault,
+            pool_tokens: pool_tokens.pubkey(),
+            stake_pool: pool.stake_pool,
+            withdraw_authority: pool.withdraw_authority,
+            reserve_stake: pool.reserve_stake,
+            manager_fee_account: pool.manager_fee_account,
+            pool_mint: pool.pool_mint,
+            stake_pool_program: SPL_STAKE_POOL_PROGRAM_ID,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            global: pda(&[b"global"]),
+        }
+        .to_account_metas(None),
+        data: conditional_transfer::instruction::StakeVault { lamports: STAKED }.data(),
+    };
+    process(&mut banks, &payer, &[stake], &[]).await;
+
+    assert_eq!(banks.get_balance(vault).await.unwrap(), funded - STAKED);
+    assert_eq!(token_amount(&mut banks, pool_tokens.pubkey()).await, STAKED);
+    assert_eq!(principal(&mut banks, vault_stake).await, STAKED);
+
+    let unstake = Instruction {
+        program_id: conditional_transfer::ID,
+        accounts: conditional_transfer::accounts::UnstakeVault {
+            config,
+            vault_stake,
+            vault,
+            pool_tokens: pool_tokens.pubkey(),
+            beneficiary,
+            stake_pool: pool.stake_pool,
+            withdraw_authority: pool.withdraw_authority,
+            reserve_stake: pool.reserve_stake,
+            manager_fee_account: pool.manager_fee_account,
+            pool_mint: pool.pool_mint,
+            clock: sysvar::clock::ID,
+            stake_history: sysvar::stake_history::ID,
+            stake_program: stake::program::ID,
+            stake_pool_program: SPL_STAKE_POOL_PROGRAM_ID,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: conditional_transfer::instruction::UnstakeVault {}.data(),
+    };
+    process(&mut banks, &payer, &[unstake], &[]).await;
+
+    assert_eq!(banks.get_balance(vault).await.unwrap(), funded);
+    assert_eq!(token_amount(&mut banks, pool_tokens.pubkey()).await, 0);
+    assert_eq!(principal(&mut banks, vault_stake).await, 0);
+}
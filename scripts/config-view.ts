@@ -0,0 +1,47 @@
+/**
+ * Print the config's send terms as `get_config` returns them.
+ * The effective threshold already reflects any linked policy and overrides.
+ *
+ * Usage:
+ *   CONFIG_ROUTE=ROUTE [POLICY=POLICY_PUBKEY] npx ts-node scripts/config-view.ts
+ *
+ * The config is `CONFIG`, or `CONFIG_ROUTE` of `CONFIG_FROM` (default: wallet).
+ * Pass `POLICY` when the config is linked to one.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {PublicKey} from "@solana/web3.js";
+import idl from "../target/idl/conditional_transfer.json" assert { type: "json" };
+import {configFromEnv} from "./sdk/config";
+
+(async () => {
+  try {
+    const provider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const programId = new PublicKey((idl as any).address ?? process.env.PROGRAM_ID!);
+    const program = new anchor.Program(idl as anchor.Idl, programId, provider);
+
+    const configPda = configFromEnv(program.programId, provider.wallet.publicKey);
+    const policy = process.env.POLICY ? new PublicKey(process.env.POLICY) : null;
+
+    // Simulated call; the view comes back as return data.
+    const view: any = await program.methods
+      .getConfig()
+      .accounts({config: configPda, policy})
+      .view();
+
+    console.log("Config PDA :", configPda.toBase58());
+    console.log("Version    :", view.version);
+    console.log("From       :", view.from.toBase58());
+    console.log("To         :", view.hashedRecipient ? "(hashed)" : view.to.toBase58());
+    const mode = Object.keys(view.terms.comparisonMode)[0];
+    console.log("Threshold  :", `${view.terms.thresholdLamports.toString()} lamports (${mode})`);
+    console.log("Max        :", view.maxLamports.isZero() ? "none" : view.maxLamports.toString());
+    console.log("USD floor  :", view.thresholdUsdCents ? `${view.thresholdUsdCents.toString()} cents` : "none");
+    console.log("Paused     :", view.paused, view.closing ? "(closing)" : "");
+    console.log("Fee        :", view.feeBps ? `${view.feeBps} bps to ${view.feeCollector.toBase58()}` : "none");
+  } catch (err) {
+    console.error(err);
+    process.exit(1);
+  }
+})();
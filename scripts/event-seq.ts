@@ -0,0 +1,37 @@
+/**
+ * Print the config's current event sequence number.
+ * Compare it with the last `seq` your indexer processed to detect missed events.
+ *
+ * Usage:
+ *   CONFIG_ROUTE=ROUTE npx ts-node scripts/event-seq.ts
+ *
+ * The config is `CONFIG`, or `CONFIG_ROUTE` of `CONFIG_FROM` (default: wallet).
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {PublicKey} from "@solana/web3.js";
+import idl from "../target/idl/conditional_transfer.json" assert { type: "json" };
+import {configFromEnv} from "./sdk/config";
+
+(async () => {
+  try {
+    const provider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const programId = new PublicKey((idl as any).address ?? process.env.PROGRAM_ID!);
+    const program = new anchor.Program(idl as anchor.Idl, programId, provider);
+
+    const configPda = configFromEnv(program.programId, provider.wallet.publicKey);
+
+    // Simulated call; the sequence comes back as return data.
+    const seq = await program.methods
+      .getEventSeq()
+      .accounts({config: configPda})
+      .view();
+
+    console.log("Config PDA :", configPda.toBase58());
+    console.log("Event seq  :", seq.toString());
+  } catch (err) {
+    console.error(err);
+    process.exit(1);
+  }
+})();
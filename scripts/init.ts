@@ -3,13 +3,17 @@
  * - A = provider wallet (from)
  * - B = CLI argument (to)
  * - threshold = second arg in lamports (defaults to 0.1 SOL = 100_000_000 lamports)
+ * - route = `ROUTE` if set, otherwise a fresh random key; printed, since
+ *   scripts find the config again by `CONFIG_ROUTE`
  *
  * Usage:
  *   npx ts-node scripts/init.ts B_PUBKEY [thresholdLamports]
  */
 import * as anchor from "@coral-xyz/anchor";
-import {PublicKey, SystemProgram} from "@solana/web3.js";
+import {Keypair, PublicKey, SystemProgram} from "@solana/web3.js";
 import idl from "../target/idl/conditional_transfer.json" assert { type: "json" };
+import {accountSpace, estimateTotalCost, recipientIndexAccount} from "./sdk/estimate-cost";
+import {configAddress} from "./sdk/config";
 
 (async () => {
   try {
@@ -25,31 +29,48 @@ import idl from "../target/idl/conditional_transfer.json" assert { type: "json"
     const programId = new PublicKey((idl as any).address ?? process.env.PROGRAM_ID!);
     const program = new anchor.Program(idl as anchor.Idl, programId, provider);
 
-    // PDA for singleton config
-    const [configPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("config")],
-      program.programId
-    );
-
     const from = provider.wallet.publicKey; // A is the provider wallet
     const to = new PublicKey(toArg);
+
+    // One config PDA per route of `from`; the recipient is not a seed
+    const route = process.env.ROUTE ? new PublicKey(process.env.ROUTE) : Keypair.generate().publicKey;
+    const configPda = configAddress(program.programId, from, route);
     const thresholdLamports = thresholdArg ? BigInt(thresholdArg) : 100_000_000n; // 0.1 SOL
 
     console.log("Program ID   :", program.programId.toBase58());
     console.log("Authority    :", provider.wallet.publicKey.toBase58());
     console.log("From (A)     :", from.toBase58());
     console.log("To (B)       :", to.toBase58());
+    console.log("Route        :", route.toBase58());
     console.log("Threshold    :", thresholdLamports.toString(), "lamports");
     console.log("Config PDA   :", configPda.toBase58());
 
-    const txSig = await program.methods
-      .initialize(from, to, new anchor.BN(thresholdLamports.toString()))
+    // Reverse index so B can discover configs paying it
+    const [recipientIndex] = PublicKey.findProgramAddressSync(
+      [Buffer.from("recipient_index"), to.toBuffer()],
+      program.programId
+    );
+
+    const initialize = program.methods
+      .initialize(route, to, new anchor.BN(thresholdLamports.toString()))
       .accounts({
         authority: provider.wallet.publicKey,
+        from,
         config: configPda,
+        recipientIndex,
         systemProgram: SystemProgram.programId,
-      })
-      .rpc();
+      });
+
+    const cost = await estimateTotalCost(provider.connection, provider.wallet.publicKey, {
+      instructions: [await initialize.instruction()],
+      creates: [
+        {label: "config", address: configPda, space: accountSpace(program, "config")},
+        recipientIndexAccount(program, to),
+      ],
+    });
+    console.log("Est. cost    :", cost.total, "lamports", JSON.stringify(cost));
+
+    const txSig = await initialize.rpc();
 
     console.log("Initialize transaction signature:", txSig);
   } catch (err) {
@@ -0,0 +1,124 @@
+/**
+ * Offline config proposals for air-gapped admin workflows.
+ *
+ * 1. write (online):  encode a ConfigProposal (Borsh) for the config's current nonce
+ * 2. sign (offline):  sign the proposal bytes with the root authority keypair
+ * 3. apply (online):  submit an ed25519 verify instruction + apply_signed_proposal
+ *
+ * Usage:
+ *   npx ts-node scripts/proposal.ts write FILE [--threshold LAMPORTS] [--to PUBKEY] [--memo TEXT] [--update-id ID]
+ *   npx ts-node scripts/proposal.ts sign FILE AUTHORITY_KEYPAIR.json
+ *   npx ts-node scripts/proposal.ts apply FILE
+ *
+ * `sign` writes FILE.sig and needs no network or provider. `write` and `apply`
+ * act on `CONFIG`, or `CONFIG_ROUTE` of `CONFIG_FROM` (default: wallet). The
+ * proposal records the slot it was written at, and the program rejects it
+ * once the cluster is more than `RECENT_SLOT_TOLERANCE` slots past it, so all
+ * three steps must finish within about a minute.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {Ed25519Program, Keypair, PublicKey, SYSVAR_INSTRUCTIONS_PUBKEY} from "@solana/web3.js";
+import {createPrivateKey, sign} from "crypto";
+import {readFileSync, writeFileSync} from "fs";
+import idl from "../target/idl/conditional_transfer.json" assert { type: "json" };
+import {configFromEnv} from "./sdk/config";
+
+const usage = "Usage: npx ts-node scripts/proposal.ts write|sign|apply FILE [...]";
+
+function flag(args: string[], name: string): string | undefined {
+  const i = args.indexOf(name);
+  return i >= 0 ? args[i + 1] : undefined;
+}
+
+function load() {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const programId = new PublicKey((idl as any).address ?? process.env.PROGRAM_ID!);
+  const program = new anchor.Program(idl as anchor.Idl, programId, provider);
+
+  const configPda = configFromEnv(program.programId, provider.wallet.publicKey);
+  return {program, configPda};
+}
+
+(async () => {
+  try {
+    const [, , command, file, ...rest] = process.argv;
+    if (!command || !file) {
+      console.error(usage);
+      process.exit(1);
+    }
+
+    if (command === "write") {
+      const {program, configPda} = load();
+      const config: any = await (program.account as any).config.fetch(configPda);
+      const threshold = flag(rest, "--threshold");
+      const to = flag(rest, "--to");
+      const memo = flag(rest, "--memo");
+      const updateId = flag(rest, "--update-id");
+      const recentSlot = await program.provider.connection.getSlot();
+
+      const proposal = {
+        config: configPda,
+        nonce: config.proposalNonce,
+        recentSlot: new anchor.BN(recentSlot),
+        updateId: new anchor.BN(updateId ?? 0),
+        thresholdLamports: threshold ? new anchor.BN(threshold) : null,
+        to: to ? new PublicKey(to) : null,
+        defaultMemo: memo ?? null,
+      };
+      const bytes = program.coder.types.encode("ConfigProposal", proposal);
+      writeFileSync(file, bytes);
+
+      console.log("Config PDA :", configPda.toBase58());
+      console.log("Nonce      :", config.proposalNonce.toString());
+      console.log("Slot       :", recentSlot);
+      console.log("Wrote      :", file, `(${bytes.length} bytes)`);
+    } else if (command === "sign") {
+      const [keypairPath] = rest;
+      if (!keypairPath) {
+        console.error(usage);
+        process.exit(1);
+      }
+      const secret = Uint8Array.from(JSON.parse(readFileSync(keypairPath, "utf8")));
+      const authority = Keypair.fromSecretKey(secret);
+      // PKCS#8 wrapper around the 32-byte ed25519 seed.
+      const der = Buffer.concat([
+        Buffer.from("302e020100300506032b657004220420", "hex"),
+        Buffer.from(authority.secretKey.slice(0, 32)),
+      ]);
+      const key = createPrivateKey({key: der, format: "der", type: "pkcs8"});
+      const signature = sign(null, readFileSync(file), key);
+      writeFileSync(`${file}.sig`, signature);
+
+      console.log("Authority  :", authority.publicKey.toBase58());
+      console.log("Wrote      :", `${file}.sig`);
+    } else if (command === "apply") {
+      const {program, configPda} = load();
+      const config: any = await (program.account as any).config.fetch(configPda);
+      const message = readFileSync(file);
+      const signature = readFileSync(`${file}.sig`);
+      const proposal = program.coder.types.decode("ConfigProposal", message);
+
+      const verifyIx = Ed25519Program.createInstructionWithPublicKey({
+        publicKey: config.authority.toBytes(),
+        message,
+        signature,
+      });
+      const tx = await program.methods
+        .applySignedProposal(proposal)
+        .accounts({config: configPda, instructions: SYSVAR_INSTRUCTIONS_PUBKEY})
+        .preInstructions([verifyIx])
+        .rpc();
+
+      console.log("Config PDA :", configPda.toBase58());
+      console.log("Tx         :", tx);
+    } else {
+      console.error(usage);
+      process.exit(1);
+    }
+  } catch (err) {
+    console.error(err);
+    process.exit(1);
+  }
+})();
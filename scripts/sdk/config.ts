@@ -0,0 +1,33 @@
+/**
+ * Config PDA addressing.
+ *
+ * Every route has its own config at `["config", from, route]`, where `route`
+ * is any 32 bytes `from` picked at `initialize` (it co-signs). The recipient
+ * is not a seed, so a hashed recipient stays private. Later
+ * `update_addresses` calls never move the account, so keep the original
+ * `from` and `route` (or the address itself) around.
+ */
+import {PublicKey} from "@solana/web3.js";
+
+export function configAddress(programId: PublicKey, from: PublicKey, route: PublicKey): PublicKey {
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config"), from.toBuffer(), route.toBuffer()],
+    programId
+  );
+  return address;
+}
+
+/**
+ * The config a script operates on: `CONFIG` if set, otherwise `CONFIG_ROUTE`
+ * of `CONFIG_FROM` (default `defaultFrom`, usually the wallet).
+ */
+export function configFromEnv(programId: PublicKey, defaultFrom: PublicKey): PublicKey {
+  if (process.env.CONFIG) {
+    return new PublicKey(process.env.CONFIG);
+  }
+  if (!process.env.CONFIG_ROUTE) {
+    throw new Error("Set CONFIG to the config address, or CONFIG_ROUTE (and optionally CONFIG_FROM)");
+  }
+  const from = process.env.CONFIG_FROM ? new PublicKey(process.env.CONFIG_FROM) : defaultFrom;
+  return configAddress(programId, from, new PublicKey(process.env.CONFIG_ROUTE));
+}
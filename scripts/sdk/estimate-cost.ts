@@ -0,0 +1,199 @@
+/**
+ * All-in cost estimate for an action, before the user signs.
+ *
+ * `estimateTotalCost(connection, payer, action)` (the SDK's
+ * `estimate_total_cost`) breaks the lamports down into the base transaction
+ * fee, the priority fee implied by the action's compute budget, and rent for
+ * every account the action would create that does not exist yet: config,
+ * recipient index, milestone, bounty, the recipient's wrapped-SOL ATA, and so
+ * on. Accounts that already exist cost nothing, so the same action can be
+ * cheaper the second time.
+ *
+ * `recommendedComputeBudget(connection, idl, instruction)` (the SDK's
+ * `recommended_compute_budget`) reads the program's per-instruction compute
+ * hints from the IDL constants and prices them from recent prioritization
+ * fees, giving the `computeUnitLimit` / `computeUnitPrice` to put on an action.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {
+  Commitment,
+  ComputeBudgetProgram,
+  Connection,
+  PublicKey,
+  TransactionInstruction,
+  TransactionMessage,
+} from "@solana/web3.js";
+
+const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hdZ4zDZx2Sma4j8Db4Gr8xRJu6");
+const NATIVE_MINT = new PublicKey("So11111111111111111111111111111111111111112");
+/** `spl_token::state::Account::LEN`. */
+const TOKEN_ACCOUNT_SPACE = 165;
+/** Runtime default compute limit per instruction when none is requested. */
+const DEFAULT_UNITS_PER_INSTRUCTION = 200_000;
+const MAX_COMPUTE_UNITS = 1_400_000;
+/** Fallbacks for IDLs built before the compute-budget constants existed. */
+const DEFAULT_HINT_UNITS = 40_000;
+const DEFAULT_PRIORITY_FEE_PERCENTILE = 50;
+
+/** An account the action creates unless it already exists. */
+export interface CreatedAccount {
+  label: string;
+  address: PublicKey;
+  /** Account data size in bytes, including any discriminator. */
+  space: number;
+}
+
+export interface Action {
+  instructions: TransactionInstruction[];
+  creates?: CreatedAccount[];
+  /** Priority fee in micro-lamports per compute unit. Default 0. */
+  computeUnitPrice?: number;
+  /** Requested compute unit limit; defaults to the runtime's implicit limit. */
+  computeUnitLimit?: number;
+}
+
+export interface RentItem {
+  label: string;
+  address: string;
+  lamports: number;
+}
+
+export interface CostEstimate {
+  transactionFee: number;
+  priorityFee: number;
+  rent: RentItem[];
+  total: number;
+}
+
+/** Space of a program account type, as declared in the IDL (e.g. "config"). */
+export function accountSpace(program: anchor.Program, name: string): number {
+  return (program.account as any)[name].size;
+}
+
+/** The wrapped-SOL associated token account `owner` would be paid into. */
+export function wrappedSolAta(owner: PublicKey): CreatedAccount {
+  const [address] = PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), NATIVE_MINT.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID
+  );
+  return {label: "wsol_ata", address, space: TOKEN_ACCOUNT_SPACE};
+}
+
+/** The recipient index PDA `initialize` / `update_addresses` create for `to`. */
+export function recipientIndexAccount(program: anchor.Program, to: PublicKey): CreatedAccount {
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from("recipient_index"), to.toBuffer()],
+    program.programId
+  );
+  return {label: "recipient_index", address, space: accountSpace(program, "recipientIndex")};
+}
+
+export async function estimateTotalCost(
+  connection: Connection,
+  payer: PublicKey,
+  action: Action,
+  commitment: Commitment = "confirmed"
+): Promise<CostEstimate> {
+  const {blockhash} = await connection.getLatestBlockhash(commitment);
+  const message = new TransactionMessage({
+    payerKey: payer,
+    recentBlockhash: blockhash,
+    instructions: action.instructions,
+  }).compileToV0Message();
+  const transactionFee = (await connection.getFeeForMessage(message, commitment)).value;
+  if (transactionFee === null) {
+    throw new Error("Could not fetch the fee for the action's message");
+  }
+
+  const price = action.computeUnitPrice ?? 0;
+  const units =
+    action.computeUnitLimit ??
+    Math.min(action.instructions.length * DEFAULT_UNITS_PER_INSTRUCTION, MAX_COMPUTE_UNITS);
+  const priorityFee = Math.ceil((price * units) / 1_000_000);
+
+  const creates = action.creates ?? [];
+  const existing = await connection.getMultipleAccountsInfo(
+    creates.map((account) => account.address),
+    commitment
+  );
+  const rent: RentItem[] = [];
+  for (const [i, account] of creates.entries()) {
+    if (existing[i] !== null) continue;
+    rent.push({
+      label: account.label,
+      address: account.address.toBase58(),
+      lamports: await connection.getMinimumBalanceForRentExemption(account.space, commitment),
+    });
+  }
+
+  const rentTotal = rent.reduce((sum, item) => sum + item.lamports, 0);
+  return {transactionFee, priorityFee, rent, total: transactionFee + priorityFee + rentTotal};
+}
+
+/** Compute budget instructions matching an action's price and limit, to prepend when sending. */
+export function computeBudgetInstructions(action: Action): TransactionInstruction[] {
+  const instructions: TransactionInstruction[] = [];
+  if (action.computeUnitLimit !== undefined) {
+    instructions.push(ComputeBudgetProgram.setComputeUnitLimit({units: action.computeUnitLimit}));
+  }
+  if (action.computeUnitPrice) {
+    instructions.push(
+      ComputeBudgetProgram.setComputeUnitPrice({microLamports: action.computeUnitPrice})
+    );
+  }
+  return instructions;
+}
+
+export interface ComputeBudgetRecommendation {
+  /** Units to request with `setComputeUnitLimit`. */
+  computeUnitLimit: number;
+  /** Micro-lamports per unit at the hinted percentile of recent fees. */
+  computeUnitPrice: number;
+  priorityFeePercentile: number;
+}
+
+export interface ComputeBudgetOptions {
+  /** Joint senders or payout-table entries, for instructions that scale with them. */
+  items?: number;
+  /** Accounts the transaction write-locks; fees are sampled for these. */
+  writableAccounts?: PublicKey[];
+}
+
+/** Numeric IDL constant `name` (e.g. "SEND_BRIDGED_COMPUTE_UNITS"), if exported. */
+function idlConstant(idl: anchor.Idl, name: string): number | undefined {
+  const constant = (idl.constants ?? []).find((c: any) => c.name === name);
+  return constant === undefined ? undefined : Number(constant.value);
+}
+
+export async function recommendedComputeBudget(
+  connection: Connection,
+  idl: anchor.Idl,
+  instruction: string,
+  options: ComputeBudgetOptions = {}
+): Promise<ComputeBudgetRecommendation> {
+  const prefix = instruction.toUpperCase();
+  const units =
+    idlConstant(idl, `${prefix}_COMPUTE_UNITS`) ??
+    idlConstant(idl, "DEFAULT_COMPUTE_UNITS") ??
+    DEFAULT_HINT_UNITS;
+  const perItem = idlConstant(idl, `${prefix}_COMPUTE_UNITS_PER_ITEM`) ?? 0;
+  const priorityFeePercentile =
+    idlConstant(idl, `${prefix}_PRIORITY_FEE_PERCENTILE`) ??
+    idlConstant(idl, "DEFAULT_PRIORITY_FEE_PERCENTILE") ??
+    DEFAULT_PRIORITY_FEE_PERCENTILE;
+
+  const fees = (
+    await connection.getRecentPrioritizationFees({
+      lockedWritableAccounts: options.writableAccounts,
+    })
+  )
+    .map((sample) => sample.prioritizationFee)
+    .sort((a, b) => a - b);
+  const index = Math.min(fees.length - 1, Math.floor((fees.length * priorityFeePercentile) / 100));
+  return {
+    computeUnitLimit: Math.min(units + perItem * (options.items ?? 0), MAX_COMPUTE_UNITS),
+    computeUnitPrice: fees.length === 0 ? 0 : fees[index],
+    priorityFeePercentile,
+  };
+}
@@ -0,0 +1,122 @@
+/**
+ * Conditional payment links: a Solana Pay-style URL for paying through a config.
+ *
+ *   solana-conditional:<config>?amount=<SOL>&reference=<base58>&label=<text>&message=<text>
+ *
+ * The path is the config PDA, not a recipient, so the wallet pays with
+ * `send_with_receipt` and the transfer goes through the config's threshold and
+ * conditions. `reference` is the 32-byte receipt reference (base58) and is
+ * required; `amount` is decimal SOL and may be omitted to let the payer choose.
+ * `label` and `message` are shown to the payer and never sent on-chain. The
+ * merchant then settles with `verify_payment`, which fails unless a receipt for
+ * the reference exists with at least the requested lamports.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {PublicKey} from "@solana/web3.js";
+
+import {receiptAddress, referenceBytes} from "./receipts";
+
+export const PAYMENT_LINK_SCHEME = "solana-conditional";
+
+const LAMPORTS_DECIMALS = 9;
+
+export interface PaymentLink {
+  config: PublicKey;
+  /** Requested amount in lamports; null lets the payer choose. */
+  lamports: bigint | null;
+  /** The 32-byte receipt reference. */
+  reference: Buffer;
+  label?: string;
+  message?: string;
+}
+
+/** `lamports` as decimal SOL without trailing zeros, e.g. 1500000000n -> "1.5". */
+function formatSol(lamports: bigint): string {
+  const whole = lamports / 1_000_000_000n;
+  const fraction = (lamports % 1_000_000_000n)
+    .toString()
+    .padStart(LAMPORTS_DECIMALS, "0")
+    .replace(/0+$/, "");
+  return fraction ? `${whole}.${fraction}` : whole.toString();
+}
+
+/** Decimal SOL to lamports; rejects signs, exponents, and more than 9 decimals. */
+function parseSol(amount: string): bigint {
+  const match = /^(\d+)(?:\.(\d{1,9}))?$/.exec(amount);
+  if (!match) {
+    throw new Error(`Invalid amount "${amount}"`);
+  }
+  const fraction = (match[2] ?? "").padEnd(LAMPORTS_DECIMALS, "0");
+  const lamports = BigInt(match[1]) * 1_000_000_000n + BigInt(fraction);
+  if (lamports === 0n) {
+    throw new Error("Amount must be positive");
+  }
+  return lamports;
+}
+
+export function encodePaymentLink(link: {
+  config: PublicKey;
+  lamports?: bigint | null;
+  reference: Uint8Array | bigint | string;
+  label?: string;
+  message?: string;
+}): string {
+  const params = new URLSearchParams();
+  if (link.lamports != null) {
+    if (link.lamports <= 0n) {
+      throw new Error("Amount must be positive");
+    }
+    params.set("amount", formatSol(link.lamports));
+  }
+  params.set("reference", anchor.utils.bytes.bs58.encode(referenceBytes(link.reference)));
+  if (link.label) params.set("label", link.label);
+  if (link.message) params.set("message", link.message);
+  return `${PAYMENT_LINK_SCHEME}:${link.config.toBase58()}?${params.toString()}`;
+}
+
+export function parsePaymentLink(url: string): PaymentLink {
+  const prefix = `${PAYMENT_LINK_SCHEME}:`;
+  if (!url.startsWith(prefix)) {
+    throw new Error(`Not a ${PAYMENT_LINK_SCHEME} link`);
+  }
+  const [path, query = ""] = url.slice(prefix.length).split("?", 2);
+  const config = new PublicKey(decodeURIComponent(path));
+  const params = new URLSearchParams(query);
+  for (const name of ["amount", "reference", "label", "message"]) {
+    if (params.getAll(name).length > 1) {
+      throw new Error(`Duplicate "${name}" parameter`);
+    }
+  }
+
+  const encodedReference = params.get("reference");
+  if (!encodedReference) {
+    throw new Error("Missing reference");
+  }
+  const reference = Buffer.from(anchor.utils.bytes.bs58.decode(encodedReference));
+  if (reference.length !== 32) {
+    throw new Error(`Reference must be 32 bytes, got ${reference.length}`);
+  }
+
+  const amount = params.get("amount");
+  return {
+    config,
+    lamports: amount === null ? null : parseSol(amount),
+    reference,
+    label: params.get("label") ?? undefined,
+    message: params.get("message") ?? undefined,
+  };
+}
+
+/**
+ * The `verify_payment` receipt for `link`, via simulation. Throws the program
+ * error (`ReferenceNotPaid`, `PaymentShortfall`) when it has not been settled.
+ */
+export async function verifyPaymentLink(program: anchor.Program, link: PaymentLink): Promise<any> {
+  return (program.methods as any)
+    .verifyPayment([...link.reference], new anchor.BN((link.lamports ?? 0n).toString()))
+    .accounts({
+      config: link.config,
+      receipt: receiptAddress(program.programId, link.config, link.reference),
+    })
+    .view();
+}
@@ -0,0 +1,51 @@
+/**
+ * Receipts written by `send_with_receipt`, looked up by external reference.
+ *
+ * The reference is any 32-byte id the payer chose (an invoice number, a hash
+ * of an order id, ...). Since it is part of the receipt PDA seeds, a payment
+ * processor can fetch the receipt for an invoice it knows without indexing
+ * events.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {createHash} from "crypto";
+import {Commitment, PublicKey} from "@solana/web3.js";
+
+/** Normalize a reference: 32 raw bytes as-is, a u128 little-endian, or any string hashed with SHA-256. */
+export function referenceBytes(reference: Uint8Array | bigint | string): Buffer {
+  if (typeof reference === "bigint") {
+    const bytes = Buffer.alloc(32);
+    bytes.writeBigUInt64LE(reference & 0xffffffffffffffffn, 0);
+    bytes.writeBigUInt64LE(reference >> 64n, 8);
+    return bytes;
+  }
+  if (typeof reference === "string") {
+    return createHash("sha256").update(reference).digest();
+  }
+  if (reference.length !== 32) {
+    throw new Error(`Reference must be 32 bytes, got ${reference.length}`);
+  }
+  return Buffer.from(reference);
+}
+
+export function receiptAddress(
+  programId: PublicKey,
+  configPda: PublicKey,
+  reference: Uint8Array | bigint | string
+): PublicKey {
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from("receipt"), configPda.toBuffer(), referenceBytes(reference)],
+    programId
+  );
+  return address;
+}
+
+/** The receipt for `reference`, or null if it has not been paid (or was closed). */
+export async function fetchReceipt(
+  program: anchor.Program,
+  configPda: PublicKey,
+  reference: Uint8Array | bigint | string,
+  commitment: Commitment = "confirmed"
+): Promise<any | null> {
+  const address = receiptAddress(program.programId, configPda, reference);
+  return (program.account as any).receipt.fetchNullable(address, commitment);
+}
@@ -0,0 +1,226 @@
+/**
+ * Send pipeline for `send_if_over_threshold`: simulate, submit, confirm.
+ *
+ * If the blockhash expires before confirmation, the config is re-fetched and
+ * compared with the snapshot taken before the first attempt. Only if the
+ * threshold, addresses, and policy are unchanged is the transfer re-signed with
+ * a fresh blockhash; otherwise `ConfigChangedError` is thrown so the sender can
+ * decide again instead of paying under terms they never saw. The snapshot
+ * includes the linked policy's terms, resolved with the config's overrides as
+ * the program does, so an `update_policy` between attempts counts as a change.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {
+  Commitment,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionExpiredBlockheightExceededError,
+} from "@solana/web3.js";
+
+export interface SendOptions {
+  /** Commitment used for the blockhash, simulation, and confirmation. Default "confirmed". */
+  commitment?: Commitment;
+  /** Submissions to try before giving up on expired blockhashes. Default 3. */
+  maxAttempts?: number;
+  /** Plaintext recipient, required when the config only stores its hash. */
+  recipient?: PublicKey;
+  /** Solana Pay reference keys, passed read-only and recorded in `TransferExecuted`. */
+  references?: PublicKey[];
+  /** Memo for this send (at most 64 bytes), e.g. an invoice id; recorded in `TransferExecuted`. */
+  memo?: string;
+  /** Pyth SOL/USD `PriceUpdateV2` account, required when the config has a USD threshold. */
+  priceFeed?: PublicKey;
+  /** ZK Token proof `RangeProofU64` context account, required when the config has a private threshold. */
+  rangeProof?: PublicKey;
+  /**
+   * Send through `send_with_nonce` so every attempt carries this nonce and at
+   * most one can land (`NonceAlreadyUsed` otherwise). Not combinable with `memo`.
+   */
+  nonce?: anchor.BN;
+}
+
+/** The config fields a sender agreed to when the transfer was first signed. */
+export interface ConfigSnapshot {
+  from: string;
+  to: string;
+  thresholdLamports: string;
+  /** How amounts are compared with `thresholdLamports` ("gte", "gt", "eq", "lte", "lt"). */
+  comparisonMode: string;
+  thresholdUsdCents: string | null;
+  privateThreshold: string | null;
+  /** Fee taken from each send, with the collector it goes to (null when no fee). */
+  feeBps: number;
+  feeCollector: string | null;
+  policy: string | null;
+  policyOverrides: string;
+  /** Threshold and below-threshold behavior in force after resolving the policy and overrides. */
+  resolvedTerms: string;
+  recipientHash: string | null;
+}
+
+export class ConfigChangedError extends Error {
+  constructor(readonly before: ConfigSnapshot, readonly after: ConfigSnapshot) {
+    super("Config changed between send attempts; not re-signing");
+  }
+}
+
+export class SimulationError extends Error {
+  constructor(readonly err: unknown, readonly logs: string[] | null) {
+    super(`Simulation failed: ${JSON.stringify(err)}`);
+  }
+}
+
+/**
+ * Mirror of `Config::resolve_terms`: the linked policy's terms, then the overrides, floored by a monotonic
+ * threshold. The maximum, window, and fee are the policy's where its `limits` set them, else the config's.
+ */
+function resolveTerms(cfg: any, policy: any | null) {
+  const base = policy ?? cfg;
+  const overrides = cfg.policyOverrides;
+  let threshold: anchor.BN = overrides.thresholdLamports ?? base.thresholdLamports;
+  if (cfg.monotonicThreshold && threshold.lt(cfg.thresholdLamports)) {
+    threshold = cfg.thresholdLamports;
+  }
+  const limits = policy?.limits;
+  const maxLamports: anchor.BN = limits && !limits.maxLamports.isZero() ? limits.maxLamports : cfg.maxLamports;
+  const window = limits && (limits.validFromUnix || limits.validUntilUnix) ? limits : cfg;
+  const fee = limits && limits.feeBps > 0 ? limits : cfg;
+  return {
+    thresholdLamports: threshold.toString(),
+    belowThresholdBehavior: Object.keys(overrides.belowThresholdBehavior ?? base.belowThresholdBehavior)[0],
+    maxLamports: maxLamports.toString(),
+    validFromUnix: window.validFromUnix?.toString() ?? null,
+    validUntilUnix: window.validUntilUnix?.toString() ?? null,
+    feeBps: fee.feeBps as number,
+    feeCollector: fee.feeBps > 0 ? (fee.feeCollector as PublicKey).toBase58() : null,
+  };
+}
+
+export async function fetchConfigSnapshot(
+  program: anchor.Program,
+  configPda: PublicKey,
+  commitment: Commitment
+): Promise<ConfigSnapshot> {
+  const cfg: any = await (program.account as any).config.fetch(configPda, commitment);
+  // Fails if the linked policy is missing or is not a `Policy` account.
+  const policy: any = cfg.policy
+    ? await (program.account as any).policy.fetch(cfg.policy, commitment)
+    : null;
+  const terms = resolveTerms(cfg, policy);
+  return {
+    from: cfg.from.toBase58(),
+    to: cfg.to.toBase58(),
+    thresholdLamports: cfg.thresholdLamports.toString(),
+    comparisonMode: Object.keys(cfg.comparisonMode)[0],
+    thresholdUsdCents: cfg.thresholdUsdCents?.toString() ?? null,
+    privateThreshold: cfg.privateThreshold ? Buffer.from(cfg.privateThreshold).toString("hex") : null,
+    feeBps: terms.feeBps,
+    feeCollector: terms.feeCollector,
+    policy: cfg.policy ? cfg.policy.toBase58() : null,
+    policyOverrides: JSON.stringify({
+      thresholdLamports: cfg.policyOverrides.thresholdLamports?.toString() ?? null,
+      belowThresholdBehavior: cfg.policyOverrides.belowThresholdBehavior ?? null,
+    }),
+    resolvedTerms: JSON.stringify(terms),
+    recipientHash: cfg.recipientHash ? Buffer.from(cfg.recipientHash).toString("hex") : null,
+  };
+}
+
+function sameSnapshot(a: ConfigSnapshot, b: ConfigSnapshot): boolean {
+  return JSON.stringify(a) === JSON.stringify(b);
+}
+
+/**
+ * Send `lamports` from the provider wallet through the config at `configPda`.
+ * Returns the confirmed signature.
+ */
+export async function sendWithRevalidation(
+  program: anchor.Program,
+  configPda: PublicKey,
+  lamports: anchor.BN,
+  options: SendOptions = {}
+): Promise<string> {
+  const commitment = options.commitment ?? "confirmed";
+  const maxAttempts = options.maxAttempts ?? 3;
+  const provider = program.provider as anchor.AnchorProvider;
+  const connection = provider.connection;
+
+  const expected = await fetchConfigSnapshot(program, configPda, commitment);
+  if (expected.recipientHash && !options.recipient) {
+    throw new Error("Config stores a hashed recipient; pass options.recipient");
+  }
+  const to = options.recipient ?? new PublicKey(expected.to);
+  if (options.nonce && options.memo) {
+    throw new Error("send_with_nonce takes no memo");
+  }
+
+  for (let attempt = 1; attempt <= maxAttempts; attempt++) {
+    if (attempt > 1) {
+      const current = await fetchConfigSnapshot(program, configPda, commitment);
+      if (!sameSnapshot(expected, current)) {
+        throw new ConfigChangedError(expected, current);
+      }
+    }
+
+    const sendAccounts = {
+      config: configPda,
+      from: provider.wallet.publicKey,
+      to,
+      systemProgram: SystemProgram.programId,
+      policy: expected.policy ? new PublicKey(expected.policy) : null,
+      priceFeed: options.priceFeed ?? null,
+      rangeProof: options.rangeProof ?? null,
+      feeCollector: expected.feeCollector ? new PublicKey(expected.feeCollector) : null,
+    };
+    const method = options.nonce
+      ? program.methods.sendWithNonce(lamports, options.nonce).accounts({
+          send: sendAccounts,
+          payer: provider.wallet.publicKey,
+          nonces: PublicKey.findProgramAddressSync(
+            [Buffer.from("send_nonces"), configPda.toBuffer(), provider.wallet.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          systemProgram: SystemProgram.programId,
+        })
+      : program.methods.sendIfOverThreshold(lamports, options.memo ?? null).accounts(sendAccounts);
+    const ix = await method
+      .remainingAccounts(
+        (options.references ?? []).map((pubkey) => ({pubkey, isSigner: false, isWritable: false}))
+      )
+      .instruction();
+
+    const {blockhash, lastValidBlockHeight} = await connection.getLatestBlockhash(commitment);
+    const tx = new Transaction({
+      feePayer: provider.wallet.publicKey,
+      blockhash,
+      lastValidBlockHeight,
+    }).add(ix);
+    const signed = await provider.wallet.signTransaction(tx);
+
+    const simulation = await connection.simulateTransaction(signed);
+    if (simulation.value.err) {
+      throw new SimulationError(simulation.value.err, simulation.value.logs);
+    }
+
+    const signature = await connection.sendRawTransaction(signed.serialize(), {
+      skipPreflight: true,
+    });
+    try {
+      const result = await connection.confirmTransaction(
+        {signature, blockhash, lastValidBlockHeight},
+        commitment
+      );
+      if (result.value.err) {
+        throw new Error(`Transaction ${signature} failed: ${JSON.stringify(result.value.err)}`);
+      }
+      return signature;
+    } catch (err) {
+      if (!(err instanceof TransactionExpiredBlockheightExceededError)) {
+        throw err;
+      }
+      console.warn(`Attempt ${attempt}: blockhash expired before confirmation`);
+    }
+  }
+  throw new Error(`Transfer not confirmed after ${maxAttempts} attempts`);
+}
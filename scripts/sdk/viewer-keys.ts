@@ -0,0 +1,44 @@
+/**
+ * Config-scoped API keys for hosted exporters and indexers.
+ *
+ * A config's authority registers `viewerKeyHash(config, apiKey)` with
+ * `register_viewer_key`; only the hash lands in the `["viewer_keys", config]`
+ * PDA. An exporter serving that config's decoded event stream hashes the key a
+ * caller presents and serves it only if `isViewerKeyRegistered` says so. Since
+ * the config address is the HMAC message, one API key never unlocks another
+ * tenant's stream.
+ */
+import * as anchor from "@coral-xyz/anchor";
+import {createHmac, timingSafeEqual} from "crypto";
+import {Commitment, PublicKey} from "@solana/web3.js";
+
+/** `HMAC-SHA256(key = apiKey, message = config address)`, as stored on-chain. */
+export function viewerKeyHash(configPda: PublicKey, apiKey: string | Uint8Array): Buffer {
+  return createHmac("sha256", apiKey).update(configPda.toBuffer()).digest();
+}
+
+export function viewerKeysAddress(programId: PublicKey, configPda: PublicKey): PublicKey {
+  const [address] = PublicKey.findProgramAddressSync(
+    [Buffer.from("viewer_keys"), configPda.toBuffer()],
+    programId
+  );
+  return address;
+}
+
+/** Whether `apiKey` is currently registered for `configPda`; false if no keys exist. */
+export async function isViewerKeyRegistered(
+  program: anchor.Program,
+  configPda: PublicKey,
+  apiKey: string | Uint8Array,
+  commitment: Commitment = "confirmed"
+): Promise<boolean> {
+  const viewerKeys: any = await (program.account as any).viewerKeys.fetchNullable(
+    viewerKeysAddress(program.programId, configPda),
+    commitment
+  );
+  if (viewerKeys === null) return false;
+  const hash = viewerKeyHash(configPda, apiKey);
+  return viewerKeys.keyHashes.some((registered: number[]) =>
+    timingSafeEqual(Buffer.from(registered), hash)
+  );
+}
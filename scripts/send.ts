@@ -3,20 +3,24 @@
  * The signer must be A (the `from` in config).
  *
  * Usage:
- *   npx ts-node scripts/send.ts AMOUNT_SOL
+ *   CONFIG_ROUTE=ROUTE npx ts-node scripts/send.ts AMOUNT_SOL [COMMITMENT]
+ *
+ * The config is `CONFIG`, or `CONFIG_ROUTE` of `CONFIG_FROM` (default: wallet).
  *
  * Example:
  *   npx ts-node scripts/send.ts 0.25
  */
 import * as anchor from "@coral-xyz/anchor";
-import {LAMPORTS_PER_SOL, PublicKey, SystemProgram} from "@solana/web3.js";
+import {Commitment, LAMPORTS_PER_SOL, PublicKey} from "@solana/web3.js";
 import idl from "../target/idl/conditional_transfer.json" assert { type: "json" };
+import {configFromEnv} from "./sdk/config";
+import {sendWithRevalidation} from "./sdk/send-pipeline";
 
 (async () => {
   try {
-    const [, , amountSolArg] = process.argv;
+    const [, , amountSolArg, commitmentArg] = process.argv;
     if (!amountSolArg) {
-      console.error("Usage: npx ts-node scripts/send.ts AMOUNT_SOL");
+      console.error("Usage: npx ts-node scripts/send.ts AMOUNT_SOL [COMMITMENT]");
       process.exit(1);
     }
     const amountSol = Number(amountSolArg);
@@ -31,10 +35,7 @@ import idl from "../target/idl/conditional_transfer.json" assert { type: "json"
     const programId = new PublicKey((idl as any).address ?? process.env.PROGRAM_ID!);
     const program = new anchor.Program(idl as anchor.Idl, programId, provider);
 
-    const [configPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("config")],
-      program.programId
-    );
+    const configPda = configFromEnv(program.programId, provider.wallet.publicKey);
 
     // Fetch config and provide helpful client-side check
     const cfg = await program.account.config.fetch(configPda);
@@ -54,15 +55,13 @@ import idl from "../target/idl/conditional_transfer.json" assert { type: "json"
       process.exit(1);
     }
 
-    const txSig = await program.methods
-      .sendIfOverThreshold(new anchor.BN(lamports.toString())) // on-chain enforces ≥ threshold
-      .accounts({
-        config: configPda,
-        from,                 // must sign and match config.from
-        to,                   // must equal config.to
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc();
+    // Simulates, confirms, and re-signs on blockhash expiry only if the config is unchanged
+    const txSig = await sendWithRevalidation(
+      program,
+      configPda,
+      new anchor.BN(lamports.toString()), // on-chain enforces ≥ threshold
+      {commitment: (commitmentArg as Commitment | undefined) ?? "confirmed"}
+    );
 
     console.log("Send tx signature:", txSig);
   } catch (err) {